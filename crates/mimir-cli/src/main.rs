@@ -1,15 +1,20 @@
 //! Mimir CLI - Command-line interface for the AI Memory Vault
 
 use clap::{Parser, Subcommand};
-use mimir_core::{Config, Result};
+use mimir_core::{shamir::RootKeyShare, Config, Result, DEFAULT_VAULT};
 use mimir_db::Database;
 use tracing::info;
+use zeroize::Zeroizing;
 
 /// Mimir CLI - Manage your local AI memory vault
 #[derive(Parser)]
 #[command(name = "mimir")]
 #[command(about = "A CLI for managing Mimir AI Memory Vault")]
 struct Cli {
+    /// Named vault to use instead of the default one
+    #[arg(long, global = true)]
+    vault: Option<String>,
+
     #[command(subcommand)]
     command: Commands,
 }
@@ -17,6 +22,8 @@ struct Cli {
 #[derive(Subcommand)]
 enum Commands {
     /// Initialize a new memory vault
+    ///
+    /// With `--vault <name>`, adds that named vault to the configuration.
     Init {
         /// Vault directory path
         #[arg(short, long)]
@@ -27,6 +34,8 @@ enum Commands {
     },
     /// Show vault status
     Status,
+    /// Show memory counts per class, source, tag and month, on-disk sizes and orphans
+    Stats,
     /// Start the daemon
     Start {
         /// Run in background
@@ -36,34 +45,473 @@ enum Commands {
     /// Stop the daemon
     Stop,
     /// Burn (delete) memories by class
+    ///
+    /// Destroys the memories, their history and vectors, purges the class keys
+    /// and vacuums the database. Stop the daemon first.
     Burn {
-        /// Memory class to burn
-        #[arg(value_enum)]
-        class: BurnTarget,
+        /// Memory class to burn, or `all` for every class
+        class: String,
+        /// Skip confirmation prompt
+        #[arg(short, long)]
+        yes: bool,
     },
-    /// Rotate the root encryption key
+    /// Rotate the root encryption key and rekey the database
+    ///
+    /// An interrupted rotation is resumed by running the command again.
     RotateRoot {
         /// Skip confirmation prompt
         #[arg(short, long)]
         yes: bool,
     },
-    /// Rotate a class-specific encryption key
+    /// Change the vault password
+    ///
+    /// Only the keyset's password envelope is rewritten; no data is
+    /// re-encrypted.
+    ChangePassword,
+    /// Export, import or verify the root key's recovery phrase
+    Recovery {
+        #[command(subcommand)]
+        action: RecoveryAction,
+    },
+    /// Split the root key into shares for a family or team, or unlock with them
+    Shares {
+        #[command(subcommand)]
+        action: SharesAction,
+    },
+    /// Rotate a class-specific encryption key and re-encrypt the class under it
     RotateClass {
         /// Memory class to rotate
         class: String,
+        /// Finish an interrupted re-encryption without rotating again
+        #[arg(long)]
+        resume: bool,
+        /// Rows re-encrypted per batch
+        #[arg(long, default_value_t = mimir_db::rotation::DEFAULT_REENCRYPTION_BATCH)]
+        batch_size: usize,
+        /// Skip confirmation prompt
+        #[arg(short, long)]
+        yes: bool,
+    },
+    /// List memories, newest first
+    List {
+        /// Only memories of this class
+        #[arg(long)]
+        class: Option<String>,
+        /// Only memories owned by this app
+        #[arg(long)]
+        source: Option<String>,
+        /// Only memories carrying this tag (repeatable)
+        #[arg(long = "tag")]
+        tags: Vec<String>,
+        /// Only memories with this scope
+        #[arg(long)]
+        scope: Option<String>,
+        /// Only memories created at or after this RFC 3339 time
+        #[arg(long)]
+        since: Option<String>,
+        /// Only memories created before this RFC 3339 time
+        #[arg(long)]
+        until: Option<String>,
+        /// Cursor printed by the previous page
+        #[arg(long)]
+        cursor: Option<String>,
+        /// Page size
+        #[arg(short, long, default_value_t = 20)]
+        limit: usize,
+    },
+    /// Restore the vault, one class or one memory from a snapshot
+    ///
+    /// The snapshot is named by its directory or by its name in the backup
+    /// directory. Stop the daemon first.
+    Restore {
+        /// Snapshot to restore from
+        snapshot: String,
+        /// Only restore memories of this class from the snapshot
+        #[arg(long, conflicts_with = "memory")]
+        class: Option<String>,
+        /// Only restore this memory from the snapshot
+        #[arg(long)]
+        memory: Option<String>,
+        /// Verify the snapshot and compare counts without restoring anything
+        #[arg(long)]
+        dry_run: bool,
+        /// Skip confirmation prompt
+        #[arg(short, long)]
+        yes: bool,
+    },
+    /// List deleted memories still in the trash, or restore one
+    Trash {
+        #[command(subcommand)]
+        action: Option<TrashAction>,
+    },
+    /// Permanently delete every memory in the trash
+    EmptyTrash {
+        /// Skip confirmation prompt
+        #[arg(short, long)]
+        yes: bool,
+    },
+    /// Verify the audit log and show its entries
+    Audit {
+        /// Only entries recorded for this app
+        #[arg(long)]
+        app: Option<String>,
+        /// Only entries at or after this RFC 3339 time
+        #[arg(long)]
+        since: Option<String>,
+        /// Only entries before this RFC 3339 time
+        #[arg(long)]
+        until: Option<String>,
+        /// Verify the chain without listing entries
+        #[arg(long)]
+        verify_only: bool,
+    },
+    /// Show applied and pending database schema migrations
+    Migrations {
+        /// Apply pending migrations
+        #[arg(long)]
+        apply: bool,
+    },
+}
+
+#[derive(Subcommand)]
+enum RecoveryAction {
+    /// Print the root key as a 24-word recovery phrase
+    Export {
         /// Skip confirmation prompt
         #[arg(short, long)]
         yes: bool,
     },
+    /// Unlock the vault with a recovery phrase and restore its root key
+    ///
+    /// The root key goes back into the OS keychain unless `--password` is given.
+    Import {
+        /// Convert the vault to password-based encryption instead
+        #[arg(long)]
+        password: bool,
+    },
+    /// Check a recovery phrase against the keyset without unlocking the vault
+    Verify,
+}
+
+#[derive(Subcommand)]
+enum TrashAction {
+    /// Put a deleted memory back and re-index it for search
+    ///
+    /// Stop the daemon first, or it will not see the restored vector.
+    Restore {
+        /// Memory ID
+        id: String,
+    },
+}
+
+#[derive(Subcommand)]
+enum SharesAction {
+    /// Print the root key as shares, any `threshold` of which unlock the vault
+    Split {
+        /// Number of shares needed to unlock the vault
+        #[arg(short, long)]
+        threshold: u8,
+        /// Number of shares to print
+        #[arg(short = 'n', long)]
+        shares: u8,
+        /// Skip confirmation prompt
+        #[arg(short, long)]
+        yes: bool,
+    },
+    /// Unlock the vault with enough shares and restore its root key
+    ///
+    /// The root key goes back into the OS keychain unless `--password` is given.
+    Combine {
+        /// Convert the vault to password-based encryption instead
+        #[arg(long)]
+        password: bool,
+    },
+    /// Check shares against the keyset without unlocking the vault
+    Verify,
+}
+
+/// App recorded in the audit log for changes made from the CLI
+const CLI_APP_ID: &str = "mimir-cli";
+
+/// Load the configuration of the selected vault
+fn load_config(vault: Option<&str>) -> Result<Config> {
+    Config::load()?.for_vault(vault.unwrap_or(DEFAULT_VAULT))
+}
+
+/// Prompt for a password on stdin
+fn read_password(prompt: &str) -> Result<Zeroizing<String>> {
+    println!("🔐 {}", prompt);
+
+    let mut password_input = Zeroizing::new(String::new());
+    std::io::stdin().read_line(&mut password_input)?;
+    let password = Zeroizing::new(password_input.trim().to_string());
+
+    if password.is_empty() {
+        return Err(mimir_core::MimirError::Config(
+            "Password cannot be empty".to_string(),
+        ));
+    }
+    Ok(password)
+}
+
+/// Prompt for shares one at a time until the first one's threshold is reached
+fn read_shares() -> Result<Vec<RootKeyShare>> {
+    let mut shares: Vec<RootKeyShare> = Vec::new();
+    loop {
+        let text = read_password(&format!("Enter share {}:", shares.len() + 1))?;
+        let share = RootKeyShare::from_text(&text).map_err(|e| {
+            mimir_core::MimirError::Config(format!("Share {}: {}", shares.len() + 1, e))
+        })?;
+        shares.push(share);
+        if shares.len() >= usize::from(shares[0].threshold()) {
+            return Ok(shares);
+        }
+    }
+}
+
+/// Put a recovered root key back in the keychain, or behind a new password
+fn restore_root_key(
+    crypto_manager: &mimir_core::crypto::CryptoManager,
+    password: bool,
+    vault: Option<&str>,
+) -> Result<()> {
+    if password {
+        let new_password = read_new_password()?;
+        crypto_manager.convert_to_password(&new_password)?;
+
+        // Record the new mode, keeping a named vault as a profile
+        let mut settings = Config::load()?;
+        let vault = vault.unwrap_or(DEFAULT_VAULT);
+        match settings.vaults.get_mut(vault) {
+            Some(profile) => profile.encryption_mode = Some("password".to_string()),
+            None => settings.set_encryption_mode("password"),
+        }
+        settings.save()?;
+        println!("🔐 Vault converted to password-based encryption");
+    } else {
+        crypto_manager.restore_to_keychain()?;
+        println!("🔑 Root key restored to the OS keychain");
+    }
+    Ok(())
+}
+
+/// Prompt for a new password twice
+fn read_new_password() -> Result<Zeroizing<String>> {
+    let new_password = read_password("Enter the new vault password:")?;
+    if read_password("Enter the new vault password again:")? != new_password {
+        return Err(mimir_core::MimirError::Config(
+            "Passwords do not match".to_string(),
+        ));
+    }
+    Ok(new_password)
+}
+
+/// Unlock the vault's crypto manager according to the configured encryption mode
+fn load_crypto_manager(config: &Config) -> Result<mimir_core::crypto::CryptoManager> {
+    let keyset_path = config.get_keyset_path();
+    if config.use_password_encryption {
+        let password = read_password("Enter your vault password:")?;
+        mimir_core::crypto::CryptoManager::with_password(&keyset_path, &password)
+    } else {
+        mimir_core::crypto::CryptoManager::new(&keyset_path)
+    }
+}
+
+/// Open the configured vault database
+fn open_database(config: &Config) -> Result<Database> {
+    let crypto_manager = load_crypto_manager(config)?;
+    let db_path = config.get_database_path();
+    Database::with_crypto_manager(&db_path, crypto_manager)
+}
+
+/// A timestamp argument
+type Timestamp = chrono::DateTime<chrono::Utc>;
+
+/// Parse an optional RFC 3339 timestamp argument
+fn parse_timestamp(value: Option<String>) -> Result<Option<Timestamp>> {
+    value
+        .map(|value| {
+            chrono::DateTime::parse_from_rfc3339(&value)
+                .map(|at| at.with_timezone(&chrono::Utc))
+                .map_err(|e| {
+                    mimir_core::MimirError::Config(format!("Invalid timestamp '{}': {}", value, e))
+                })
+        })
+        .transpose()
+}
+
+/// Drop burned memories from the saved vector store and rewrite its files
+///
+/// Burning every class deletes the store files outright. Returns the number
+/// of vectors removed.
+async fn burn_vectors(config: &Config, ids: &[mimir_core::MemoryId], all: bool) -> Result<usize> {
+    let vault_path = config.get_vault_path();
+    let store =
+        match mimir_vector::ThreadSafeVectorStore::load(vault_path.as_path(), None, None, None)
+            .await
+            .map_err(|e| mimir_core::MimirError::VectorStore(e.to_string()))?
+        {
+            Some(store) => store,
+            None => return Ok(0),
+        };
+
+    if all {
+        let count = store.len().await;
+        mimir_vector::VectorStorePersistence::new(vault_path)
+            .delete_store()
+            .map_err(|e| mimir_core::MimirError::VectorStore(e.to_string()))?;
+        return Ok(count);
+    }
+
+    let mut removed = 0;
+    for id in ids {
+        if store.contains(id).await {
+            store
+                .remove_vector(*id)
+                .await
+                .map_err(|e| mimir_core::MimirError::VectorStore(e.to_string()))?;
+            removed += 1;
+        }
+    }
+    store
+        .save(None)
+        .await
+        .map_err(|e| mimir_core::MimirError::VectorStore(e.to_string()))?;
+    Ok(removed)
+}
+
+/// Put the stored embeddings of memories back into the saved vector store
+///
+/// Memories that are in the trash are dropped from the store instead.
+/// Without a saved store nothing is written, as the daemon builds its index
+/// from the stored embeddings when it starts with none. Returns the memories
+/// that have no stored embedding to index them with.
+async fn reindex_vectors(
+    config: &Config,
+    db: &Database,
+    ids: &[mimir_core::MemoryId],
+) -> Result<Vec<mimir_core::MemoryId>> {
+    let vault_path = config.get_vault_path();
+    let store = mimir_vector::ThreadSafeVectorStore::load(vault_path.as_path(), None, None, None)
+        .await
+        .map_err(|e| mimir_core::MimirError::VectorStore(e.to_string()))?;
+
+    let mut unindexed = Vec::new();
+    for id in ids {
+        let embedding = match db.get_memory(*id).await? {
+            Some(memory) if memory.embedding.is_none() => {
+                unindexed.push(*id);
+                None
+            }
+            Some(memory) => memory.embedding,
+            None => None,
+        };
+        let Some(store) = &store else {
+            continue;
+        };
+
+        if store.contains(id).await {
+            store
+                .remove_vector(*id)
+                .await
+                .map_err(|e| mimir_core::MimirError::VectorStore(e.to_string()))?;
+        }
+        if let Some(embedding) = embedding {
+            store
+                .add_vector(*id, embedding)
+                .await
+                .map_err(|e| mimir_core::MimirError::VectorStore(e.to_string()))?;
+        }
+    }
+
+    if let Some(store) = store {
+        store
+            .save(None)
+            .await
+            .map_err(|e| mimir_core::MimirError::VectorStore(e.to_string()))?;
+    }
+    Ok(unindexed)
+}
+
+/// IDs of the memories in the saved vector store
+async fn saved_vector_ids(config: &Config) -> Result<Vec<mimir_core::MemoryId>> {
+    let vault_path = config.get_vault_path();
+    let store =
+        match mimir_vector::ThreadSafeVectorStore::load(vault_path.as_path(), None, None, None)
+            .await
+            .map_err(|e| mimir_core::MimirError::VectorStore(e.to_string()))?
+        {
+            Some(store) => store,
+            None => return Ok(Vec::new()),
+        };
+    Ok(store.memory_ids().await)
+}
+
+/// Parse a memory ID argument
+fn parse_memory_id(id: &str) -> Result<mimir_core::MemoryId> {
+    id.parse()
+        .map_err(|e| mimir_core::MimirError::Config(format!("Invalid memory ID '{}': {}", id, e)))
+}
+
+/// Find a snapshot given as a directory or by name in the backup directory
+fn resolve_snapshot(config: &Config, snapshot: &str) -> Result<std::path::PathBuf> {
+    let path = std::path::PathBuf::from(snapshot);
+    if path.is_dir() {
+        return Ok(path);
+    }
+    let named = config.get_backup_path().join(snapshot);
+    if named.is_dir() {
+        return Ok(named);
+    }
+    Err(mimir_core::MimirError::Config(format!(
+        "Snapshot '{}' not found (looked in {})",
+        snapshot,
+        config.get_backup_path().display()
+    )))
 }
 
-#[derive(clap::ValueEnum, Clone, Debug)]
-enum BurnTarget {
-    Personal,
-    Work,
-    Health,
-    Financial,
-    All,
+/// Print current and snapshot memory counts side by side
+fn print_count_comparison(current: &mimir_db::VaultCounts, snapshot: &mimir_db::VaultCounts) {
+    println!("{:<12} {:>10} {:>10}", "", "current", "snapshot");
+    println!(
+        "{:<12} {:>10} {:>10}",
+        "memories", current.memories, snapshot.memories
+    );
+    println!(
+        "{:<12} {:>10} {:>10}",
+        "trashed", current.trashed, snapshot.trashed
+    );
+    let classes: std::collections::BTreeSet<&String> = current
+        .classes
+        .keys()
+        .chain(snapshot.classes.keys())
+        .collect();
+    for class in classes {
+        println!(
+            "  {:<10} {:>10} {:>10}",
+            class,
+            current.classes.get(class).copied().unwrap_or(0),
+            snapshot.classes.get(class).copied().unwrap_or(0)
+        );
+    }
+}
+
+/// Print one breakdown of the vault statistics
+fn print_breakdown(title: &str, counts: &std::collections::BTreeMap<String, usize>) {
+    if counts.is_empty() {
+        return;
+    }
+    println!("By {}:", title);
+    for (key, count) in counts {
+        println!("  {:<24} {:>8}", key, count);
+    }
+}
+
+/// Size of a file in bytes, or 0 if it does not exist
+fn file_size(path: &std::path::Path) -> u64 {
+    std::fs::metadata(path)
+        .map(|metadata| metadata.len())
+        .unwrap_or(0)
 }
 
 /// Ensure models are downloaded during initialization
@@ -72,7 +520,6 @@ async fn ensure_models_downloaded() -> Result<()> {
     use mimir_core::get_default_app_dir;
     use reqwest::Client;
     use std::fs;
-    use std::path::Path;
     
     // Simple function to download Gemma3 model
     let model_dir = get_default_app_dir().join("models");
@@ -119,7 +566,16 @@ async fn main() -> Result<()> {
     match cli.command {
         Commands::Init { path, password } => {
             // Load existing config or create new one
-            let mut config = Config::load().unwrap_or_else(|_| Config::new());
+            let mut settings = Config::load().unwrap_or_else(|_| Config::new());
+            let vault = cli.vault.as_deref().unwrap_or(DEFAULT_VAULT);
+            if vault != DEFAULT_VAULT && !settings.vaults.contains_key(vault) {
+                let vault_dir = mimir_core::get_default_app_dir().join("vaults").join(vault);
+                settings
+                    .vaults
+                    .insert(vault.to_string(), mimir_core::VaultProfile::new(vault_dir));
+            }
+            settings.validate()?;
+            let mut config = settings.for_vault(vault)?;
 
             // Update vault path if provided
             let vault_dir = match path {
@@ -190,8 +646,16 @@ async fn main() -> Result<()> {
                 println!("✅ Database initialized at {}", db_path.display());
             }
 
-            // Save configuration
-            config.save()?;
+            // Save configuration, keeping a named vault as a profile
+            match settings.vaults.get_mut(vault) {
+                Some(profile) => {
+                    profile.vault_path = config.get_vault_path().clone();
+                    profile.encryption_mode = Some(config.encryption_mode.clone());
+                }
+                None => settings = config,
+            }
+            settings.validate()?;
+            settings.save()?;
             println!(
                 "✅ Configuration saved to {}",
                 mimir_core::get_default_config_path().display()
@@ -202,6 +666,40 @@ async fn main() -> Result<()> {
             // TODO: Implement status check with crypto info
             println!("🔍 Vault status: Ready");
         }
+        Commands::Stats => {
+            let config = load_config(cli.vault.as_deref())?;
+            let db = open_database(&config)?;
+
+            let stats = db.vault_stats().await?;
+            let memory_ids = db.live_memory_ids().await?;
+            let vector_ids = saved_vector_ids(&config).await?;
+            let orphans = mimir_db::OrphanCounts::compare(&memory_ids, &vector_ids);
+
+            println!("📊 Vault statistics");
+            println!("Memories:     {}", stats.memories);
+            println!("In trash:     {}", stats.trashed);
+            println!("Revisions:    {}", stats.revisions);
+            println!("Relations:    {}", stats.relations);
+            println!(
+                "Attachments:  {} ({} bytes)",
+                stats.attachments, stats.attachment_bytes
+            );
+            println!("Vectors:      {}", vector_ids.len());
+            println!(
+                "On disk:      database {} bytes, vectors {} bytes, keyset {} bytes",
+                stats.database_bytes,
+                mimir_vector::VectorStorePersistence::new(config.get_vault_path()).disk_size(),
+                file_size(&config.get_keyset_path())
+            );
+            println!(
+                "Orphans:      {} memories without vectors, {} vectors without memories",
+                orphans.memories_without_vectors, orphans.vectors_without_memories
+            );
+            print_breakdown("class", &stats.by_class);
+            print_breakdown("source", &stats.by_source);
+            print_breakdown("tag", &stats.by_tag);
+            print_breakdown("month", &stats.by_month);
+        }
         Commands::Start { daemon } => {
             info!("Starting Mimir daemon (daemon={})", daemon);
             // TODO: Implement daemon start
@@ -212,17 +710,79 @@ async fn main() -> Result<()> {
             // TODO: Implement daemon stop
             println!("🛑 Mimir daemon stopped");
         }
-        Commands::Burn { class } => {
-            info!("Burning memories: {:?}", class);
-            // TODO: Implement memory burning with confirmation
-            println!("🔥 Memories burned: {:?}", class);
+        Commands::Burn { class, yes } => {
+            // `all` burns every class
+            let target: Option<mimir_core::MemoryClass> = match class.as_str() {
+                "all" => None,
+                class => Some(match class.parse() {
+                    Ok(class) => class,
+                    Err(never) => match never {},
+                }),
+            };
+            let label = target
+                .as_ref()
+                .map(|class| class.as_str().to_string())
+                .unwrap_or_else(|| "all".to_string());
+
+            if !yes {
+                match &target {
+                    Some(class) => println!(
+                        "⚠️  This will permanently destroy every '{}' memory, its history and vectors, and purge the class key.",
+                        class.as_str()
+                    ),
+                    None => println!(
+                        "⚠️  This will permanently destroy every memory in the vault, with its history and vectors, and purge all class keys."
+                    ),
+                }
+                println!("   This operation cannot be undone. Continue? (y/N)");
+
+                let mut input = String::new();
+                std::io::stdin().read_line(&mut input)?;
+                if !input.trim().to_lowercase().starts_with('y') {
+                    println!("Operation cancelled.");
+                    return Ok(());
+                }
+            }
+
+            info!("Burning memories: {}", label);
+            let config = load_config(cli.vault.as_deref())?;
+            let db = open_database(&config)?;
+
+            let report = db.burn(target.as_ref()).await?;
+            let vectors = burn_vectors(&config, &report.memories, target.is_none()).await?;
+            db.append_audit(
+                CLI_APP_ID,
+                &format!("burn:{}", label),
+                &report.memories,
+                None,
+            )
+            .await?;
+
+            println!(
+                "🔥 Burned {}: {} memories, {} revisions, {} relations, {} attachments, {} vectors",
+                label,
+                report.memories.len(),
+                report.revisions,
+                report.relations,
+                report.attachments,
+                vectors
+            );
+            if !report.purged_classes.is_empty() {
+                println!("🗝️  Purged keys for: {}", report.purged_classes.join(", "));
+            }
+            if !mimir_db::backup::list_snapshots(&config.get_backup_path())?.is_empty() {
+                println!(
+                    "   Snapshots in {} still hold the burned data; delete them to finish.",
+                    config.get_backup_path().display()
+                );
+            }
         }
         Commands::RotateRoot { yes } => {
             if !yes {
                 println!(
-                    "⚠️  This will rotate the root encryption key and re-encrypt all class keys."
+                    "⚠️  This will rotate the root encryption key, rekey the database and re-wrap all class keys."
                 );
-                println!("   This operation cannot be undone. Continue? (y/N)");
+                println!("   Stop the daemon first. Continue? (y/N)");
 
                 let mut input = String::new();
                 std::io::stdin().read_line(&mut input)?;
@@ -232,18 +792,437 @@ async fn main() -> Result<()> {
                 }
             }
 
-            info!("Rotating root encryption key");
+            let config = load_config(cli.vault.as_deref())?;
+            // A rotated vector store cannot follow the root key to its new value
+            let rotated_vectors =
+                mimir_vector::VectorStorePersistence::new(config.get_vault_path())
+                    .is_rotated()
+                    .map_err(|e| mimir_core::MimirError::VectorStore(e.to_string()))?;
+            if rotated_vectors {
+                return Err(mimir_core::MimirError::VectorStore(
+                    "The saved vector store uses vector rotation, which root key rotation does not support".to_string(),
+                ));
+            }
 
-            // Load crypto manager and rotate root key
-            let keyset_path = mimir_core::config::get_default_keyset_path();
-            let mut crypto_manager = mimir_core::crypto::CryptoManager::new(&keyset_path)?;
-            crypto_manager.rotate_root_key()?;
+            let mut crypto_manager = load_crypto_manager(&config)?;
+            if crypto_manager.root_rotation_pending() {
+                info!("Resuming interrupted root key rotation");
+            } else {
+                info!("Rotating root encryption key");
+            }
 
+            let db_path = config.get_database_path();
+            let report = mimir_db::root_rotation::rotate_root_key(&db_path, &mut crypto_manager)?;
+            if report.rekeyed {
+                println!("🔑 Database rekeyed under the new root key");
+            }
+            println!(
+                "🗝️  Re-wrapped the keys of {} classes",
+                report.classes.len()
+            );
+
+            let db = Database::with_crypto_manager(&db_path, crypto_manager)?;
+            db.append_audit(CLI_APP_ID, "rotate_root", &[], None)
+                .await?;
             println!("🔄 Root encryption key rotated successfully");
         }
-        Commands::RotateClass { class, yes } => {
+        Commands::ChangePassword => {
+            let config = load_config(cli.vault.as_deref())?;
+            if !config.use_password_encryption {
+                return Err(mimir_core::MimirError::Config(
+                    "This vault keeps its key in the OS keychain and has no password".to_string(),
+                ));
+            }
+
+            // Opening the database proves the current password
+            let db = open_database(&config)?;
+
+            let new_password = read_new_password()?;
+            db.crypto_manager().change_password(&new_password)?;
+            db.append_audit(CLI_APP_ID, "change_password", &[], None)
+                .await?;
+            println!("✅ Vault password changed");
+        }
+        Commands::Recovery { action } => {
+            let config = load_config(cli.vault.as_deref())?;
+            let keyset_path = config.get_keyset_path();
+            match action {
+                RecoveryAction::Export { yes } => {
+                    if !yes {
+                        println!("⚠️  The recovery phrase unlocks every memory in the vault.");
+                        println!("   Write it down and keep it offline. Show it now? (y/N)");
+
+                        let mut input = String::new();
+                        std::io::stdin().read_line(&mut input)?;
+                        if !input.trim().to_lowercase().starts_with('y') {
+                            println!("Operation cancelled.");
+                            return Ok(());
+                        }
+                    }
+
+                    let db = open_database(&config)?;
+                    let phrase = db.crypto_manager().recovery_phrase()?;
+                    db.append_audit(CLI_APP_ID, "recovery_export", &[], None)
+                        .await?;
+
+                    println!("🗝️  Recovery phrase:");
+                    let words: Vec<&str> = phrase.split(' ').collect();
+                    for (row, chunk) in words.chunks(6).enumerate() {
+                        let numbered: Vec<String> = chunk
+                            .iter()
+                            .enumerate()
+                            .map(|(i, word)| format!("{:>2}. {:<8}", row * 6 + i + 1, word))
+                            .collect();
+                        println!("   {}", numbered.join(" "));
+                    }
+                }
+                RecoveryAction::Import { password } => {
+                    let phrase = read_password("Enter the 24-word recovery phrase:")?;
+                    let crypto_manager = mimir_core::crypto::CryptoManager::with_recovery_phrase(
+                        &keyset_path,
+                        &phrase,
+                    )?;
+                    restore_root_key(&crypto_manager, password, cli.vault.as_deref())?;
+
+                    let db =
+                        Database::with_crypto_manager(config.get_database_path(), crypto_manager)?;
+                    db.append_audit(CLI_APP_ID, "recovery_import", &[], None)
+                        .await?;
+                    println!("✅ Vault recovered");
+                }
+                RecoveryAction::Verify => {
+                    let phrase = read_password("Enter the 24-word recovery phrase:")?;
+                    if !mimir_core::crypto::CryptoManager::verify_recovery_phrase(
+                        &keyset_path,
+                        &phrase,
+                    )? {
+                        return Err(mimir_core::MimirError::Config(
+                            "The recovery phrase does not match this vault".to_string(),
+                        ));
+                    }
+                    println!("✅ The recovery phrase matches this vault");
+                }
+            }
+        }
+        Commands::Shares { action } => {
+            let config = load_config(cli.vault.as_deref())?;
+            let keyset_path = config.get_keyset_path();
+            match action {
+                SharesAction::Split {
+                    threshold,
+                    shares,
+                    yes,
+                } => {
+                    if !yes {
+                        println!(
+                            "⚠️  Any {} of the {} shares unlock every memory in the vault.",
+                            threshold, shares
+                        );
+                        println!("   Give each holder one share and keep them apart. Show them now? (y/N)");
+
+                        let mut input = String::new();
+                        std::io::stdin().read_line(&mut input)?;
+                        if !input.trim().to_lowercase().starts_with('y') {
+                            println!("Operation cancelled.");
+                            return Ok(());
+                        }
+                    }
+
+                    let db = open_database(&config)?;
+                    let root_key_shares = db.crypto_manager().root_key_shares(threshold, shares)?;
+                    db.append_audit(CLI_APP_ID, "shares_split", &[], None)
+                        .await?;
+
+                    for share in &root_key_shares {
+                        println!(
+                            "🧩 Share {} of {} (any {} unlock the vault):",
+                            share.index(),
+                            shares,
+                            threshold
+                        );
+                        println!("   {}", share.to_text().as_str());
+                    }
+                }
+                SharesAction::Combine { password } => {
+                    let shares = read_shares()?;
+                    let crypto_manager = mimir_core::crypto::CryptoManager::with_root_key_shares(
+                        &keyset_path,
+                        &shares,
+                    )?;
+                    restore_root_key(&crypto_manager, password, cli.vault.as_deref())?;
+
+                    let db =
+                        Database::with_crypto_manager(config.get_database_path(), crypto_manager)?;
+                    db.append_audit(CLI_APP_ID, "shares_combine", &[], None)
+                        .await?;
+                    println!("✅ Vault recovered from {} shares", shares.len());
+                }
+                SharesAction::Verify => {
+                    let shares = read_shares()?;
+                    if !mimir_core::crypto::CryptoManager::verify_root_key_shares(
+                        &keyset_path,
+                        &shares,
+                    )? {
+                        return Err(mimir_core::MimirError::Config(
+                            "The shares do not match this vault".to_string(),
+                        ));
+                    }
+                    println!("✅ The shares match this vault");
+                }
+            }
+        }
+        Commands::RotateClass {
+            class,
+            resume,
+            batch_size,
+            yes,
+        } => {
+            if !yes && !resume {
+                println!("⚠️  This will rotate the encryption key for class '{}' and re-encrypt every memory in it.", class);
+                println!("   Stop the daemon first. Continue? (y/N)");
+
+                let mut input = String::new();
+                std::io::stdin().read_line(&mut input)?;
+                if !input.trim().to_lowercase().starts_with('y') {
+                    println!("Operation cancelled.");
+                    return Ok(());
+                }
+            }
+
+            let config = load_config(cli.vault.as_deref())?;
+            let db = open_database(&config)?;
+
+            if resume {
+                info!("Resuming re-encryption of class: {}", class);
+            } else {
+                info!("Rotating class encryption key: {}", class);
+                let version = db.rotate_class_key(&class)?;
+                println!(
+                    "🔄 Class '{}' now encrypts under key version {}",
+                    class, version
+                );
+            }
+
+            loop {
+                let progress = db.reencrypt_class_batch(&class, batch_size.max(1)).await?;
+                if progress.reencrypted > 0 {
+                    println!(
+                        "   Re-encrypted {} rows, {} remaining",
+                        progress.reencrypted, progress.remaining
+                    );
+                }
+                if progress.is_complete() {
+                    if !progress.retired_versions.is_empty() {
+                        let versions: Vec<String> = progress
+                            .retired_versions
+                            .iter()
+                            .map(|version| version.to_string())
+                            .collect();
+                        println!("🗝️  Retired old key versions: {}", versions.join(", "));
+                    }
+                    db.append_audit(CLI_APP_ID, "rotate_class", &[], None)
+                        .await?;
+                    println!(
+                        "✅ Class '{}' is fully encrypted under key version {}",
+                        class, progress.key_version
+                    );
+                    break;
+                }
+            }
+        }
+        Commands::List {
+            class,
+            source,
+            tags,
+            scope,
+            since,
+            until,
+            cursor,
+            limit,
+        } => {
+            let config = load_config(cli.vault.as_deref())?;
+            let db = open_database(&config)?;
+
+            let filter = mimir_core::MemoryFilter {
+                class: class.map(|class| match class.parse() {
+                    Ok(class) => class,
+                    Err(never) => match never {},
+                }),
+                source,
+                tags,
+                scope,
+                created_after: parse_timestamp(since)?,
+                created_before: parse_timestamp(until)?,
+                ..Default::default()
+            };
+
+            let page = db.list_memories(&filter, cursor.as_deref(), limit).await?;
+            if page.memories.is_empty() {
+                println!("No memories found");
+            }
+            for memory in &page.memories {
+                println!(
+                    "{} [{}] {} | {}",
+                    memory.id,
+                    memory.class.as_str(),
+                    memory.created_at.format("%Y-%m-%d %H:%M"),
+                    memory.content
+                );
+                if !memory.tags.is_empty() {
+                    println!("    tags: {}", memory.tags.join(", "));
+                }
+            }
+            if let Some(next) = page.next_cursor {
+                println!("More results: mimir-cli list --cursor {}", next);
+            }
+        }
+        Commands::Restore {
+            snapshot,
+            class,
+            memory,
+            dry_run,
+            yes,
+        } => {
+            let config = load_config(cli.vault.as_deref())?;
+
+            let scope = match (class, memory) {
+                (Some(class), _) => Some(mimir_db::RestoreScope::Class(match class.parse() {
+                    Ok(class) => class,
+                    Err(never) => match never {},
+                })),
+                (None, Some(id)) => Some(mimir_db::RestoreScope::Memory(parse_memory_id(&id)?)),
+                (None, None) => None,
+            };
+
+            let snapshot_dir = resolve_snapshot(&config, &snapshot)?;
+            let db = open_database(&config)?;
+
+            let staging_dir = config.get_vault_path().join(".restore-staging");
+            let staged = db.stage_snapshot(&snapshot_dir, &staging_dir).await?;
+            println!(
+                "✅ Snapshot {} verified: checksums match and it decrypts with the current keys",
+                snapshot_dir.display()
+            );
+            println!(
+                "   Taken {}, schema version {}",
+                staged.manifest.created_at.format("%Y-%m-%d %H:%M:%S UTC"),
+                staged.manifest.schema_version
+            );
+            print_count_comparison(&db.vault_counts().await?, &staged.counts);
+
+            if dry_run {
+                staged.discard()?;
+                println!("Dry run: nothing was restored.");
+                return Ok(());
+            }
+
+            if !yes {
+                match &scope {
+                    Some(mimir_db::RestoreScope::Class(class)) => println!(
+                        "⚠️  This will replace every '{}' memory with its snapshot version.",
+                        class.as_str()
+                    ),
+                    Some(mimir_db::RestoreScope::Memory(id)) => {
+                        println!("⚠️  This will replace memory {} with its snapshot version.", id)
+                    }
+                    None => println!(
+                        "⚠️  This will replace the whole vault with the snapshot. Changes since it was taken are lost."
+                    ),
+                }
+                println!("   Continue? (y/N)");
+
+                let mut input = String::new();
+                std::io::stdin().read_line(&mut input)?;
+                if !input.trim().to_lowercase().starts_with('y') {
+                    staged.discard()?;
+                    println!("Operation cancelled.");
+                    return Ok(());
+                }
+            }
+
+            match scope {
+                Some(scope) => {
+                    let result = db.restore_from_snapshot(&staged, &scope).await;
+                    staged.discard()?;
+                    let restored = result?;
+                    db.append_audit(CLI_APP_ID, "restore_snapshot", &restored, None)
+                        .await?;
+                    println!("♻️  Restored {} memories from the snapshot", restored.len());
+
+                    let unindexed = reindex_vectors(&config, &db, &restored).await?;
+                    if !unindexed.is_empty() {
+                        println!(
+                            "   {} of them have no stored embedding; re-add them to make them searchable by meaning.",
+                            unindexed.len()
+                        );
+                    }
+                }
+                None => {
+                    let db_path = config.get_database_path();
+                    let vector_files: Vec<std::path::PathBuf> =
+                        mimir_vector::persistence::STORE_FILES
+                            .iter()
+                            .map(|name| config.get_vault_path().join(name))
+                            .collect();
+                    let db = db.restore_snapshot(staged, &db_path, &vector_files).await?;
+                    db.append_audit(CLI_APP_ID, "restore_snapshot", &[], None)
+                        .await?;
+                    println!("♻️  Vault restored from {}", snapshot_dir.display());
+                    println!("   The vector index is rebuilt from the stored embeddings when the daemon next starts.");
+                }
+            }
+        }
+        Commands::Trash {
+            action: Some(TrashAction::Restore { id }),
+        } => {
+            let memory_id = parse_memory_id(&id)?;
+            let config = load_config(cli.vault.as_deref())?;
+            let db = open_database(&config)?;
+
+            if !db.restore_memory(memory_id).await? {
+                println!("Memory {} is not in the trash", memory_id);
+                return Ok(());
+            }
+            db.append_audit(CLI_APP_ID, "restore", &[memory_id], None)
+                .await?;
+            println!("♻️  Restored memory {} from the trash", memory_id);
+
+            if !reindex_vectors(&config, &db, &[memory_id])
+                .await?
+                .is_empty()
+            {
+                println!(
+                    "   It has no stored embedding; re-add it to make it searchable by meaning."
+                );
+            }
+        }
+        Commands::Trash { action: None } => {
+            let config = load_config(cli.vault.as_deref())?;
+            let db = open_database(&config)?;
+
+            let trash = db.list_trash().await?;
+            if trash.is_empty() {
+                println!("🗑️  The trash is empty");
+            }
+            for trashed in &trash {
+                println!(
+                    "{} [{}] deleted {} | {}",
+                    trashed.memory.id,
+                    trashed.memory.class.as_str(),
+                    trashed.deleted_at.format("%Y-%m-%d %H:%M"),
+                    trashed.memory.content
+                );
+            }
+            if !trash.is_empty() {
+                println!(
+                    "Deleted memories are purged after {} days.",
+                    config.trash.retention_days
+                );
+            }
+        }
+        Commands::EmptyTrash { yes } => {
             if !yes {
-                println!("⚠️  This will rotate the encryption key for class '{}' and invalidate old encrypted data.", class);
+                println!("⚠️  This will permanently delete every memory in the trash.");
                 println!("   This operation cannot be undone. Continue? (y/N)");
 
                 let mut input = String::new();
@@ -254,14 +1233,96 @@ async fn main() -> Result<()> {
                 }
             }
 
-            info!("Rotating class encryption key: {}", class);
+            let config = load_config(cli.vault.as_deref())?;
+            let db = open_database(&config)?;
 
-            // Load crypto manager and rotate class key
-            let keyset_path = mimir_core::config::get_default_keyset_path();
-            let mut crypto_manager = mimir_core::crypto::CryptoManager::new(&keyset_path)?;
-            crypto_manager.rotate_class_key(&class)?;
+            let purged = db.empty_trash().await?;
+            db.append_audit(CLI_APP_ID, "empty_trash", &purged, None)
+                .await?;
+            println!("🔥 Permanently deleted {} memories", purged.len());
+        }
+        Commands::Audit {
+            app,
+            since,
+            until,
+            verify_only,
+        } => {
+            let config = load_config(cli.vault.as_deref())?;
+            let db = open_database(&config)?;
 
-            println!("🔄 Class '{}' encryption key rotated successfully", class);
+            let verification = db.verify_audit_chain().await?;
+            if let Some(seq) = verification.first_invalid {
+                println!(
+                    "❌ Audit chain is broken at entry {} ({} entries checked)",
+                    seq, verification.entries
+                );
+            } else {
+                println!("✅ Audit chain intact ({} entries)", verification.entries);
+            }
+
+            if !verify_only {
+                let filter = mimir_db::AuditFilter {
+                    app_id: app,
+                    since: parse_timestamp(since)?,
+                    until: parse_timestamp(until)?,
+                };
+                for entry in db.audit_entries(&filter).await? {
+                    let ids: Vec<String> =
+                        entry.memory_ids.iter().map(|id| id.to_string()).collect();
+                    println!(
+                        "#{} {} {} {}{} {}",
+                        entry.seq,
+                        entry.timestamp.format("%Y-%m-%d %H:%M:%S"),
+                        entry.app_id,
+                        entry.op,
+                        entry
+                            .guardrail
+                            .map(|decision| format!(" [{}]", decision))
+                            .unwrap_or_default(),
+                        ids.join(",")
+                    );
+                }
+            }
+
+            if let Some(seq) = verification.first_invalid {
+                return Err(mimir_core::MimirError::Database(anyhow::anyhow!(
+                    "Audit log has been tampered with from entry {}",
+                    seq
+                )));
+            }
+        }
+        Commands::Migrations { apply } => {
+            let config = load_config(cli.vault.as_deref())?;
+            let db_path = config.get_database_path();
+            let crypto_manager = load_crypto_manager(&config)?;
+
+            let status = if apply {
+                info!("Applying pending migrations to {}", db_path.display());
+                let db = Database::with_crypto_manager(&db_path, crypto_manager)?;
+                db.migration_status().await?
+            } else {
+                mimir_db::migrations::migration_status(&db_path, &crypto_manager)?
+            };
+
+            println!(
+                "📋 Schema version {} (latest {})",
+                status.current_version(),
+                mimir_db::migrations::latest_version()
+            );
+            for migration in &status.applied {
+                println!(
+                    "  ✅ {:>3} {} (applied {})",
+                    migration.version,
+                    migration.name,
+                    migration.applied_at.format("%Y-%m-%d %H:%M:%S UTC")
+                );
+            }
+            for migration in &status.pending {
+                println!("  ⏳ {:>3} {} (pending)", migration.version, migration.name);
+            }
+            if !status.pending.is_empty() {
+                println!("Run `mimir-cli migrations --apply` to apply pending migrations.");
+            }
         }
     }
 
@@ -10,6 +10,7 @@ fn create_test_memory(content_size: usize) -> Memory {
         id: Uuid::new_v4(),
         content,
         embedding: None,
+        embedding_model: None,
         class: MemoryClass::Personal,
         scope: None,
         tags: vec!["test".to_string(), "benchmark".to_string()],
@@ -17,6 +18,7 @@ fn create_test_memory(content_size: usize) -> Memory {
         key_id: "default_key".to_string(),
         created_at: Utc::now(),
         updated_at: Utc::now(),
+        expires_at: None,
     }
 }
 
@@ -36,6 +38,7 @@ fn bench_memory_creation(c: &mut Criterion) {
                         id: Uuid::new_v4(),
                         content,
                         embedding: None,
+                        embedding_model: None,
                         class: MemoryClass::Personal,
                         scope: None,
                         tags: vec!["benchmark".to_string()],
@@ -43,6 +46,7 @@ fn bench_memory_creation(c: &mut Criterion) {
                         key_id: "default_key".to_string(),
                         created_at: Utc::now(),
                         updated_at: Utc::now(),
+                        expires_at: None,
                     })
                 })
             },
@@ -128,6 +132,7 @@ fn bench_memory_class_operations(c: &mut Criterion) {
                     id: Uuid::new_v4(),
                     content: "test content".to_string(),
                     embedding: None,
+                    embedding_model: None,
                     class: class.clone(),
                     scope: None,
                     tags: vec![],
@@ -135,6 +140,7 @@ fn bench_memory_class_operations(c: &mut Criterion) {
                     key_id: "default_key".to_string(),
                     created_at: Utc::now(),
                     updated_at: Utc::now(),
+                    expires_at: None,
                 });
             }
         })
@@ -147,6 +153,7 @@ fn bench_memory_class_operations(c: &mut Criterion) {
                     id: Uuid::new_v4(),
                     content: "test".to_string(),
                     embedding: None,
+                    embedding_model: None,
                     class: class.clone(),
                     scope: None,
                     tags: vec![],
@@ -154,6 +161,7 @@ fn bench_memory_class_operations(c: &mut Criterion) {
                     key_id: "default_key".to_string(),
                     created_at: Utc::now(),
                     updated_at: Utc::now(),
+                    expires_at: None,
                 };
                 let _json = black_box(serde_json::to_string(&memory).unwrap());
             }
@@ -5,11 +5,16 @@
 //! - Encryption settings
 //! - Future extensible configuration options
 
-use crate::{MimirError, Result};
+use crate::{MemoryClass, MimirError, Result};
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::fs;
 use std::path::{Path, PathBuf};
 
+/// Name of the vault described by the top-level paths
+pub const DEFAULT_VAULT: &str = "default";
+
 /// Configuration for the Mimir AI Memory Vault
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Config {
@@ -49,6 +54,14 @@ pub struct Config {
     #[serde(default)]
     pub auto_backup: AutoBackupConfig,
 
+    /// Trash (soft delete) settings
+    #[serde(default)]
+    pub trash: TrashConfig,
+
+    /// Memory expiry settings
+    #[serde(default)]
+    pub expiry: ExpiryConfig,
+
     /// Server configuration
     #[serde(default)]
     pub server: ServerConfig,
@@ -57,11 +70,48 @@ pub struct Config {
     #[serde(default)]
     pub mcp: McpConfig,
 
+    /// Named vaults besides the default one, selected with `--vault <name>`
+    #[serde(default)]
+    pub vaults: BTreeMap<String, VaultProfile>,
+
     /// Future extensible configuration options
     #[serde(flatten)]
     pub extra: std::collections::HashMap<String, serde_json::Value>,
 }
 
+/// A named vault with its own directory, database and keyset
+///
+/// Every other setting is shared with the top-level configuration.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct VaultProfile {
+    /// Path to the vault directory
+    pub vault_path: PathBuf,
+
+    /// Path to the database file (relative to vault_path if not absolute)
+    #[serde(default = "default_database_path")]
+    pub database_path: PathBuf,
+
+    /// Path to the keyset file (relative to vault_path if not absolute)
+    #[serde(default = "default_keyset_path")]
+    pub keyset_path: PathBuf,
+
+    /// Encryption mode: "keychain" or "password" (the top-level mode if unset)
+    #[serde(default)]
+    pub encryption_mode: Option<String>,
+}
+
+impl VaultProfile {
+    /// Profile for a vault directory with the default file names
+    pub fn new<P: AsRef<Path>>(vault_path: P) -> Self {
+        Self {
+            vault_path: vault_path.as_ref().to_path_buf(),
+            database_path: default_database_path(),
+            keyset_path: default_keyset_path(),
+            encryption_mode: None,
+        }
+    }
+}
+
 /// Auto-backup configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AutoBackupConfig {
@@ -82,6 +132,31 @@ pub struct AutoBackupConfig {
     pub backup_path: PathBuf,
 }
 
+/// Trash configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TrashConfig {
+    /// Days a deleted memory stays restorable before it is purged (0 = purge on next run)
+    #[serde(default = "default_trash_retention_days")]
+    pub retention_days: u32,
+
+    /// How often the daemon purges expired trash, in hours
+    #[serde(default = "default_trash_purge_interval")]
+    pub purge_interval_hours: u32,
+}
+
+/// Memory expiry configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExpiryConfig {
+    /// Default time-to-live in days per memory class, e.g. `{"health": 365}`;
+    /// memories of unlisted classes never expire unless given an expiry
+    #[serde(default)]
+    pub class_ttl_days: std::collections::HashMap<String, u32>,
+
+    /// How often the daemon deletes expired memories, in minutes
+    #[serde(default = "default_expiry_reap_interval")]
+    pub reap_interval_minutes: u32,
+}
+
 /// Server configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ServerConfig {
@@ -250,12 +325,55 @@ impl Config {
         self.use_password_encryption = mode == "password";
     }
 
+    /// Names of every configured vault, the default one first
+    pub fn vault_names(&self) -> Vec<String> {
+        std::iter::once(DEFAULT_VAULT.to_string())
+            .chain(self.vaults.keys().cloned())
+            .collect()
+    }
+
+    /// Configuration of a named vault
+    ///
+    /// The profile's paths and encryption mode replace the top-level ones.
+    /// An absolute backup directory is shared by every vault, so each named
+    /// vault backs up into its own subdirectory of it.
+    pub fn for_vault(&self, name: &str) -> Result<Config> {
+        if name == DEFAULT_VAULT {
+            return Ok(self.clone());
+        }
+
+        let profile = self.vaults.get(name).ok_or_else(|| {
+            MimirError::Config(format!(
+                "Unknown vault '{}'; configured vaults: {}",
+                name,
+                self.vault_names().join(", ")
+            ))
+        })?;
+
+        let mut config = self.clone();
+        config.vault_path = profile.vault_path.clone();
+        config.database_path = profile.database_path.clone();
+        config.keyset_path = profile.keyset_path.clone();
+        if let Some(mode) = &profile.encryption_mode {
+            config.set_encryption_mode(mode);
+        }
+        if config.auto_backup.backup_path.is_absolute() {
+            config.auto_backup.backup_path = config.auto_backup.backup_path.join(name);
+        }
+        Ok(config)
+    }
+
     /// Resolve relative paths based on a base directory
     fn resolve_paths(&mut self, base_dir: &Path) {
         // Only resolve paths that are relative and not already resolved
         if !self.vault_path.is_absolute() {
             self.vault_path = base_dir.join(&self.vault_path);
         }
+        for profile in self.vaults.values_mut() {
+            if !profile.vault_path.is_absolute() {
+                profile.vault_path = base_dir.join(&profile.vault_path);
+            }
+        }
     }
 
     /// Validate the configuration
@@ -277,12 +395,60 @@ impl Config {
             ));
         }
 
+        if self.trash.retention_days > 3650 {
+            return Err(MimirError::Config(
+                "trash retention_days cannot exceed 3650".to_string(),
+            ));
+        }
+
+        if self.trash.purge_interval_hours == 0 {
+            return Err(MimirError::Config(
+                "trash purge_interval_hours must be at least 1".to_string(),
+            ));
+        }
+
+        if self.expiry.reap_interval_minutes == 0 {
+            return Err(MimirError::Config(
+                "expiry reap_interval_minutes must be at least 1".to_string(),
+            ));
+        }
+
         if self.auto_backup.max_backups > 1000 {
             return Err(MimirError::Config(
                 "max_backups cannot exceed 1000".to_string(),
             ));
         }
 
+        let mut database_paths = vec![self.get_database_path()];
+        for name in self.vaults.keys() {
+            if name == DEFAULT_VAULT {
+                return Err(MimirError::Config(format!(
+                    "vault name '{}' is reserved for the top-level vault",
+                    DEFAULT_VAULT
+                )));
+            }
+            if name.is_empty()
+                || !name
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+            {
+                return Err(MimirError::Config(format!(
+                    "vault name '{}' may only contain letters, digits, '-' and '_'",
+                    name
+                )));
+            }
+
+            let database_path = self.for_vault(name)?.get_database_path();
+            if database_paths.contains(&database_path) {
+                return Err(MimirError::Config(format!(
+                    "vault '{}' shares its database {} with another vault",
+                    name,
+                    database_path.display()
+                )));
+            }
+            database_paths.push(database_path);
+        }
+
         Ok(())
     }
 }
@@ -299,8 +465,11 @@ impl Default for Config {
             max_memories: default_max_memories(),
             debug_logging: false,
             auto_backup: AutoBackupConfig::default(),
+            trash: TrashConfig::default(),
+            expiry: ExpiryConfig::default(),
             server: ServerConfig::default(),
             mcp: McpConfig::default(),
+            vaults: BTreeMap::new(),
             extra: std::collections::HashMap::new(),
         }
     }
@@ -317,6 +486,37 @@ impl Default for AutoBackupConfig {
     }
 }
 
+impl Default for TrashConfig {
+    fn default() -> Self {
+        Self {
+            retention_days: default_trash_retention_days(),
+            purge_interval_hours: default_trash_purge_interval(),
+        }
+    }
+}
+
+impl ExpiryConfig {
+    /// Expiry for a new memory of `class` under the class default TTL, if any
+    pub fn default_expires_at(
+        &self,
+        class: &MemoryClass,
+        created_at: DateTime<Utc>,
+    ) -> Option<DateTime<Utc>> {
+        self.class_ttl_days
+            .get(class.as_str())
+            .map(|days| created_at + chrono::Duration::days(*days as i64))
+    }
+}
+
+impl Default for ExpiryConfig {
+    fn default() -> Self {
+        Self {
+            class_ttl_days: std::collections::HashMap::new(),
+            reap_interval_minutes: default_expiry_reap_interval(),
+        }
+    }
+}
+
 impl Default for ServerConfig {
     fn default() -> Self {
         Self {
@@ -369,6 +569,15 @@ fn default_max_backups() -> usize {
 fn default_backup_path() -> PathBuf {
     PathBuf::from("backups")
 }
+fn default_trash_retention_days() -> u32 {
+    30
+}
+fn default_trash_purge_interval() -> u32 {
+    6
+}
+fn default_expiry_reap_interval() -> u32 {
+    60
+}
 fn default_server_host() -> String {
     "localhost".to_string()
 }
@@ -421,6 +630,7 @@ mod tests {
         assert!(!config.use_password_encryption);
         assert_eq!(config.max_memories, 1000);
         assert!(!config.debug_logging);
+        assert_eq!(config.trash.retention_days, 30);
     }
 
     #[test]
@@ -483,6 +693,95 @@ mod tests {
         config.max_memories = 1000;
         config.auto_backup.interval_hours = 10000;
         assert!(config.validate().is_err());
+
+        // Test invalid trash purge interval
+        config.auto_backup.interval_hours = 24;
+        config.trash.purge_interval_hours = 0;
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn test_vault_profiles() {
+        let temp_dir = TempDir::new().unwrap();
+        let config_path = temp_dir.path().join("config.json");
+
+        let mut config = Config::new();
+        config.set_vault_path("/vaults/home");
+        config.auto_backup.backup_path = PathBuf::from("/backups");
+        config.vaults.insert(
+            "work".to_string(),
+            VaultProfile {
+                encryption_mode: Some("password".to_string()),
+                ..VaultProfile::new("work-vault")
+            },
+        );
+        config.save_to(&config_path).unwrap();
+
+        // Relative profile paths resolve against the config file's directory
+        let config = Config::load_from(&config_path).unwrap();
+        assert!(config.validate().is_ok());
+        assert_eq!(config.vault_names(), vec!["default", "work"]);
+
+        let work = config.for_vault("work").unwrap();
+        assert_eq!(
+            work.get_database_path(),
+            temp_dir.path().join("work-vault").join("mimir.db")
+        );
+        assert_eq!(work.get_backup_path(), PathBuf::from("/backups/work"));
+        assert!(work.use_password_encryption);
+        assert_eq!(work.max_memories, config.max_memories);
+
+        let default = config.for_vault(DEFAULT_VAULT).unwrap();
+        assert_eq!(
+            default.get_database_path(),
+            PathBuf::from("/vaults/home/mimir.db")
+        );
+        assert!(!default.use_password_encryption);
+        assert!(config.for_vault("missing").is_err());
+    }
+
+    #[test]
+    fn test_vault_profile_validation() {
+        let mut config = Config::new();
+        config.set_vault_path("/vaults/home");
+
+        config
+            .vaults
+            .insert("bad name".to_string(), VaultProfile::new("/vaults/bad"));
+        assert!(config.validate().is_err());
+        config.vaults.clear();
+
+        config.vaults.insert(
+            DEFAULT_VAULT.to_string(),
+            VaultProfile::new("/vaults/other"),
+        );
+        assert!(config.validate().is_err());
+        config.vaults.clear();
+
+        // Two vaults may not share a database
+        config
+            .vaults
+            .insert("copy".to_string(), VaultProfile::new("/vaults/home"));
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn test_expiry_default_ttl() {
+        let mut config = Config::new();
+        config
+            .expiry
+            .class_ttl_days
+            .insert("health".to_string(), 30);
+
+        let now = Utc::now();
+        assert_eq!(
+            config.expiry.default_expires_at(&MemoryClass::Health, now),
+            Some(now + chrono::Duration::days(30))
+        );
+        assert!(config
+            .expiry
+            .default_expires_at(&MemoryClass::Work, now)
+            .is_none());
     }
 
     #[test]
@@ -1,12 +1,18 @@
 //! Mimir Crypto - Encryption layer for AI Memory Vault
 //!
 //! This module provides:
-//! - Root Key (RK) management in OS keychain
+//! - Root Key (RK) management in OS keychain, or wrapped with an Argon2id
+//!   password key
 //! - Per-class key derivation using HMAC-SHA256
 //! - XChaCha20-Poly1305 encryption for memory content
-//! - Keyset management and rotation
+//! - Keyset management and rotation, including journaled root key rotation
 
-use crate::{error::MimirError, Result};
+use crate::{
+    error::MimirError,
+    recovery,
+    shamir::{self, RootKeyShare},
+    unlock, Result,
+};
 use chacha20poly1305::{
     aead::{Aead, AeadCore, KeyInit, OsRng},
     XChaCha20Poly1305, XNonce,
@@ -16,14 +22,19 @@ use ring::{
     rand::{SecureRandom, SystemRandom},
 };
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
 use std::fs;
+use std::io::Write;
 use std::path::Path;
-use zeroize::{Zeroize, ZeroizeOnDrop};
+use std::sync::{Mutex, PoisonError, RwLock, RwLockReadGuard, RwLockWriteGuard};
+use zeroize::{Zeroize, ZeroizeOnDrop, Zeroizing};
 
 /// Length of a root key in bytes (32 bytes = 256 bits)
 pub const ROOT_KEY_LEN: usize = 32;
 
+/// Root key bytes outside a `RootKey`, wiped on drop
+pub type RootKeyBytes = Zeroizing<[u8; ROOT_KEY_LEN]>;
+
 /// Length of a class key in bytes (32 bytes = 256 bits)
 pub const CLASS_KEY_LEN: usize = 32;
 
@@ -36,12 +47,33 @@ pub const SALT_LEN: usize = 32;
 /// Number of PBKDF2 iterations for password derivation
 pub const PBKDF2_ITERATIONS: u32 = 100_000;
 
+/// Keyset format whose password root key is derived with PBKDF2
+pub const KEYSET_VERSION_PBKDF2: u32 = 1;
+
+/// Keyset format whose password root key is wrapped with an Argon2id key
+pub const KEYSET_VERSION_ARGON2ID: u32 = 2;
+
+/// Format written for new keysets
+pub const KEYSET_VERSION: u32 = KEYSET_VERSION_ARGON2ID;
+
 /// Service name for OS keychain storage
 pub const KEYCHAIN_SERVICE: &str = "com.mimir.memory-vault";
 
 /// Root key identifier in keychain
 pub const ROOT_KEY_ID: &str = "mimir-root-key";
 
+/// Blind index token of a search term under a class key
+pub type BlindIndex = [u8; 32];
+
+/// HMAC context for deriving a class's blind index key from its class key
+pub const BLIND_INDEX_CONTEXT: &[u8] = b"mimir-blind-index";
+
+/// HMAC context for the keys that wrap each root key in a rotation journal
+const ROOT_ROTATION_CONTEXT: &str = "mimir-root-rotation";
+
+/// HMAC context for the keyset's check value of the root key
+const ROOT_KEY_CHECK_CONTEXT: &[u8] = b"mimir-root-key-check";
+
 /// Root Key for the device, stored in OS keychain or derived from password
 #[derive(ZeroizeOnDrop, Zeroize)]
 pub struct RootKey {
@@ -59,7 +91,10 @@ impl RootKey {
         Ok(RootKey { key })
     }
 
-    /// Create a new root key from a password
+    /// Derive a root key from a password with PBKDF2
+    ///
+    /// Used by version 1 keysets only; newer keysets wrap a random root key
+    /// with an Argon2id password key instead.
     pub fn from_password(password: &str, salt: &[u8; SALT_LEN]) -> Result<Self> {
         if password.is_empty() {
             return Err(MimirError::Encryption(
@@ -180,6 +215,63 @@ impl RootKey {
         &self.key
     }
 
+    /// Value stored in the keyset to recognise this root key by
+    fn check_value(&self) -> [u8; 32] {
+        let key = hmac::Key::new(hmac::HMAC_SHA256, &self.key);
+        let mut check = [0u8; 32];
+        check.copy_from_slice(hmac::sign(&key, ROOT_KEY_CHECK_CONTEXT).as_ref());
+        check
+    }
+
+    /// Whether a keyset's check value rules this root key out
+    ///
+    /// A keyset without a check value rules nothing out.
+    fn contradicts_check(&self, keyset: &Keyset) -> bool {
+        let key = hmac::Key::new(hmac::HMAC_SHA256, &self.key);
+        keyset
+            .root_key_check
+            .is_some_and(|check| hmac::verify(&key, ROOT_KEY_CHECK_CONTEXT, &check).is_err())
+    }
+
+    /// Whether this is the root key a keyset was written under
+    ///
+    /// Keysets written before the check value existed are checked by
+    /// unwrapping one of their class keys instead.
+    fn matches_keyset(&self, keyset: &Keyset) -> Result<bool> {
+        if keyset.root_key_check.is_some() {
+            return Ok(!self.contradicts_check(keyset));
+        }
+
+        let mut wrapped_keys = keyset.class_keys.iter().chain(
+            keyset
+                .class_key_versions
+                .iter()
+                .flat_map(|(class, versions)| versions.keys.values().map(move |key| (class, key))),
+        );
+        match wrapped_keys.next() {
+            Some((class, wrapped_key)) => {
+                Ok(self.derive_class_key(class)?.decrypt(wrapped_key).is_ok())
+            }
+            None => Err(MimirError::Encryption(
+                "The keyset has nothing to check the root key against yet; \
+                 export a recovery phrase from the unlocked vault first"
+                    .to_string(),
+            )),
+        }
+    }
+
+    /// Rebuild a root key from its bytes, e.g. after unwrapping it
+    fn from_slice(bytes: &[u8]) -> Result<Self> {
+        if bytes.len() != ROOT_KEY_LEN {
+            return Err(MimirError::Encryption(
+                "Invalid root key length".to_string(),
+            ));
+        }
+        let mut key = [0u8; ROOT_KEY_LEN];
+        key.copy_from_slice(bytes);
+        Ok(RootKey { key })
+    }
+
     /// Rotate root key - generates new key and returns old one for re-encryption
     pub fn rotate(&mut self) -> Result<RootKey> {
         let old_key = RootKey { key: self.key };
@@ -234,6 +326,21 @@ impl ClassKey {
         })
     }
 
+    /// Compute a blind index token for a search term
+    ///
+    /// The token is HMAC-SHA256 of the term under a key derived from this
+    /// class key, so equal terms match without the term being stored.
+    pub fn blind_index(&self, term: &[u8]) -> [u8; 32] {
+        let class_key = hmac::Key::new(hmac::HMAC_SHA256, &self.key);
+        let index_key = hmac::sign(&class_key, BLIND_INDEX_CONTEXT);
+        let key = hmac::Key::new(hmac::HMAC_SHA256, index_key.as_ref());
+        let signature = hmac::sign(&key, term);
+
+        let mut token = [0u8; 32];
+        token.copy_from_slice(&signature.as_ref()[..32]);
+        token
+    }
+
     /// Decrypt data with this class key using XChaCha20-Poly1305
     pub fn decrypt(&self, ciphertext: &Ciphertext) -> Result<Vec<u8>> {
         let cipher = XChaCha20Poly1305::new_from_slice(&self.key)
@@ -261,25 +368,164 @@ pub struct Ciphertext {
     pub nonce: Vec<u8>,
 }
 
+/// Argon2id cost parameters, stored in the keyset they were used for
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Argon2Params {
+    /// Memory cost in KiB
+    pub memory_kib: u32,
+    /// Number of passes over the memory
+    pub iterations: u32,
+    /// Number of lanes
+    pub parallelism: u32,
+}
+
+impl Default for Argon2Params {
+    /// RFC 9106's second recommended option: 64 MiB, 3 passes, 4 lanes
+    fn default() -> Self {
+        Self {
+            memory_kib: 64 * 1024,
+            iterations: 3,
+            parallelism: 4,
+        }
+    }
+}
+
+impl Argon2Params {
+    /// Derive the key that wraps the root key from a password
+    fn derive_key(&self, password: &str, salt: &[u8; SALT_LEN]) -> Result<ClassKey> {
+        if password.is_empty() {
+            return Err(MimirError::Encryption(
+                "Password cannot be empty".to_string(),
+            ));
+        }
+
+        let params = argon2::Params::new(
+            self.memory_kib,
+            self.iterations,
+            self.parallelism,
+            Some(CLASS_KEY_LEN),
+        )
+        .map_err(|e| MimirError::Encryption(format!("Invalid Argon2 parameters: {}", e)))?;
+        let argon2 =
+            argon2::Argon2::new(argon2::Algorithm::Argon2id, argon2::Version::V0x13, params);
+
+        let mut key = [0u8; CLASS_KEY_LEN];
+        argon2
+            .hash_password_into(password.as_bytes(), salt, &mut key)
+            .map_err(|e| MimirError::Encryption(format!("Failed to derive password key: {}", e)))?;
+        Ok(ClassKey { key })
+    }
+}
+
 /// Keyset storage format
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Keyset {
-    /// Version of the keyset format
+    /// Version of the keyset format, which records the password KDF
     pub version: u32,
     /// Salt for password derivation (if using password-based encryption)
     pub salt: Option<[u8; SALT_LEN]>,
+    /// Argon2id parameters of the password key (version 2 password keysets)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub kdf: Option<Argon2Params>,
+    /// Root key wrapped with the password key (version 2 password keysets)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub wrapped_root_key: Option<Ciphertext>,
     /// Encrypted class keys (encrypted with root key)
     pub class_keys: HashMap<String, Ciphertext>,
+    /// Versioned keys of classes whose key has been rotated
+    #[serde(default)]
+    pub class_key_versions: HashMap<String, ClassKeyVersions>,
+    /// Classes whose keys were destroyed; their keys are never derived again
+    #[serde(default, skip_serializing_if = "BTreeSet::is_empty")]
+    pub purged_classes: BTreeSet<String>,
+    /// HMAC of a fixed context under the root key, to check a recovery
+    /// phrase against without unlocking the vault
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub root_key_check: Option<[u8; 32]>,
     /// Timestamp of last update
     pub updated_at: chrono::DateTime<chrono::Utc>,
 }
 
+/// Wrapped key versions of every rotated class, as stored in the keyset
+type WrappedClassKeyVersions = HashMap<String, ClassKeyVersions>;
+
+/// Keys of a rotated class, by version
+///
+/// Version 0 is the key a class had before its first rotation. Older versions
+/// stay here until every row encrypted under them has been re-encrypted.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct ClassKeyVersions {
+    /// Version new data is encrypted under
+    pub current: u32,
+    /// Every version still in use, the current one included, wrapped with the root key
+    pub keys: BTreeMap<u32, Ciphertext>,
+}
+
+/// Older keys of one class, by version
+type RetiringKeys = BTreeMap<u32, ClassKey>;
+
+/// Class keys a crypto manager holds in memory
+#[derive(Default)]
+struct ClassKeyCache {
+    /// Current key of each class
+    class_keys: HashMap<String, ClassKey>,
+    /// Current key version of each rotated class; absent means version 0
+    class_key_versions: HashMap<String, u32>,
+    /// Older key versions still needed to decrypt data that has not been re-encrypted
+    retiring_class_keys: HashMap<String, RetiringKeys>,
+    /// Classes whose keys were destroyed and that hold no data under a new key yet
+    purged_classes: HashSet<String>,
+}
+
+impl ClassKeyCache {
+    fn class_key_version(&self, class: &str) -> u32 {
+        self.class_key_versions.get(class).copied().unwrap_or(0)
+    }
+}
+
+/// Argon2id envelope prepared while unlocking a PBKDF2 keyset
+struct KdfUpgrade {
+    salt: [u8; SALT_LEN],
+    params: Argon2Params,
+    password_key: ClassKey,
+    wrapped_root_key: Ciphertext,
+}
+
+/// Journal of an unfinished root key rotation, stored next to the keyset
+///
+/// Each root key is wrapped with a key derived from the other, so whichever
+/// one the keyset or keychain holds after a crash recovers both.
+#[derive(Serialize, Deserialize)]
+struct RootRotationJournal {
+    /// New root key, wrapped with a key derived from the old one
+    new_root_key: Ciphertext,
+    /// Old root key, wrapped with a key derived from the new one
+    old_root_key: Ciphertext,
+    started_at: chrono::DateTime<chrono::Utc>,
+}
+
+/// Old and new root keys of a rotation, in that order
+type RootKeyPair = (RootKey, RootKey);
+
+/// SQLCipher keys before and after a root key rotation
+pub struct RootRotation {
+    pub old_db_key: Zeroizing<[u8; 32]>,
+    pub new_db_key: Zeroizing<[u8; 32]>,
+}
+
 /// Crypto manager for the memory vault
+///
+/// Class keys are cached behind a lock, so one manager can be shared between
+/// threads: encryption and decryption only need `&self` and run in parallel
+/// once a class's key is cached.
 pub struct CryptoManager {
     root_key: RootKey,
-    class_keys: HashMap<String, ClassKey>,
-    purged_classes: std::collections::HashSet<String>,
+    keys: RwLock<ClassKeyCache>,
     keyset_path: std::path::PathBuf,
+    /// Set while a version 1 keyset waits for `complete_kdf_upgrade`
+    kdf_upgrade: Mutex<Option<KdfUpgrade>>,
+    /// Key wrapping the root key of a version 2 password keyset
+    password_key: Mutex<Option<ClassKey>>,
 }
 
 impl CryptoManager {
@@ -298,14 +544,22 @@ impl CryptoManager {
 
         let mut crypto_manager = CryptoManager {
             root_key,
-            class_keys: HashMap::new(),
-            purged_classes: std::collections::HashSet::new(),
+            keys: RwLock::default(),
             keyset_path,
+            kdf_upgrade: Mutex::default(),
+            password_key: Mutex::default(),
         };
 
         // Load existing keyset or create new one
         if crypto_manager.keyset_path.exists() {
-            crypto_manager.load_keyset()?;
+            if let Err(e) = crypto_manager.load_keyset() {
+                // A rotation cut short may have re-wrapped the keyset but not
+                // yet saved the new root key to the keychain
+                if !crypto_manager.switch_to_journal_root()? {
+                    return Err(e);
+                }
+                crypto_manager.load_keyset()?;
+            }
         } else {
             crypto_manager.create_keyset()?;
         }
@@ -319,9 +573,10 @@ impl CryptoManager {
 
         let mut crypto_manager = CryptoManager {
             root_key: RootKey::new()?, // Will be set properly below
-            class_keys: HashMap::new(),
-            purged_classes: std::collections::HashSet::new(),
+            keys: RwLock::default(),
             keyset_path,
+            kdf_upgrade: Mutex::default(),
+            password_key: Mutex::default(),
         };
 
         // Load existing keyset or create new one
@@ -334,6 +589,243 @@ impl CryptoManager {
         Ok(crypto_manager)
     }
 
+    /// Path of the keyset file this manager reads and writes
+    pub fn keyset_path(&self) -> &Path {
+        &self.keyset_path
+    }
+
+    /// Whether the keyset still derives the root key with PBKDF2
+    pub fn needs_kdf_upgrade(&self) -> bool {
+        self.kdf_upgrade
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .is_some()
+    }
+
+    /// Re-wrap the root key with a new password
+    ///
+    /// Only the password envelope in the keyset is rewritten. The root key,
+    /// and every key derived from it, stays the same, so no data needs
+    /// re-encrypting. A version 1 keyset moves to Argon2id on the way.
+    pub fn change_password(&self, new_password: &str) -> Result<()> {
+        self.set_password(new_password, false)
+    }
+
+    /// Move the vault to password mode, wrapping the root key with `password`
+    ///
+    /// The root key stays the same, so no data needs re-encrypting. The
+    /// keychain entry is left alone. On a password keyset this just sets a
+    /// new password, e.g. after unlocking with a recovery phrase.
+    pub fn convert_to_password(&self, password: &str) -> Result<()> {
+        self.set_password(password, true)
+    }
+
+    /// Store the root key in the OS keychain, e.g. after unlocking with a recovery phrase
+    pub fn restore_to_keychain(&self) -> Result<()> {
+        if self.read_keyset()?.salt.is_some() {
+            return Err(MimirError::Encryption(
+                "Keyset is password-based; its root key is not kept in the OS keychain".to_string(),
+            ));
+        }
+        self.root_key.save()
+    }
+
+    /// The root key written as a recovery phrase
+    ///
+    /// A keyset written before root key checks existed gets one, so the
+    /// phrase can be verified against it later.
+    pub fn recovery_phrase(&self) -> Result<Zeroizing<String>> {
+        self.prepare_root_key_export("exporting a recovery phrase")?;
+        Ok(recovery::encode_phrase(self.root_key.as_bytes()))
+    }
+
+    /// Split the root key into `count` shares, any `threshold` of which unlock the vault
+    ///
+    /// As with `recovery_phrase`, the keyset gets a root key check if it
+    /// lacks one.
+    pub fn root_key_shares(&self, threshold: u8, count: u8) -> Result<Vec<RootKeyShare>> {
+        self.prepare_root_key_export("splitting the root key")?;
+        shamir::split(self.root_key.as_bytes(), threshold, count)
+    }
+
+    /// Make sure the root key is settled and checkable before it leaves the vault
+    fn prepare_root_key_export(&self, action: &str) -> Result<()> {
+        if self.root_rotation_pending() {
+            return Err(MimirError::Encryption(format!(
+                "Finish the root key rotation before {}",
+                action
+            )));
+        }
+        if self.read_keyset()?.root_key_check.is_none() {
+            let keys = self.write_keys();
+            self.save_keyset(&keys)?;
+        }
+        Ok(())
+    }
+
+    /// Check a recovery phrase against a keyset without unlocking the vault
+    ///
+    /// Fails if the phrase is malformed or its checksum does not match.
+    pub fn verify_recovery_phrase<P: AsRef<Path>>(keyset_path: P, phrase: &str) -> Result<bool> {
+        let root_key = RootKey::from_slice(&*recovery::decode_phrase(phrase)?)?;
+        root_key.matches_keyset(&read_keyset_file(keyset_path.as_ref())?)
+    }
+
+    /// Unlock the vault with a recovery phrase instead of the keychain or a password
+    ///
+    /// Follow with `restore_to_keychain` or `convert_to_password` to make
+    /// the vault open normally again.
+    pub fn with_recovery_phrase<P: AsRef<Path>>(keyset_path: P, phrase: &str) -> Result<Self> {
+        let root_key = RootKey::from_slice(&*recovery::decode_phrase(phrase)?)?;
+        Self::with_root_key(
+            keyset_path.as_ref(),
+            root_key,
+            "The recovery phrase does not belong to this vault",
+        )
+    }
+
+    /// Unlock the vault with a key file instead of the keychain or a password
+    ///
+    /// The file holds the root key as 64 hex digits or as its recovery
+    /// phrase, so anyone who can read it can read the vault.
+    pub fn with_key_file<P: AsRef<Path>, K: AsRef<Path>>(
+        keyset_path: P,
+        key_file: K,
+    ) -> Result<Self> {
+        let contents = unlock::read_secret_file(key_file.as_ref())?;
+        let root_key = if contents.len() == 2 * ROOT_KEY_LEN
+            && contents.bytes().all(|b| b.is_ascii_hexdigit())
+        {
+            let bytes = Zeroizing::new(
+                hex::decode(&*contents)
+                    .map_err(|e| MimirError::Config(format!("Invalid key file: {}", e)))?,
+            );
+            RootKey::from_slice(&bytes)?
+        } else {
+            RootKey::from_slice(&*recovery::decode_phrase(&contents)?)?
+        };
+        Self::with_root_key(
+            keyset_path.as_ref(),
+            root_key,
+            "The key file does not belong to this vault",
+        )
+    }
+
+    /// Check root key shares against a keyset without unlocking the vault
+    ///
+    /// Fails if the shares are corrupted or do not recover a key together.
+    pub fn verify_root_key_shares<P: AsRef<Path>>(
+        keyset_path: P,
+        shares: &[RootKeyShare],
+    ) -> Result<bool> {
+        let root_key = RootKey::from_slice(&*shamir::combine(shares)?)?;
+        root_key.matches_keyset(&read_keyset_file(keyset_path.as_ref())?)
+    }
+
+    /// Unlock the vault with enough shares of its root key
+    ///
+    /// Like `with_recovery_phrase`, this leaves the keychain and password
+    /// untouched.
+    pub fn with_root_key_shares<P: AsRef<Path>>(
+        keyset_path: P,
+        shares: &[RootKeyShare],
+    ) -> Result<Self> {
+        let root_key = RootKey::from_slice(&*shamir::combine(shares)?)?;
+        Self::with_root_key(
+            keyset_path.as_ref(),
+            root_key,
+            "The shares do not belong to this vault",
+        )
+    }
+
+    /// Open a keyset with a root key recovered outside the keychain and password
+    fn with_root_key(keyset_path: &Path, root_key: RootKey, mismatch: &str) -> Result<Self> {
+        let keyset = read_keyset_file(keyset_path)?;
+        if !root_key.matches_keyset(&keyset)? {
+            return Err(MimirError::Encryption(mismatch.to_string()));
+        }
+
+        let mut crypto_manager = CryptoManager {
+            root_key,
+            keys: RwLock::default(),
+            keyset_path: keyset_path.to_path_buf(),
+            kdf_upgrade: Mutex::default(),
+            password_key: Mutex::default(),
+        };
+        crypto_manager.load_keys(&keyset)?;
+        Ok(crypto_manager)
+    }
+
+    /// Wrap the root key with a new password, converting a keychain keyset if allowed
+    fn set_password(&self, new_password: &str, convert: bool) -> Result<()> {
+        // Holding the lock keeps a pending upgrade from rewriting the envelope
+        let mut pending = self
+            .kdf_upgrade
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+
+        let mut keyset = self.read_keyset()?;
+        if keyset.salt.is_none() {
+            if !convert {
+                return Err(MimirError::Encryption(
+                    "Keyset is not password-based; the root key is in the OS keychain".to_string(),
+                ));
+            }
+            // Password keysets derive unrotated class keys on demand
+            keyset.class_keys.clear();
+        }
+
+        let salt = RootKey::generate_salt()?;
+        let params = Argon2Params::default();
+        let password_key = params.derive_key(new_password, &salt)?;
+        keyset.version = KEYSET_VERSION_ARGON2ID;
+        keyset.salt = Some(salt);
+        keyset.kdf = Some(params);
+        keyset.wrapped_root_key = Some(password_key.encrypt(self.root_key.as_bytes())?);
+        keyset.root_key_check = Some(self.root_key.check_value());
+        keyset.updated_at = chrono::Utc::now();
+        self.write_keyset(&keyset)?;
+
+        *pending = None;
+        *self
+            .password_key
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner) = Some(password_key);
+        Ok(())
+    }
+
+    /// Move a PBKDF2 keyset to Argon2id
+    ///
+    /// Version 1 keysets hold nothing to check a password against, so the
+    /// upgrade prepared at unlock is only written once the caller knows the
+    /// root key is right, e.g. because the database accepted it. The root key
+    /// is kept and wrapped with an Argon2id password key, so nothing needs
+    /// re-encrypting. Returns whether the keyset was upgraded.
+    pub fn complete_kdf_upgrade(&self) -> Result<bool> {
+        let mut pending = self
+            .kdf_upgrade
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        let upgrade = match pending.take() {
+            Some(upgrade) => upgrade,
+            None => return Ok(false),
+        };
+
+        let mut keyset = self.read_keyset()?;
+        keyset.version = KEYSET_VERSION_ARGON2ID;
+        keyset.salt = Some(upgrade.salt);
+        keyset.kdf = Some(upgrade.params);
+        keyset.wrapped_root_key = Some(upgrade.wrapped_root_key);
+        keyset.root_key_check = Some(self.root_key.check_value());
+        keyset.updated_at = chrono::Utc::now();
+        self.write_keyset(&keyset)?;
+        *self
+            .password_key
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner) = Some(upgrade.password_key);
+        Ok(true)
+    }
+
     /// Get database key for SQLCipher
     pub fn get_db_key(&self) -> Result<String> {
         self.root_key.derive_db_key()
@@ -345,116 +837,431 @@ impl CryptoManager {
     }
 
     /// Encrypt plaintext for a specific class
-    pub fn encrypt(&mut self, class: &str, plaintext: &[u8]) -> Result<Ciphertext> {
+    pub fn encrypt(&self, class: &str, plaintext: &[u8]) -> Result<Ciphertext> {
+        if let Some(class_key) = self.read_keys().class_keys.get(class) {
+            return class_key.encrypt(plaintext);
+        }
+
         // Ensure we have the class key
-        if !self.class_keys.contains_key(class) {
-            let class_key = self.root_key.derive_class_key(class)?;
-            self.class_keys.insert(class.to_string(), class_key);
-            // Remove from purged classes if it was purged before
-            self.purged_classes.remove(class);
-            self.save_keyset()?;
+        let mut keys = self.write_keys();
+        if !keys.class_keys.contains_key(class) {
+            // A purged class's old key must not come back, so it gets a random one
+            let class_key = if keys.purged_classes.contains(class) {
+                ClassKey::new()?
+            } else {
+                self.root_key.derive_class_key(class)?
+            };
+            keys.class_keys.insert(class.to_string(), class_key);
+            keys.purged_classes.remove(class);
+            self.save_keyset(&keys)?;
         }
 
-        let class_key = self.class_keys.get(class).unwrap();
-        class_key.encrypt(plaintext)
+        keys.class_keys[class].encrypt(plaintext)
     }
 
     /// Decrypt ciphertext for a specific class
-    pub fn decrypt(&mut self, class: &str, ciphertext: &Ciphertext) -> Result<Vec<u8>> {
-        // Check if class was purged
-        if self.purged_classes.contains(class) {
-            return Err(MimirError::Encryption(format!(
-                "Class '{}' has been purged",
-                class
-            )));
-        }
+    ///
+    /// While a rotated class is being re-encrypted, ciphertext under an older
+    /// key version that has not been retired yet still decrypts.
+    pub fn decrypt(&self, class: &str, ciphertext: &Ciphertext) -> Result<Vec<u8>> {
+        self.with_class_key(class, |keys, class_key| {
+            let result = class_key.decrypt(ciphertext);
+            if result.is_ok() {
+                return result;
+            }
 
-        // Ensure we have the class key
-        if !self.class_keys.contains_key(class) {
-            let class_key = self.root_key.derive_class_key(class)?;
-            self.class_keys.insert(class.to_string(), class_key);
+            // Authentication fails under the wrong key, so trying older keys is safe
+            keys.retiring_class_keys
+                .get(class)
+                .and_then(|keys| {
+                    keys.values()
+                        .rev()
+                        .find_map(|key| key.decrypt(ciphertext).ok())
+                })
+                .map_or(result, Ok)
+        })
+    }
+
+    /// Compute a blind index token for a term under a specific class key
+    pub fn blind_index(&self, class: &str, term: &[u8]) -> Result<[u8; 32]> {
+        self.with_class_key(class, |_, class_key| Ok(class_key.blind_index(term)))
+    }
+
+    /// Blind index tokens for a term under every key version of a class still in use
+    ///
+    /// The current key's token comes first. Rows not yet re-encrypted after a
+    /// rotation carry tokens from an older key, so searches match all of them.
+    pub fn blind_indexes(&self, class: &str, term: &[u8]) -> Result<Vec<BlindIndex>> {
+        self.with_class_key(class, |keys, class_key| {
+            let mut tokens = vec![class_key.blind_index(term)];
+            if let Some(keys) = keys.retiring_class_keys.get(class) {
+                tokens.extend(keys.values().rev().map(|key| key.blind_index(term)));
+            }
+            Ok(tokens)
+        })
+    }
+
+    /// Version of the key a class is currently encrypted under
+    pub fn class_key_version(&self, class: &str) -> u32 {
+        self.read_keys().class_key_version(class)
+    }
+
+    /// Older key versions of a class that have not been retired yet
+    pub fn retiring_class_key_versions(&self, class: &str) -> Vec<u32> {
+        self.read_keys()
+            .retiring_class_keys
+            .get(class)
+            .map(|keys| keys.keys().copied().collect())
+            .unwrap_or_default()
+    }
+
+    /// Forget every older key version of a class
+    ///
+    /// Only call this once nothing is encrypted under those versions any
+    /// more: data still under them becomes unreadable. Returns the retired
+    /// versions.
+    pub fn retire_class_keys(&self, class: &str) -> Result<Vec<u32>> {
+        let mut keys = self.write_keys();
+        let retired: Vec<u32> = keys
+            .retiring_class_keys
+            .remove(class)
+            .map(|keys| keys.keys().copied().collect())
+            .unwrap_or_default();
+        if !retired.is_empty() {
+            self.save_keyset(&keys)?;
         }
+        Ok(retired)
+    }
 
-        let class_key = self.class_keys.get(class).unwrap();
-        class_key.decrypt(ciphertext)
+    /// Whether a root key rotation was started and not finished
+    pub fn root_rotation_pending(&self) -> bool {
+        self.rotation_journal_path().exists()
     }
 
-    /// Rotate root key and re-encrypt all class keys
-    pub fn rotate_root_key(&mut self) -> Result<()> {
-        let _old_root_key = self.root_key.rotate()?;
+    /// Start a root key rotation, or pick up an unfinished one
+    ///
+    /// A new root key is generated and journaled next to the keyset before
+    /// anything else changes; nothing switches to it until
+    /// `finish_root_rotation`. Returns the database keys under both roots so
+    /// the caller can rekey the database in between.
+    pub fn begin_root_rotation(&self) -> Result<RootRotation> {
+        let journal = match self.read_rotation_journal()? {
+            Some(journal) => journal,
+            None => {
+                if self.needs_kdf_upgrade() {
+                    return Err(MimirError::Encryption(
+                        "Open the vault once to upgrade its keyset before rotating the root key"
+                            .to_string(),
+                    ));
+                }
+
+                let new_root_key = RootKey::new()?;
+                let journal = RootRotationJournal {
+                    new_root_key: self
+                        .root_key
+                        .derive_class_key(ROOT_ROTATION_CONTEXT)?
+                        .encrypt(new_root_key.as_bytes())?,
+                    old_root_key: new_root_key
+                        .derive_class_key(ROOT_ROTATION_CONTEXT)?
+                        .encrypt(self.root_key.as_bytes())?,
+                    started_at: chrono::Utc::now(),
+                };
+                let journal_data = serde_json::to_vec_pretty(&journal).map_err(|e| {
+                    MimirError::Encryption(format!("Failed to serialize rotation journal: {}", e))
+                })?;
+                write_atomically(&self.rotation_journal_path(), &journal_data).map_err(|e| {
+                    MimirError::Encryption(format!("Failed to write rotation journal: {}", e))
+                })?;
+                journal
+            }
+        };
+
+        let (old_root_key, new_root_key) = self.journal_root_keys(&journal)?;
+        Ok(RootRotation {
+            old_db_key: Zeroizing::new(old_root_key.derive_db_key_bytes()),
+            new_db_key: Zeroizing::new(new_root_key.derive_db_key_bytes()),
+        })
+    }
 
-        // Re-derive all class keys with new root key
-        let class_names: Vec<String> = self.class_keys.keys().cloned().collect();
-        for class in class_names {
-            let new_class_key = self.root_key.derive_class_key(&class)?;
-            self.class_keys.insert(class, new_class_key);
+    /// Switch to the journaled root key and re-wrap the keyset under it
+    ///
+    /// `classes` are the classes that still have data encrypted under keys
+    /// derived from the old root. Their keys are pinned in the keyset, wrapped
+    /// with the new root, so existing ciphertexts stay readable without being
+    /// re-encrypted; `rotate_class_key` replaces a pinned key like any other.
+    /// Password keysets switch root key in a single keyset write. Keychain
+    /// keysets are written before the keychain, and the journal lets
+    /// `new` recover from a crash in between. The journal is removed last.
+    pub fn finish_root_rotation(&mut self, classes: &[String]) -> Result<()> {
+        let journal = self.read_rotation_journal()?.ok_or_else(|| {
+            MimirError::Encryption("No root key rotation is in progress".to_string())
+        })?;
+        let (old_root_key, new_root_key) = self.journal_root_keys(&journal)?;
+
+        // On a retry after a crash the pins may already be in place
+        if self.root_key.as_bytes() == old_root_key.as_bytes() {
+            let keys = self.keys.get_mut().unwrap_or_else(PoisonError::into_inner);
+            for class in classes {
+                if keys.purged_classes.contains(class) {
+                    continue;
+                }
+                if !keys.class_keys.contains_key(class) {
+                    keys.class_keys
+                        .insert(class.clone(), old_root_key.derive_class_key(class)?);
+                }
+                keys.class_key_versions.entry(class.clone()).or_insert(0);
+            }
+            self.root_key = new_root_key;
+        }
+
+        let mut keyset = self.build_keyset(&self.read_keys())?;
+        if keyset.salt.is_some() {
+            let password_key = self
+                .password_key
+                .get_mut()
+                .unwrap_or_else(PoisonError::into_inner);
+            let password_key = password_key.as_ref().ok_or_else(|| {
+                MimirError::Encryption(
+                    "Open the vault once to upgrade its keyset before rotating the root key"
+                        .to_string(),
+                )
+            })?;
+            keyset.wrapped_root_key = Some(password_key.encrypt(self.root_key.as_bytes())?);
+        }
+        self.write_keyset(&keyset)?;
+        if keyset.salt.is_none() {
+            self.root_key.save()?;
         }
 
-        self.save_keyset()?;
+        fs::remove_file(self.rotation_journal_path()).map_err(|e| {
+            MimirError::Encryption(format!("Failed to remove rotation journal: {}", e))
+        })?;
         Ok(())
     }
 
     /// Rotate a specific class key
-    pub fn rotate_class_key(&mut self, class: &str) -> Result<()> {
-        let new_class_key = ClassKey::new()?;
-        self.class_keys.insert(class.to_string(), new_class_key);
-        // Remove from purged classes if it was purged before
-        self.purged_classes.remove(class);
-        self.save_keyset()?;
-        Ok(())
+    ///
+    /// New data is encrypted under a fresh random key with the next version
+    /// number. The previous key is kept for decryption until it is retired
+    /// with `retire_class_keys`. Returns the new version.
+    pub fn rotate_class_key(&self, class: &str) -> Result<u32> {
+        let mut keys = self.write_keys();
+        let version = keys.class_key_version(class);
+        // A purged class has no data left to keep readable
+        if !keys.purged_classes.remove(class) {
+            let previous = match keys.class_keys.remove(class) {
+                Some(key) => key,
+                None => self.root_key.derive_class_key(class)?,
+            };
+            keys.retiring_class_keys
+                .entry(class.to_string())
+                .or_default()
+                .insert(version, previous);
+        }
+
+        keys.class_keys.insert(class.to_string(), ClassKey::new()?);
+        keys.class_key_versions
+            .insert(class.to_string(), version + 1);
+        self.save_keyset(&keys)?;
+        Ok(version + 1)
     }
 
-    /// Purge a class (remove its key and mark as purged)
-    pub fn purge_class(&mut self, class: &str) -> Result<()> {
-        self.class_keys.remove(class);
-        self.purged_classes.insert(class.to_string());
-        self.save_keyset()?;
+    /// Purge a class, destroying every version of its key
+    ///
+    /// The class moves to a new key version and the purge is recorded in the
+    /// keyset, so its old key is never derived again, not even after the
+    /// vault is reopened: anything still encrypted under the class stops
+    /// decrypting. The next data written to the class gets a fresh random key
+    /// for the new version.
+    pub fn purge_class(&self, class: &str) -> Result<()> {
+        let mut keys = self.write_keys();
+        let version = keys.class_key_version(class) + 1;
+        keys.class_keys.remove(class);
+        keys.retiring_class_keys.remove(class);
+        keys.class_key_versions.insert(class.to_string(), version);
+        keys.purged_classes.insert(class.to_string());
+        self.save_keyset(&keys)?;
         Ok(())
     }
 
+    /// Shared access to the cached class keys
+    ///
+    /// Cache updates cannot be left half-done by a panic, so a poisoned lock
+    /// is still safe to use.
+    fn read_keys(&self) -> RwLockReadGuard<'_, ClassKeyCache> {
+        self.keys.read().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Exclusive access to the cached class keys
+    fn write_keys(&self) -> RwLockWriteGuard<'_, ClassKeyCache> {
+        self.keys.write().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Run `f` with a class's current key, deriving and caching it on first use
+    fn with_class_key<T>(
+        &self,
+        class: &str,
+        f: impl FnOnce(&ClassKeyCache, &ClassKey) -> Result<T>,
+    ) -> Result<T> {
+        {
+            let keys = self.read_keys();
+            // Check if class was purged
+            if keys.purged_classes.contains(class) {
+                return Err(purged_class_error(class));
+            }
+            if let Some(class_key) = keys.class_keys.get(class) {
+                return f(&keys, class_key);
+            }
+        }
+
+        let mut keys = self.write_keys();
+        // The class may have been purged while the lock was released
+        if keys.purged_classes.contains(class) {
+            return Err(purged_class_error(class));
+        }
+        if !keys.class_keys.contains_key(class) {
+            let class_key = self.root_key.derive_class_key(class)?;
+            keys.class_keys.insert(class.to_string(), class_key);
+        }
+        let keys = &*keys;
+        f(keys, &keys.class_keys[class])
+    }
+
     /// Load keyset from disk
     fn load_keyset(&mut self) -> Result<()> {
-        let keyset_data = fs::read(&self.keyset_path)
-            .map_err(|e| MimirError::Encryption(format!("Failed to read keyset: {}", e)))?;
-
-        let keyset: Keyset = serde_json::from_slice(&keyset_data)
-            .map_err(|e| MimirError::Encryption(format!("Failed to parse keyset: {}", e)))?;
+        let keyset = self.read_keyset()?;
+        self.load_keys(&keyset)
+    }
 
+    /// Unwrap a keyset's class keys into the cache
+    fn load_keys(&mut self, keyset: &Keyset) -> Result<()> {
         // Decrypt class keys using root key
-        for (class, encrypted_key) in keyset.class_keys {
-            let decrypted_key_bytes = self
-                .root_key
-                .derive_class_key(&class)?
-                .decrypt(&encrypted_key)?;
+        let mut keys = ClassKeyCache::default();
+        for (class, encrypted_key) in &keyset.class_keys {
+            let class_key = self.unwrap_class_key(class, encrypted_key)?;
+            keys.class_keys.insert(class.clone(), class_key);
+        }
 
-            if decrypted_key_bytes.len() != CLASS_KEY_LEN {
-                return Err(MimirError::Encryption(
-                    "Invalid class key length".to_string(),
-                ));
-            }
+        self.load_class_key_versions(keyset, &mut keys)?;
+        self.keys = RwLock::new(keys);
+        Ok(())
+    }
 
-            let mut class_key = [0u8; CLASS_KEY_LEN];
-            class_key.copy_from_slice(&decrypted_key_bytes);
+    /// Unwrap a class key stored in the keyset
+    fn unwrap_class_key(&self, class: &str, encrypted_key: &Ciphertext) -> Result<ClassKey> {
+        let decrypted_key_bytes = self
+            .root_key
+            .derive_class_key(class)?
+            .decrypt(encrypted_key)?;
 
-            self.class_keys.insert(class, ClassKey { key: class_key });
+        if decrypted_key_bytes.len() != CLASS_KEY_LEN {
+            return Err(MimirError::Encryption(
+                "Invalid class key length".to_string(),
+            ));
         }
 
+        let mut class_key = [0u8; CLASS_KEY_LEN];
+        class_key.copy_from_slice(&decrypted_key_bytes);
+        Ok(ClassKey { key: class_key })
+    }
+
+    /// Load the key versions of rotated classes, replacing their current keys
+    ///
+    /// Purged classes are loaded too, so their keys are not derived again.
+    fn load_class_key_versions(&self, keyset: &Keyset, keys: &mut ClassKeyCache) -> Result<()> {
+        keys.purged_classes
+            .extend(keyset.purged_classes.iter().cloned());
+        for (class, versions) in &keyset.class_key_versions {
+            for (version, encrypted_key) in &versions.keys {
+                let class_key = self.unwrap_class_key(class, encrypted_key)?;
+                if *version == versions.current {
+                    keys.class_keys.insert(class.clone(), class_key);
+                } else {
+                    keys.retiring_class_keys
+                        .entry(class.clone())
+                        .or_default()
+                        .insert(*version, class_key);
+                }
+            }
+            // Version 0 entries pin a key from before a root key rotation
+            keys.class_key_versions
+                .insert(class.clone(), versions.current);
+        }
         Ok(())
     }
 
-    /// Save keyset to disk
-    fn save_keyset(&self) -> Result<()> {
-        // Check if this is a password-based keyset by reading the existing keyset
-        let salt = if self.keyset_path.exists() {
-            let keyset_data = fs::read(&self.keyset_path)
-                .map_err(|e| MimirError::Encryption(format!("Failed to read keyset: {}", e)))?;
+    /// Wrap the key versions of rotated classes for the keyset
+    fn wrap_class_key_versions(&self, keys: &ClassKeyCache) -> Result<WrappedClassKeyVersions> {
+        let mut wrapped = HashMap::new();
+        let classes: std::collections::BTreeSet<&String> = keys
+            .class_key_versions
+            .keys()
+            .chain(keys.retiring_class_keys.keys())
+            .collect();
+        for class in classes {
+            let wrapping_key = self.root_key.derive_class_key(class)?;
+            let mut versions = ClassKeyVersions {
+                current: keys.class_key_version(class),
+                keys: BTreeMap::new(),
+            };
+            if let Some(current) = keys.class_keys.get(class) {
+                versions
+                    .keys
+                    .insert(versions.current, wrapping_key.encrypt(current.as_bytes())?);
+            }
+            for (version, key) in keys.retiring_class_keys.get(class).into_iter().flatten() {
+                versions
+                    .keys
+                    .insert(*version, wrapping_key.encrypt(key.as_bytes())?);
+            }
+            wrapped.insert(class.clone(), versions);
+        }
+        Ok(wrapped)
+    }
+
+    /// Read and parse the keyset file
+    fn read_keyset(&self) -> Result<Keyset> {
+        read_keyset_file(&self.keyset_path)
+    }
+
+    /// Write the keyset file atomically, creating its directory if needed
+    fn write_keyset(&self, keyset: &Keyset) -> Result<()> {
+        let keyset_data = serde_json::to_vec_pretty(keyset)
+            .map_err(|e| MimirError::Encryption(format!("Failed to serialize keyset: {}", e)))?;
+
+        // Ensure the parent directory exists before writing
+        let parent = self
+            .keyset_path
+            .parent()
+            .filter(|p| !p.as_os_str().is_empty());
+        if let Some(parent) = parent {
+            fs::create_dir_all(parent).map_err(|e| {
+                MimirError::Encryption(format!("Failed to create keyset directory: {}", e))
+            })?;
+        }
+
+        write_atomically(&self.keyset_path, &keyset_data)
+            .map_err(|e| MimirError::Encryption(format!("Failed to write keyset: {}", e)))
+    }
 
-            let keyset: Keyset = serde_json::from_slice(&keyset_data)
-                .map_err(|e| MimirError::Encryption(format!("Failed to parse keyset: {}", e)))?;
+    /// Save keyset to disk
+    fn save_keyset(&self, keys: &ClassKeyCache) -> Result<()> {
+        self.write_keyset(&self.build_keyset(keys)?)
+    }
 
-            keyset.salt
+    /// Build the keyset for the cached class keys under the current root key
+    fn build_keyset(&self, keys: &ClassKeyCache) -> Result<Keyset> {
+        // The password envelope is kept as it is on disk
+        let (version, salt, kdf, wrapped_root_key) = if self.keyset_path.exists() {
+            let keyset = self.read_keyset()?;
+            (
+                keyset.version,
+                keyset.salt,
+                keyset.kdf,
+                keyset.wrapped_root_key,
+            )
         } else {
-            None
+            (KEYSET_VERSION, None, None, None)
         };
 
         let mut encrypted_class_keys = HashMap::new();
@@ -463,7 +1270,7 @@ impl CryptoManager {
         // For keychain-based keysets, we encrypt and store the class keys
         if salt.is_none() {
             // Encrypt class keys with root key (keychain-based)
-            for (class, class_key) in &self.class_keys {
+            for (class, class_key) in &keys.class_keys {
                 let root_derived_key = self.root_key.derive_class_key(class)?;
                 let encrypted_key = root_derived_key.encrypt(class_key.as_bytes())?;
                 encrypted_class_keys.insert(class.clone(), encrypted_key);
@@ -471,90 +1278,129 @@ impl CryptoManager {
         }
         // For password-based keysets, class_keys remains empty since keys are derived on-demand
 
-        let keyset = Keyset {
-            version: 1,
+        Ok(Keyset {
+            version,
             salt,
+            kdf,
+            wrapped_root_key,
             class_keys: encrypted_class_keys,
+            // Rotated and pinned keys are not derived, so they are stored in both modes
+            class_key_versions: self.wrap_class_key_versions(keys)?,
+            purged_classes: keys.purged_classes.iter().cloned().collect(),
+            root_key_check: Some(self.root_key.check_value()),
             updated_at: chrono::Utc::now(),
-        };
+        })
+    }
 
-        let keyset_data = serde_json::to_vec_pretty(&keyset)
-            .map_err(|e| MimirError::Encryption(format!("Failed to serialize keyset: {}", e)))?;
+    /// Path of the root key rotation journal
+    fn rotation_journal_path(&self) -> std::path::PathBuf {
+        let mut path = self.keyset_path.as_os_str().to_owned();
+        path.push(".rotation");
+        path.into()
+    }
 
-        // Ensure the parent directory exists before writing
-        if let Some(parent) = self.keyset_path.parent() {
-            fs::create_dir_all(parent).map_err(|e| {
-                MimirError::Encryption(format!("Failed to create keyset directory: {}", e))
-            })?;
-        }
+    /// Read the rotation journal, if a rotation is unfinished
+    fn read_rotation_journal(&self) -> Result<Option<RootRotationJournal>> {
+        let journal_data = match fs::read(self.rotation_journal_path()) {
+            Ok(data) => data,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => {
+                return Err(MimirError::Encryption(format!(
+                    "Failed to read rotation journal: {}",
+                    e
+                )))
+            }
+        };
+        serde_json::from_slice(&journal_data)
+            .map(Some)
+            .map_err(|e| MimirError::Encryption(format!("Failed to parse rotation journal: {}", e)))
+    }
 
-        fs::write(&self.keyset_path, keyset_data)
-            .map_err(|e| MimirError::Encryption(format!("Failed to write keyset: {}", e)))?;
+    /// The old and new root keys of a journaled rotation
+    ///
+    /// Works whichever of the two this manager holds.
+    fn journal_root_keys(&self, journal: &RootRotationJournal) -> Result<RootKeyPair> {
+        let wrapping_key = self.root_key.derive_class_key(ROOT_ROTATION_CONTEXT)?;
+        let current = RootKey {
+            key: *self.root_key.as_bytes(),
+        };
+        if let Ok(new_root_key) = wrapping_key.decrypt(&journal.new_root_key) {
+            return Ok((current, RootKey::from_slice(&Zeroizing::new(new_root_key))?));
+        }
+        if let Ok(old_root_key) = wrapping_key.decrypt(&journal.old_root_key) {
+            return Ok((RootKey::from_slice(&Zeroizing::new(old_root_key))?, current));
+        }
+        Err(MimirError::Encryption(
+            "The rotation journal does not belong to this root key".to_string(),
+        ))
+    }
 
-        Ok(())
+    /// Swap to the other root key of an unfinished rotation
+    ///
+    /// Returns false if no rotation is journaled.
+    fn switch_to_journal_root(&mut self) -> Result<bool> {
+        let journal = match self.read_rotation_journal()? {
+            Some(journal) => journal,
+            None => return Ok(false),
+        };
+        let (old_root_key, new_root_key) = self.journal_root_keys(&journal)?;
+        self.root_key = if self.root_key.as_bytes() == old_root_key.as_bytes() {
+            new_root_key
+        } else {
+            old_root_key
+        };
+        Ok(true)
     }
 
     /// Create initial keyset
     fn create_keyset(&mut self) -> Result<()> {
-        let keyset = Keyset {
-            version: 1,
+        self.write_keyset(&Keyset {
+            version: KEYSET_VERSION,
             salt: None, // No salt for keychain-based encryption
+            kdf: None,
+            wrapped_root_key: None,
             class_keys: HashMap::new(),
+            class_key_versions: HashMap::new(),
+            purged_classes: BTreeSet::new(),
+            root_key_check: Some(self.root_key.check_value()),
             updated_at: chrono::Utc::now(),
-        };
-
-        let keyset_data = serde_json::to_vec_pretty(&keyset)
-            .map_err(|e| MimirError::Encryption(format!("Failed to serialize keyset: {}", e)))?;
-
-        // Ensure the parent directory exists before writing
-        if let Some(parent) = self.keyset_path.parent() {
-            fs::create_dir_all(parent).map_err(|e| {
-                MimirError::Encryption(format!("Failed to create keyset directory: {}", e))
-            })?;
-        }
-
-        fs::write(&self.keyset_path, keyset_data)
-            .map_err(|e| MimirError::Encryption(format!("Failed to write keyset: {}", e)))?;
-
-        Ok(())
+        })
     }
 
     /// Create initial keyset with password-based encryption
+    ///
+    /// The root key is random and stored wrapped with a key derived from the
+    /// password with Argon2id.
     fn create_keyset_with_password(&mut self, password: &str) -> Result<()> {
-        // Generate salt and derive root key from password
         let salt = RootKey::generate_salt()?;
-        self.root_key = RootKey::from_password(password, &salt)?;
+        let params = Argon2Params::default();
+        let password_key = params.derive_key(password, &salt)?;
+        self.root_key = RootKey::new()?;
 
-        let keyset = Keyset {
-            version: 1,
+        self.write_keyset(&Keyset {
+            version: KEYSET_VERSION_ARGON2ID,
             salt: Some(salt),
+            kdf: Some(params),
+            wrapped_root_key: Some(password_key.encrypt(self.root_key.as_bytes())?),
             class_keys: HashMap::new(),
+            class_key_versions: HashMap::new(),
+            purged_classes: BTreeSet::new(),
+            root_key_check: Some(self.root_key.check_value()),
             updated_at: chrono::Utc::now(),
-        };
-
-        let keyset_data = serde_json::to_vec_pretty(&keyset)
-            .map_err(|e| MimirError::Encryption(format!("Failed to serialize keyset: {}", e)))?;
-
-        // Ensure the parent directory exists before writing
-        if let Some(parent) = self.keyset_path.parent() {
-            fs::create_dir_all(parent).map_err(|e| {
-                MimirError::Encryption(format!("Failed to create keyset directory: {}", e))
-            })?;
-        }
-
-        fs::write(&self.keyset_path, keyset_data)
-            .map_err(|e| MimirError::Encryption(format!("Failed to write keyset: {}", e)))?;
-
+        })?;
+        *self
+            .password_key
+            .get_mut()
+            .unwrap_or_else(PoisonError::into_inner) = Some(password_key);
         Ok(())
     }
 
     /// Load keyset with password-based decryption
+    ///
+    /// A version 1 keyset is unlocked with PBKDF2 and an Argon2id envelope
+    /// for the same root key is prepared for `complete_kdf_upgrade`.
     fn load_keyset_with_password(&mut self, password: &str) -> Result<()> {
-        let keyset_data = fs::read(&self.keyset_path)
-            .map_err(|e| MimirError::Encryption(format!("Failed to read keyset: {}", e)))?;
-
-        let keyset: Keyset = serde_json::from_slice(&keyset_data)
-            .map_err(|e| MimirError::Encryption(format!("Failed to parse keyset: {}", e)))?;
+        let keyset = self.read_keyset()?;
 
         // Check if this is a password-based keyset
         let salt = keyset.salt.ok_or_else(|| {
@@ -563,15 +1409,113 @@ impl CryptoManager {
             )
         })?;
 
-        // Derive root key from password and salt
-        self.root_key = RootKey::from_password(password, &salt)?;
+        match keyset.version {
+            KEYSET_VERSION_PBKDF2 => {
+                self.root_key = RootKey::from_password(password, &salt)?;
+                if self.root_key.contradicts_check(&keyset) {
+                    return Err(MimirError::WrongPassword);
+                }
+
+                let salt = RootKey::generate_salt()?;
+                let params = Argon2Params::default();
+                let password_key = params.derive_key(password, &salt)?;
+                let wrapped_root_key = password_key.encrypt(self.root_key.as_bytes())?;
+                *self
+                    .kdf_upgrade
+                    .get_mut()
+                    .unwrap_or_else(PoisonError::into_inner) = Some(KdfUpgrade {
+                    salt,
+                    params,
+                    password_key,
+                    wrapped_root_key,
+                });
+            }
+            KEYSET_VERSION_ARGON2ID => {
+                let (params, wrapped_root_key) = match (keyset.kdf, &keyset.wrapped_root_key) {
+                    (Some(params), Some(wrapped_root_key)) => (params, wrapped_root_key),
+                    _ => {
+                        return Err(MimirError::CorruptKeyset(
+                            "missing its Argon2 parameters or wrapped root key".to_string(),
+                        ))
+                    }
+                };
+                let password_key = params.derive_key(password, &salt)?;
+                let root_key = Zeroizing::new(
+                    password_key
+                        .decrypt(wrapped_root_key)
+                        .map_err(|_| MimirError::WrongPassword)?,
+                );
+                // The password opened the envelope, so a mismatch is damage, not a typo
+                self.root_key = RootKey::from_slice(&root_key).map_err(|_| {
+                    MimirError::CorruptKeyset("wrapped root key has the wrong length".to_string())
+                })?;
+                if self.root_key.contradicts_check(&keyset) {
+                    return Err(MimirError::CorruptKeyset(
+                        "root key does not match its check value".to_string(),
+                    ));
+                }
+                *self
+                    .password_key
+                    .get_mut()
+                    .unwrap_or_else(PoisonError::into_inner) = Some(password_key);
+            }
+            version => {
+                return Err(MimirError::Encryption(format!(
+                    "Keyset version {} is not supported",
+                    version
+                )))
+            }
+        }
 
         // For password-based keysets, class keys are derived from the root key, not stored encrypted
-        // So we don't need to decrypt them - they'll be derived on-demand when needed
+        // So we don't need to decrypt them - they'll be derived on-demand when needed.
+        // Only keys produced by rotation are stored.
+        let mut keys = ClassKeyCache::default();
+        self.load_class_key_versions(&keyset, &mut keys)?;
+        self.keys = RwLock::new(keys);
         Ok(())
     }
 }
 
+/// Read and parse a keyset file
+fn read_keyset_file(path: &Path) -> Result<Keyset> {
+    let keyset_data = fs::read(path)
+        .map_err(|e| MimirError::Encryption(format!("Failed to read keyset: {}", e)))?;
+
+    serde_json::from_slice(&keyset_data).map_err(|e| MimirError::CorruptKeyset(e.to_string()))
+}
+
+/// Replace a file through a synced temporary file
+///
+/// A crash leaves either the old contents or the new ones.
+fn write_atomically(path: &Path, data: &[u8]) -> std::io::Result<()> {
+    let mut temp_path = path.as_os_str().to_owned();
+    temp_path.push(".tmp");
+    let write_temp = || -> std::io::Result<()> {
+        let mut file = fs::File::create(&temp_path)?;
+        file.write_all(data)?;
+        file.sync_all()
+    };
+    if let Err(e) = write_temp().and_then(|()| fs::rename(&temp_path, path)) {
+        let _ = fs::remove_file(&temp_path);
+        return Err(e);
+    }
+
+    // Persist the rename itself
+    #[cfg(unix)]
+    if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        if let Ok(dir) = fs::File::open(parent) {
+            let _ = dir.sync_all();
+        }
+    }
+    Ok(())
+}
+
+/// Error for an operation on a class whose key has been purged
+fn purged_class_error(class: &str) -> MimirError {
+    MimirError::Encryption(format!("Class '{}' has been purged", class))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -613,7 +1557,7 @@ mod tests {
         let keyset_path = temp_dir.path().join("keyset.json");
 
         let password = "test-password-for-ci";
-        let mut crypto_manager = CryptoManager::with_password(&keyset_path, password).unwrap();
+        let crypto_manager = CryptoManager::with_password(&keyset_path, password).unwrap();
 
         let plaintext = b"Hello, secure world!";
         let ciphertext = crypto_manager.encrypt("personal", plaintext).unwrap();
@@ -622,29 +1566,66 @@ mod tests {
         assert_eq!(plaintext, decrypted.as_slice());
     }
 
+    #[test]
+    fn test_blind_index() {
+        let root_key = RootKey::new().unwrap();
+        let personal = root_key.derive_class_key("personal").unwrap();
+        let work = root_key.derive_class_key("work").unwrap();
+
+        // Deterministic per class, distinct across classes and terms
+        assert_eq!(
+            personal.blind_index(b"mimir"),
+            personal.blind_index(b"mimir")
+        );
+        assert_ne!(
+            personal.blind_index(b"mimir"),
+            personal.blind_index(b"odin")
+        );
+        assert_ne!(personal.blind_index(b"mimir"), work.blind_index(b"mimir"));
+    }
+
     #[test]
     fn test_class_key_rotation() {
         let temp_dir = TempDir::new().unwrap();
         let keyset_path = temp_dir.path().join("keyset.json");
 
         let password = "test-password-for-ci";
-        let mut crypto_manager = CryptoManager::with_password(&keyset_path, password).unwrap();
+        let crypto_manager = CryptoManager::with_password(&keyset_path, password).unwrap();
 
         // Encrypt some data
         let plaintext = b"Test data";
         let ciphertext = crypto_manager.encrypt("personal", plaintext).unwrap();
 
         // Rotate class key
-        crypto_manager.rotate_class_key("personal").unwrap();
+        assert_eq!(crypto_manager.rotate_class_key("personal").unwrap(), 1);
+        assert_eq!(crypto_manager.class_key_version("personal"), 1);
 
-        // Old ciphertext should not decrypt with new key
-        let decrypt_result = crypto_manager.decrypt("personal", &ciphertext);
-        assert!(decrypt_result.is_err());
+        // Old ciphertext still decrypts until the old key is retired
+        let old_decrypted = crypto_manager.decrypt("personal", &ciphertext).unwrap();
+        assert_eq!(plaintext, old_decrypted.as_slice());
 
         // New encryption should work
         let new_ciphertext = crypto_manager.encrypt("personal", plaintext).unwrap();
         let new_decrypted = crypto_manager.decrypt("personal", &new_ciphertext).unwrap();
         assert_eq!(plaintext, new_decrypted.as_slice());
+
+        // Both versions survive a reload
+        let reloaded = CryptoManager::with_password(&keyset_path, password).unwrap();
+        assert_eq!(reloaded.class_key_version("personal"), 1);
+        assert_eq!(reloaded.retiring_class_key_versions("personal"), vec![0]);
+        assert!(reloaded.decrypt("personal", &ciphertext).is_ok());
+        assert!(reloaded.decrypt("personal", &new_ciphertext).is_ok());
+
+        // Old ciphertext should not decrypt once the old key is retired
+        assert_eq!(
+            crypto_manager.retire_class_keys("personal").unwrap(),
+            vec![0]
+        );
+        let decrypt_result = crypto_manager.decrypt("personal", &ciphertext);
+        assert!(decrypt_result.is_err());
+        let reloaded = CryptoManager::with_password(&keyset_path, password).unwrap();
+        assert!(reloaded.decrypt("personal", &ciphertext).is_err());
+        assert!(reloaded.decrypt("personal", &new_ciphertext).is_ok());
     }
 
     #[test]
@@ -653,17 +1634,71 @@ mod tests {
         let keyset_path = temp_dir.path().join("keyset.json");
 
         let password = "test-password-for-ci";
-        let mut crypto_manager = CryptoManager::with_password(&keyset_path, password).unwrap();
+        let crypto_manager = CryptoManager::with_password(&keyset_path, password).unwrap();
 
         // Create some encrypted data
         let plaintext = b"Sensitive data";
-        let _ciphertext = crypto_manager.encrypt("personal", plaintext).unwrap();
+        let ciphertext = crypto_manager.encrypt("personal", plaintext).unwrap();
 
         // Purge the class
         crypto_manager.purge_class("personal").unwrap();
 
         // Should not be able to decrypt anymore
-        assert!(!crypto_manager.class_keys.contains_key("personal"));
+        assert!(!crypto_manager
+            .read_keys()
+            .class_keys
+            .contains_key("personal"));
+        assert_eq!(crypto_manager.class_key_version("personal"), 1);
+
+        // New data gets a random key, not the old one derived again
+        let new_ciphertext = crypto_manager.encrypt("personal", plaintext).unwrap();
+        assert!(crypto_manager.decrypt("personal", &ciphertext).is_err());
+
+        // The purge survives reopening the keyset
+        let reloaded = CryptoManager::with_password(&keyset_path, password).unwrap();
+        assert!(reloaded.decrypt("personal", &ciphertext).is_err());
+        assert_eq!(
+            reloaded.decrypt("personal", &new_ciphertext).unwrap(),
+            plaintext
+        );
+
+        // A class purged before any new data is written stays unreadable too
+        reloaded.purge_class("personal").unwrap();
+        let reloaded = CryptoManager::with_password(&keyset_path, password).unwrap();
+        assert!(reloaded.decrypt("personal", &new_ciphertext).is_err());
+    }
+
+    #[test]
+    fn test_shared_between_threads() {
+        let temp_dir = TempDir::new().unwrap();
+        let keyset_path = temp_dir.path().join("keyset.json");
+        let crypto_manager =
+            CryptoManager::with_password(&keyset_path, "test-password-for-ci").unwrap();
+
+        // Threads fill the key cache concurrently and read each other's data
+        let ciphertexts: Vec<(&str, Ciphertext)> = std::thread::scope(|scope| {
+            ["personal", "work", "health", "personal"]
+                .into_iter()
+                .map(|class| {
+                    let crypto_manager = &crypto_manager;
+                    scope.spawn(move || (class, crypto_manager.encrypt(class, b"shared").unwrap()))
+                })
+                .collect::<Vec<_>>()
+                .into_iter()
+                .map(|handle| handle.join().unwrap())
+                .collect()
+        });
+        std::thread::scope(|scope| {
+            for (class, ciphertext) in &ciphertexts {
+                let crypto_manager = &crypto_manager;
+                scope.spawn(move || {
+                    assert_eq!(
+                        crypto_manager.decrypt(class, ciphertext).unwrap(),
+                        b"shared"
+                    );
+                });
+            }
+        });
     }
 
     #[test]
@@ -675,7 +1710,7 @@ mod tests {
         let test_data = "Sensitive information encrypted with password";
 
         // Create crypto manager with password
-        let mut crypto_manager = CryptoManager::with_password(&keyset_path, password).unwrap();
+        let crypto_manager = CryptoManager::with_password(&keyset_path, password).unwrap();
 
         // Encrypt data
         let ciphertext = crypto_manager
@@ -703,7 +1738,7 @@ mod tests {
 
         // Create first manager and encrypt data
         let ciphertext = {
-            let mut crypto_manager = CryptoManager::with_password(&keyset_path, password).unwrap();
+            let crypto_manager = CryptoManager::with_password(&keyset_path, password).unwrap();
             crypto_manager
                 .encrypt("work", test_data.as_bytes())
                 .unwrap()
@@ -711,7 +1746,7 @@ mod tests {
 
         // Create second manager and decrypt data
         {
-            let mut crypto_manager = CryptoManager::with_password(&keyset_path, password).unwrap();
+            let crypto_manager = CryptoManager::with_password(&keyset_path, password).unwrap();
             let decrypted = crypto_manager.decrypt("work", &ciphertext).unwrap();
             assert_eq!(String::from_utf8(decrypted).unwrap(), test_data);
         }
@@ -721,6 +1756,265 @@ mod tests {
         let keyset_data = fs::read(&keyset_path).unwrap();
         let keyset: Keyset = serde_json::from_slice(&keyset_data).unwrap();
         assert!(keyset.salt.is_some());
+        assert_eq!(keyset.version, KEYSET_VERSION_ARGON2ID);
+        assert_eq!(keyset.kdf, Some(Argon2Params::default()));
+        assert!(keyset.wrapped_root_key.is_some());
+
+        // A wrong password does not unwrap the root key
+        assert!(matches!(
+            CryptoManager::with_password(&keyset_path, "wrong-password"),
+            Err(MimirError::WrongPassword)
+        ));
+    }
+
+    #[test]
+    fn test_corrupt_keyset_errors() {
+        let temp_dir = TempDir::new().unwrap();
+        let keyset_path = temp_dir.path().join("keyset.json");
+        CryptoManager::with_password(&keyset_path, "right").unwrap();
+        let original = fs::read(&keyset_path).unwrap();
+
+        // A check value that no longer matches is damage, not a wrong password
+        let mut keyset: Keyset = serde_json::from_slice(&original).unwrap();
+        keyset.root_key_check = Some([0; 32]);
+        fs::write(&keyset_path, serde_json::to_vec(&keyset).unwrap()).unwrap();
+        assert!(matches!(
+            CryptoManager::with_password(&keyset_path, "right"),
+            Err(MimirError::CorruptKeyset(_))
+        ));
+
+        keyset.kdf = None;
+        fs::write(&keyset_path, serde_json::to_vec(&keyset).unwrap()).unwrap();
+        assert!(matches!(
+            CryptoManager::with_password(&keyset_path, "right"),
+            Err(MimirError::CorruptKeyset(_))
+        ));
+
+        fs::write(&keyset_path, &original[..original.len() / 2]).unwrap();
+        assert!(matches!(
+            CryptoManager::with_password(&keyset_path, "right"),
+            Err(MimirError::CorruptKeyset(_))
+        ));
+    }
+
+    #[test]
+    fn test_key_file() {
+        let temp_dir = TempDir::new().unwrap();
+        let keyset_path = temp_dir.path().join("keyset.json");
+        let key_file = temp_dir.path().join("vault.key");
+
+        let crypto_manager = CryptoManager::with_password(&keyset_path, "unused").unwrap();
+        let ciphertext = crypto_manager.encrypt("work", b"door code").unwrap();
+
+        // Hex and recovery phrase key files both unlock the vault
+        fs::write(
+            &key_file,
+            format!("{}\n", hex::encode(crypto_manager.root_key.as_bytes())),
+        )
+        .unwrap();
+        let unlocked = CryptoManager::with_key_file(&keyset_path, &key_file).unwrap();
+        assert_eq!(unlocked.decrypt("work", &ciphertext).unwrap(), b"door code");
+
+        fs::write(&key_file, &*crypto_manager.recovery_phrase().unwrap()).unwrap();
+        assert!(CryptoManager::with_key_file(&keyset_path, &key_file).is_ok());
+
+        fs::write(&key_file, hex::encode([7u8; ROOT_KEY_LEN])).unwrap();
+        assert!(CryptoManager::with_key_file(&keyset_path, &key_file).is_err());
+        fs::write(&key_file, "").unwrap();
+        assert!(CryptoManager::with_key_file(&keyset_path, &key_file).is_err());
+    }
+
+    #[test]
+    fn test_change_password() {
+        let temp_dir = TempDir::new().unwrap();
+        let keyset_path = temp_dir.path().join("keyset.json");
+
+        let crypto_manager = CryptoManager::with_password(&keyset_path, "old-password").unwrap();
+        let ciphertext = crypto_manager.encrypt("health", b"blood type O+").unwrap();
+        let rotated = crypto_manager.rotate_class_key("work").unwrap();
+        let work = crypto_manager.encrypt("work", b"badge 4411").unwrap();
+        let db_key = crypto_manager.get_db_key_bytes();
+
+        assert!(crypto_manager.change_password("").is_err());
+        crypto_manager.change_password("new-password").unwrap();
+        assert!(!temp_dir.path().join("keyset.json.tmp").exists());
+
+        // The old password no longer unlocks the keyset
+        assert!(CryptoManager::with_password(&keyset_path, "old-password").is_err());
+
+        // The root key and class keys are unchanged
+        let reloaded = CryptoManager::with_password(&keyset_path, "new-password").unwrap();
+        assert_eq!(reloaded.get_db_key_bytes(), db_key);
+        assert_eq!(
+            reloaded.decrypt("health", &ciphertext).unwrap(),
+            b"blood type O+"
+        );
+        assert_eq!(reloaded.class_key_version("work"), rotated);
+        assert_eq!(reloaded.decrypt("work", &work).unwrap(), b"badge 4411");
+    }
+
+    #[test]
+    fn test_root_key_rotation() {
+        let temp_dir = TempDir::new().unwrap();
+        let keyset_path = temp_dir.path().join("keyset.json");
+
+        let crypto_manager = CryptoManager::with_password(&keyset_path, "password").unwrap();
+        let health = crypto_manager.encrypt("health", b"blood type O+").unwrap();
+        let rotated = crypto_manager.rotate_class_key("work").unwrap();
+        let work = crypto_manager.encrypt("work", b"badge 4411").unwrap();
+        let old_db_key = crypto_manager.get_db_key_bytes();
+
+        let rotation = crypto_manager.begin_root_rotation().unwrap();
+        assert_eq!(*rotation.old_db_key, old_db_key);
+        assert_ne!(*rotation.new_db_key, old_db_key);
+        assert!(crypto_manager.root_rotation_pending());
+        drop(crypto_manager);
+
+        // An interrupted rotation resumes with the journaled root key
+        let mut crypto_manager = CryptoManager::with_password(&keyset_path, "password").unwrap();
+        assert_eq!(crypto_manager.get_db_key_bytes(), old_db_key);
+        let resumed = crypto_manager.begin_root_rotation().unwrap();
+        assert_eq!(*resumed.new_db_key, *rotation.new_db_key);
+
+        crypto_manager
+            .finish_root_rotation(&["health".to_string(), "work".to_string()])
+            .unwrap();
+        assert_eq!(crypto_manager.get_db_key_bytes(), *rotation.new_db_key);
+        assert!(!crypto_manager.root_rotation_pending());
+        assert!(crypto_manager.finish_root_rotation(&[]).is_err());
+
+        // Existing ciphertexts stay readable under the new root key
+        let reloaded = CryptoManager::with_password(&keyset_path, "password").unwrap();
+        assert_eq!(reloaded.get_db_key_bytes(), *rotation.new_db_key);
+        assert_eq!(
+            reloaded.decrypt("health", &health).unwrap(),
+            b"blood type O+"
+        );
+        assert_eq!(reloaded.class_key_version("health"), 0);
+        assert_eq!(reloaded.decrypt("work", &work).unwrap(), b"badge 4411");
+        assert_eq!(reloaded.class_key_version("work"), rotated);
+
+        // Classes without data get keys derived from the new root key
+        let personal = reloaded.encrypt("personal", b"new data").unwrap();
+        assert!(crypto_manager.decrypt("personal", &personal).is_ok());
+    }
+
+    #[test]
+    fn test_recovery_phrase() {
+        let temp_dir = TempDir::new().unwrap();
+        let keyset_path = temp_dir.path().join("keyset.json");
+
+        let crypto_manager = CryptoManager::with_password(&keyset_path, "forgotten").unwrap();
+        let ciphertext = crypto_manager.encrypt("health", b"blood type O+").unwrap();
+        let phrase = crypto_manager.recovery_phrase().unwrap();
+        assert_eq!(phrase.split(' ').count(), recovery::PHRASE_WORDS);
+
+        assert!(CryptoManager::verify_recovery_phrase(&keyset_path, &phrase).unwrap());
+        let other = recovery::encode_phrase(RootKey::new().unwrap().as_bytes());
+        assert!(!CryptoManager::verify_recovery_phrase(&keyset_path, &other).unwrap());
+        assert!(CryptoManager::with_recovery_phrase(&keyset_path, &other).is_err());
+
+        // The phrase unlocks the vault and sets a new password
+        let recovered = CryptoManager::with_recovery_phrase(&keyset_path, &phrase).unwrap();
+        assert_eq!(
+            recovered.get_db_key_bytes(),
+            crypto_manager.get_db_key_bytes()
+        );
+        assert!(recovered.restore_to_keychain().is_err());
+        recovered.convert_to_password("remembered").unwrap();
+        assert!(CryptoManager::with_password(&keyset_path, "forgotten").is_err());
+        let reopened = CryptoManager::with_password(&keyset_path, "remembered").unwrap();
+        assert_eq!(
+            reopened.decrypt("health", &ciphertext).unwrap(),
+            b"blood type O+"
+        );
+
+        // Keysets without a root key check fall back to their wrapped class keys
+        reopened.rotate_class_key("work").unwrap();
+        let mut keyset: serde_json::Value =
+            serde_json::from_slice(&fs::read(&keyset_path).unwrap()).unwrap();
+        keyset.as_object_mut().unwrap().remove("root_key_check");
+        fs::write(&keyset_path, serde_json::to_vec(&keyset).unwrap()).unwrap();
+        assert!(CryptoManager::verify_recovery_phrase(&keyset_path, &phrase).unwrap());
+        assert!(!CryptoManager::verify_recovery_phrase(&keyset_path, &other).unwrap());
+    }
+
+    #[test]
+    fn test_root_key_shares() {
+        let temp_dir = TempDir::new().unwrap();
+        let keyset_path = temp_dir.path().join("keyset.json");
+
+        let crypto_manager = CryptoManager::with_password(&keyset_path, "family").unwrap();
+        let ciphertext = crypto_manager
+            .encrypt("personal", b"safe combination")
+            .unwrap();
+        let shares = crypto_manager.root_key_shares(2, 3).unwrap();
+        assert_eq!(shares.len(), 3);
+
+        // Any two shares unlock the vault
+        let chosen: Vec<RootKeyShare> = [&shares[2], &shares[0]]
+            .iter()
+            .map(|share| RootKeyShare::from_text(&share.to_text()).unwrap())
+            .collect();
+        assert!(CryptoManager::verify_root_key_shares(&keyset_path, &chosen).unwrap());
+        let recovered = CryptoManager::with_root_key_shares(&keyset_path, &chosen).unwrap();
+        assert_eq!(
+            recovered.decrypt("personal", &ciphertext).unwrap(),
+            b"safe combination"
+        );
+        assert!(CryptoManager::with_root_key_shares(&keyset_path, &chosen[..1]).is_err());
+
+        // Shares of another vault's key are turned away
+        let other = shamir::split(RootKey::new().unwrap().as_bytes(), 2, 2).unwrap();
+        assert!(!CryptoManager::verify_root_key_shares(&keyset_path, &other).unwrap());
+        assert!(CryptoManager::with_root_key_shares(&keyset_path, &other).is_err());
+    }
+
+    #[test]
+    fn test_pbkdf2_keyset_upgrade() {
+        let temp_dir = TempDir::new().unwrap();
+        let keyset_path = temp_dir.path().join("keyset.json");
+        let password = "legacy-password";
+
+        // A version 1 keyset derives the root key straight from the password
+        let salt = RootKey::generate_salt().unwrap();
+        let legacy = Keyset {
+            version: KEYSET_VERSION_PBKDF2,
+            salt: Some(salt),
+            kdf: None,
+            wrapped_root_key: None,
+            class_keys: HashMap::new(),
+            class_key_versions: HashMap::new(),
+            purged_classes: BTreeSet::new(),
+            root_key_check: None,
+            updated_at: chrono::Utc::now(),
+        };
+        fs::write(&keyset_path, serde_json::to_vec(&legacy).unwrap()).unwrap();
+        let db_key = RootKey::from_password(password, &salt)
+            .unwrap()
+            .derive_db_key_bytes();
+
+        let crypto_manager = CryptoManager::with_password(&keyset_path, password).unwrap();
+        assert!(crypto_manager.needs_kdf_upgrade());
+        assert_eq!(crypto_manager.get_db_key_bytes(), db_key);
+        let ciphertext = crypto_manager.encrypt("personal", b"before").unwrap();
+
+        assert!(crypto_manager.complete_kdf_upgrade().unwrap());
+        assert!(!crypto_manager.needs_kdf_upgrade());
+        assert!(!crypto_manager.complete_kdf_upgrade().unwrap());
+        let keyset: Keyset = serde_json::from_slice(&fs::read(&keyset_path).unwrap()).unwrap();
+        assert_eq!(keyset.version, KEYSET_VERSION_ARGON2ID);
+        assert_ne!(keyset.salt, Some(salt));
+
+        // The root key is unchanged, so existing data still decrypts
+        let upgraded = CryptoManager::with_password(&keyset_path, password).unwrap();
+        assert!(!upgraded.needs_kdf_upgrade());
+        assert_eq!(upgraded.get_db_key_bytes(), db_key);
+        assert_eq!(
+            upgraded.decrypt("personal", &ciphertext).unwrap(),
+            b"before"
+        );
+        assert!(CryptoManager::with_password(&keyset_path, "wrong-password").is_err());
     }
 
     #[test]
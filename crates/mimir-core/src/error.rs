@@ -12,6 +12,12 @@ pub enum MimirError {
     #[error("Encryption error: {0}")]
     Encryption(String),
 
+    #[error("Wrong password")]
+    WrongPassword,
+
+    #[error("Corrupt keyset: {0}")]
+    CorruptKeyset(String),
+
     #[error("Guardrails error: {0}")]
     Guardrails(String),
 
@@ -85,6 +91,10 @@ mod tests {
 
         let server_error = MimirError::ServerError("Bind failed".to_string());
         assert_eq!(server_error.to_string(), "Server error: Bind failed");
+
+        assert_eq!(MimirError::WrongPassword.to_string(), "Wrong password");
+        let keyset_error = MimirError::CorruptKeyset("missing salt".to_string());
+        assert_eq!(keyset_error.to_string(), "Corrupt keyset: missing salt");
     }
 
     #[test]
@@ -6,11 +6,17 @@
 pub mod config;
 pub mod crypto;
 pub mod error;
+pub mod recovery;
+pub mod shamir;
 pub mod types;
+pub mod unlock;
 
 #[cfg(any(test, feature = "test-utils"))]
 pub mod test_utils;
 
-pub use config::{get_default_app_dir, get_default_config_path, get_default_keyset_path, Config};
+pub use config::{
+    get_default_app_dir, get_default_config_path, get_default_keyset_path, Config, VaultProfile,
+    DEFAULT_VAULT,
+};
 pub use error::{MimirError, Result};
 pub use types::*;
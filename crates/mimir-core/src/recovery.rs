@@ -0,0 +1,159 @@
+//! Recovery phrases for the root key
+//!
+//! A root key is written as 24 words from the BIP39 English word list: its
+//! 256 bits followed by the first 8 bits of their SHA-256 are read as 11-bit
+//! word indexes. The checksum catches a mistyped, swapped or missing word
+//! before a phrase is ever tried against a keyset.
+
+use crate::{
+    crypto::{RootKeyBytes, ROOT_KEY_LEN},
+    error::MimirError,
+    Result,
+};
+use ring::digest;
+use zeroize::Zeroizing;
+
+/// Number of words in a recovery phrase
+pub const PHRASE_WORDS: usize = 24;
+
+/// Bits encoded by each word
+const BITS_PER_WORD: usize = 11;
+
+/// The BIP39 English word list, sorted, one word per line
+const WORDLIST: &str = include_str!("bip39_english.txt");
+
+/// First byte of the key's SHA-256, whose bits follow the key in the phrase
+fn checksum(key: &[u8]) -> u8 {
+    digest::digest(&digest::SHA256, key).as_ref()[0]
+}
+
+/// Index of a word in the list
+///
+/// As in BIP39, the first four letters identify a word, so a unique prefix of
+/// at least four letters is accepted too.
+fn word_index(word: &str) -> Option<usize> {
+    WORDLIST
+        .lines()
+        .position(|w| w == word || (word.len() >= 4 && w.starts_with(word)))
+}
+
+/// Write a root key as a recovery phrase
+pub fn encode_phrase(key: &[u8; ROOT_KEY_LEN]) -> Zeroizing<String> {
+    let mut bytes = Zeroizing::new([0u8; ROOT_KEY_LEN + 1]);
+    bytes[..ROOT_KEY_LEN].copy_from_slice(key);
+    bytes[ROOT_KEY_LEN] = checksum(key);
+
+    let mut phrase = Zeroizing::new(String::new());
+    for word in 0..PHRASE_WORDS {
+        let index = (0..BITS_PER_WORD).fold(0, |index, bit| {
+            let pos = word * BITS_PER_WORD + bit;
+            (index << 1) | usize::from((bytes[pos / 8] >> (7 - pos % 8)) & 1)
+        });
+        if word > 0 {
+            phrase.push(' ');
+        }
+        phrase.push_str(WORDLIST.lines().nth(index).unwrap_or_default());
+    }
+    phrase
+}
+
+/// Read a root key back from a recovery phrase
+///
+/// Case and whitespace do not matter.
+pub fn decode_phrase(phrase: &str) -> Result<RootKeyBytes> {
+    let words: Vec<&str> = phrase.split_whitespace().collect();
+    if words.len() != PHRASE_WORDS {
+        return Err(MimirError::Encryption(format!(
+            "A recovery phrase has {} words, not {}",
+            PHRASE_WORDS,
+            words.len()
+        )));
+    }
+
+    let mut bytes = Zeroizing::new([0u8; ROOT_KEY_LEN + 1]);
+    for (position, word) in words.iter().enumerate() {
+        let word = Zeroizing::new(word.to_lowercase());
+        let index = word_index(&word).ok_or_else(|| {
+            MimirError::Encryption(format!(
+                "Word {} of the recovery phrase is not in the word list",
+                position + 1
+            ))
+        })?;
+        for bit in 0..BITS_PER_WORD {
+            if index >> (BITS_PER_WORD - 1 - bit) & 1 == 1 {
+                let pos = position * BITS_PER_WORD + bit;
+                bytes[pos / 8] |= 1 << (7 - pos % 8);
+            }
+        }
+    }
+
+    let mut key = Zeroizing::new([0u8; ROOT_KEY_LEN]);
+    key.copy_from_slice(&bytes[..ROOT_KEY_LEN]);
+    if checksum(&*key) != bytes[ROOT_KEY_LEN] {
+        return Err(MimirError::Encryption(
+            "The recovery phrase checksum does not match; check the words".to_string(),
+        ));
+    }
+    Ok(key)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_wordlist() {
+        let words: Vec<&str> = WORDLIST.lines().collect();
+        assert_eq!(words.len(), 1 << BITS_PER_WORD);
+        assert!(words.windows(2).all(|pair| pair[0] < pair[1]));
+    }
+
+    #[test]
+    fn test_bip39_vectors() {
+        assert_eq!(
+            encode_phrase(&[0; ROOT_KEY_LEN]).as_str(),
+            format!("{}art", "abandon ".repeat(23))
+        );
+        assert_eq!(
+            encode_phrase(&[0x7f; ROOT_KEY_LEN]).as_str(),
+            "legal winner thank year wave sausage worth useful legal winner thank year \
+             wave sausage worth useful legal winner thank year wave sausage worth title"
+        );
+        assert_eq!(
+            encode_phrase(&[0xff; ROOT_KEY_LEN]).as_str(),
+            format!("{}vote", "zoo ".repeat(23))
+        );
+
+        let key = hex::decode("15da872c95a13dd738fbf50e427583ad61f18fd99f628c417a61cf8343c90419")
+            .unwrap();
+        let phrase = "beyond stage sleep clip because twist token leaf atom beauty genius food \
+                      business side grid unable middle armed observe pair crouch tonight away coconut";
+        assert_eq!(
+            encode_phrase(key.as_slice().try_into().unwrap()).as_str(),
+            phrase
+        );
+        assert_eq!(decode_phrase(phrase).unwrap().as_slice(), key.as_slice());
+    }
+
+    #[test]
+    fn test_decode_phrase() {
+        let key = [0x5a; ROOT_KEY_LEN];
+        let phrase = encode_phrase(&key);
+        assert_eq!(*decode_phrase(&phrase).unwrap(), key);
+
+        // Case, spacing and four-letter prefixes do not matter
+        let loose: Vec<String> = phrase
+            .split(' ')
+            .map(|word| word[..word.len().min(4)].to_uppercase())
+            .collect();
+        assert_eq!(*decode_phrase(&loose.join("  \n")).unwrap(), key);
+
+        // A wrong word fails the checksum
+        let mut words: Vec<&str> = phrase.split(' ').collect();
+        words[5] = if words[5] == "zoo" { "abandon" } else { "zoo" };
+        assert!(decode_phrase(&words.join(" ")).is_err());
+
+        assert!(decode_phrase(&phrase.replace(words[0], "mimir")).is_err());
+        assert!(decode_phrase("abandon abandon").is_err());
+    }
+}
@@ -0,0 +1,355 @@
+//! Shamir secret sharing of the root key
+//!
+//! The root key is split byte by byte over GF(256), using the AES polynomial
+//! x^8 + x^4 + x^3 + x + 1: each byte is the constant term of a random
+//! polynomial of degree `threshold - 1`, and share `i` holds every
+//! polynomial's value at `x = i`. Any `threshold` shares give the key back by
+//! Lagrange interpolation at zero; fewer reveal nothing about it.
+//!
+//! A share also carries the ID of the split it came from, a short check
+//! value of the key to confirm a reconstruction, and an integrity tag over
+//! everything, so a mistyped or damaged share is rejected on its own.
+//!
+//! As text, a share is `MIMIR-SHARE-` followed by hex digits in groups of
+//! eight. Only upper-case letters, digits and dashes are used, which QR
+//! codes store in their compact alphanumeric mode.
+
+use crate::{
+    crypto::{RootKeyBytes, ROOT_KEY_LEN},
+    error::MimirError,
+    Result,
+};
+use ring::{
+    digest, hmac,
+    rand::{SecureRandom, SystemRandom},
+};
+use zeroize::Zeroizing;
+
+/// Prefix of a share written as text
+pub const SHARE_PREFIX: &str = "MIMIR-SHARE-";
+
+/// Version of the share encoding
+const SHARE_FORMAT_VERSION: u8 = 1;
+
+/// HMAC context for a share's check value of the key
+const SHARE_KEY_CHECK_CONTEXT: &[u8] = b"mimir-root-key-share";
+
+/// Encoded share: version, set ID, threshold, index, value, key check and tag
+const SHARE_LEN: usize = 1 + 4 + 1 + 1 + ROOT_KEY_LEN + 4 + 4;
+
+/// Hex digits per dash-separated group in a share's text
+const TEXT_GROUP_LEN: usize = 8;
+
+/// One share of a split root key
+pub struct RootKeyShare {
+    /// Random ID shared by every share of one split
+    set_id: [u8; 4],
+    threshold: u8,
+    /// The share's x coordinate, from 1
+    index: u8,
+    value: Zeroizing<[u8; ROOT_KEY_LEN]>,
+    /// Truncated HMAC of the split's set ID under the key
+    key_check: [u8; 4],
+}
+
+/// Multiply in GF(256) without branching on the operands
+fn gf_mul(mut a: u8, mut b: u8) -> u8 {
+    let mut product = 0u8;
+    for _ in 0..8 {
+        product ^= a & (b & 1).wrapping_neg();
+        let carry = (a >> 7).wrapping_neg();
+        a = (a << 1) ^ (0x1b & carry);
+        b >>= 1;
+    }
+    product
+}
+
+/// Multiplicative inverse in GF(256), as a^254
+fn gf_inv(a: u8) -> u8 {
+    let mut result = 1;
+    let mut base = a;
+    let mut exponent = 254u8;
+    while exponent > 0 {
+        if exponent & 1 == 1 {
+            result = gf_mul(result, base);
+        }
+        base = gf_mul(base, base);
+        exponent >>= 1;
+    }
+    result
+}
+
+/// Check value of a key for one split
+fn key_check(key: &[u8; ROOT_KEY_LEN], set_id: &[u8; 4]) -> [u8; 4] {
+    let hmac_key = hmac::Key::new(hmac::HMAC_SHA256, key);
+    let mut context = SHARE_KEY_CHECK_CONTEXT.to_vec();
+    context.extend_from_slice(set_id);
+    let mut check = [0u8; 4];
+    check.copy_from_slice(&hmac::sign(&hmac_key, &context).as_ref()[..4]);
+    check
+}
+
+/// Split a key into `shares` shares, any `threshold` of which recover it
+pub fn split(key: &[u8; ROOT_KEY_LEN], threshold: u8, shares: u8) -> Result<Vec<RootKeyShare>> {
+    if threshold < 2 || threshold > shares {
+        return Err(MimirError::Encryption(format!(
+            "A threshold of {} does not work with {} shares; it must be at least 2 and at most the number of shares",
+            threshold, shares
+        )));
+    }
+
+    let rng = SystemRandom::new();
+    let mut set_id = [0u8; 4];
+    rng.fill(&mut set_id)
+        .map_err(|_| MimirError::Encryption("Failed to generate share set ID".to_string()))?;
+
+    // Coefficients 1.. of each byte's polynomial; the constant term is the key byte
+    let degree = usize::from(threshold) - 1;
+    let mut coefficients = Zeroizing::new(vec![0u8; ROOT_KEY_LEN * degree]);
+    rng.fill(&mut coefficients)
+        .map_err(|_| MimirError::Encryption("Failed to generate share polynomials".to_string()))?;
+
+    let check = key_check(key, &set_id);
+    Ok((1..=shares)
+        .map(|x| {
+            let mut value = Zeroizing::new([0u8; ROOT_KEY_LEN]);
+            for (byte, y) in value.iter_mut().enumerate() {
+                let terms = &coefficients[byte * degree..(byte + 1) * degree];
+                // Horner's rule, from the highest coefficient down
+                *y = terms
+                    .iter()
+                    .rev()
+                    .fold(0, |acc, &coefficient| gf_mul(acc, x) ^ coefficient);
+                *y = gf_mul(*y, x) ^ key[byte];
+            }
+            RootKeyShare {
+                set_id,
+                threshold,
+                index: x,
+                value,
+                key_check: check,
+            }
+        })
+        .collect())
+}
+
+/// Recover a key from at least `threshold` shares of one split
+pub fn combine(shares: &[RootKeyShare]) -> Result<RootKeyBytes> {
+    let first = shares
+        .first()
+        .ok_or_else(|| MimirError::Encryption("No shares given".to_string()))?;
+    if shares
+        .iter()
+        .any(|share| share.set_id != first.set_id || share.threshold != first.threshold)
+    {
+        return Err(MimirError::Encryption(
+            "The shares come from different splits".to_string(),
+        ));
+    }
+    let mut indexes: Vec<u8> = shares.iter().map(|share| share.index).collect();
+    indexes.sort_unstable();
+    indexes.dedup();
+    if indexes.len() != shares.len() {
+        return Err(MimirError::Encryption(
+            "The same share was given twice".to_string(),
+        ));
+    }
+    if shares.len() < usize::from(first.threshold) {
+        return Err(MimirError::Encryption(format!(
+            "{} shares are needed, only {} given",
+            first.threshold,
+            shares.len()
+        )));
+    }
+
+    // Lagrange basis polynomials at zero; subtraction is XOR in GF(256)
+    let shares = &shares[..usize::from(first.threshold)];
+    let mut key = Zeroizing::new([0u8; ROOT_KEY_LEN]);
+    for share in shares {
+        let basis = shares
+            .iter()
+            .filter(|other| other.index != share.index)
+            .fold(1, |acc, other| {
+                gf_mul(acc, gf_mul(other.index, gf_inv(other.index ^ share.index)))
+            });
+        for (byte, y) in key.iter_mut().zip(share.value.iter()) {
+            *byte ^= gf_mul(*y, basis);
+        }
+    }
+
+    if key_check(&key, &first.set_id) != first.key_check {
+        return Err(MimirError::Encryption(
+            "The shares do not recover the key; one of them is wrong".to_string(),
+        ));
+    }
+    Ok(key)
+}
+
+impl RootKeyShare {
+    /// Number of shares needed to recover the key
+    pub fn threshold(&self) -> u8 {
+        self.threshold
+    }
+
+    /// Position of this share in its split, from 1
+    pub fn index(&self) -> u8 {
+        self.index
+    }
+
+    /// Encode the share with its integrity tag
+    fn to_bytes(&self) -> Zeroizing<[u8; SHARE_LEN]> {
+        let mut bytes = Zeroizing::new([0u8; SHARE_LEN]);
+        bytes[0] = SHARE_FORMAT_VERSION;
+        bytes[1..5].copy_from_slice(&self.set_id);
+        bytes[5] = self.threshold;
+        bytes[6] = self.index;
+        bytes[7..7 + ROOT_KEY_LEN].copy_from_slice(&*self.value);
+        bytes[7 + ROOT_KEY_LEN..SHARE_LEN - 4].copy_from_slice(&self.key_check);
+        let tag = digest::digest(&digest::SHA256, &bytes[..SHARE_LEN - 4]);
+        bytes[SHARE_LEN - 4..].copy_from_slice(&tag.as_ref()[..4]);
+        bytes
+    }
+
+    /// Write the share as QR-friendly text
+    pub fn to_text(&self) -> Zeroizing<String> {
+        let hex = Zeroizing::new(hex::encode_upper(*self.to_bytes()));
+        let groups: Vec<&str> = hex
+            .as_bytes()
+            .chunks(TEXT_GROUP_LEN)
+            .map(|group| std::str::from_utf8(group).unwrap_or_default())
+            .collect();
+        Zeroizing::new(format!("{}{}", SHARE_PREFIX, groups.join("-")))
+    }
+
+    /// Read a share from its text, checking its integrity tag
+    ///
+    /// Case, whitespace and dashes between groups do not matter.
+    pub fn from_text(text: &str) -> Result<Self> {
+        let text = Zeroizing::new(text.trim().to_uppercase());
+        let body = text.strip_prefix(SHARE_PREFIX).ok_or_else(|| {
+            MimirError::Encryption(format!("A share starts with {}", SHARE_PREFIX))
+        })?;
+        let hex: Zeroizing<String> = Zeroizing::new(
+            body.chars()
+                .filter(|c| *c != '-' && !c.is_whitespace())
+                .collect(),
+        );
+        let bytes = Zeroizing::new(
+            hex::decode(&*hex)
+                .map_err(|_| MimirError::Encryption("The share is not valid hex".to_string()))?,
+        );
+        if bytes.len() != SHARE_LEN {
+            return Err(MimirError::Encryption(
+                "The share is too short or too long".to_string(),
+            ));
+        }
+        let tag = digest::digest(&digest::SHA256, &bytes[..SHARE_LEN - 4]);
+        if tag.as_ref()[..4] != bytes[SHARE_LEN - 4..] {
+            return Err(MimirError::Encryption(
+                "The share is corrupted: its integrity tag does not match".to_string(),
+            ));
+        }
+        if bytes[0] != SHARE_FORMAT_VERSION {
+            return Err(MimirError::Encryption(format!(
+                "Share format version {} is not supported",
+                bytes[0]
+            )));
+        }
+
+        let mut share = RootKeyShare {
+            set_id: [0; 4],
+            threshold: bytes[5],
+            index: bytes[6],
+            value: Zeroizing::new([0; ROOT_KEY_LEN]),
+            key_check: [0; 4],
+        };
+        share.set_id.copy_from_slice(&bytes[1..5]);
+        share.value.copy_from_slice(&bytes[7..7 + ROOT_KEY_LEN]);
+        share
+            .key_check
+            .copy_from_slice(&bytes[7 + ROOT_KEY_LEN..SHARE_LEN - 4]);
+        if share.index == 0 || share.threshold < 2 {
+            return Err(MimirError::Encryption(
+                "The share has an invalid index or threshold".to_string(),
+            ));
+        }
+        Ok(share)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_gf_arithmetic() {
+        // Inverse pair from the AES specification
+        assert_eq!(gf_mul(0x53, 0xca), 0x01);
+        assert_eq!(gf_mul(0x57, 0x83), 0xc1);
+        for a in 1..=255u8 {
+            assert_eq!(gf_mul(a, gf_inv(a)), 1);
+        }
+    }
+
+    #[test]
+    fn test_split_and_combine() {
+        let key = [0x42; ROOT_KEY_LEN];
+        let shares = split(&key, 3, 5).unwrap();
+        assert_eq!(shares.len(), 5);
+
+        // Every choice of three shares recovers the key
+        for a in 0..5 {
+            for b in a + 1..5 {
+                for c in b + 1..5 {
+                    let chosen: Vec<RootKeyShare> = [a, b, c]
+                        .iter()
+                        .map(|&i| RootKeyShare::from_text(&shares[i].to_text()).unwrap())
+                        .collect();
+                    assert_eq!(*combine(&chosen).unwrap(), key);
+                }
+            }
+        }
+
+        let two: Vec<RootKeyShare> = shares[..2]
+            .iter()
+            .map(|share| RootKeyShare::from_text(&share.to_text()).unwrap())
+            .collect();
+        assert!(combine(&two).is_err());
+        assert!(split(&key, 1, 5).is_err());
+        assert!(split(&key, 4, 3).is_err());
+    }
+
+    #[test]
+    fn test_share_integrity() {
+        let key = [0x17; ROOT_KEY_LEN];
+        let shares = split(&key, 2, 3).unwrap();
+        let text = shares[0].to_text();
+        assert!(text.starts_with(SHARE_PREFIX));
+        assert!(text
+            .chars()
+            .all(|c| c.is_ascii_uppercase() || c.is_ascii_digit() || c == '-'));
+
+        // Loose formatting is accepted
+        let loose = text.to_lowercase().replace('-', " ").replacen(' ', "-", 2);
+        assert_eq!(RootKeyShare::from_text(&loose).unwrap().index(), 1);
+
+        // A changed digit fails the integrity tag
+        let mut corrupted = text.to_string();
+        let last = corrupted.pop().unwrap();
+        corrupted.push(if last == '0' { '1' } else { '0' });
+        assert!(RootKeyShare::from_text(&corrupted).is_err());
+
+        // A share with a valid tag but a wrong value fails the key check
+        let mut wrong = RootKeyShare::from_text(&shares[1].to_text()).unwrap();
+        wrong.value[0] ^= 1;
+        let wrong = RootKeyShare::from_text(&wrong.to_text()).unwrap();
+        let first = RootKeyShare::from_text(&text).unwrap();
+        assert!(combine(&[first, wrong]).is_err());
+
+        // Shares of different splits do not mix
+        let other = split(&key, 2, 3).unwrap();
+        let first = RootKeyShare::from_text(&text).unwrap();
+        let foreign = RootKeyShare::from_text(&other[1].to_text()).unwrap();
+        assert!(combine(&[first, foreign]).is_err());
+    }
+}
@@ -12,6 +12,7 @@ pub struct MemoryBuilder {
     id: MemoryId,
     content: String,
     embedding: Option<Vec<f32>>,
+    embedding_model: Option<String>,
     class: MemoryClass,
     scope: Option<String>,
     tags: Vec<String>,
@@ -19,6 +20,7 @@ pub struct MemoryBuilder {
     key_id: String,
     created_at: DateTime<Utc>,
     updated_at: DateTime<Utc>,
+    expires_at: Option<DateTime<Utc>>,
 }
 
 impl Default for MemoryBuilder {
@@ -28,6 +30,7 @@ impl Default for MemoryBuilder {
             id: Uuid::new_v4(),
             content: "Test memory content".to_string(),
             embedding: None,
+            embedding_model: None,
             class: MemoryClass::Personal,
             scope: None,
             tags: vec![],
@@ -35,6 +38,7 @@ impl Default for MemoryBuilder {
             key_id: "test-key".to_string(),
             created_at: now,
             updated_at: now,
+            expires_at: None,
         }
     }
 }
@@ -59,6 +63,11 @@ impl MemoryBuilder {
         self
     }
 
+    pub fn with_embedding_model(mut self, model: impl Into<String>) -> Self {
+        self.embedding_model = Some(model.into());
+        self
+    }
+
     pub fn with_class(mut self, class: MemoryClass) -> Self {
         self.class = class;
         self
@@ -79,11 +88,17 @@ impl MemoryBuilder {
         self
     }
 
+    pub fn with_expires_at(mut self, expires_at: DateTime<Utc>) -> Self {
+        self.expires_at = Some(expires_at);
+        self
+    }
+
     pub fn build(self) -> Memory {
         Memory {
             id: self.id,
             content: self.content,
             embedding: self.embedding,
+            embedding_model: self.embedding_model,
             class: self.class,
             scope: self.scope,
             tags: self.tags,
@@ -91,6 +106,7 @@ impl MemoryBuilder {
             key_id: self.key_id,
             created_at: self.created_at,
             updated_at: self.updated_at,
+            expires_at: self.expires_at,
         }
     }
 }
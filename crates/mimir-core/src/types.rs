@@ -19,12 +19,92 @@ pub enum MemoryClass {
     Other(String),
 }
 
+impl MemoryClass {
+    /// Identifier used for storage and class key derivation
+    pub fn as_str(&self) -> &str {
+        match self {
+            MemoryClass::Personal => "personal",
+            MemoryClass::Work => "work",
+            MemoryClass::Health => "health",
+            MemoryClass::Financial => "financial",
+            MemoryClass::Other(s) => s,
+        }
+    }
+}
+
+impl std::str::FromStr for MemoryClass {
+    type Err = std::convert::Infallible;
+
+    /// Parse a class identifier; unknown identifiers become `Other`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s {
+            "personal" => MemoryClass::Personal,
+            "work" => MemoryClass::Work,
+            "health" => MemoryClass::Health,
+            "financial" => MemoryClass::Financial,
+            other => MemoryClass::Other(other.to_string()),
+        })
+    }
+}
+
+/// Type of a directed edge between two memories
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum RelationKind {
+    /// The source memory replaces the target
+    Supersedes,
+    RelatedTo,
+    /// The source memory was produced from the target
+    DerivedFrom,
+    Contradicts,
+}
+
+impl RelationKind {
+    /// Identifier used for storage
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RelationKind::Supersedes => "supersedes",
+            RelationKind::RelatedTo => "related_to",
+            RelationKind::DerivedFrom => "derived_from",
+            RelationKind::Contradicts => "contradicts",
+        }
+    }
+}
+
+impl std::str::FromStr for RelationKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "supersedes" => Ok(RelationKind::Supersedes),
+            "related_to" => Ok(RelationKind::RelatedTo),
+            "derived_from" => Ok(RelationKind::DerivedFrom),
+            "contradicts" => Ok(RelationKind::Contradicts),
+            other => Err(format!("Unknown relation type: {}", other)),
+        }
+    }
+}
+
+/// A typed edge from one memory to another
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct MemoryRelation {
+    pub source: MemoryId,
+    pub target: MemoryId,
+    pub kind: RelationKind,
+    /// Free-form description, stored encrypted
+    pub label: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
 /// A memory entry in the vault
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Memory {
     pub id: MemoryId,
     pub content: String,
     pub embedding: Option<Vec<f32>>,
+    /// Model that produced `embedding`
+    #[serde(default)]
+    pub embedding_model: Option<String>,
     pub class: MemoryClass,
     pub scope: Option<String>,
     pub tags: Vec<String>,
@@ -32,6 +112,9 @@ pub struct Memory {
     pub key_id: String,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
+    /// When the memory is deleted by the expiry reaper (`None` = never)
+    #[serde(default)]
+    pub expires_at: Option<DateTime<Utc>>,
 }
 
 /// Memory ingestion request
@@ -61,6 +144,30 @@ pub struct MemoryResult {
     pub score: f32,
 }
 
+/// Filter for listing memories; unset fields match everything
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct MemoryFilter {
+    pub class: Option<MemoryClass>,
+    /// Owning app (first entry of the memory's ACL)
+    pub source: Option<AppId>,
+    /// Memories must carry all of these tags
+    #[serde(default)]
+    pub tags: Vec<String>,
+    pub scope: Option<String>,
+    pub created_after: Option<DateTime<Utc>>,
+    pub created_before: Option<DateTime<Utc>>,
+    pub updated_after: Option<DateTime<Utc>>,
+    pub updated_before: Option<DateTime<Utc>>,
+}
+
+/// One page of a memory listing
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct MemoryPage {
+    pub memories: Vec<Memory>,
+    /// Opaque cursor for the next page, `None` when the listing is complete
+    pub next_cursor: Option<String>,
+}
+
 /// Application authentication token
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AuthToken {
@@ -110,6 +217,36 @@ mod tests {
         assert_eq!(financial, MemoryClass::Financial);
     }
 
+    #[test]
+    fn test_memory_class_id_roundtrip() {
+        for class in [
+            MemoryClass::Personal,
+            MemoryClass::Work,
+            MemoryClass::Health,
+            MemoryClass::Financial,
+            MemoryClass::Other("travel".to_string()),
+        ] {
+            assert_eq!(class.as_str().parse::<MemoryClass>().unwrap(), class);
+        }
+    }
+
+    #[test]
+    fn test_relation_kind_roundtrip() {
+        for kind in [
+            RelationKind::Supersedes,
+            RelationKind::RelatedTo,
+            RelationKind::DerivedFrom,
+            RelationKind::Contradicts,
+        ] {
+            assert_eq!(kind.as_str().parse::<RelationKind>().unwrap(), kind);
+            assert_eq!(
+                serde_json::to_string(&kind).unwrap(),
+                format!("\"{}\"", kind.as_str())
+            );
+        }
+        assert!("likes".parse::<RelationKind>().is_err());
+    }
+
     #[test]
     fn test_memory_builder() {
         let memory = MemoryBuilder::new()
@@ -0,0 +1,164 @@
+//! Unlocking a vault without a terminal
+//!
+//! A daemon under systemd or in a container has no one to type a password.
+//! It can read one from a file, from a descriptor its parent left open, or
+//! from an environment variable it was explicitly told to use, or it can
+//! skip the password and open the vault with a key file holding the root key.
+//!
+//! Secrets are read into zeroizing buffers, and surrounding whitespace is
+//! dropped as it is at the password prompt.
+
+use crate::{crypto::CryptoManager, error::MimirError, Result};
+use std::fs;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use zeroize::Zeroizing;
+
+/// How to unlock a vault
+pub enum Unlock {
+    /// With the root key in the OS keychain
+    Keychain,
+    /// With the vault password
+    Password(Zeroizing<String>),
+    /// With a key file holding the root key
+    KeyFile(PathBuf),
+}
+
+/// Read a secret to the end, trimmed
+fn read_secret<R: Read>(mut reader: R, source: &str) -> Result<Zeroizing<String>> {
+    let mut bytes = Zeroizing::new(Vec::new());
+    reader
+        .read_to_end(&mut bytes)
+        .map_err(|e| MimirError::Config(format!("Failed to read {}: {}", source, e)))?;
+    let text = std::str::from_utf8(&bytes)
+        .map_err(|_| MimirError::Config(format!("The {} is not valid UTF-8", source)))?;
+
+    let secret = Zeroizing::new(text.trim().to_string());
+    if secret.is_empty() {
+        return Err(MimirError::Config(format!("The {} is empty", source)));
+    }
+    Ok(secret)
+}
+
+/// Read a password or key file
+pub(crate) fn read_secret_file(path: &Path) -> Result<Zeroizing<String>> {
+    let file = fs::File::open(path)
+        .map_err(|e| MimirError::Config(format!("Failed to open {}: {}", path.display(), e)))?;
+    read_secret(file, &path.display().to_string())
+}
+
+impl Unlock {
+    /// Use the password in a file, such as a systemd credential
+    pub fn password_from_file<P: AsRef<Path>>(path: P) -> Result<Self> {
+        read_secret_file(path.as_ref()).map(Unlock::Password)
+    }
+
+    /// Use the password written to an inherited file descriptor
+    ///
+    /// The descriptor is read to the end and closed.
+    #[cfg(unix)]
+    pub fn password_from_fd(fd: i32) -> Result<Self> {
+        use std::os::unix::io::FromRawFd;
+
+        if fd < 0 {
+            return Err(MimirError::Config(format!(
+                "Invalid file descriptor {}",
+                fd
+            )));
+        }
+        // SAFETY: the descriptor was handed to this process to be consumed,
+        // and nothing else in it reads or closes it
+        let file = unsafe { fs::File::from_raw_fd(fd) };
+        read_secret(file, &format!("password from file descriptor {}", fd)).map(Unlock::Password)
+    }
+
+    /// Use the password written to an inherited file descriptor
+    #[cfg(not(unix))]
+    pub fn password_from_fd(fd: i32) -> Result<Self> {
+        Err(MimirError::Config(format!(
+            "Reading a password from file descriptor {} needs a Unix system",
+            fd
+        )))
+    }
+
+    /// Use the password in an environment variable
+    ///
+    /// Other processes of the same user can often read a process's
+    /// environment, so this is only done for a variable named by the caller.
+    pub fn password_from_env(var: &str) -> Result<Self> {
+        let value = Zeroizing::new(std::env::var(var).map_err(|e| {
+            MimirError::Config(format!("Failed to read password from ${}: {}", var, e))
+        })?);
+        read_secret(value.as_bytes(), &format!("password in ${}", var)).map(Unlock::Password)
+    }
+
+    /// Open the vault whose keyset is at `keyset_path`
+    ///
+    /// A wrong password fails with `MimirError::WrongPassword`, a damaged
+    /// keyset with `MimirError::CorruptKeyset`.
+    pub fn crypto_manager<P: AsRef<Path>>(&self, keyset_path: P) -> Result<CryptoManager> {
+        match self {
+            Unlock::Keychain => CryptoManager::new(keyset_path),
+            Unlock::Password(password) => CryptoManager::with_password(keyset_path, password),
+            Unlock::KeyFile(key_file) => CryptoManager::with_key_file(keyset_path, key_file),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serial_test::serial;
+    use tempfile::TempDir;
+
+    #[test]
+    #[serial]
+    fn test_password_sources() {
+        let temp_dir = TempDir::new().unwrap();
+        let keyset_path = temp_dir.path().join("keyset.json");
+        CryptoManager::with_password(&keyset_path, "correct horse").unwrap();
+
+        let password_file = temp_dir.path().join("password");
+        fs::write(&password_file, "correct horse\n").unwrap();
+        let unlock = Unlock::password_from_file(&password_file).unwrap();
+        assert!(unlock.crypto_manager(&keyset_path).is_ok());
+
+        fs::write(&password_file, "battery staple\n").unwrap();
+        let unlock = Unlock::password_from_file(&password_file).unwrap();
+        assert!(matches!(
+            unlock.crypto_manager(&keyset_path),
+            Err(MimirError::WrongPassword)
+        ));
+
+        fs::write(&password_file, "\n").unwrap();
+        assert!(Unlock::password_from_file(&password_file).is_err());
+        assert!(Unlock::password_from_file(temp_dir.path().join("missing")).is_err());
+
+        std::env::set_var("MIMIR_TEST_UNLOCK_PASSWORD", "correct horse");
+        let unlock = Unlock::password_from_env("MIMIR_TEST_UNLOCK_PASSWORD").unwrap();
+        assert!(unlock.crypto_manager(&keyset_path).is_ok());
+        assert!(Unlock::password_from_env("MIMIR_TEST_UNLOCK_UNSET").is_err());
+        std::env::remove_var("MIMIR_TEST_UNLOCK_PASSWORD");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_password_from_fd() {
+        use std::io::Write;
+        use std::os::unix::io::IntoRawFd;
+
+        let temp_dir = TempDir::new().unwrap();
+        let password_file = temp_dir.path().join("password");
+        fs::File::create(&password_file)
+            .unwrap()
+            .write_all(b"  from a pipe  \n")
+            .unwrap();
+
+        let fd = fs::File::open(&password_file).unwrap().into_raw_fd();
+        match Unlock::password_from_fd(fd).unwrap() {
+            Unlock::Password(password) => assert_eq!(password.as_str(), "from a pipe"),
+            _ => panic!("Expected a password"),
+        }
+        assert!(Unlock::password_from_fd(-1).is_err());
+    }
+}
@@ -25,7 +25,7 @@ fn create_test_crypto_manager_with_password(password: &str) -> (CryptoManager, T
 
 #[test]
 fn test_round_trip() {
-    let (mut crypto_manager, _temp_dir) = create_test_crypto_manager();
+    let (crypto_manager, _temp_dir) = create_test_crypto_manager();
 
     let test_data = "This is sensitive information that needs encryption";
     let class = "personal";
@@ -47,7 +47,7 @@ fn test_round_trip() {
 
 #[test]
 fn test_round_trip_multiple_classes() {
-    let (mut crypto_manager, _temp_dir) = create_test_crypto_manager();
+    let (crypto_manager, _temp_dir) = create_test_crypto_manager();
 
     let test_cases = vec![
         ("personal", "Personal secret information"),
@@ -74,7 +74,7 @@ fn test_round_trip_multiple_classes() {
 
 #[test]
 fn test_class_rotation_preserves_plaintext() {
-    let (mut crypto_manager, _temp_dir) = create_test_crypto_manager();
+    let (crypto_manager, _temp_dir) = create_test_crypto_manager();
 
     let test_data = "Data that should survive key rotation";
     let class = "personal";
@@ -89,11 +89,16 @@ fn test_class_rotation_preserves_plaintext() {
     // Rotate the class key
     crypto_manager.rotate_class_key(class).unwrap();
 
-    // Original ciphertext should no longer decrypt (key has changed)
+    // Original ciphertext still decrypts while the old key is retiring
+    let decrypted_retiring = crypto_manager.decrypt(class, &original_ciphertext).unwrap();
+    assert_eq!(String::from_utf8(decrypted_retiring).unwrap(), test_data);
+
+    // Once the old key is retired it no longer decrypts
+    crypto_manager.retire_class_keys(class).unwrap();
     let decrypt_result = crypto_manager.decrypt(class, &original_ciphertext);
     assert!(
         decrypt_result.is_err(),
-        "Old ciphertext should not decrypt with new key"
+        "Old ciphertext should not decrypt once its key is retired"
     );
 
     // New encryption should work with rotated key
@@ -106,7 +111,7 @@ fn test_class_rotation_preserves_plaintext() {
 }
 
 #[test]
-fn test_root_rotation_breaks_old_key() {
+fn test_root_rotation_keeps_data_readable() {
     let temp_dir = TempDir::new().unwrap();
     let keyset_path = temp_dir.path().join("keyset.json");
 
@@ -114,26 +119,32 @@ fn test_root_rotation_breaks_old_key() {
     let test_data = "Data encrypted with original root key";
     let class = "personal";
 
-    let original_ciphertext = {
-        let mut crypto_manager = CryptoManager::new(&keyset_path).unwrap();
-        crypto_manager.encrypt(class, test_data.as_bytes()).unwrap()
+    let (original_ciphertext, old_db_key) = {
+        let crypto_manager = CryptoManager::new(&keyset_path).unwrap();
+        (
+            crypto_manager.encrypt(class, test_data.as_bytes()).unwrap(),
+            crypto_manager.get_db_key_bytes(),
+        )
     };
 
     // Rotate root key
     {
         let mut crypto_manager = CryptoManager::new(&keyset_path).unwrap();
-        crypto_manager.rotate_root_key().unwrap();
+        crypto_manager.begin_root_rotation().unwrap();
+        crypto_manager
+            .finish_root_rotation(&[class.to_string()])
+            .unwrap();
     }
 
     // Create new crypto manager with rotated root key
-    let mut new_crypto_manager = CryptoManager::new(&keyset_path).unwrap();
+    let new_crypto_manager = CryptoManager::new(&keyset_path).unwrap();
+    assert_ne!(new_crypto_manager.get_db_key_bytes(), old_db_key);
 
-    // Original data encrypted with old derived key should not decrypt
-    let decrypt_result = new_crypto_manager.decrypt(class, &original_ciphertext);
-    assert!(
-        decrypt_result.is_err(),
-        "Old ciphertext should not decrypt after root key rotation"
-    );
+    // The class key was re-wrapped, so original data still decrypts
+    let decrypted = new_crypto_manager
+        .decrypt(class, &original_ciphertext)
+        .unwrap();
+    assert_eq!(String::from_utf8(decrypted).unwrap(), test_data);
 
     // New encryption should work
     let new_ciphertext = new_crypto_manager
@@ -145,7 +156,7 @@ fn test_root_rotation_breaks_old_key() {
 
 #[test]
 fn test_different_classes_different_keys() {
-    let (mut crypto_manager, _temp_dir) = create_test_crypto_manager();
+    let (crypto_manager, _temp_dir) = create_test_crypto_manager();
 
     let test_data = "Same data, different classes";
 
@@ -179,7 +190,7 @@ fn test_different_classes_different_keys() {
 
 #[test]
 fn test_nonce_uniqueness() {
-    let (mut crypto_manager, _temp_dir) = create_test_crypto_manager();
+    let (crypto_manager, _temp_dir) = create_test_crypto_manager();
 
     let test_data = "Same data for nonce test";
     let class = "personal";
@@ -206,7 +217,7 @@ fn test_nonce_uniqueness() {
 
 #[test]
 fn test_empty_data_encryption() {
-    let (mut crypto_manager, _temp_dir) = create_test_crypto_manager();
+    let (crypto_manager, _temp_dir) = create_test_crypto_manager();
 
     let empty_data = b"";
     let class = "personal";
@@ -222,7 +233,7 @@ fn test_empty_data_encryption() {
 
 #[test]
 fn test_large_data_encryption() {
-    let (mut crypto_manager, _temp_dir) = create_test_crypto_manager();
+    let (crypto_manager, _temp_dir) = create_test_crypto_manager();
 
     // Create 1MB of test data
     let large_data = "A".repeat(1024 * 1024);
@@ -239,7 +250,7 @@ fn test_large_data_encryption() {
 
 #[test]
 fn test_unicode_data_encryption() {
-    let (mut crypto_manager, _temp_dir) = create_test_crypto_manager();
+    let (crypto_manager, _temp_dir) = create_test_crypto_manager();
 
     let unicode_data = "🔒 Encrypted: 你好世界 🌍 Здравствуй мир 🇺🇳";
     let class = "personal";
@@ -254,7 +265,7 @@ fn test_unicode_data_encryption() {
 
 #[test]
 fn test_purge_class() {
-    let (mut crypto_manager, _temp_dir) = create_test_crypto_manager();
+    let (crypto_manager, _temp_dir) = create_test_crypto_manager();
 
     let test_data = "Data to be purged";
     let class = "personal";
@@ -292,13 +303,13 @@ fn test_keyset_persistence() {
 
     // Create first manager and encrypt data
     let ciphertext = {
-        let mut crypto_manager = CryptoManager::new(&keyset_path).unwrap();
+        let crypto_manager = CryptoManager::new(&keyset_path).unwrap();
         crypto_manager.encrypt(class, test_data.as_bytes()).unwrap()
     };
 
     // Create second manager and decrypt data
     {
-        let mut crypto_manager = CryptoManager::new(&keyset_path).unwrap();
+        let crypto_manager = CryptoManager::new(&keyset_path).unwrap();
         let decrypted = crypto_manager.decrypt(class, &ciphertext).unwrap();
         assert_eq!(String::from_utf8(decrypted).unwrap(), test_data);
     }
@@ -336,7 +347,7 @@ fn test_deterministic_class_key_derivation() {
 
 #[test]
 fn test_malformed_ciphertext() {
-    let (mut crypto_manager, _temp_dir) = create_test_crypto_manager();
+    let (crypto_manager, _temp_dir) = create_test_crypto_manager();
 
     let class = "personal";
 
@@ -379,7 +390,7 @@ fn test_concurrent_encryption() {
                 let test_data = format!("Thread {} data", i);
                 let class = "personal";
 
-                let mut manager = crypto_manager.lock().unwrap();
+                let manager = crypto_manager.lock().unwrap();
                 let ciphertext = manager.encrypt(class, test_data.as_bytes()).unwrap();
                 let decrypted = manager.decrypt(class, &ciphertext).unwrap();
 
@@ -396,7 +407,7 @@ fn test_concurrent_encryption() {
 #[test]
 fn test_password_based_encryption_integration() {
     let password = "test-password-123";
-    let (mut crypto_manager, _temp_dir) = create_test_crypto_manager_with_password(password);
+    let (crypto_manager, _temp_dir) = create_test_crypto_manager_with_password(password);
 
     let test_data = "Sensitive data encrypted with password";
     let class = "personal";
@@ -427,13 +438,13 @@ fn test_password_based_persistence_integration() {
 
     // Create first manager and encrypt data
     let ciphertext = {
-        let mut crypto_manager = CryptoManager::with_password(&keyset_path, password).unwrap();
+        let crypto_manager = CryptoManager::with_password(&keyset_path, password).unwrap();
         crypto_manager.encrypt(class, test_data.as_bytes()).unwrap()
     };
 
     // Create second manager and decrypt data
     {
-        let mut crypto_manager = CryptoManager::with_password(&keyset_path, password).unwrap();
+        let crypto_manager = CryptoManager::with_password(&keyset_path, password).unwrap();
         let decrypted = crypto_manager.decrypt(class, &ciphertext).unwrap();
         assert_eq!(String::from_utf8(decrypted).unwrap(), test_data);
     }
@@ -455,38 +466,18 @@ fn test_wrong_password_fails() {
 
     // Create manager with correct password and encrypt some data
     {
-        let mut crypto_manager =
-            CryptoManager::with_password(&keyset_path, correct_password).unwrap();
+        let crypto_manager = CryptoManager::with_password(&keyset_path, correct_password).unwrap();
         let test_data = "Secret data";
         let _ciphertext = crypto_manager
             .encrypt("personal", test_data.as_bytes())
             .unwrap();
     }
 
-    // Try to initialize with wrong password - this should fail during decryption
-    // because the derived root key will be different
+    // A wrong password does not unwrap the root key, and says so
     let result = CryptoManager::with_password(&keyset_path, wrong_password);
     assert!(
-        result.is_ok(),
-        "Should be able to initialize with wrong password (keyset exists)"
-    );
-
-    // But decryption should fail when we try to use it
-    let mut crypto_manager = result.unwrap();
-    let test_data = "Secret data";
-    let ciphertext = {
-        let mut correct_manager =
-            CryptoManager::with_password(&keyset_path, correct_password).unwrap();
-        correct_manager
-            .encrypt("personal", test_data.as_bytes())
-            .unwrap()
-    };
-
-    // This should fail because the wrong password produces a different root key
-    let decrypt_result = crypto_manager.decrypt("personal", &ciphertext);
-    assert!(
-        decrypt_result.is_err(),
-        "Should not be able to decrypt with wrong password"
+        matches!(result, Err(mimir_core::MimirError::WrongPassword)),
+        "Should not be able to initialize with wrong password"
     );
 }
 
@@ -503,13 +494,13 @@ fn test_mixed_encryption_modes() {
     let test_data = "Test data for mixed modes";
 
     // Create keychain-based manager
-    let mut keychain_manager = CryptoManager::new(&keyset_path1).unwrap();
+    let keychain_manager = CryptoManager::new(&keyset_path1).unwrap();
     let keychain_ciphertext = keychain_manager
         .encrypt("personal", test_data.as_bytes())
         .unwrap();
 
     // Create password-based manager
-    let mut password_manager = CryptoManager::with_password(&keyset_path2, password).unwrap();
+    let password_manager = CryptoManager::with_password(&keyset_path2, password).unwrap();
     let password_ciphertext = password_manager
         .encrypt("personal", test_data.as_bytes())
         .unwrap();
@@ -536,7 +527,7 @@ mod performance_tests {
 
     #[test]
     fn test_encryption_performance() {
-        let (mut crypto_manager, _temp_dir) = create_test_crypto_manager();
+        let (crypto_manager, _temp_dir) = create_test_crypto_manager();
 
         let test_data = "Performance test data";
         let class = "personal";
@@ -562,7 +553,7 @@ mod performance_tests {
     #[test]
     fn test_password_based_encryption_performance() {
         let password = "performance-test-password";
-        let (mut crypto_manager, _temp_dir) = create_test_crypto_manager_with_password(password);
+        let (crypto_manager, _temp_dir) = create_test_crypto_manager_with_password(password);
 
         let test_data = "Performance test data with password";
         let class = "personal";
@@ -0,0 +1,415 @@
+//! Encrypted attachments
+//!
+//! Files attached to a memory (screenshots, PDFs, audio snippets) live in
+//! `memory_attachment`, encrypted with XChaCha20-Poly1305 under the class key
+//! of the memory they belong to. Their name and MIME type are encrypted like
+//! other metadata; the contents are stored as the raw ciphertext with its
+//! nonce in a separate column, since the JSON used for other columns would
+//! more than double the size of binary data. Attachments are hidden while
+//! their memory is in the trash and destroyed along with it.
+
+use crate::{decrypt_blob, encrypt_blob, timestamp_to_datetime, Database};
+use mimir_core::crypto::{Ciphertext, CryptoManager};
+use mimir_core::{MemoryId, Result};
+use rusqlite::{params, Connection, OptionalExtension};
+
+/// Largest attachment accepted, in bytes
+pub const MAX_ATTACHMENT_SIZE: usize = 32 * 1024 * 1024;
+
+/// Unique identifier of an attachment
+pub type AttachmentId = uuid::Uuid;
+
+/// Attachment metadata, without the contents
+#[derive(Debug, Clone, PartialEq)]
+pub struct AttachmentInfo {
+    pub id: AttachmentId,
+    pub memory_id: MemoryId,
+    /// File name given when attaching
+    pub name: String,
+    pub mime_type: String,
+    /// Size of the contents in bytes
+    pub size: u64,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+}
+
+/// An attachment with its decrypted contents
+#[derive(Debug, Clone, PartialEq)]
+pub struct Attachment {
+    pub info: AttachmentInfo,
+    pub data: Vec<u8>,
+}
+
+/// Columns selected for attachment metadata, in `decode_info` order
+const ATTACHMENT_COLUMNS: &str =
+    "a.id, a.memory_id, a.class_id, a.name_enc, a.mime_type_enc, a.size, a.ts";
+
+/// Attachment metadata row as stored on disk, before decryption
+type InfoRow = (String, String, String, Vec<u8>, Vec<u8>, i64, i64);
+
+fn read_info_row(row: &rusqlite::Row<'_>) -> rusqlite::Result<InfoRow> {
+    Ok((
+        row.get(0)?,
+        row.get(1)?,
+        row.get(2)?,
+        row.get(3)?,
+        row.get(4)?,
+        row.get(5)?,
+        row.get(6)?,
+    ))
+}
+
+fn parse_id(id: &str) -> Result<uuid::Uuid> {
+    uuid::Uuid::parse_str(id)
+        .map_err(|e| mimir_core::MimirError::Database(anyhow::anyhow!("Invalid UUID: {}", e)))
+}
+
+fn decrypt_string(crypto_manager: &CryptoManager, class_id: &str, data: &[u8]) -> Result<String> {
+    String::from_utf8(decrypt_blob(crypto_manager, class_id, data)?).map_err(|e| {
+        mimir_core::MimirError::Database(anyhow::anyhow!("Invalid UTF-8 in attachment: {}", e))
+    })
+}
+
+/// Decrypt a metadata row selected with `ATTACHMENT_COLUMNS`
+fn decode_info(crypto_manager: &CryptoManager, row: InfoRow) -> Result<AttachmentInfo> {
+    let (id, memory_id, class_id, name_enc, mime_type_enc, size, ts) = row;
+    Ok(AttachmentInfo {
+        id: parse_id(&id)?,
+        memory_id: parse_id(&memory_id)?,
+        name: decrypt_string(crypto_manager, &class_id, &name_enc)?,
+        mime_type: decrypt_string(crypto_manager, &class_id, &mime_type_enc)?,
+        size: size as u64,
+        created_at: timestamp_to_datetime(ts)?,
+    })
+}
+
+/// Attachment ciphertext and its nonce
+pub(crate) type EncryptedData = (Vec<u8>, Vec<u8>);
+
+/// Encrypt attachment contents, returning the ciphertext and its nonce
+pub(crate) fn encrypt_data(
+    crypto_manager: &CryptoManager,
+    class_id: &str,
+    data: &[u8],
+) -> Result<EncryptedData> {
+    let ciphertext = crypto_manager.encrypt(class_id, data)?;
+    Ok((ciphertext.data, ciphertext.nonce))
+}
+
+/// Decrypt attachment contents written by `encrypt_data`
+pub(crate) fn decrypt_data(
+    crypto_manager: &CryptoManager,
+    class_id: &str,
+    data: Vec<u8>,
+    nonce: Vec<u8>,
+) -> Result<Vec<u8>> {
+    crypto_manager.decrypt(class_id, &Ciphertext { data, nonce })
+}
+
+/// Permanently remove the attachments of the given memories
+///
+/// Callers should run this inside a transaction.
+pub(crate) fn destroy(conn: &Connection, memory_ids: &[String]) -> rusqlite::Result<usize> {
+    let mut removed = 0;
+    for id in memory_ids {
+        removed += conn.execute(
+            "DELETE FROM memory_attachment WHERE memory_id = ?1",
+            params![id],
+        )?;
+    }
+    Ok(removed)
+}
+
+impl Database {
+    /// Attach a file to a memory, encrypted under the memory's class key
+    pub async fn add_attachment(
+        &self,
+        memory_id: MemoryId,
+        name: &str,
+        mime_type: &str,
+        data: &[u8],
+    ) -> Result<AttachmentInfo> {
+        if data.len() > MAX_ATTACHMENT_SIZE {
+            return Err(mimir_core::MimirError::Database(anyhow::anyhow!(
+                "Attachment is {} bytes; the limit is {}",
+                data.len(),
+                MAX_ATTACHMENT_SIZE
+            )));
+        }
+
+        let class_id: String = {
+            let conn = self.reader().await?;
+            conn.query_row(
+                "SELECT class_id FROM memory WHERE id = ?1 AND deleted_ts IS NULL",
+                params![memory_id.to_string()],
+                |row| row.get(0),
+            )
+            .optional()
+            .map_err(|e| {
+                mimir_core::MimirError::Database(anyhow::anyhow!("Failed to read memory: {}", e))
+            })?
+            .ok_or_else(|| {
+                mimir_core::MimirError::Database(anyhow::anyhow!("Memory {} not found", memory_id))
+            })?
+        };
+
+        // Encrypt before taking the write lock; the insert re-checks the memory
+        let name_enc = encrypt_blob(&self.crypto_manager, &class_id, name.as_bytes())?;
+        let mime_type_enc = encrypt_blob(&self.crypto_manager, &class_id, mime_type.as_bytes())?;
+        let (data_enc, nonce) = encrypt_data(&self.crypto_manager, &class_id, data)?;
+        let ts = chrono::Utc::now().timestamp();
+        let info = AttachmentInfo {
+            id: uuid::Uuid::new_v4(),
+            memory_id,
+            name: name.to_string(),
+            mime_type: mime_type.to_string(),
+            size: data.len() as u64,
+            created_at: timestamp_to_datetime(ts)?,
+        };
+
+        let conn = self.conn.lock().await;
+        let inserted = conn
+            .execute(
+                "INSERT INTO memory_attachment
+                    (id, memory_id, class_id, name_enc, mime_type_enc, data_enc, nonce, size, ts,
+                     key_version)
+                 SELECT ?1, id, class_id, ?3, ?4, ?5, ?6, ?7, ?8, ?9 FROM memory
+                 WHERE id = ?2 AND class_id = ?10 AND deleted_ts IS NULL",
+                params![
+                    info.id.to_string(),
+                    memory_id.to_string(),
+                    name_enc,
+                    mime_type_enc,
+                    data_enc,
+                    nonce,
+                    info.size as i64,
+                    ts,
+                    self.crypto_manager.class_key_version(&class_id),
+                    class_id,
+                ],
+            )
+            .map_err(|e| {
+                mimir_core::MimirError::Database(anyhow::anyhow!(
+                    "Failed to store attachment: {}",
+                    e
+                ))
+            })?;
+        if inserted == 0 {
+            return Err(mimir_core::MimirError::Database(anyhow::anyhow!(
+                "Memory {} changed while attaching; try again",
+                memory_id
+            )));
+        }
+        Ok(info)
+    }
+
+    /// List the attachments of a memory, oldest first
+    pub async fn list_attachments(&self, memory_id: MemoryId) -> Result<Vec<AttachmentInfo>> {
+        let conn = self.reader().await?;
+        let rows = conn
+            .prepare(&format!(
+                "SELECT {} FROM memory_attachment a
+                 JOIN memory m ON m.id = a.memory_id
+                 WHERE a.memory_id = ?1 AND m.deleted_ts IS NULL
+                 ORDER BY a.ts ASC, a.id ASC",
+                ATTACHMENT_COLUMNS
+            ))
+            .and_then(|mut stmt| {
+                stmt.query_map(params![memory_id.to_string()], read_info_row)?
+                    .collect::<rusqlite::Result<Vec<_>>>()
+            })
+            .map_err(|e| {
+                mimir_core::MimirError::Database(anyhow::anyhow!(
+                    "Failed to list attachments: {}",
+                    e
+                ))
+            })?;
+        drop(conn);
+
+        rows.into_iter()
+            .map(|row| decode_info(&self.crypto_manager, row))
+            .collect()
+    }
+
+    /// Read and decrypt an attachment
+    ///
+    /// Returns `None` if it does not exist or its memory is in the trash.
+    pub async fn get_attachment(&self, id: AttachmentId) -> Result<Option<Attachment>> {
+        let conn = self.reader().await?;
+        let row = conn
+            .query_row(
+                &format!(
+                    "SELECT {}, a.data_enc, a.nonce FROM memory_attachment a
+                     JOIN memory m ON m.id = a.memory_id
+                     WHERE a.id = ?1 AND m.deleted_ts IS NULL",
+                    ATTACHMENT_COLUMNS
+                ),
+                params![id.to_string()],
+                |row| {
+                    Ok((
+                        read_info_row(row)?,
+                        row.get::<_, Vec<u8>>(7)?,
+                        row.get::<_, Vec<u8>>(8)?,
+                    ))
+                },
+            )
+            .optional()
+            .map_err(|e| {
+                mimir_core::MimirError::Database(anyhow::anyhow!(
+                    "Failed to read attachment: {}",
+                    e
+                ))
+            })?;
+        drop(conn);
+
+        let (info_row, data_enc, nonce) = match row {
+            Some(row) => row,
+            None => return Ok(None),
+        };
+        let class_id = info_row.2.clone();
+        let info = decode_info(&self.crypto_manager, info_row)?;
+        let data = decrypt_data(&self.crypto_manager, &class_id, data_enc, nonce)?;
+        Ok(Some(Attachment { info, data }))
+    }
+
+    /// Permanently remove an attachment
+    ///
+    /// Returns the memory it was attached to, or `None` if it did not exist.
+    pub async fn remove_attachment(&self, id: AttachmentId) -> Result<Option<MemoryId>> {
+        let conn = self.conn.lock().await;
+        let memory_id: Option<String> = conn
+            .query_row(
+                "DELETE FROM memory_attachment WHERE id = ?1 RETURNING memory_id",
+                params![id.to_string()],
+                |row| row.get(0),
+            )
+            .optional()
+            .map_err(|e| {
+                mimir_core::MimirError::Database(anyhow::anyhow!(
+                    "Failed to remove attachment: {}",
+                    e
+                ))
+            })?;
+        memory_id.as_deref().map(parse_id).transpose()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mimir_core::test_utils::MemoryBuilder;
+    use mimir_core::MemoryClass;
+    use tempfile::TempDir;
+
+    fn create_test_database() -> (Database, TempDir) {
+        let temp_dir = TempDir::new().unwrap();
+        let crypto_manager =
+            CryptoManager::with_password(temp_dir.path().join("keyset.json"), "test-password")
+                .unwrap();
+        let db =
+            Database::with_crypto_manager(temp_dir.path().join("test.db"), crypto_manager).unwrap();
+        (db, temp_dir)
+    }
+
+    #[tokio::test]
+    async fn test_attachment_round_trip() {
+        let (db, _temp_dir) = create_test_database();
+
+        let memory = MemoryBuilder::new()
+            .with_content("X-ray of the left wrist")
+            .with_class(MemoryClass::Health)
+            .build();
+        db.store_memory(&memory).await.unwrap();
+
+        let scan = b"\x89PNG\r\n\x1a\nnot really an image".to_vec();
+        let info = db
+            .add_attachment(memory.id, "wrist.png", "image/png", &scan)
+            .await
+            .unwrap();
+        assert_eq!(info.memory_id, memory.id);
+        assert_eq!(info.size, scan.len() as u64);
+
+        assert_eq!(
+            db.list_attachments(memory.id).await.unwrap(),
+            vec![info.clone()]
+        );
+        let attachment = db.get_attachment(info.id).await.unwrap().unwrap();
+        assert_eq!(attachment.info, info);
+        assert_eq!(attachment.data, scan);
+
+        // Name, type and contents are all ciphertext on disk
+        let conn = db.conn.lock().await;
+        let (name_enc, data_enc) = conn
+            .query_row(
+                "SELECT name_enc, data_enc FROM memory_attachment WHERE id = ?1",
+                [info.id.to_string()],
+                |row| Ok((row.get::<_, Vec<u8>>(0)?, row.get::<_, Vec<u8>>(1)?)),
+            )
+            .unwrap();
+        drop(conn);
+        assert!(!String::from_utf8_lossy(&name_enc).contains("wrist"));
+        assert!(!data_enc.windows(4).any(|window| window == b"PNG\r"));
+
+        assert_eq!(
+            db.remove_attachment(info.id).await.unwrap(),
+            Some(memory.id)
+        );
+        assert_eq!(db.remove_attachment(info.id).await.unwrap(), None);
+        assert!(db.get_attachment(info.id).await.unwrap().is_none());
+        assert!(db.list_attachments(memory.id).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_attachment_requires_live_memory() {
+        let (db, _temp_dir) = create_test_database();
+
+        let missing = uuid::Uuid::new_v4();
+        assert!(db
+            .add_attachment(missing, "note.txt", "text/plain", b"hello")
+            .await
+            .is_err());
+
+        let memory = MemoryBuilder::new().with_content("Voice memo").build();
+        db.store_memory(&memory).await.unwrap();
+        let too_large = vec![0u8; MAX_ATTACHMENT_SIZE + 1];
+        assert!(db
+            .add_attachment(memory.id, "memo.ogg", "audio/ogg", &too_large)
+            .await
+            .is_err());
+
+        db.delete_memory(memory.id).await.unwrap();
+        assert!(db
+            .add_attachment(memory.id, "memo.ogg", "audio/ogg", b"OggS")
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_attachments_follow_their_memory() {
+        let (db, _temp_dir) = create_test_database();
+
+        let memory = MemoryBuilder::new().with_content("Signed lease").build();
+        db.store_memory(&memory).await.unwrap();
+        let info = db
+            .add_attachment(memory.id, "lease.pdf", "application/pdf", b"%PDF-1.7")
+            .await
+            .unwrap();
+
+        // Hidden in the trash, back on restore
+        db.delete_memory(memory.id).await.unwrap();
+        assert!(db.get_attachment(info.id).await.unwrap().is_none());
+        assert!(db.list_attachments(memory.id).await.unwrap().is_empty());
+        db.restore_memory(memory.id).await.unwrap();
+        assert!(db.get_attachment(info.id).await.unwrap().is_some());
+
+        // Destroyed when the memory is purged
+        db.delete_memory(memory.id).await.unwrap();
+        db.empty_trash().await.unwrap();
+        let conn = db.conn.lock().await;
+        let count: i64 = conn
+            .query_row("SELECT COUNT(*) FROM memory_attachment", [], |row| {
+                row.get(0)
+            })
+            .unwrap();
+        assert_eq!(count, 0);
+    }
+}
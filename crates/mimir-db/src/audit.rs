@@ -0,0 +1,475 @@
+//! Tamper-evident access audit log
+//!
+//! Every entry in `audit_log` stores the SHA-256 hash of its own contents
+//! chained to the hash of the entry before it, so editing, removing or
+//! reordering entries breaks the chain from that point on. Triggers reject
+//! UPDATE and DELETE on the table; the chain catches changes made around them.
+//! Removing entries from the end leaves an intact but shorter chain, so the
+//! newest entry is also kept in `audit_head`, which can only move forward.
+
+use crate::Database;
+use mimir_core::{MemoryId, Result};
+use rusqlite::{params, params_from_iter, types::Value, OptionalExtension};
+use sha2::{Digest, Sha256};
+
+/// `prev_hash` of the first entry
+const GENESIS_HASH: [u8; 32] = [0; 32];
+
+/// One recorded operation
+#[derive(Debug, Clone, PartialEq)]
+pub struct AuditEntry {
+    /// Position in the chain, starting at 1
+    pub seq: i64,
+    pub timestamp: chrono::DateTime<chrono::Utc>,
+    /// App that performed the operation
+    pub app_id: String,
+    /// Operation name, e.g. `add`, `search`, `delete`
+    pub op: String,
+    /// Memories the operation touched
+    pub memory_ids: Vec<MemoryId>,
+    /// Guardrail decision for the operation, if one was made
+    pub guardrail: Option<String>,
+    pub prev_hash: [u8; 32],
+    pub hash: [u8; 32],
+}
+
+/// Filter for reading audit entries; unset fields match everything
+#[derive(Debug, Clone, Default)]
+pub struct AuditFilter {
+    pub app_id: Option<String>,
+    pub since: Option<chrono::DateTime<chrono::Utc>>,
+    pub until: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+/// Outcome of checking the audit chain
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AuditVerification {
+    /// Entries checked
+    pub entries: usize,
+    /// Sequence number of the first entry that does not match the chain, or
+    /// of the first one missing from its end
+    pub first_invalid: Option<i64>,
+}
+
+impl AuditVerification {
+    pub fn is_valid(&self) -> bool {
+        self.first_invalid.is_none()
+    }
+}
+
+/// Hash an entry's fields together with the previous hash
+///
+/// Fields are length-prefixed so that different entries never encode alike.
+fn entry_hash(
+    prev_hash: &[u8],
+    seq: i64,
+    ts: i64,
+    app_id: &str,
+    op: &str,
+    memory_ids: &str,
+    guardrail: Option<&str>,
+) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update(prev_hash);
+    hasher.update(seq.to_be_bytes());
+    hasher.update(ts.to_be_bytes());
+    for field in [Some(app_id), Some(op), Some(memory_ids), guardrail] {
+        match field {
+            Some(value) => {
+                hasher.update([1]);
+                hasher.update((value.len() as u64).to_be_bytes());
+                hasher.update(value.as_bytes());
+            }
+            None => hasher.update([0]),
+        }
+    }
+    hasher.finalize().into()
+}
+
+/// Raw audit columns as stored
+struct AuditRow {
+    seq: i64,
+    ts: i64,
+    app_id: String,
+    op: String,
+    memory_ids: String,
+    guardrail: Option<String>,
+    prev_hash: Vec<u8>,
+    hash: Vec<u8>,
+}
+
+impl AuditRow {
+    fn from_row(row: &rusqlite::Row<'_>) -> rusqlite::Result<Self> {
+        Ok(Self {
+            seq: row.get(0)?,
+            ts: row.get(1)?,
+            app_id: row.get(2)?,
+            op: row.get(3)?,
+            memory_ids: row.get(4)?,
+            guardrail: row.get(5)?,
+            prev_hash: row.get(6)?,
+            hash: row.get(7)?,
+        })
+    }
+
+    fn computed_hash(&self) -> [u8; 32] {
+        entry_hash(
+            &self.prev_hash,
+            self.seq,
+            self.ts,
+            &self.app_id,
+            &self.op,
+            &self.memory_ids,
+            self.guardrail.as_deref(),
+        )
+    }
+
+    fn into_entry(self) -> Result<AuditEntry> {
+        let seq = self.seq;
+        let hash_array = |bytes: Vec<u8>| {
+            <[u8; 32]>::try_from(bytes).map_err(|_| {
+                mimir_core::MimirError::Database(anyhow::anyhow!(
+                    "Invalid hash in audit entry {}",
+                    seq
+                ))
+            })
+        };
+        let memory_ids: Vec<MemoryId> = serde_json::from_str(&self.memory_ids).map_err(|e| {
+            mimir_core::MimirError::Database(anyhow::anyhow!(
+                "Invalid memory IDs in audit entry {}: {}",
+                self.seq,
+                e
+            ))
+        })?;
+
+        Ok(AuditEntry {
+            seq: self.seq,
+            timestamp: crate::timestamp_to_datetime(self.ts)?,
+            app_id: self.app_id,
+            op: self.op,
+            memory_ids,
+            guardrail: self.guardrail,
+            prev_hash: hash_array(self.prev_hash)?,
+            hash: hash_array(self.hash)?,
+        })
+    }
+}
+
+const AUDIT_COLUMNS: &str = "seq, ts, app_id, op, memory_ids, guardrail, prev_hash, hash";
+
+/// Sequence number and hash of the newest audit entry
+type ChainHead = (i64, Vec<u8>);
+
+impl Database {
+    /// Append an entry to the audit log
+    pub async fn append_audit(
+        &self,
+        app_id: &str,
+        op: &str,
+        memory_ids: &[MemoryId],
+        guardrail: Option<&str>,
+    ) -> Result<AuditEntry> {
+        let ids_json = serde_json::to_string(memory_ids).map_err(|e| {
+            mimir_core::MimirError::Database(anyhow::anyhow!(
+                "Failed to serialize memory IDs: {}",
+                e
+            ))
+        })?;
+        let ts = chrono::Utc::now().timestamp();
+
+        let mut conn = self.conn.lock().await;
+        let tx = conn.transaction().map_err(|e| {
+            mimir_core::MimirError::Database(anyhow::anyhow!("Failed to begin transaction: {}", e))
+        })?;
+
+        // Chain from the recorded head, so a cut-off tail stays a visible gap
+        let last: Option<ChainHead> = tx
+            .query_row("SELECT seq, hash FROM audit_head WHERE id = 1", [], |row| {
+                Ok((row.get(0)?, row.get(1)?))
+            })
+            .optional()
+            .map_err(|e| {
+                mimir_core::MimirError::Database(anyhow::anyhow!("Failed to read audit log: {}", e))
+            })?;
+        let (seq, prev_hash) = match last {
+            Some((seq, hash)) => (seq + 1, hash),
+            None => (1, GENESIS_HASH.to_vec()),
+        };
+        let hash = entry_hash(&prev_hash, seq, ts, app_id, op, &ids_json, guardrail);
+
+        tx.execute(
+            "INSERT INTO audit_log (seq, ts, app_id, op, memory_ids, guardrail, prev_hash, hash)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+            params![seq, ts, app_id, op, ids_json, guardrail, prev_hash, hash],
+        )
+        .map_err(|e| {
+            mimir_core::MimirError::Database(anyhow::anyhow!("Failed to append audit entry: {}", e))
+        })?;
+        tx.commit().map_err(|e| {
+            mimir_core::MimirError::Database(anyhow::anyhow!("Failed to commit transaction: {}", e))
+        })?;
+
+        AuditRow {
+            seq,
+            ts,
+            app_id: app_id.to_string(),
+            op: op.to_string(),
+            memory_ids: ids_json,
+            guardrail: guardrail.map(str::to_string),
+            prev_hash,
+            hash: hash.to_vec(),
+        }
+        .into_entry()
+    }
+
+    /// Read audit entries matching `filter`, oldest first
+    pub async fn audit_entries(&self, filter: &AuditFilter) -> Result<Vec<AuditEntry>> {
+        let mut conditions: Vec<&str> = Vec::new();
+        let mut values: Vec<Value> = Vec::new();
+        if let Some(app_id) = &filter.app_id {
+            conditions.push("app_id = ?");
+            values.push(Value::Text(app_id.clone()));
+        }
+        if let Some(since) = filter.since {
+            conditions.push("ts >= ?");
+            values.push(Value::Integer(since.timestamp()));
+        }
+        if let Some(until) = filter.until {
+            conditions.push("ts < ?");
+            values.push(Value::Integer(until.timestamp()));
+        }
+
+        let mut sql = format!("SELECT {} FROM audit_log", AUDIT_COLUMNS);
+        if !conditions.is_empty() {
+            sql.push_str(" WHERE ");
+            sql.push_str(&conditions.join(" AND "));
+        }
+        sql.push_str(" ORDER BY seq ASC");
+
+        let rows = {
+            let conn = self.reader().await?;
+            let mut stmt = conn.prepare(&sql).map_err(|e| {
+                mimir_core::MimirError::Database(anyhow::anyhow!("Failed to prepare query: {}", e))
+            })?;
+            let rows = stmt
+                .query_map(params_from_iter(values), AuditRow::from_row)
+                .and_then(|rows| rows.collect::<rusqlite::Result<Vec<_>>>())
+                .map_err(|e| {
+                    mimir_core::MimirError::Database(anyhow::anyhow!(
+                        "Failed to read audit log: {}",
+                        e
+                    ))
+                })?;
+            rows
+        };
+
+        rows.into_iter().map(AuditRow::into_entry).collect()
+    }
+
+    /// Walk the whole audit log and check every entry against the chain
+    ///
+    /// The chain must also reach the entry recorded in `audit_head`.
+    pub async fn verify_audit_chain(&self) -> Result<AuditVerification> {
+        let conn = self.reader().await?;
+        // Read the head first: entries appended meanwhile only lengthen the chain
+        let head: Option<ChainHead> = conn
+            .query_row("SELECT seq, hash FROM audit_head WHERE id = 1", [], |row| {
+                Ok((row.get(0)?, row.get(1)?))
+            })
+            .optional()
+            .map_err(|e| {
+                mimir_core::MimirError::Database(anyhow::anyhow!(
+                    "Failed to read audit head: {}",
+                    e
+                ))
+            })?;
+        let mut stmt = conn
+            .prepare(&format!(
+                "SELECT {} FROM audit_log ORDER BY seq ASC",
+                AUDIT_COLUMNS
+            ))
+            .map_err(|e| {
+                mimir_core::MimirError::Database(anyhow::anyhow!("Failed to prepare query: {}", e))
+            })?;
+        let rows = stmt.query_map([], AuditRow::from_row).map_err(|e| {
+            mimir_core::MimirError::Database(anyhow::anyhow!("Failed to read audit log: {}", e))
+        })?;
+
+        let mut verification = AuditVerification {
+            entries: 0,
+            first_invalid: None,
+        };
+        let mut expected_prev = GENESIS_HASH.to_vec();
+        let mut expected_seq = 1;
+        // A missing head cannot be reached
+        let mut head_reached = matches!(head, Some((0, _)));
+        for row in rows {
+            let row = row.map_err(|e| {
+                mimir_core::MimirError::Database(anyhow::anyhow!("Failed to read row: {}", e))
+            })?;
+            verification.entries += 1;
+
+            let mut intact = row.seq == expected_seq
+                && row.prev_hash == expected_prev
+                && row.hash == row.computed_hash();
+            if let Some((head_seq, head_hash)) = &head {
+                if row.seq == *head_seq {
+                    head_reached = true;
+                    intact &= row.hash == *head_hash;
+                }
+            }
+            if !intact && verification.first_invalid.is_none() {
+                verification.first_invalid = Some(row.seq);
+            }
+            expected_seq = row.seq + 1;
+            expected_prev = row.hash;
+        }
+
+        if !head_reached && verification.first_invalid.is_none() {
+            verification.first_invalid = Some(expected_seq);
+        }
+        Ok(verification)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mimir_core::crypto::CryptoManager;
+    use tempfile::TempDir;
+
+    fn create_test_database() -> (Database, TempDir) {
+        let temp_dir = TempDir::new().unwrap();
+        let crypto_manager =
+            CryptoManager::with_password(temp_dir.path().join("keyset.json"), "test-password")
+                .unwrap();
+        let db =
+            Database::with_crypto_manager(temp_dir.path().join("test.db"), crypto_manager).unwrap();
+        (db, temp_dir)
+    }
+
+    #[tokio::test]
+    async fn test_append_and_filter() {
+        let (db, _temp_dir) = create_test_database();
+
+        let id = uuid::Uuid::new_v4();
+        let first = db
+            .append_audit("notes-app", "add", &[id], Some("allowed"))
+            .await
+            .unwrap();
+        assert_eq!(first.seq, 1);
+        assert_eq!(first.prev_hash, GENESIS_HASH);
+        let second = db
+            .append_audit("chat-app", "search", &[], None)
+            .await
+            .unwrap();
+        assert_eq!(second.prev_hash, first.hash);
+
+        let all = db.audit_entries(&AuditFilter::default()).await.unwrap();
+        assert_eq!(all, vec![first.clone(), second]);
+
+        let notes = db
+            .audit_entries(&AuditFilter {
+                app_id: Some("notes-app".to_string()),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        assert_eq!(notes, vec![first]);
+        assert_eq!(notes[0].memory_ids, vec![id]);
+
+        let future = db
+            .audit_entries(&AuditFilter {
+                since: Some(chrono::Utc::now() + chrono::Duration::hours(1)),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        assert!(future.is_empty());
+
+        let verification = db.verify_audit_chain().await.unwrap();
+        assert_eq!(verification.entries, 2);
+        assert!(verification.is_valid());
+    }
+
+    #[tokio::test]
+    async fn test_log_is_append_only() {
+        let (db, _temp_dir) = create_test_database();
+        db.append_audit("notes-app", "add", &[], None)
+            .await
+            .unwrap();
+
+        let conn = db.conn.lock().await;
+        assert!(conn
+            .execute("UPDATE audit_log SET app_id = 'other'", [])
+            .is_err());
+        assert!(conn.execute("DELETE FROM audit_log", []).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_tampering_breaks_chain() {
+        let (db, _temp_dir) = create_test_database();
+        for op in ["add", "search", "delete"] {
+            db.append_audit("notes-app", op, &[], None).await.unwrap();
+        }
+
+        {
+            let conn = db.conn.lock().await;
+            conn.execute_batch(
+                "DROP TRIGGER audit_log_no_update;
+                 UPDATE audit_log SET op = 'read' WHERE seq = 2;",
+            )
+            .unwrap();
+        }
+        let verification = db.verify_audit_chain().await.unwrap();
+        assert_eq!(verification.entries, 3);
+        assert_eq!(verification.first_invalid, Some(2));
+
+        // Removing an entry is caught at the gap
+        {
+            let conn = db.conn.lock().await;
+            conn.execute_batch(
+                "UPDATE audit_log SET op = 'search' WHERE seq = 2;
+                 DROP TRIGGER audit_log_no_delete;
+                 DELETE FROM audit_log WHERE seq = 1;",
+            )
+            .unwrap();
+        }
+        assert_eq!(
+            db.verify_audit_chain().await.unwrap().first_invalid,
+            Some(2)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_truncation_breaks_chain() {
+        let (db, _temp_dir) = create_test_database();
+        for op in ["add", "search", "delete"] {
+            db.append_audit("notes-app", op, &[], None).await.unwrap();
+        }
+
+        {
+            let conn = db.conn.lock().await;
+            // The head cannot be wound back to hide the cut
+            assert!(conn.execute("UPDATE audit_head SET seq = 2", []).is_err());
+            assert!(conn.execute("DELETE FROM audit_head", []).is_err());
+            conn.execute_batch(
+                "DROP TRIGGER audit_log_no_delete;
+                 DELETE FROM audit_log WHERE seq = 3;",
+            )
+            .unwrap();
+        }
+        let verification = db.verify_audit_chain().await.unwrap();
+        assert_eq!(verification.entries, 2);
+        assert_eq!(verification.first_invalid, Some(3));
+
+        // Later entries chain from the head, so the gap stays visible
+        let next = db
+            .append_audit("notes-app", "read", &[], None)
+            .await
+            .unwrap();
+        assert_eq!(next.seq, 4);
+        let verification = db.verify_audit_chain().await.unwrap();
+        assert_eq!(verification.first_invalid, Some(4));
+    }
+}
@@ -0,0 +1,391 @@
+//! Online snapshots of the vault
+//!
+//! A snapshot is a directory under the backup path holding a consistent copy
+//! of the database taken with SQLite's online backup API, the keyset and a
+//! `manifest.json` listing every file with its size and SHA-256. The database
+//! copy stays encrypted under the same SQLCipher key. Snapshots are written to
+//! a temporary directory and renamed into place once complete, so a crash
+//! never leaves a half-written snapshot behind.
+//!
+//! The vector store is not copied: it is saved independently of the database,
+//! so its files could come from a different point in time. Every memory keeps
+//! its embedding in the database, and the vector index is rebuilt from those
+//! after a restore.
+
+use crate::{apply_key, migrations, Database};
+use mimir_core::{crypto::CryptoManager, Result};
+use rusqlite::{backup::Backup, Connection};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+/// Name of the manifest inside a snapshot directory
+pub const MANIFEST_FILE: &str = "manifest.json";
+
+/// Name of the database copy inside a snapshot directory
+pub const SNAPSHOT_DATABASE_FILE: &str = "mimir.db";
+
+/// Name of the keyset copy inside a snapshot directory
+pub const SNAPSHOT_KEYSET_FILE: &str = "keyset.json";
+
+/// Prefix of snapshot directory names; the rest is the creation time
+pub const SNAPSHOT_PREFIX: &str = "snapshot-";
+
+/// Current manifest format
+const MANIFEST_VERSION: u32 = 1;
+
+/// Database pages copied per online backup step
+const BACKUP_PAGES_PER_STEP: std::os::raw::c_int = 256;
+
+/// What a file in a snapshot is
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SnapshotFileKind {
+    Database,
+    Keyset,
+    /// Vector store file, only found in older snapshots and never restored
+    Vector,
+}
+
+/// One file recorded in a snapshot manifest
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SnapshotFile {
+    /// File name inside the snapshot directory
+    pub name: String,
+    pub kind: SnapshotFileKind,
+    pub size: u64,
+    /// Hex-encoded SHA-256 of the file contents
+    pub sha256: String,
+}
+
+/// Description of a snapshot, stored as `manifest.json`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SnapshotManifest {
+    pub version: u32,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    /// Schema version of the database copy
+    pub schema_version: u32,
+    /// Memories in the database copy, trash excluded
+    pub memory_count: usize,
+    pub files: Vec<SnapshotFile>,
+}
+
+/// Hex-encoded SHA-256 of a file, and its size
+pub type FileChecksum = (String, u64);
+
+/// Hex-encoded SHA-256 of a file, and its size
+pub fn file_checksum(path: &Path) -> std::io::Result<FileChecksum> {
+    let mut file = fs::File::open(path)?;
+    let mut hasher = Sha256::new();
+    let mut buffer = [0u8; 64 * 1024];
+    let mut size = 0u64;
+    loop {
+        let read = file.read(&mut buffer)?;
+        if read == 0 {
+            break;
+        }
+        hasher.update(&buffer[..read]);
+        size += read as u64;
+    }
+    Ok((hex::encode(hasher.finalize()), size))
+}
+
+/// Read the manifest of a snapshot directory
+pub fn read_manifest(snapshot_dir: &Path) -> Result<SnapshotManifest> {
+    let data = fs::read(snapshot_dir.join(MANIFEST_FILE)).map_err(|e| {
+        mimir_core::MimirError::Database(anyhow::anyhow!(
+            "Failed to read manifest in {}: {}",
+            snapshot_dir.display(),
+            e
+        ))
+    })?;
+    serde_json::from_slice(&data).map_err(|e| {
+        mimir_core::MimirError::Database(anyhow::anyhow!("Invalid snapshot manifest: {}", e))
+    })
+}
+
+/// Completed snapshots in `backup_dir`, oldest first
+pub fn list_snapshots(backup_dir: &Path) -> Result<Vec<PathBuf>> {
+    if !backup_dir.exists() {
+        return Ok(vec![]);
+    }
+    let entries = fs::read_dir(backup_dir).map_err(|e| {
+        mimir_core::MimirError::Database(anyhow::anyhow!("Failed to read backup directory: {}", e))
+    })?;
+
+    let mut snapshots: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| {
+            path.file_name()
+                .and_then(|name| name.to_str())
+                .is_some_and(|name| name.starts_with(SNAPSHOT_PREFIX))
+                && path.join(MANIFEST_FILE).is_file()
+        })
+        .collect();
+    // Names embed the creation time, so they sort chronologically
+    snapshots.sort();
+    Ok(snapshots)
+}
+
+/// Delete the oldest snapshots so that at most `max_backups` remain
+///
+/// `max_backups` of 0 keeps every snapshot. Returns the removed directories.
+pub fn rotate_snapshots(backup_dir: &Path, max_backups: usize) -> Result<Vec<PathBuf>> {
+    let snapshots = list_snapshots(backup_dir)?;
+    if max_backups == 0 || snapshots.len() <= max_backups {
+        return Ok(vec![]);
+    }
+
+    let excess = snapshots.len() - max_backups;
+    let removed: Vec<PathBuf> = snapshots.into_iter().take(excess).collect();
+    for snapshot in &removed {
+        fs::remove_dir_all(snapshot).map_err(|e| {
+            mimir_core::MimirError::Database(anyhow::anyhow!(
+                "Failed to remove old snapshot {}: {}",
+                snapshot.display(),
+                e
+            ))
+        })?;
+    }
+    Ok(removed)
+}
+
+/// Copy a file into the snapshot and record it
+fn add_file(
+    snapshot_dir: &Path,
+    source: &Path,
+    name: &str,
+    kind: SnapshotFileKind,
+) -> Result<SnapshotFile> {
+    let target = snapshot_dir.join(name);
+    if target != source {
+        fs::copy(source, &target).map_err(|e| {
+            mimir_core::MimirError::Database(anyhow::anyhow!(
+                "Failed to copy {} into snapshot: {}",
+                source.display(),
+                e
+            ))
+        })?;
+    }
+    let (sha256, size) = file_checksum(&target).map_err(|e| {
+        mimir_core::MimirError::Database(anyhow::anyhow!("Failed to checksum {}: {}", name, e))
+    })?;
+    Ok(SnapshotFile {
+        name: name.to_string(),
+        kind,
+        size,
+        sha256,
+    })
+}
+
+/// Copy an open database to `dest` with SQLite's online backup API
+///
+/// The copy is keyed with the vault's SQLCipher key.
+pub(crate) fn copy_database(
+    conn: &Connection,
+    dest: &Path,
+    crypto_manager: &CryptoManager,
+) -> Result<()> {
+    let mut dest_conn = Connection::open(dest).map_err(|e| {
+        mimir_core::MimirError::Database(anyhow::anyhow!("Failed to create backup database: {}", e))
+    })?;
+    apply_key(&dest_conn, crypto_manager)?;
+
+    let backup = Backup::new(conn, &mut dest_conn).map_err(|e| {
+        mimir_core::MimirError::Database(anyhow::anyhow!("Failed to start backup: {}", e))
+    })?;
+    backup
+        .run_to_completion(
+            BACKUP_PAGES_PER_STEP,
+            std::time::Duration::from_millis(0),
+            None,
+        )
+        .map_err(|e| {
+            mimir_core::MimirError::Database(anyhow::anyhow!("Failed to back up database: {}", e))
+        })
+}
+
+impl Database {
+    /// Copy the live database to `dest` with SQLite's online backup API
+    ///
+    /// The copy is keyed with the same SQLCipher key as this database.
+    pub async fn backup_to(&self, dest: &Path) -> Result<()> {
+        let conn = self.conn.lock().await;
+        copy_database(&conn, dest, &self.crypto_manager)
+    }
+
+    /// Write a new snapshot of the vault into `backup_dir`
+    ///
+    /// Returns the snapshot directory.
+    pub async fn create_snapshot(&self, backup_dir: &Path, keyset_path: &Path) -> Result<PathBuf> {
+        let created_at = chrono::Utc::now();
+        let name = format!(
+            "{}{}",
+            SNAPSHOT_PREFIX,
+            created_at.format("%Y%m%dT%H%M%S%.3fZ")
+        );
+        let snapshot_dir = backup_dir.join(&name);
+        let partial_dir = backup_dir.join(format!(".{}.partial", name));
+
+        fs::create_dir_all(&partial_dir).map_err(|e| {
+            mimir_core::MimirError::Database(anyhow::anyhow!(
+                "Failed to create snapshot directory: {}",
+                e
+            ))
+        })?;
+
+        let result = self
+            .write_snapshot(&partial_dir, created_at, keyset_path)
+            .await
+            .and_then(|_| {
+                fs::rename(&partial_dir, &snapshot_dir).map_err(|e| {
+                    mimir_core::MimirError::Database(anyhow::anyhow!(
+                        "Failed to finalize snapshot: {}",
+                        e
+                    ))
+                })
+            });
+        if result.is_err() {
+            let _ = fs::remove_dir_all(&partial_dir);
+        }
+        result.map(|_| snapshot_dir)
+    }
+
+    async fn write_snapshot(
+        &self,
+        dir: &Path,
+        created_at: chrono::DateTime<chrono::Utc>,
+        keyset_path: &Path,
+    ) -> Result<()> {
+        let db_copy = dir.join(SNAPSHOT_DATABASE_FILE);
+
+        // Read the manifest values under the same lock as the copy, so no
+        // write can land in between
+        let (schema_version, memory_count) = {
+            let conn = self.conn.lock().await;
+            copy_database(&conn, &db_copy, &self.crypto_manager)?;
+            let status = migrations::read_status(&conn)?;
+            let count: i64 = conn
+                .query_row(
+                    "SELECT COUNT(*) FROM memory WHERE deleted_ts IS NULL",
+                    [],
+                    |row| row.get(0),
+                )
+                .map_err(|e| {
+                    mimir_core::MimirError::Database(anyhow::anyhow!(
+                        "Failed to count memories: {}",
+                        e
+                    ))
+                })?;
+            (status.current_version(), count as usize)
+        };
+
+        let files = vec![
+            add_file(
+                dir,
+                &db_copy,
+                SNAPSHOT_DATABASE_FILE,
+                SnapshotFileKind::Database,
+            )?,
+            add_file(
+                dir,
+                keyset_path,
+                SNAPSHOT_KEYSET_FILE,
+                SnapshotFileKind::Keyset,
+            )?,
+        ];
+
+        let manifest = SnapshotManifest {
+            version: MANIFEST_VERSION,
+            created_at,
+            schema_version,
+            memory_count,
+            files,
+        };
+        let json = serde_json::to_vec_pretty(&manifest).map_err(|e| {
+            mimir_core::MimirError::Database(anyhow::anyhow!("Failed to serialize manifest: {}", e))
+        })?;
+        fs::write(dir.join(MANIFEST_FILE), json).map_err(|e| {
+            mimir_core::MimirError::Database(anyhow::anyhow!("Failed to write manifest: {}", e))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mimir_core::crypto::CryptoManager;
+    use mimir_core::test_utils::MemoryBuilder;
+    use tempfile::TempDir;
+
+    fn create_test_database(dir: &Path) -> Database {
+        let crypto_manager =
+            CryptoManager::with_password(dir.join("keyset.json"), "test-password").unwrap();
+        Database::with_crypto_manager(dir.join("test.db"), crypto_manager).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_create_snapshot() {
+        let temp_dir = TempDir::new().unwrap();
+        let db = create_test_database(temp_dir.path());
+        let memory = MemoryBuilder::new().with_content("Backed up").build();
+        db.store_memory(&memory).await.unwrap();
+
+        let backup_dir = temp_dir.path().join("backups");
+        let snapshot = db
+            .create_snapshot(&backup_dir, &temp_dir.path().join("keyset.json"))
+            .await
+            .unwrap();
+
+        let manifest = read_manifest(&snapshot).unwrap();
+        assert_eq!(manifest.memory_count, 1);
+        assert_eq!(manifest.schema_version, migrations::latest_version());
+        let kinds: Vec<SnapshotFileKind> = manifest.files.iter().map(|file| file.kind).collect();
+        assert_eq!(
+            kinds,
+            vec![SnapshotFileKind::Database, SnapshotFileKind::Keyset]
+        );
+        for file in &manifest.files {
+            let (sha256, size) = file_checksum(&snapshot.join(&file.name)).unwrap();
+            assert_eq!((sha256, size), (file.sha256.clone(), file.size));
+        }
+
+        // The copy opens with the same keys and holds the memory
+        let crypto_manager =
+            CryptoManager::with_password(snapshot.join(SNAPSHOT_KEYSET_FILE), "test-password")
+                .unwrap();
+        let copy =
+            Database::with_crypto_manager(snapshot.join(SNAPSHOT_DATABASE_FILE), crypto_manager)
+                .unwrap();
+        assert_eq!(
+            copy.get_memory(memory.id).await.unwrap().unwrap().content,
+            "Backed up"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_rotate_snapshots() {
+        let temp_dir = TempDir::new().unwrap();
+        let db = create_test_database(temp_dir.path());
+        let backup_dir = temp_dir.path().join("backups");
+        let keyset_path = temp_dir.path().join("keyset.json");
+
+        let mut created = Vec::new();
+        for _ in 0..3 {
+            created.push(db.create_snapshot(&backup_dir, &keyset_path).await.unwrap());
+            tokio::time::sleep(std::time::Duration::from_millis(5)).await;
+        }
+        // Leftovers of an interrupted snapshot are never listed
+        fs::create_dir_all(backup_dir.join(".snapshot-interrupted.partial")).unwrap();
+
+        assert_eq!(list_snapshots(&backup_dir).unwrap(), created);
+        assert!(rotate_snapshots(&backup_dir, 0).unwrap().is_empty());
+        assert_eq!(
+            rotate_snapshots(&backup_dir, 2).unwrap(),
+            vec![created[0].clone()]
+        );
+        assert_eq!(list_snapshots(&backup_dir).unwrap(), created[1..].to_vec());
+    }
+}
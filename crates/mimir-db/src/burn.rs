@@ -0,0 +1,314 @@
+//! Burning a class
+//!
+//! Burning destroys every row of a class, or of the whole vault: memories,
+//! trashed or not, their revisions, keyword entries, relations and
+//! attachments. The class keys are purged from the keyset before any row is
+//! deleted, so ciphertext left behind no longer decrypts with this vault's
+//! keys, and an interrupted burn never leaves a key whose rows are gone;
+//! running it again deletes what is left. The database file is then vacuumed
+//! with `secure_delete` on so freed pages and the WAL do not keep old data.
+//! The audit log is kept.
+
+use crate::Database;
+use mimir_core::{MemoryClass, MemoryId, Result};
+use rusqlite::params;
+
+/// What a burn destroyed
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BurnReport {
+    /// Memories deleted, including trashed ones
+    pub memories: Vec<MemoryId>,
+    pub revisions: usize,
+    pub relations: usize,
+    pub keyword_entries: usize,
+    pub attachments: usize,
+    /// Classes whose keys were purged
+    pub purged_classes: Vec<String>,
+}
+
+impl Database {
+    /// Destroy every row of `class`, or of every class when `None`
+    ///
+    /// Returns what was destroyed; callers drop the memories' vectors.
+    pub async fn burn(&self, class: Option<&MemoryClass>) -> Result<BurnReport> {
+        let class_id = class.map(|class| class.as_str().to_string());
+        let mut conn = self.conn.lock().await;
+
+        // Zero deleted content instead of only unlinking the pages
+        conn.execute_batch("PRAGMA secure_delete = ON;")
+            .map_err(|e| {
+                mimir_core::MimirError::Database(anyhow::anyhow!(
+                    "Failed to enable secure delete: {}",
+                    e
+                ))
+            })?;
+
+        let tx = conn.transaction().map_err(|e| {
+            mimir_core::MimirError::Database(anyhow::anyhow!("Failed to begin transaction: {}", e))
+        })?;
+
+        let (ids, mut purged_classes) = tx
+            .prepare("SELECT id FROM memory WHERE ?1 IS NULL OR class_id = ?1")
+            .and_then(|mut stmt| {
+                stmt.query_map(params![class_id], |row| row.get::<_, String>(0))?
+                    .collect::<rusqlite::Result<Vec<_>>>()
+            })
+            .and_then(|ids| {
+                let classes = tx
+                    .prepare(
+                        "SELECT class_id FROM memory WHERE ?1 IS NULL OR class_id = ?1
+                         UNION SELECT class_id FROM memory_revision WHERE ?1 IS NULL OR class_id = ?1
+                         UNION SELECT class_id FROM memory_relation WHERE ?1 IS NULL OR class_id = ?1
+                         UNION SELECT class_id FROM memory_keyword WHERE ?1 IS NULL OR class_id = ?1
+                         UNION SELECT class_id FROM memory_attachment WHERE ?1 IS NULL OR class_id = ?1",
+                    )?
+                    .query_map(params![class_id], |row| row.get::<_, String>(0))?
+                    .collect::<rusqlite::Result<Vec<_>>>()?;
+                Ok((ids, classes))
+            })
+            .map_err(|e| {
+                mimir_core::MimirError::Database(anyhow::anyhow!(
+                    "Failed to read memories to burn: {}",
+                    e
+                ))
+            })?;
+
+        // Destroy the keys first, so no row is deleted while its key survives
+        if let Some(class_id) = &class_id {
+            if !purged_classes.contains(class_id) {
+                purged_classes.push(class_id.clone());
+            }
+        }
+        purged_classes.sort();
+        for class in &purged_classes {
+            self.crypto_manager.purge_class(class)?;
+        }
+
+        let delete = |sql: &str| {
+            tx.execute(sql, params![class_id]).map_err(|e| {
+                mimir_core::MimirError::Database(anyhow::anyhow!("Failed to burn memories: {}", e))
+            })
+        };
+        // Edges touching a burned memory go too, whichever class labelled them
+        let relations = delete(
+            "DELETE FROM memory_relation
+             WHERE ?1 IS NULL OR class_id = ?1
+                OR source_id IN (SELECT id FROM memory WHERE class_id = ?1)
+                OR target_id IN (SELECT id FROM memory WHERE class_id = ?1)",
+        )?;
+        let revisions = delete(
+            "DELETE FROM memory_revision
+             WHERE ?1 IS NULL OR class_id = ?1
+                OR memory_id IN (SELECT id FROM memory WHERE class_id = ?1)",
+        )?;
+        let keyword_entries = delete(
+            "DELETE FROM memory_keyword
+             WHERE ?1 IS NULL OR class_id = ?1
+                OR memory_id IN (SELECT id FROM memory WHERE class_id = ?1)",
+        )?;
+        let attachments = delete(
+            "DELETE FROM memory_attachment
+             WHERE ?1 IS NULL OR class_id = ?1
+                OR memory_id IN (SELECT id FROM memory WHERE class_id = ?1)",
+        )?;
+        delete("DELETE FROM memory WHERE ?1 IS NULL OR class_id = ?1")?;
+
+        tx.commit().map_err(|e| {
+            mimir_core::MimirError::Database(anyhow::anyhow!("Failed to commit transaction: {}", e))
+        })?;
+
+        // Checkpoint before and after so neither the WAL nor free pages keep old rows
+        conn.execute_batch(
+            "PRAGMA wal_checkpoint(TRUNCATE);
+             VACUUM;
+             PRAGMA wal_checkpoint(TRUNCATE);",
+        )
+        .map_err(|e| {
+            mimir_core::MimirError::Database(anyhow::anyhow!("Failed to vacuum database: {}", e))
+        })?;
+        drop(conn);
+
+        let memories = ids
+            .iter()
+            .map(|id| {
+                uuid::Uuid::parse_str(id).map_err(|e| {
+                    mimir_core::MimirError::Database(anyhow::anyhow!("Invalid UUID: {}", e))
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(BurnReport {
+            memories,
+            revisions,
+            relations,
+            keyword_entries,
+            attachments,
+            purged_classes,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mimir_core::crypto::{Ciphertext, CryptoManager};
+    use mimir_core::test_utils::MemoryBuilder;
+    use mimir_core::{MemoryRelation, RelationKind};
+    use tempfile::TempDir;
+
+    fn create_test_database() -> (Database, TempDir) {
+        let temp_dir = TempDir::new().unwrap();
+        let crypto_manager =
+            CryptoManager::with_password(temp_dir.path().join("keyset.json"), "test-password")
+                .unwrap();
+        let db =
+            Database::with_crypto_manager(temp_dir.path().join("test.db"), crypto_manager).unwrap();
+        (db, temp_dir)
+    }
+
+    #[tokio::test]
+    async fn test_burn_class() {
+        let (db, _temp_dir) = create_test_database();
+
+        let mut health = MemoryBuilder::new()
+            .with_content("Blood test results")
+            .with_class(MemoryClass::Health)
+            .build();
+        let trashed = MemoryBuilder::new()
+            .with_content("Old prescription")
+            .with_class(MemoryClass::Health)
+            .build();
+        let work = MemoryBuilder::new()
+            .with_content("Standup notes")
+            .with_class(MemoryClass::Work)
+            .build();
+        db.store_memories(&[health.clone(), trashed.clone(), work.clone()])
+            .await
+            .unwrap();
+        health.content = "Blood test results, follow-up booked".to_string();
+        db.update_memory(&health).await.unwrap();
+        db.delete_memory(trashed.id).await.unwrap();
+        db.add_attachment(health.id, "results.pdf", "application/pdf", b"%PDF-1.7")
+            .await
+            .unwrap();
+        let work_attachment = db
+            .add_attachment(work.id, "board.jpg", "image/jpeg", b"JFIF")
+            .await
+            .unwrap();
+        db.link_memories(&MemoryRelation {
+            source: work.id,
+            target: health.id,
+            kind: RelationKind::RelatedTo,
+            label: None,
+            created_at: chrono::Utc::now(),
+        })
+        .await
+        .unwrap();
+
+        let report = db.burn(Some(&MemoryClass::Health)).await.unwrap();
+        let mut burned = report.memories.clone();
+        burned.sort();
+        let mut expected = vec![health.id, trashed.id];
+        expected.sort();
+        assert_eq!(burned, expected);
+        assert_eq!(
+            (report.revisions, report.relations, report.attachments),
+            (1, 1, 1)
+        );
+        assert!(report.keyword_entries > 0);
+        assert_eq!(report.purged_classes, vec!["health".to_string()]);
+
+        assert!(db.get_memory(health.id).await.unwrap().is_none());
+        assert!(db.list_trash().await.unwrap().is_empty());
+        assert!(db.list_relations(work.id).await.unwrap().is_empty());
+        assert!(db.keyword_search("blood", 10).await.unwrap().is_empty());
+        assert!(db.get_memory(work.id).await.unwrap().is_some());
+        assert!(db
+            .get_attachment(work_attachment.id)
+            .await
+            .unwrap()
+            .is_some());
+
+        // Vacuumed: no free pages left holding old rows
+        let conn = db.conn.lock().await;
+        let free_pages: i64 = conn
+            .query_row("PRAGMA freelist_count", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(free_pages, 0);
+    }
+
+    #[tokio::test]
+    async fn test_burn_all() {
+        let (db, _temp_dir) = create_test_database();
+
+        let memories = vec![
+            MemoryBuilder::new()
+                .with_content("One")
+                .with_class(MemoryClass::Personal)
+                .build(),
+            MemoryBuilder::new()
+                .with_content("Two")
+                .with_class(MemoryClass::Financial)
+                .build(),
+        ];
+        db.store_memories(&memories).await.unwrap();
+        db.append_audit("test", "add", &[memories[0].id], None)
+            .await
+            .unwrap();
+
+        let report = db.burn(None).await.unwrap();
+        assert_eq!(report.memories.len(), 2);
+        assert_eq!(
+            report.purged_classes,
+            vec!["financial".to_string(), "personal".to_string()]
+        );
+        assert_eq!(db.vault_counts().await.unwrap().memories, 0);
+
+        // The audit trail survives a burn
+        assert!(db.verify_audit_chain().await.unwrap().is_valid());
+        assert_eq!(db.verify_audit_chain().await.unwrap().entries, 1);
+    }
+
+    #[tokio::test]
+    async fn test_burned_ciphertext_stays_unreadable_after_reopen() {
+        let (db, temp_dir) = create_test_database();
+        let burned = MemoryBuilder::new()
+            .with_content("Diagnosis")
+            .with_class(MemoryClass::Health)
+            .build();
+        db.store_memory(&burned).await.unwrap();
+        let ciphertext: Ciphertext = {
+            let conn = db.conn.lock().await;
+            let text_enc: Vec<u8> = conn
+                .query_row(
+                    "SELECT text_enc FROM memory WHERE id = ?1",
+                    params![burned.id.to_string()],
+                    |row| row.get(0),
+                )
+                .unwrap();
+            serde_json::from_slice(&text_enc).unwrap()
+        };
+
+        db.burn(Some(&MemoryClass::Health)).await.unwrap();
+        drop(db);
+
+        let crypto_manager =
+            CryptoManager::with_password(temp_dir.path().join("keyset.json"), "test-password")
+                .unwrap();
+        let db =
+            Database::with_crypto_manager(temp_dir.path().join("test.db"), crypto_manager).unwrap();
+        assert!(db.crypto_manager.decrypt("health", &ciphertext).is_err());
+
+        // New memories in the class get a new key, not the burned one again
+        let later = MemoryBuilder::new()
+            .with_content("Checkup")
+            .with_class(MemoryClass::Health)
+            .build();
+        db.store_memory(&later).await.unwrap();
+        assert!(db.crypto_manager.decrypt("health", &ciphertext).is_err());
+        assert_eq!(
+            db.get_memory(later.id).await.unwrap().unwrap().content,
+            "Checkup"
+        );
+    }
+}
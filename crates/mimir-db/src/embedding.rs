@@ -0,0 +1,234 @@
+//! Stored embeddings
+//!
+//! Each memory's embedding is kept in `embedding_enc`, encrypted under the
+//! class key next to its content, with the ID of the model that produced it.
+//! The vector index holds a copy, so it can be rebuilt from the database
+//! without running the embedder again.
+
+use crate::{decrypt_blob, Database};
+use mimir_core::{crypto::CryptoManager, MemoryId, Result};
+
+/// An embedding read back from the database
+#[derive(Debug, Clone, PartialEq)]
+pub struct StoredEmbedding {
+    pub memory_id: MemoryId,
+    /// Model that produced the vector
+    pub model: String,
+    pub vector: Vec<f32>,
+}
+
+/// Embeddings of the live memories, for rebuilding the vector index
+#[derive(Debug, Clone, Default)]
+pub struct EmbeddingScan {
+    pub embeddings: Vec<StoredEmbedding>,
+    /// Memories stored without an embedding; they need embedding again
+    pub missing: Vec<MemoryId>,
+}
+
+/// Model ID and vector of a decoded embedding
+type DecodedEmbedding = (String, Vec<f32>);
+
+/// Serialize an embedding before encryption
+///
+/// Layout: model ID length (u16), model ID (UTF-8), dimension (u32), then
+/// `dimension` f32 values, all little-endian.
+pub(crate) fn encode(model: &str, vector: &[f32]) -> Result<Vec<u8>> {
+    let model_len = u16::try_from(model.len()).map_err(|_| {
+        mimir_core::MimirError::Database(anyhow::anyhow!("Embedding model ID is too long"))
+    })?;
+    let dimension = u32::try_from(vector.len())
+        .map_err(|_| mimir_core::MimirError::Database(anyhow::anyhow!("Embedding is too large")))?;
+
+    let mut bytes = Vec::with_capacity(2 + model.len() + 4 + vector.len() * 4);
+    bytes.extend_from_slice(&model_len.to_le_bytes());
+    bytes.extend_from_slice(model.as_bytes());
+    bytes.extend_from_slice(&dimension.to_le_bytes());
+    for value in vector {
+        bytes.extend_from_slice(&value.to_le_bytes());
+    }
+    Ok(bytes)
+}
+
+/// Parse an embedding written by `encode` into its model ID and vector
+pub(crate) fn decode(bytes: &[u8]) -> Result<DecodedEmbedding> {
+    let invalid = || mimir_core::MimirError::Database(anyhow::anyhow!("Malformed embedding"));
+
+    let model_len: [u8; 2] = bytes
+        .get(..2)
+        .and_then(|prefix| prefix.try_into().ok())
+        .ok_or_else(invalid)?;
+    let model_len = u16::from_le_bytes(model_len) as usize;
+    let rest = &bytes[2..];
+    if rest.len() < model_len {
+        return Err(invalid());
+    }
+    let (model, rest) = rest.split_at(model_len);
+    let model = String::from_utf8(model.to_vec()).map_err(|_| invalid())?;
+
+    let dimension: [u8; 4] = rest
+        .get(..4)
+        .and_then(|prefix| prefix.try_into().ok())
+        .ok_or_else(invalid)?;
+    let dimension = u32::from_le_bytes(dimension) as usize;
+    let rest = &rest[4..];
+    if rest.len() != dimension * 4 {
+        return Err(invalid());
+    }
+    let vector = rest
+        .chunks_exact(4)
+        .map(|chunk| f32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]))
+        .collect();
+
+    Ok((model, vector))
+}
+
+/// Decrypt an `embedding_enc` column
+pub(crate) fn decrypt(
+    crypto_manager: &CryptoManager,
+    class_id: &str,
+    data: &[u8],
+) -> Result<DecodedEmbedding> {
+    decode(&decrypt_blob(crypto_manager, class_id, data)?)
+}
+
+impl Database {
+    /// Read the stored embeddings of every memory not in the trash
+    ///
+    /// Only the embedding column is decrypted.
+    pub async fn stored_embeddings(&self) -> Result<EmbeddingScan> {
+        let conn = self.reader().await?;
+        let rows = conn
+            .prepare(
+                "SELECT id, class_id, embedding_enc FROM memory
+                 WHERE deleted_ts IS NULL ORDER BY ts ASC, id ASC",
+            )
+            .and_then(|mut stmt| {
+                stmt.query_map([], |row| {
+                    Ok((
+                        row.get::<_, String>(0)?,
+                        row.get::<_, String>(1)?,
+                        row.get::<_, Option<Vec<u8>>>(2)?,
+                    ))
+                })?
+                .collect::<rusqlite::Result<Vec<_>>>()
+            })
+            .map_err(|e| {
+                mimir_core::MimirError::Database(anyhow::anyhow!(
+                    "Failed to read embeddings: {}",
+                    e
+                ))
+            })?;
+        drop(conn);
+
+        let mut scan = EmbeddingScan::default();
+        for (id, class_id, embedding_enc) in rows {
+            let memory_id = uuid::Uuid::parse_str(&id).map_err(|e| {
+                mimir_core::MimirError::Database(anyhow::anyhow!("Invalid UUID: {}", e))
+            })?;
+            match embedding_enc {
+                Some(data) => {
+                    let (model, vector) = decrypt(&self.crypto_manager, &class_id, &data)?;
+                    scan.embeddings.push(StoredEmbedding {
+                        memory_id,
+                        model,
+                        vector,
+                    });
+                }
+                None => scan.missing.push(memory_id),
+            }
+        }
+        Ok(scan)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mimir_core::test_utils::MemoryBuilder;
+    use mimir_core::MemoryClass;
+    use tempfile::TempDir;
+
+    fn create_test_database() -> (Database, TempDir) {
+        let temp_dir = TempDir::new().unwrap();
+        let crypto_manager =
+            CryptoManager::with_password(temp_dir.path().join("keyset.json"), "test-password")
+                .unwrap();
+        let db =
+            Database::with_crypto_manager(temp_dir.path().join("test.db"), crypto_manager).unwrap();
+        (db, temp_dir)
+    }
+
+    #[test]
+    fn test_encode_round_trip() {
+        let vector = vec![0.25, -1.5, f32::MIN_POSITIVE, 3.0e7];
+        let bytes = encode("bge-small-en", &vector).unwrap();
+        assert_eq!(
+            decode(&bytes).unwrap(),
+            ("bge-small-en".to_string(), vector)
+        );
+
+        // Truncated or padded blobs are rejected
+        assert!(decode(&bytes[..bytes.len() - 1]).is_err());
+        assert!(decode(&[bytes.clone(), vec![0]].concat()).is_err());
+        assert!(decode(&[]).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_embedding_stored_with_memory() {
+        let (db, _temp_dir) = create_test_database();
+
+        let mut memory = MemoryBuilder::new()
+            .with_content("Allergic to penicillin")
+            .with_class(MemoryClass::Health)
+            .with_embedding(vec![0.1, 0.2, 0.3])
+            .with_embedding_model("bge-small-en")
+            .build();
+        let plain = MemoryBuilder::new().with_content("No embedding").build();
+        db.store_memories(&[memory.clone(), plain.clone()])
+            .await
+            .unwrap();
+
+        let stored = db.get_memory(memory.id).await.unwrap().unwrap();
+        assert_eq!(stored.embedding, Some(vec![0.1, 0.2, 0.3]));
+        assert_eq!(stored.embedding_model.as_deref(), Some("bge-small-en"));
+
+        let scan = db.stored_embeddings().await.unwrap();
+        assert_eq!(
+            scan.embeddings,
+            vec![StoredEmbedding {
+                memory_id: memory.id,
+                model: "bge-small-en".to_string(),
+                vector: vec![0.1, 0.2, 0.3],
+            }]
+        );
+        assert_eq!(scan.missing, vec![plain.id]);
+
+        // The column holds a ciphertext, not the raw floats
+        let conn = db.conn.lock().await;
+        let raw: Vec<u8> = conn
+            .query_row(
+                "SELECT embedding_enc FROM memory WHERE id = ?1",
+                [memory.id.to_string()],
+                |row| row.get(0),
+            )
+            .unwrap();
+        drop(conn);
+        assert!(serde_json::from_slice::<mimir_core::crypto::Ciphertext>(&raw).is_ok());
+
+        // Updating the content with a new embedding replaces it; the old one
+        // stays with the revision
+        memory.content = "Allergic to penicillin and latex".to_string();
+        memory.embedding = Some(vec![0.4, 0.5, 0.6]);
+        db.update_memory(&memory).await.unwrap();
+        let scan = db.stored_embeddings().await.unwrap();
+        assert_eq!(scan.embeddings[0].vector, vec![0.4, 0.5, 0.6]);
+        let revision = db.get_revision(memory.id, 1).await.unwrap().unwrap();
+        assert_eq!(revision.memory.embedding, Some(vec![0.1, 0.2, 0.3]));
+
+        // Trashed memories are not part of the index
+        db.delete_memory(memory.id).await.unwrap();
+        let scan = db.stored_embeddings().await.unwrap();
+        assert!(scan.embeddings.is_empty());
+        assert_eq!(scan.missing, vec![plain.id]);
+    }
+}
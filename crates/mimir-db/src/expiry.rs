@@ -0,0 +1,128 @@
+//! Memory expiry
+//!
+//! A memory with `expires_ts` set is hard-deleted once that time has passed,
+//! whether or not it is in the trash. Nothing is kept: the row, its revisions,
+//! keyword entries and attachments all go.
+
+use crate::{trash, Database};
+use mimir_core::{MemoryId, Result};
+use rusqlite::params;
+
+impl Database {
+    /// Permanently remove every memory that expired at or before `now`
+    ///
+    /// Returns the IDs that were destroyed so callers can drop their vectors.
+    pub async fn purge_expired(&self, now: chrono::DateTime<chrono::Utc>) -> Result<Vec<MemoryId>> {
+        let mut conn = self.conn.lock().await;
+        let tx = conn.transaction().map_err(|e| {
+            mimir_core::MimirError::Database(anyhow::anyhow!("Failed to begin transaction: {}", e))
+        })?;
+
+        let ids: Vec<String> = tx
+            .prepare("SELECT id FROM memory WHERE expires_ts IS NOT NULL AND expires_ts <= ?1")
+            .and_then(|mut stmt| {
+                stmt.query_map(params![now.timestamp()], |row| row.get(0))?
+                    .collect::<rusqlite::Result<Vec<_>>>()
+            })
+            .map_err(|e| {
+                mimir_core::MimirError::Database(anyhow::anyhow!(
+                    "Failed to read expired memories: {}",
+                    e
+                ))
+            })?;
+
+        trash::destroy(&tx, &ids).map_err(|e| {
+            mimir_core::MimirError::Database(anyhow::anyhow!(
+                "Failed to purge expired memories: {}",
+                e
+            ))
+        })?;
+        tx.commit().map_err(|e| {
+            mimir_core::MimirError::Database(anyhow::anyhow!("Failed to commit transaction: {}", e))
+        })?;
+
+        ids.iter()
+            .map(|id| {
+                uuid::Uuid::parse_str(id).map_err(|e| {
+                    mimir_core::MimirError::Database(anyhow::anyhow!("Invalid UUID: {}", e))
+                })
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mimir_core::crypto::CryptoManager;
+    use mimir_core::test_utils::MemoryBuilder;
+    use tempfile::TempDir;
+
+    fn create_test_database() -> (Database, TempDir) {
+        let temp_dir = TempDir::new().unwrap();
+        let crypto_manager =
+            CryptoManager::with_password(temp_dir.path().join("keyset.json"), "test-password")
+                .unwrap();
+        let db =
+            Database::with_crypto_manager(temp_dir.path().join("test.db"), crypto_manager).unwrap();
+        (db, temp_dir)
+    }
+
+    #[tokio::test]
+    async fn test_expires_at_round_trip() {
+        let (db, _temp_dir) = create_test_database();
+
+        let expires_at = chrono::DateTime::from_timestamp(4_102_444_800, 0).unwrap();
+        let memory = MemoryBuilder::new()
+            .with_content("Short-lived")
+            .with_expires_at(expires_at)
+            .build();
+        db.store_memory(&memory).await.unwrap();
+
+        let stored = db.get_memory(memory.id).await.unwrap().unwrap();
+        assert_eq!(stored.expires_at, Some(expires_at));
+    }
+
+    #[tokio::test]
+    async fn test_purge_expired() {
+        let (db, _temp_dir) = create_test_database();
+
+        let now = chrono::Utc::now();
+        let expired = MemoryBuilder::new()
+            .with_content("Expired")
+            .with_expires_at(now - chrono::Duration::minutes(5))
+            .build();
+        let trashed = MemoryBuilder::new()
+            .with_content("Expired in the trash")
+            .with_expires_at(now - chrono::Duration::minutes(5))
+            .build();
+        let pending = MemoryBuilder::new()
+            .with_content("Not yet")
+            .with_expires_at(now + chrono::Duration::days(1))
+            .build();
+        let forever = MemoryBuilder::new().with_content("Forever").build();
+        db.store_memories(&[
+            expired.clone(),
+            trashed.clone(),
+            pending.clone(),
+            forever.clone(),
+        ])
+        .await
+        .unwrap();
+        db.delete_memory(trashed.id).await.unwrap();
+
+        let mut purged = db.purge_expired(now).await.unwrap();
+        purged.sort();
+        let mut expected = vec![expired.id, trashed.id];
+        expected.sort();
+        assert_eq!(purged, expected);
+
+        assert!(db.get_memory(expired.id).await.unwrap().is_none());
+        assert!(db.list_trash().await.unwrap().is_empty());
+        assert!(db.keyword_search("expired", 10).await.unwrap().is_empty());
+        assert!(db.get_memory(pending.id).await.unwrap().is_some());
+        assert!(db.get_memory(forever.id).await.unwrap().is_some());
+
+        assert!(db.purge_expired(now).await.unwrap().is_empty());
+    }
+}
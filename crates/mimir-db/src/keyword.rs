@@ -0,0 +1,324 @@
+//! Encrypted keyword search via blind indexes
+//!
+//! Each memory's content is tokenized and every distinct term is stored as an
+//! HMAC token keyed from the memory's class key, together with its frequency.
+//! Plaintext terms never reach the database; a query is answered by blinding
+//! its terms the same way and matching tokens.
+
+use crate::{decode_memory, Database, MemoryRow, MEMORY_COLUMNS};
+use mimir_core::{crypto::CryptoManager, MemoryId, Result};
+use rusqlite::{params, params_from_iter, types::Value, Connection};
+use std::collections::HashMap;
+
+/// Shortest term that is indexed
+const MIN_TERM_LEN: usize = 2;
+
+/// A blind index token with the frequency of its term
+pub(crate) type BlindTerm = (Vec<u8>, u32);
+
+/// A matching memory's ID, number of matched terms and summed term frequency
+type KeywordScore = (String, u32, u32);
+
+/// Split text into lowercase alphanumeric terms
+pub fn tokenize(text: &str) -> Vec<String> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|term| term.chars().count() >= MIN_TERM_LEN)
+        .map(str::to_lowercase)
+        .collect()
+}
+
+/// Blind the terms of `content` under a class key, with term frequencies
+pub(crate) fn blind_terms(
+    crypto_manager: &CryptoManager,
+    class_id: &str,
+    content: &str,
+) -> Result<Vec<BlindTerm>> {
+    let mut frequencies: HashMap<String, u32> = HashMap::new();
+    for term in tokenize(content) {
+        *frequencies.entry(term).or_insert(0) += 1;
+    }
+
+    frequencies
+        .into_iter()
+        .map(|(term, tf)| {
+            let token = crypto_manager.blind_index(class_id, term.as_bytes())?;
+            Ok((token.to_vec(), tf))
+        })
+        .collect()
+}
+
+/// Replace the index entries of a memory
+pub(crate) fn write_terms(
+    conn: &Connection,
+    memory_id: &str,
+    class_id: &str,
+    terms: &[BlindTerm],
+) -> rusqlite::Result<()> {
+    conn.execute(
+        "DELETE FROM memory_keyword WHERE memory_id = ?1",
+        params![memory_id],
+    )?;
+
+    let mut stmt = conn.prepare_cached(
+        "INSERT INTO memory_keyword (memory_id, class_id, token, tf) VALUES (?1, ?2, ?3, ?4)",
+    )?;
+    for (token, tf) in terms {
+        stmt.execute(params![memory_id, class_id, token, tf])?;
+    }
+
+    Ok(())
+}
+
+/// Rebuild the whole keyword index from decrypted memory content
+pub(crate) fn rebuild_index(
+    conn: &mut Connection,
+    crypto_manager: &CryptoManager,
+) -> Result<usize> {
+    let tx = conn.transaction().map_err(|e| {
+        mimir_core::MimirError::Database(anyhow::anyhow!("Failed to begin transaction: {}", e))
+    })?;
+
+    let rows = {
+        let mut stmt = tx
+            .prepare(&format!("SELECT {} FROM memory", MEMORY_COLUMNS))
+            .map_err(|e| {
+                mimir_core::MimirError::Database(anyhow::anyhow!("Failed to prepare query: {}", e))
+            })?;
+        let rows = stmt
+            .query_map([], MemoryRow::from_row)
+            .map_err(|e| {
+                mimir_core::MimirError::Database(anyhow::anyhow!("Failed to execute query: {}", e))
+            })?
+            .collect::<rusqlite::Result<Vec<_>>>()
+            .map_err(|e| {
+                mimir_core::MimirError::Database(anyhow::anyhow!("Failed to read row: {}", e))
+            })?;
+        rows
+    };
+
+    tx.execute("DELETE FROM memory_keyword", []).map_err(|e| {
+        mimir_core::MimirError::Database(anyhow::anyhow!("Failed to clear keyword index: {}", e))
+    })?;
+
+    let count = rows.len();
+    for row in rows {
+        let class_id = row.class_id.clone();
+        let memory = decode_memory(crypto_manager, row)?;
+        let terms = blind_terms(crypto_manager, &class_id, &memory.content)?;
+        write_terms(&tx, &memory.id.to_string(), &class_id, &terms).map_err(|e| {
+            mimir_core::MimirError::Database(anyhow::anyhow!(
+                "Failed to write keyword index: {}",
+                e
+            ))
+        })?;
+    }
+
+    tx.commit().map_err(|e| {
+        mimir_core::MimirError::Database(anyhow::anyhow!("Failed to commit transaction: {}", e))
+    })?;
+
+    Ok(count)
+}
+
+impl Database {
+    /// Find memories containing the query's terms, best matches first
+    ///
+    /// Memories are ranked by the number of distinct query terms they contain,
+    /// then by the summed frequency of those terms.
+    pub async fn keyword_search(&self, query: &str, limit: usize) -> Result<Vec<MemoryId>> {
+        let mut terms = tokenize(query);
+        terms.sort();
+        terms.dedup();
+        if terms.is_empty() || limit == 0 {
+            return Ok(vec![]);
+        }
+
+        let conn = self.reader().await?;
+
+        // Tokens are class-specific, so blind the query once per indexed class
+        let class_ids: Vec<String> = conn
+            .prepare("SELECT DISTINCT class_id FROM memory_keyword")
+            .and_then(|mut stmt| {
+                stmt.query_map([], |row| row.get::<_, String>(0))?
+                    .collect::<rusqlite::Result<Vec<_>>>()
+            })
+            .map_err(|e| {
+                mimir_core::MimirError::Database(anyhow::anyhow!(
+                    "Failed to read indexed classes: {}",
+                    e
+                ))
+            })?;
+
+        let mut scored: Vec<KeywordScore> = Vec::new();
+        for class_id in class_ids {
+            // A purged class has no key and cannot be searched. While a class is
+            // being re-encrypted each term has a token per key version in use; a
+            // memory's entries are all under one version, so at most one matches.
+            let tokens: Vec<[u8; 32]> = match terms
+                .iter()
+                .map(|term| {
+                    self.crypto_manager
+                        .blind_indexes(&class_id, term.as_bytes())
+                })
+                .collect::<Result<Vec<_>>>()
+            {
+                Ok(tokens) => tokens.into_iter().flatten().collect(),
+                Err(_) => continue,
+            };
+
+            let placeholders = vec!["?"; tokens.len()].join(", ");
+            let sql = format!(
+                "SELECT memory_id, COUNT(*), SUM(tf) FROM memory_keyword
+                 WHERE class_id = ? AND token IN ({})
+                   AND memory_id IN (SELECT id FROM memory WHERE deleted_ts IS NULL)
+                 GROUP BY memory_id",
+                placeholders
+            );
+            let mut stmt = conn.prepare_cached(&sql).map_err(|e| {
+                mimir_core::MimirError::Database(anyhow::anyhow!("Failed to prepare query: {}", e))
+            })?;
+            let params = std::iter::once(Value::Text(class_id))
+                .chain(tokens.into_iter().map(|token| Value::Blob(token.to_vec())));
+            let rows = stmt
+                .query_map(params_from_iter(params), |row| {
+                    Ok((
+                        row.get::<_, String>(0)?,
+                        row.get::<_, u32>(1)?,
+                        row.get::<_, u32>(2)?,
+                    ))
+                })
+                .map_err(|e| {
+                    mimir_core::MimirError::Database(anyhow::anyhow!(
+                        "Failed to execute query: {}",
+                        e
+                    ))
+                })?;
+            for row in rows {
+                scored.push(row.map_err(|e| {
+                    mimir_core::MimirError::Database(anyhow::anyhow!("Failed to read row: {}", e))
+                })?);
+            }
+        }
+
+        scored.sort_by(|a, b| b.1.cmp(&a.1).then(b.2.cmp(&a.2)).then(a.0.cmp(&b.0)));
+        scored
+            .into_iter()
+            .take(limit)
+            .map(|(id, _, _)| {
+                uuid::Uuid::parse_str(&id).map_err(|e| {
+                    mimir_core::MimirError::Database(anyhow::anyhow!("Invalid UUID: {}", e))
+                })
+            })
+            .collect()
+    }
+
+    /// Rebuild the keyword index for every memory, returning the number indexed
+    pub async fn rebuild_keyword_index(&self) -> Result<usize> {
+        let mut conn = self.conn.lock().await;
+        rebuild_index(&mut conn, &self.crypto_manager)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mimir_core::test_utils::MemoryBuilder;
+    use mimir_core::MemoryClass;
+    use tempfile::TempDir;
+
+    fn create_test_database() -> (Database, TempDir) {
+        let temp_dir = TempDir::new().unwrap();
+        let crypto_manager =
+            CryptoManager::with_password(temp_dir.path().join("keyset.json"), "test-password")
+                .unwrap();
+        let db =
+            Database::with_crypto_manager(temp_dir.path().join("test.db"), crypto_manager).unwrap();
+        (db, temp_dir)
+    }
+
+    #[test]
+    fn test_tokenize() {
+        assert_eq!(
+            tokenize("Ticket JIRA-1234 for Project Odin, a test"),
+            vec!["ticket", "jira", "1234", "for", "project", "odin", "test"]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_keyword_search_ranking() {
+        let (db, _temp_dir) = create_test_database();
+
+        let once = MemoryBuilder::new()
+            .with_content("Odin review scheduled")
+            .build();
+        let twice = MemoryBuilder::new()
+            .with_content("Odin launch: Odin ships Friday")
+            .with_class(MemoryClass::Work)
+            .build();
+        let both = MemoryBuilder::new()
+            .with_content("Odin ticket JIRA-1234")
+            .build();
+        let none = MemoryBuilder::new().with_content("Unrelated note").build();
+        for memory in [&once, &twice, &both, &none] {
+            db.store_memory(memory).await.unwrap();
+        }
+
+        let results = db.keyword_search("odin", 10).await.unwrap();
+        assert_eq!(results.len(), 3);
+        assert_eq!(results[0], twice.id);
+
+        // Matching more distinct terms outranks repeating one term
+        let results = db.keyword_search("ODIN jira-1234", 10).await.unwrap();
+        assert_eq!(results[0], both.id);
+
+        assert_eq!(db.keyword_search("odin", 1).await.unwrap().len(), 1);
+        assert!(db.keyword_search("missing", 10).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_keyword_index_follows_updates_and_deletes() {
+        let (db, _temp_dir) = create_test_database();
+
+        let mut memory = MemoryBuilder::new()
+            .with_content("Codename Bifrost")
+            .build();
+        db.store_memory(&memory).await.unwrap();
+        assert_eq!(
+            db.keyword_search("bifrost", 10).await.unwrap(),
+            vec![memory.id]
+        );
+
+        memory.content = "Codename Asgard".to_string();
+        db.update_memory(&memory).await.unwrap();
+        assert!(db.keyword_search("bifrost", 10).await.unwrap().is_empty());
+        assert_eq!(
+            db.keyword_search("asgard", 10).await.unwrap(),
+            vec![memory.id]
+        );
+
+        db.delete_memory(memory.id).await.unwrap();
+        assert!(db.keyword_search("asgard", 10).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_rebuild_keyword_index() {
+        let (db, _temp_dir) = create_test_database();
+
+        let memory = MemoryBuilder::new()
+            .with_content("Heimdall watches")
+            .build();
+        db.store_memory(&memory).await.unwrap();
+
+        {
+            let conn = db.conn.lock().await;
+            conn.execute("DELETE FROM memory_keyword", []).unwrap();
+        }
+        assert!(db.keyword_search("heimdall", 10).await.unwrap().is_empty());
+
+        assert_eq!(db.rebuild_keyword_index().await.unwrap(), 1);
+        assert_eq!(
+            db.keyword_search("heimdall", 10).await.unwrap(),
+            vec![memory.id]
+        );
+    }
+}
@@ -1,23 +1,161 @@
 //! Mimir Database - Encrypted storage for memory entries
 
 use mimir_core::{crypto::CryptoManager, Memory, MemoryClass, MemoryId, Result};
-use rusqlite::{params, Connection};
+use rusqlite::{params, Connection, Row};
 use std::path::Path;
 use std::sync::Arc;
 use tokio::sync::Mutex;
 
+pub mod attachment;
+pub mod audit;
+pub mod backup;
+pub mod burn;
+pub mod embedding;
+pub mod expiry;
+pub mod keyword;
+pub mod listing;
+pub mod migrations;
+pub mod pool;
+pub mod relations;
+pub mod restore;
+pub mod revisions;
+pub mod root_rotation;
+pub mod rotation;
+pub mod stats;
+pub mod trash;
+
+pub use attachment::{Attachment, AttachmentId, AttachmentInfo};
+pub use audit::{AuditEntry, AuditFilter, AuditVerification};
+pub use backup::{SnapshotFile, SnapshotFileKind, SnapshotManifest};
+pub use burn::BurnReport;
+pub use embedding::{EmbeddingScan, StoredEmbedding};
+pub use migrations::MigrationStatus;
+pub use pool::DEFAULT_READ_CONNECTIONS;
+pub use relations::RelatedMemory;
+pub use restore::{RestoreScope, StagedSnapshot, VaultCounts};
+pub use revisions::MemoryRevision;
+pub use root_rotation::RootRotationReport;
+pub use rotation::ReencryptionProgress;
+pub use stats::{OrphanCounts, VaultStats};
+pub use trash::TrashedMemory;
+
+/// Source recorded for memories that carry no app ACL entry
+pub const DEFAULT_SOURCE: &str = "default_source";
+
+/// Columns selected for every memory read, in `MemoryRow::from_row` order
+const MEMORY_COLUMNS: &str = "id, source, class_id, text_enc, vec_id, ts, updated_ts, key_id, \
+     scope_enc, tags_enc, acl_enc, expires_ts, embedding_enc";
+
 /// Encrypted database for storing memories
+///
+/// Writes are serialized on one connection; reads run in parallel on a pool
+/// of read-only connections. Every method takes `&self`, so a database can be
+/// shared behind an `Arc`.
 pub struct Database {
     conn: Arc<Mutex<Connection>>,
-    crypto_manager: CryptoManager,
+    readers: pool::ReadPool,
+    crypto_manager: Arc<CryptoManager>,
+}
+
+/// Raw `memory` row as stored on disk, before decryption
+struct MemoryRow {
+    id: String,
+    source: String,
+    class_id: String,
+    text_enc: Vec<u8>,
+    ts: i64,
+    updated_ts: Option<i64>,
+    key_id: Option<String>,
+    scope_enc: Option<Vec<u8>>,
+    tags_enc: Option<Vec<u8>>,
+    acl_enc: Option<Vec<u8>>,
+    expires_ts: Option<i64>,
+    embedding_enc: Option<Vec<u8>>,
+}
+
+impl MemoryRow {
+    /// Read a row selected with `MEMORY_COLUMNS`
+    fn from_row(row: &Row<'_>) -> rusqlite::Result<Self> {
+        Ok(Self {
+            id: row.get(0)?,
+            source: row.get(1)?,
+            class_id: row.get(2)?,
+            text_enc: row.get(3)?,
+            // vec_id (column 4) predates stored embeddings and is unused
+            ts: row.get(5)?,
+            updated_ts: row.get(6)?,
+            key_id: row.get(7)?,
+            scope_enc: row.get(8)?,
+            tags_enc: row.get(9)?,
+            acl_enc: row.get(10)?,
+            expires_ts: row.get(11)?,
+            embedding_enc: row.get(12)?,
+        })
+    }
+}
+
+/// Memory encrypted and ready to be written as a `memory` row
+struct EncodedMemory {
+    id: String,
+    source: String,
+    class_id: String,
+    text_enc: Vec<u8>,
+    vec_id: i64,
+    ts: i64,
+    updated_ts: i64,
+    key_id: String,
+    scope_enc: Option<Vec<u8>>,
+    tags_enc: Vec<u8>,
+    acl_enc: Vec<u8>,
+    expires_ts: Option<i64>,
+    embedding_enc: Option<Vec<u8>>,
+    /// Version of the class key the row is encrypted under
+    key_version: u32,
+    /// Blind index tokens of the content with their term frequencies
+    keywords: Vec<keyword::BlindTerm>,
+}
+
+impl EncodedMemory {
+    /// Insert or replace the `memory` row and its keyword index entries
+    ///
+    /// Callers should run this inside a transaction.
+    fn write(&self, conn: &Connection) -> rusqlite::Result<()> {
+        conn.execute(
+            "INSERT OR REPLACE INTO memory
+                (id, source, class_id, text_enc, vec_id, ts, updated_ts, key_id, scope_enc, tags_enc, acl_enc,
+                 expires_ts, embedding_enc, key_version)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14)",
+            params![
+                self.id,
+                self.source,
+                self.class_id,
+                self.text_enc,
+                self.vec_id,
+                self.ts,
+                self.updated_ts,
+                self.key_id,
+                self.scope_enc,
+                self.tags_enc,
+                self.acl_enc,
+                self.expires_ts,
+                self.embedding_enc,
+                self.key_version,
+            ],
+        )?;
+        keyword::write_terms(conn, &self.id, &self.class_id, &self.keywords)
+    }
 }
 
 impl Database {
     /// Create a new encrypted database with an existing crypto manager
+    ///
+    /// Pass an `Arc<CryptoManager>` to share one manager, and its class key
+    /// cache, with other components.
     pub fn with_crypto_manager<P: AsRef<Path>>(
         db_path: P,
-        crypto_manager: CryptoManager,
+        crypto_manager: impl Into<Arc<CryptoManager>>,
     ) -> Result<Self> {
+        let crypto_manager = crypto_manager.into();
         let db_path = db_path.as_ref();
 
         // Validate path is not empty
@@ -37,8 +175,16 @@ impl Database {
             })?;
         }
 
+        if crypto_manager.root_rotation_pending() {
+            return Err(mimir_core::MimirError::Database(anyhow::anyhow!(
+                "A root key rotation is unfinished; run it again to resume it"
+            )));
+        }
+
+        let existed = db_path.exists();
+
         // Create an encrypted database with SQLCipher
-        let conn = Connection::open_with_flags(
+        let mut conn = Connection::open_with_flags(
             db_path,
             rusqlite::OpenFlags::SQLITE_OPEN_READ_WRITE | rusqlite::OpenFlags::SQLITE_OPEN_CREATE,
         )
@@ -46,75 +192,53 @@ impl Database {
             mimir_core::MimirError::Database(anyhow::anyhow!("Failed to open database: {}", e))
         })?;
 
-        // Set the SQLCipher key using PRAGMA - SQLCipher returns results from PRAGMA commands
-        let db_key_bytes = crypto_manager.get_db_key_bytes();
-        let db_key_hex = hex::encode(db_key_bytes);
-        let pragma_sql = format!("PRAGMA key = \"x'{}'\"", db_key_hex);
-        conn.execute_batch(&pragma_sql).map_err(|e| {
-            mimir_core::MimirError::Database(anyhow::anyhow!("Failed to set SQLCipher key: {}", e))
-        })?;
-
-        // Test that the key is correct by executing a simple query
-        conn.query_row("SELECT count(*) FROM sqlite_master", [], |row| {
-            let count: i64 = row.get(0)?;
-            Ok(count)
-        })
-        .map_err(|e| {
-            mimir_core::MimirError::Database(anyhow::anyhow!(
-                "Failed to verify SQLCipher key: {}",
+        // Set the SQLCipher key and verify it opens the database. A PBKDF2
+        // keyset without a root key check cannot tell a wrong password until now
+        apply_key(&conn, &crypto_manager).map_err(|e| {
+            if existed && crypto_manager.needs_kdf_upgrade() {
+                mimir_core::MimirError::WrongPassword
+            } else {
                 e
-            ))
+            }
         })?;
 
-        // Test inserting into memory table immediately after creation
-        conn.execute(
-            "CREATE TABLE IF NOT EXISTS memory (
-                id        TEXT PRIMARY KEY,
-                source    TEXT NOT NULL,
-                class_id  TEXT NOT NULL,
-                text_enc  BLOB NOT NULL,
-                vec_id    INTEGER NOT NULL,
-                ts        INTEGER NOT NULL
-            )",
-            [],
-        )
-        .map_err(|e| {
-            mimir_core::MimirError::Database(anyhow::anyhow!(
-                "Failed to create memory table: {}",
-                e
-            ))
-        })?;
+        // An existing database that opens proves the password of a PBKDF2
+        // keyset, which can now move to Argon2id
+        if existed {
+            crypto_manager.complete_kdf_upgrade()?;
+        }
 
-        // Create indexes for performance
-        conn.execute(
-            "CREATE INDEX IF NOT EXISTS idx_memory_class_id ON memory(class_id)",
-            [],
-        )
-        .map_err(|e| {
-            mimir_core::MimirError::Database(anyhow::anyhow!(
-                "Failed to create class_id index: {}",
-                e
-            ))
+        // WAL lets the read pool run alongside the writer
+        conn.execute_batch("PRAGMA journal_mode = WAL; PRAGMA synchronous = NORMAL;")
+            .map_err(|e| {
+                mimir_core::MimirError::Database(anyhow::anyhow!(
+                    "Failed to enable WAL mode: {}",
+                    e
+                ))
+            })?;
+        conn.busy_timeout(pool::BUSY_TIMEOUT).map_err(|e| {
+            mimir_core::MimirError::Database(anyhow::anyhow!("Failed to set busy timeout: {}", e))
         })?;
 
-        conn.execute(
-            "CREATE INDEX IF NOT EXISTS idx_memory_source_ts ON memory(source, ts DESC)",
-            [],
-        )
-        .map_err(|e| {
-            mimir_core::MimirError::Database(anyhow::anyhow!(
-                "Failed to create source_ts index: {}",
-                e
-            ))
-        })?;
+        // Bring the schema up to date
+        let applied = migrations::run_migrations(&mut conn, db_path, &crypto_manager)?;
+
+        // Index memories written before the keyword index existed
+        if applied.contains(&migrations::KEYWORD_INDEX_VERSION) {
+            keyword::rebuild_index(&mut conn, &crypto_manager)?;
+        }
 
         Ok(Database {
             conn: Arc::new(Mutex::new(conn)),
+            readers: pool::ReadPool::new(db_path, pool::DEFAULT_READ_CONNECTIONS),
             crypto_manager,
         })
     }
 
     /// Create a new encrypted database (backward compatibility - uses keychain-based crypto)
+    ///
+    /// Password-based and key file vaults are opened with `with_crypto_manager`,
+    /// e.g. from `mimir_core::unlock::Unlock`.
     pub fn new<P: AsRef<Path>>(db_path: P, keyset_path: P) -> Result<Self> {
         let db_path = db_path.as_ref();
         let keyset_path = keyset_path.as_ref();
@@ -139,107 +263,96 @@ impl Database {
             )));
         }
 
-        // Initialize crypto manager (use password-based in CI, keychain-based otherwise)
-        let crypto_manager = if std::env::var("CI").is_ok() {
-            // Use password-based encryption in CI environments
-            CryptoManager::with_password(keyset_path, "test-password-for-ci")?
-        } else {
-            // Use OS keychain in development/production
-            CryptoManager::new(keyset_path)?
-        };
+        Self::with_crypto_manager(db_path, CryptoManager::new(keyset_path)?)
+    }
 
-        Self::with_crypto_manager(db_path, crypto_manager)
+    /// Crypto manager the database encrypts with
+    pub fn crypto_manager(&self) -> &Arc<CryptoManager> {
+        &self.crypto_manager
     }
 
-    /// Store a memory in the database
-    pub async fn store_memory(&mut self, memory: &Memory) -> Result<()> {
-        // Determine class_id string
-        let class_id = match &memory.class {
-            MemoryClass::Personal => "personal",
-            MemoryClass::Work => "work",
-            MemoryClass::Health => "health",
-            MemoryClass::Financial => "financial",
-            MemoryClass::Other(s) => s,
-        };
+    /// Take a connection from the read pool
+    pub(crate) async fn reader(&self) -> Result<pool::PooledConnection<'_>> {
+        self.readers.get(&self.crypto_manager).await
+    }
 
-        // Encrypt memory content with class-specific key
-        let content_bytes = memory.content.as_bytes();
-        let ciphertext = self.crypto_manager.encrypt(class_id, content_bytes)?;
+    /// Store a memory in the database
+    ///
+    /// Content, scope, tags and the app ACL are encrypted under the class key.
+    pub async fn store_memory(&self, memory: &Memory) -> Result<()> {
+        let encoded = encode_memory(&self.crypto_manager, memory)?;
 
-        // Serialize the ciphertext (including nonce) for storage
-        let ciphertext_data = serde_json::to_vec(&ciphertext).map_err(|e| {
-            mimir_core::MimirError::Database(anyhow::anyhow!(
-                "Failed to serialize ciphertext: {}",
-                e
-            ))
+        // Insert into database
+        let mut conn = self.conn.lock().await;
+        let tx = conn.transaction().map_err(|e| {
+            mimir_core::MimirError::Database(anyhow::anyhow!("Failed to begin transaction: {}", e))
         })?;
+        if let Err(e) = encoded.write(&tx) {
+            // Try to get more information about the error
+            let error_msg = format!("Failed to store memory: {} (Error code: {:?})", e, e);
+            return Err(mimir_core::MimirError::Database(anyhow::anyhow!(error_msg)));
+        }
+        tx.commit().map_err(|e| {
+            mimir_core::MimirError::Database(anyhow::anyhow!("Failed to commit transaction: {}", e))
+        })
+    }
 
-        // Use a default source for now (can be made configurable later)
-        let source = "default_source";
-
-        // Use vec_id as 0 for now (can be updated when vector storage is implemented)
-        let vec_id = 0;
-
-        // Convert timestamp to Unix timestamp (seconds since epoch)
-        let ts = memory.created_at.timestamp();
-
-        // Insert into database
-        let conn = self.conn.lock().await;
-        let result = conn.execute(
-            "INSERT OR REPLACE INTO memory (id, source, class_id, text_enc, vec_id, ts)
-             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
-            params![
-                memory.id.to_string(),
-                source,
-                class_id,
-                ciphertext_data,
-                vec_id,
-                ts,
-            ],
-        );
+    /// Store a batch of memories in a single transaction
+    ///
+    /// Either every memory is written or none is: the batch is encrypted up
+    /// front and any failure rolls the whole transaction back. The error names
+    /// the memory that could not be stored.
+    pub async fn store_memories(&self, memories: &[Memory]) -> Result<usize> {
+        let encoded = memories
+            .iter()
+            .map(|memory| {
+                encode_memory(&self.crypto_manager, memory).map_err(|e| {
+                    mimir_core::MimirError::Database(anyhow::anyhow!(
+                        "Failed to encrypt memory {}: {}",
+                        memory.id,
+                        e
+                    ))
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
 
-        match result {
-            Ok(_) => Ok(()),
-            Err(e) => {
-                // Try to get more information about the error
-                let error_msg = format!("Failed to store memory: {} (Error code: {:?})", e, e);
-                Err(mimir_core::MimirError::Database(anyhow::anyhow!(error_msg)))
-            }
+        let mut conn = self.conn.lock().await;
+        let tx = conn.transaction().map_err(|e| {
+            mimir_core::MimirError::Database(anyhow::anyhow!("Failed to begin transaction: {}", e))
+        })?;
+        for memory in &encoded {
+            // Dropping the transaction on error rolls back the whole batch
+            memory.write(&tx).map_err(|e| {
+                mimir_core::MimirError::Database(anyhow::anyhow!(
+                    "Failed to store memory {}: {}",
+                    memory.id,
+                    e
+                ))
+            })?;
         }
+        tx.commit().map_err(|e| {
+            mimir_core::MimirError::Database(anyhow::anyhow!("Failed to commit transaction: {}", e))
+        })?;
+
+        Ok(encoded.len())
     }
 
     /// Get memories by classification
-    pub async fn get_memories_by_class(&mut self, class: &MemoryClass) -> Result<Vec<Memory>> {
-        let class_id = match class {
-            MemoryClass::Personal => "personal",
-            MemoryClass::Work => "work",
-            MemoryClass::Health => "health",
-            MemoryClass::Financial => "financial",
-            MemoryClass::Other(s) => s,
-        };
+    pub async fn get_memories_by_class(&self, class: &MemoryClass) -> Result<Vec<Memory>> {
+        let class_id = class.as_str();
 
-        let conn = self.conn.lock().await;
+        let conn = self.reader().await?;
         let mut stmt = conn
-            .prepare(
-                "SELECT id, source, class_id, text_enc, vec_id, ts
-             FROM memory WHERE class_id = ?1
-             ORDER BY ts DESC",
-            )
+            .prepare(&format!(
+                "SELECT {} FROM memory WHERE class_id = ?1 AND deleted_ts IS NULL ORDER BY ts DESC",
+                MEMORY_COLUMNS
+            ))
             .map_err(|e| {
                 mimir_core::MimirError::Database(anyhow::anyhow!("Failed to prepare query: {}", e))
             })?;
 
         let memory_iter = stmt
-            .query_map([class_id], |row| {
-                Ok((
-                    row.get::<_, String>(0)?,  // id
-                    row.get::<_, String>(1)?,  // source
-                    row.get::<_, String>(2)?,  // class_id
-                    row.get::<_, Vec<u8>>(3)?, // text_enc
-                    row.get::<_, i64>(4)?,     // vec_id
-                    row.get::<_, i64>(5)?,     // ts
-                ))
-            })
+            .query_map([class_id], MemoryRow::from_row)
             .map_err(|e| {
                 mimir_core::MimirError::Database(anyhow::anyhow!("Failed to execute query: {}", e))
             })?;
@@ -247,92 +360,30 @@ impl Database {
         let mut memories = Vec::new();
 
         for memory_result in memory_iter {
-            let (id_str, source, class_id, text_enc, _vec_id, ts) =
-                memory_result.map_err(|e| {
-                    mimir_core::MimirError::Database(anyhow::anyhow!("Failed to read row: {}", e))
-                })?;
-
-            // Parse ID
-            let id = uuid::Uuid::parse_str(&id_str).map_err(|e| {
-                mimir_core::MimirError::Database(anyhow::anyhow!("Invalid UUID: {}", e))
+            let row = memory_result.map_err(|e| {
+                mimir_core::MimirError::Database(anyhow::anyhow!("Failed to read row: {}", e))
             })?;
-
-            // Deserialize and decrypt content
-            let ciphertext: mimir_core::crypto::Ciphertext = serde_json::from_slice(&text_enc)
-                .map_err(|e| {
-                    mimir_core::MimirError::Database(anyhow::anyhow!(
-                        "Failed to deserialize ciphertext: {}",
-                        e
-                    ))
-                })?;
-            let plaintext_bytes = self.crypto_manager.decrypt(&class_id, &ciphertext)?;
-            let content = String::from_utf8(plaintext_bytes).map_err(|e| {
-                mimir_core::MimirError::Database(anyhow::anyhow!("Invalid UTF-8: {}", e))
-            })?;
-
-            // Parse class
-            let memory_class = match class_id.as_ref() {
-                "personal" => MemoryClass::Personal,
-                "work" => MemoryClass::Work,
-                "health" => MemoryClass::Health,
-                "financial" => MemoryClass::Financial,
-                other => MemoryClass::Other(other.to_string()),
-            };
-
-            // Convert timestamp back to DateTime
-            let created_at = chrono::DateTime::from_timestamp(ts, 0)
-                .ok_or_else(|| {
-                    mimir_core::MimirError::Database(anyhow::anyhow!("Invalid timestamp: {}", ts))
-                })?
-                .with_timezone(&chrono::Utc);
-
-            // For now, use created_at as updated_at since we don't store it separately
-            let updated_at = created_at;
-
-            let memory = Memory {
-                id,
-                content,
-                embedding: None, // TODO: Add embedding support when vec_id is implemented
-                class: memory_class,
-                scope: None,                  // Not stored in new schema
-                tags: vec![],                 // Not stored in new schema
-                app_acl: vec![source],       // Use source as app_acl for now
-                key_id: class_id.to_string(), // Use class_id as key_id
-                created_at,
-                updated_at,
-            };
-
-            memories.push(memory);
+            memories.push(decode_memory(&self.crypto_manager, row)?);
         }
 
         Ok(memories)
     }
 
-    /// Get the last N memories for a user
-    pub async fn get_last_memories(&mut self, source: &str, limit: usize) -> Result<Vec<Memory>> {
-        let conn = self.conn.lock().await;
+    /// Get the last N memories written by a source app
+    pub async fn get_last_memories(&self, source: &str, limit: usize) -> Result<Vec<Memory>> {
+        let conn = self.reader().await?;
         let mut stmt = conn
-            .prepare(
-                "SELECT id, source, class_id, text_enc, vec_id, ts
-             FROM memory WHERE source = ?1
-             ORDER BY ts DESC
-             LIMIT ?2",
-            )
+            .prepare(&format!(
+                "SELECT {} FROM memory WHERE source = ?1 AND deleted_ts IS NULL
+                 ORDER BY ts DESC LIMIT ?2",
+                MEMORY_COLUMNS
+            ))
             .map_err(|e| {
                 mimir_core::MimirError::Database(anyhow::anyhow!("Failed to prepare query: {}", e))
             })?;
 
         let memory_iter = stmt
-            .query_map(params![source, limit as i64], |row| {
-                Ok((
-                    row.get::<_, String>(0)?,  // id
-                    row.get::<_, String>(1)?,  // source
-                    row.get::<_, String>(2)?,  // class_id
-                    row.get::<_, Vec<u8>>(3)?, // text_enc
-                    row.get::<_, i64>(4)?,     // vec_id
-                    row.get::<_, i64>(5)?,     // ts
-                ))
-            })
+            .query_map(params![source, limit as i64], MemoryRow::from_row)
             .map_err(|e| {
                 mimir_core::MimirError::Database(anyhow::anyhow!("Failed to execute query: {}", e))
             })?;
@@ -340,187 +391,326 @@ impl Database {
         let mut memories = Vec::new();
 
         for memory_result in memory_iter {
-            let (id_str, source, class_id, text_enc, _vec_id, ts) =
-                memory_result.map_err(|e| {
-                    mimir_core::MimirError::Database(anyhow::anyhow!("Failed to read row: {}", e))
-                })?;
-
-            // Parse ID
-            let id = uuid::Uuid::parse_str(&id_str).map_err(|e| {
-                mimir_core::MimirError::Database(anyhow::anyhow!("Invalid UUID: {}", e))
-            })?;
-
-            // Deserialize and decrypt content
-            let ciphertext: mimir_core::crypto::Ciphertext = serde_json::from_slice(&text_enc)
-                .map_err(|e| {
-                    mimir_core::MimirError::Database(anyhow::anyhow!(
-                        "Failed to deserialize ciphertext: {}",
-                        e
-                    ))
-                })?;
-            let plaintext_bytes = self.crypto_manager.decrypt(&class_id, &ciphertext)?;
-            let content = String::from_utf8(plaintext_bytes).map_err(|e| {
-                mimir_core::MimirError::Database(anyhow::anyhow!("Invalid UTF-8: {}", e))
+            let row = memory_result.map_err(|e| {
+                mimir_core::MimirError::Database(anyhow::anyhow!("Failed to read row: {}", e))
             })?;
-
-            // Parse class
-            let memory_class = match class_id.as_ref() {
-                "personal" => MemoryClass::Personal,
-                "work" => MemoryClass::Work,
-                "health" => MemoryClass::Health,
-                "financial" => MemoryClass::Financial,
-                other => MemoryClass::Other(other.to_string()),
-            };
-
-            // Convert timestamp back to DateTime
-            let created_at = chrono::DateTime::from_timestamp(ts, 0)
-                .ok_or_else(|| {
-                    mimir_core::MimirError::Database(anyhow::anyhow!("Invalid timestamp: {}", ts))
-                })?
-                .with_timezone(&chrono::Utc);
-
-            // For now, use created_at as updated_at since we don't store it separately
-            let updated_at = created_at;
-
-            let memory = Memory {
-                id,
-                content,
-                embedding: None, // TODO: Add embedding support when vec_id is implemented
-                class: memory_class,
-                scope: None,                  // Not stored in new schema
-                tags: vec![],                 // Not stored in new schema
-                app_acl: vec![source],       // Use source as app_acl for now
-                key_id: class_id.to_string(), // Use class_id as key_id
-                created_at,
-                updated_at,
-            };
-
-            memories.push(memory);
+            memories.push(decode_memory(&self.crypto_manager, row)?);
         }
 
         Ok(memories)
     }
 
     /// Delete a memory by ID
+    ///
+    /// The memory is moved to the trash, where it stays encrypted and hidden
+    /// from reads until it is restored or purged.
     pub async fn delete_memory(&self, id: MemoryId) -> Result<()> {
         let conn = self.conn.lock().await;
-        conn.execute("DELETE FROM memory WHERE id = ?1", params![id.to_string()])
-            .map_err(|e| {
-                mimir_core::MimirError::Database(anyhow::anyhow!("Failed to delete memory: {}", e))
-            })?;
+        conn.execute(
+            "UPDATE memory SET deleted_ts = ?2 WHERE id = ?1 AND deleted_ts IS NULL",
+            params![id.to_string(), chrono::Utc::now().timestamp()],
+        )
+        .map_err(|e| {
+            mimir_core::MimirError::Database(anyhow::anyhow!("Failed to delete memory: {}", e))
+        })?;
 
         Ok(())
     }
 
     /// Get memory by ID
-    pub async fn get_memory(&mut self, id: MemoryId) -> Result<Option<Memory>> {
-        let conn = self.conn.lock().await;
+    pub async fn get_memory(&self, id: MemoryId) -> Result<Option<Memory>> {
+        let conn = self.reader().await?;
         let mut stmt = conn
-            .prepare(
-                "SELECT id, source, class_id, text_enc, vec_id, ts
-             FROM memory WHERE id = ?1",
-            )
+            .prepare(&format!(
+                "SELECT {} FROM memory WHERE id = ?1 AND deleted_ts IS NULL",
+                MEMORY_COLUMNS
+            ))
             .map_err(|e| {
                 mimir_core::MimirError::Database(anyhow::anyhow!("Failed to prepare query: {}", e))
             })?;
 
         let mut rows = stmt
-            .query_map([id.to_string()], |row| {
-                Ok((
-                    row.get::<_, String>(0)?,  // id
-                    row.get::<_, String>(1)?,  // source
-                    row.get::<_, String>(2)?,  // class_id
-                    row.get::<_, Vec<u8>>(3)?, // text_enc
-                    row.get::<_, i64>(4)?,     // vec_id
-                    row.get::<_, i64>(5)?,     // ts
-                ))
-            })
+            .query_map([id.to_string()], MemoryRow::from_row)
             .map_err(|e| {
                 mimir_core::MimirError::Database(anyhow::anyhow!("Failed to execute query: {}", e))
             })?;
 
         if let Some(memory_result) = rows.next() {
-            let (id_str, source, class_id, text_enc, _vec_id, ts) =
-                memory_result.map_err(|e| {
-                    mimir_core::MimirError::Database(anyhow::anyhow!("Failed to read row: {}", e))
-                })?;
-
-            // Parse ID
-            let id = uuid::Uuid::parse_str(&id_str).map_err(|e| {
-                mimir_core::MimirError::Database(anyhow::anyhow!("Invalid UUID: {}", e))
+            let row = memory_result.map_err(|e| {
+                mimir_core::MimirError::Database(anyhow::anyhow!("Failed to read row: {}", e))
             })?;
-
-            // Deserialize and decrypt content
-            let ciphertext: mimir_core::crypto::Ciphertext = serde_json::from_slice(&text_enc)
-                .map_err(|e| {
-                    mimir_core::MimirError::Database(anyhow::anyhow!(
-                        "Failed to deserialize ciphertext: {}",
-                        e
-                    ))
-                })?;
-            let plaintext_bytes = self.crypto_manager.decrypt(&class_id, &ciphertext)?;
-            let content = String::from_utf8(plaintext_bytes).map_err(|e| {
-                mimir_core::MimirError::Database(anyhow::anyhow!("Invalid UTF-8: {}", e))
-            })?;
-
-            // Parse class
-            let memory_class = match class_id.as_ref() {
-                "personal" => MemoryClass::Personal,
-                "work" => MemoryClass::Work,
-                "health" => MemoryClass::Health,
-                "financial" => MemoryClass::Financial,
-                other => MemoryClass::Other(other.to_string()),
-            };
-
-            // Convert timestamp back to DateTime
-            let created_at = chrono::DateTime::from_timestamp(ts, 0)
-                .ok_or_else(|| {
-                    mimir_core::MimirError::Database(anyhow::anyhow!("Invalid timestamp: {}", ts))
-                })?
-                .with_timezone(&chrono::Utc);
-
-            // For now, use created_at as updated_at since we don't store it separately
-            let updated_at = created_at;
-
-            let memory = Memory {
-                id,
-                content,
-                embedding: None, // TODO: Add embedding support when vec_id is implemented
-                class: memory_class,
-                scope: None,                  // Not stored in new schema
-                tags: vec![],                 // Not stored in new schema
-                app_acl: vec![source],       // Use source as app_acl for now
-                key_id: class_id.to_string(), // Use class_id as key_id
-                created_at,
-                updated_at,
-            };
-
-            Ok(Some(memory))
+            Ok(Some(decode_memory(&self.crypto_manager, row)?))
         } else {
             Ok(None)
         }
     }
 
-    // Note: Transaction support removed due to async/thread-safety constraints
-    // All database operations are now atomic and thread-safe
+    /// Applied and pending schema migrations for this database
+    pub async fn migration_status(&self) -> Result<MigrationStatus> {
+        let conn = self.conn.lock().await;
+        migrations::read_status(&conn)
+    }
 
     /// Update an existing memory in the database
-    pub async fn update_memory(&mut self, memory: &Memory) -> Result<()> {
-        // Use store_memory which handles INSERT OR REPLACE
-        self.store_memory(memory).await
+    ///
+    /// The row being replaced is kept as an encrypted revision in the same
+    /// transaction, so earlier content can be listed, read or reverted to.
+    /// Fails if the memory does not exist or is in the trash; a trashed
+    /// memory has to be restored before it can be changed.
+    pub async fn update_memory(&self, memory: &Memory) -> Result<()> {
+        let encoded = encode_memory(&self.crypto_manager, memory)?;
+
+        let mut conn = self.conn.lock().await;
+        let tx = conn.transaction().map_err(|e| {
+            mimir_core::MimirError::Database(anyhow::anyhow!("Failed to begin transaction: {}", e))
+        })?;
+
+        let live: bool = tx
+            .query_row(
+                "SELECT EXISTS(SELECT 1 FROM memory WHERE id = ?1 AND deleted_ts IS NULL)",
+                params![encoded.id],
+                |row| row.get(0),
+            )
+            .map_err(|e| {
+                mimir_core::MimirError::Database(anyhow::anyhow!("Failed to read memory: {}", e))
+            })?;
+        if !live {
+            return Err(mimir_core::MimirError::Database(anyhow::anyhow!(
+                "Memory {} not found",
+                memory.id
+            )));
+        }
+
+        revisions::snapshot_revision(&tx, &encoded.id, encoded.updated_ts)?;
+        encoded.write(&tx).map_err(|e| {
+            mimir_core::MimirError::Database(anyhow::anyhow!("Failed to update memory: {}", e))
+        })?;
+
+        tx.commit().map_err(|e| {
+            mimir_core::MimirError::Database(anyhow::anyhow!("Failed to commit transaction: {}", e))
+        })
     }
 
     /// Clear all memories from the database
-    pub async fn clear_all_memories(&mut self) -> Result<usize> {
+    pub async fn clear_all_memories(&self) -> Result<usize> {
         let conn = self.conn.lock().await;
         let result = conn.execute("DELETE FROM memory", []).map_err(|e| {
             mimir_core::MimirError::Database(anyhow::anyhow!("Failed to clear memories: {}", e))
         })?;
+        conn.execute_batch(
+            "DELETE FROM memory_revision; DELETE FROM memory_keyword; DELETE FROM memory_relation;
+             DELETE FROM memory_attachment;",
+        )
+        .map_err(|e| {
+            mimir_core::MimirError::Database(anyhow::anyhow!(
+                "Failed to clear memory revisions: {}",
+                e
+            ))
+        })?;
 
         Ok(result as usize)
     }
 }
 
+/// Key an open SQLCipher connection with the vault database key and verify it
+pub(crate) fn apply_key(conn: &Connection, crypto_manager: &CryptoManager) -> Result<()> {
+    apply_db_key(conn, &crypto_manager.get_db_key_bytes())
+}
+
+/// Set a raw SQLCipher key on a connection and verify it opens the database
+pub(crate) fn apply_db_key(conn: &Connection, db_key_bytes: &[u8; 32]) -> Result<()> {
+    // Set the SQLCipher key using PRAGMA - SQLCipher returns results from PRAGMA commands
+    let db_key_hex = hex::encode(db_key_bytes);
+    let pragma_sql = format!("PRAGMA key = \"x'{}'\"", db_key_hex);
+    conn.execute_batch(&pragma_sql).map_err(|e| {
+        mimir_core::MimirError::Database(anyhow::anyhow!("Failed to set SQLCipher key: {}", e))
+    })?;
+
+    // Test that the key is correct by executing a simple query
+    conn.query_row("SELECT count(*) FROM sqlite_master", [], |row| {
+        let count: i64 = row.get(0)?;
+        Ok(count)
+    })
+    .map_err(|e| {
+        mimir_core::MimirError::Database(anyhow::anyhow!("Failed to verify SQLCipher key: {}", e))
+    })?;
+
+    Ok(())
+}
+
+/// Encrypt bytes under a class key and serialize the ciphertext (including nonce) for storage
+fn encrypt_blob(
+    crypto_manager: &CryptoManager,
+    class_id: &str,
+    plaintext: &[u8],
+) -> Result<Vec<u8>> {
+    let ciphertext = crypto_manager.encrypt(class_id, plaintext)?;
+    serde_json::to_vec(&ciphertext).map_err(|e| {
+        mimir_core::MimirError::Database(anyhow::anyhow!("Failed to serialize ciphertext: {}", e))
+    })
+}
+
+/// Encrypt a memory's content and metadata under its class key
+///
+/// The owning app (first ACL entry) is kept in plaintext as `source` so
+/// per-app queries can use the `source` index.
+fn encode_memory(crypto_manager: &CryptoManager, memory: &Memory) -> Result<EncodedMemory> {
+    let class_id = memory.class.as_str();
+
+    // Encrypt memory content and metadata with class-specific key
+    let text_enc = encrypt_blob(crypto_manager, class_id, memory.content.as_bytes())?;
+    let scope_enc = match &memory.scope {
+        Some(scope) => Some(encrypt_blob(crypto_manager, class_id, scope.as_bytes())?),
+        None => None,
+    };
+    let tags_enc = encrypt_strings(crypto_manager, class_id, &memory.tags)?;
+    let acl_enc = encrypt_strings(crypto_manager, class_id, &memory.app_acl)?;
+    let keywords = keyword::blind_terms(crypto_manager, class_id, &memory.content)?;
+    let embedding_enc = match &memory.embedding {
+        Some(vector) => {
+            let model = memory.embedding_model.as_deref().unwrap_or_default();
+            let bytes = embedding::encode(model, vector)?;
+            Some(encrypt_blob(crypto_manager, class_id, &bytes)?)
+        }
+        None => None,
+    };
+
+    let source = memory
+        .app_acl
+        .first()
+        .map(String::as_str)
+        .unwrap_or(DEFAULT_SOURCE);
+
+    Ok(EncodedMemory {
+        id: memory.id.to_string(),
+        source: source.to_string(),
+        class_id: class_id.to_string(),
+        text_enc,
+        // Embeddings are stored in embedding_enc; vec_id is kept for older schemas
+        vec_id: 0,
+        // Convert timestamps to Unix timestamps (seconds since epoch)
+        ts: memory.created_at.timestamp(),
+        updated_ts: memory.updated_at.timestamp(),
+        key_id: memory.key_id.clone(),
+        scope_enc,
+        tags_enc,
+        acl_enc,
+        expires_ts: memory.expires_at.map(|at| at.timestamp()),
+        embedding_enc,
+        key_version: crypto_manager.class_key_version(class_id),
+        keywords,
+    })
+}
+
+/// Deserialize a stored ciphertext and decrypt it under a class key
+fn decrypt_blob(crypto_manager: &CryptoManager, class_id: &str, data: &[u8]) -> Result<Vec<u8>> {
+    let ciphertext: mimir_core::crypto::Ciphertext = serde_json::from_slice(data).map_err(|e| {
+        mimir_core::MimirError::Database(anyhow::anyhow!("Failed to deserialize ciphertext: {}", e))
+    })?;
+    crypto_manager.decrypt(class_id, &ciphertext)
+}
+
+/// Encrypt a list of strings (tags, ACL entries) as a JSON array
+fn encrypt_strings(
+    crypto_manager: &CryptoManager,
+    class_id: &str,
+    values: &[String],
+) -> Result<Vec<u8>> {
+    let json = serde_json::to_vec(values).map_err(|e| {
+        mimir_core::MimirError::Database(anyhow::anyhow!("Failed to serialize list: {}", e))
+    })?;
+    encrypt_blob(crypto_manager, class_id, &json)
+}
+
+/// Decrypt a list of strings written by `encrypt_strings`
+fn decrypt_strings(
+    crypto_manager: &CryptoManager,
+    class_id: &str,
+    data: &[u8],
+) -> Result<Vec<String>> {
+    let json = decrypt_blob(crypto_manager, class_id, data)?;
+    serde_json::from_slice(&json).map_err(|e| {
+        mimir_core::MimirError::Database(anyhow::anyhow!("Failed to deserialize list: {}", e))
+    })
+}
+
+/// Convert a Unix timestamp column back into a UTC datetime
+fn timestamp_to_datetime(ts: i64) -> Result<chrono::DateTime<chrono::Utc>> {
+    chrono::DateTime::from_timestamp(ts, 0).ok_or_else(|| {
+        mimir_core::MimirError::Database(anyhow::anyhow!("Invalid timestamp: {}", ts))
+    })
+}
+
+/// Decrypt a stored row into a full memory
+///
+/// Rows written before the metadata columns existed have NULL metadata; they
+/// fall back to the previous behaviour (ACL from `source`, `updated_at` from
+/// `created_at`, key ID from the class).
+fn decode_memory(crypto_manager: &CryptoManager, row: MemoryRow) -> Result<Memory> {
+    // Parse ID and class
+    let class: MemoryClass = match row.class_id.parse() {
+        Ok(class) => class,
+        Err(never) => match never {},
+    };
+    let id = uuid::Uuid::parse_str(&row.id)
+        .map_err(|e| mimir_core::MimirError::Database(anyhow::anyhow!("Invalid UUID: {}", e)))?;
+
+    // Decrypt content
+    let plaintext_bytes = decrypt_blob(crypto_manager, &row.class_id, &row.text_enc)?;
+    let content = String::from_utf8(plaintext_bytes)
+        .map_err(|e| mimir_core::MimirError::Database(anyhow::anyhow!("Invalid UTF-8: {}", e)))?;
+
+    // Decrypt metadata
+    let scope = match &row.scope_enc {
+        Some(data) => {
+            let bytes = decrypt_blob(crypto_manager, &row.class_id, data)?;
+            Some(String::from_utf8(bytes).map_err(|e| {
+                mimir_core::MimirError::Database(anyhow::anyhow!("Invalid UTF-8: {}", e))
+            })?)
+        }
+        None => None,
+    };
+    let tags = match &row.tags_enc {
+        Some(data) => decrypt_strings(crypto_manager, &row.class_id, data)?,
+        None => vec![],
+    };
+    let app_acl = match &row.acl_enc {
+        Some(data) => decrypt_strings(crypto_manager, &row.class_id, data)?,
+        None => vec![row.source.clone()],
+    };
+
+    let created_at = timestamp_to_datetime(row.ts)?;
+    let updated_at = match row.updated_ts {
+        Some(ts) => timestamp_to_datetime(ts)?,
+        None => created_at,
+    };
+
+    let key_id = row.key_id.unwrap_or_else(|| row.class_id.clone());
+    let expires_at = row.expires_ts.map(timestamp_to_datetime).transpose()?;
+    let (embedding_model, embedding) = match &row.embedding_enc {
+        Some(data) => {
+            let (model, vector) = embedding::decrypt(crypto_manager, &row.class_id, data)?;
+            (Some(model), Some(vector))
+        }
+        None => (None, None),
+    };
+
+    Ok(Memory {
+        id,
+        content,
+        embedding,
+        embedding_model,
+        class,
+        scope,
+        tags,
+        app_acl,
+        key_id,
+        created_at,
+        updated_at,
+        expires_at,
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -533,6 +723,15 @@ mod tests {
     use mimir_core::MemoryClass;
     use serial_test::serial;
 
+    /// Password the test keysets are created with
+    const TEST_PASSWORD: &str = "test-password-for-ci";
+
+    /// Open a database on a password keyset, as the daemon does
+    fn open_test_database<P: AsRef<Path>>(db_path: P, keyset_path: P) -> Result<Database> {
+        let crypto_manager = CryptoManager::with_password(keyset_path, TEST_PASSWORD)?;
+        Database::with_crypto_manager(db_path, crypto_manager)
+    }
+
     fn create_test_database() -> (Database, tempfile::TempDir) {
         let temp_dir = create_temp_dir();
         let db_path = get_test_db_path(&temp_dir);
@@ -545,10 +744,10 @@ mod tests {
 
         // Use password-based CryptoManager to create a proper keyset file
         let _crypto_manager =
-            mimir_core::crypto::CryptoManager::with_password(&keyset_path, "test-password")
+            mimir_core::crypto::CryptoManager::with_password(&keyset_path, TEST_PASSWORD)
                 .expect("Failed to create test crypto manager");
 
-        let db = Database::new(db_path, keyset_path).expect("Failed to create test database");
+        let db = open_test_database(db_path, keyset_path).expect("Failed to create test database");
         (db, temp_dir)
     }
 
@@ -565,10 +764,10 @@ mod tests {
 
         // Use password-based CryptoManager to create a proper keyset file
         let _crypto_manager =
-            mimir_core::crypto::CryptoManager::with_password(&keyset_path, "test-password")
+            mimir_core::crypto::CryptoManager::with_password(&keyset_path, TEST_PASSWORD)
                 .expect("Failed to create test crypto manager");
 
-        let result = Database::new(db_path, keyset_path);
+        let result = open_test_database(db_path, keyset_path);
         assert!(result.is_ok());
     }
 
@@ -584,7 +783,7 @@ mod tests {
 
         // Use password-based CryptoManager to create a proper keyset file
         let _crypto_manager =
-            mimir_core::crypto::CryptoManager::with_password(&keyset_path, "test-password")
+            mimir_core::crypto::CryptoManager::with_password(&keyset_path, TEST_PASSWORD)
                 .expect("Failed to create test crypto manager");
 
         let test_cases = vec!["test1.db", "subdir/test2.db", "memory_vault.sqlite"];
@@ -595,7 +794,7 @@ mod tests {
                 std::fs::create_dir_all(parent).ok();
             }
 
-            let result = Database::new(&db_path, &keyset_path);
+            let result = open_test_database(&db_path, &keyset_path);
             assert!(
                 result.is_ok(),
                 "Failed to create database at path: {:?}",
@@ -606,7 +805,7 @@ mod tests {
 
     #[tokio::test]
     async fn test_store_and_retrieve_memory() {
-        let (mut db, _temp_dir) = create_test_database();
+        let (db, _temp_dir) = create_test_database();
 
         let memory = MemoryBuilder::new()
             .with_content("Test memory content")
@@ -629,7 +828,7 @@ mod tests {
 
     #[tokio::test]
     async fn test_store_multiple_memories() {
-        let (mut db, _temp_dir) = create_test_database();
+        let (db, _temp_dir) = create_test_database();
 
         let memories = generate_test_memories(5);
 
@@ -648,9 +847,48 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn test_store_memories_batch() {
+        let (db, _temp_dir) = create_test_database();
+
+        let memories = generate_test_memories(5);
+        assert_eq!(db.store_memories(&memories).await.unwrap(), 5);
+
+        for memory in &memories {
+            let retrieved = db.get_memory(memory.id).await.unwrap().unwrap();
+            assert_eq!(retrieved.content, memory.content);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_store_memories_is_all_or_nothing() {
+        let (db, _temp_dir) = create_test_database();
+
+        let good = MemoryBuilder::new().with_content("Good").build();
+        let bad = MemoryBuilder::new().with_content("Bad").build();
+
+        // Make the second row of the batch fail to insert
+        {
+            let conn = db.conn.lock().await;
+            conn.execute_batch(&format!(
+                "CREATE TRIGGER reject_bad BEFORE INSERT ON memory
+                 WHEN NEW.id = '{}' BEGIN SELECT RAISE(ABORT, 'rejected'); END;",
+                bad.id
+            ))
+            .unwrap();
+        }
+
+        let error = db
+            .store_memories(&[good.clone(), bad.clone()])
+            .await
+            .unwrap_err();
+        assert!(error.to_string().contains(&bad.id.to_string()));
+        assert!(db.get_memory(good.id).await.unwrap().is_none());
+    }
+
     #[tokio::test]
     async fn test_get_memories_by_class() {
-        let (mut db, _temp_dir) = create_test_database();
+        let (db, _temp_dir) = create_test_database();
 
         // Store memories in different classes
         let personal_memory = MemoryBuilder::new()
@@ -681,7 +919,7 @@ mod tests {
 
     #[tokio::test]
     async fn test_get_last_memories() {
-        let (mut db, _temp_dir) = create_test_database();
+        let (db, _temp_dir) = create_test_database();
 
         // Store multiple memories
         for i in 0..10 {
@@ -692,14 +930,14 @@ mod tests {
             db.store_memory(&memory).await.unwrap();
         }
 
-        // Get last 5 memories
-        let last_memories = db.get_last_memories("default_source", 5).await.unwrap();
+        // Get last 5 memories (source is the owning app from the ACL)
+        let last_memories = db.get_last_memories("test-app", 5).await.unwrap();
         assert_eq!(last_memories.len(), 5);
     }
 
     #[tokio::test]
     async fn test_delete_memory() {
-        let (mut db, _temp_dir) = create_test_database();
+        let (db, _temp_dir) = create_test_database();
 
         let memory = MemoryBuilder::new()
             .with_content("Memory to delete")
@@ -722,9 +960,33 @@ mod tests {
         assert!(retrieved.is_none());
     }
 
+    #[tokio::test]
+    async fn test_update_trashed_or_unknown_memory_fails() {
+        let (db, _temp_dir) = create_test_database();
+
+        let mut memory = MemoryBuilder::new().with_content("Original").build();
+        db.store_memory(&memory).await.unwrap();
+        db.delete_memory(memory.id).await.unwrap();
+
+        // Updating a trashed memory neither changes nor restores it
+        memory.content = "Changed".to_string();
+        assert!(db.update_memory(&memory).await.is_err());
+        assert!(db.get_memory(memory.id).await.unwrap().is_none());
+        let trash = db.list_trash().await.unwrap();
+        assert_eq!(trash.len(), 1);
+        assert_eq!(trash[0].memory.content, "Original");
+        assert!(db.list_revisions(memory.id).await.unwrap().is_empty());
+
+        // An unknown ID is not created
+        let unknown = MemoryBuilder::new().with_content("Unknown").build();
+        assert!(db.update_memory(&unknown).await.is_err());
+        assert!(db.get_memory(unknown.id).await.unwrap().is_none());
+        assert_eq!(db.list_trash().await.unwrap().len(), 1);
+    }
+
     #[tokio::test]
     async fn test_concurrent_operations() {
-        let (mut db, _temp_dir) = create_test_database();
+        let (db, _temp_dir) = create_test_database();
 
         let memory1 = MemoryBuilder::new()
             .with_content("Concurrent memory 1")
@@ -763,11 +1025,11 @@ mod tests {
 
         // Use password-based CryptoManager to create a proper keyset file
         let _crypto_manager =
-            mimir_core::crypto::CryptoManager::with_password(&keyset_path, "test-password")
+            mimir_core::crypto::CryptoManager::with_password(&keyset_path, TEST_PASSWORD)
                 .expect("Failed to create test crypto manager");
 
-        let mut db1 = Database::new(&db_path1, &keyset_path).unwrap();
-        let mut db2 = Database::new(&db_path2, &keyset_path).unwrap();
+        let db1 = open_test_database(&db_path1, &keyset_path).unwrap();
+        let db2 = open_test_database(&db_path2, &keyset_path).unwrap();
 
         let memory1 = MemoryBuilder::new().with_content("DB1 memory").build();
         let memory2 = MemoryBuilder::new().with_content("DB2 memory").build();
@@ -786,7 +1048,7 @@ mod tests {
         let keyset_path = temp_dir.path().join("keyset.json");
         let keyset_path_str = keyset_path.to_str().unwrap();
         let _crypto_manager =
-            mimir_core::crypto::CryptoManager::with_password(&keyset_path, "test-password")
+            mimir_core::crypto::CryptoManager::with_password(&keyset_path, TEST_PASSWORD)
                 .expect("Failed to create test crypto manager");
         let invalid_paths = vec![
             "",                       // Empty path
@@ -835,20 +1097,88 @@ mod tests {
 
         // Test that the CryptoManager can be created and used properly
         let crypto_manager =
-            mimir_core::crypto::CryptoManager::with_password(&keyset_path, "test-password");
+            mimir_core::crypto::CryptoManager::with_password(&keyset_path, TEST_PASSWORD);
         assert!(crypto_manager.is_ok(), "Failed to create crypto manager");
 
         // Test that we can create a database with the crypto manager
-        let result = Database::new(&db_path, &keyset_path);
+        let result = open_test_database(&db_path, &keyset_path);
         assert!(
             result.is_ok(),
             "Failed to create database with crypto manager"
         );
     }
 
+    #[tokio::test]
+    async fn test_open_upgrades_pbkdf2_keyset() {
+        use mimir_core::crypto::{Keyset, RootKey, KEYSET_VERSION_ARGON2ID, KEYSET_VERSION_PBKDF2};
+
+        let temp_dir = create_temp_dir();
+        let db_path = get_test_db_path(&temp_dir);
+        let keyset_path = temp_dir.path().join("keyset.json");
+        let legacy = Keyset {
+            version: KEYSET_VERSION_PBKDF2,
+            salt: Some(RootKey::generate_salt().unwrap()),
+            kdf: None,
+            wrapped_root_key: None,
+            class_keys: std::collections::HashMap::new(),
+            class_key_versions: std::collections::HashMap::new(),
+            purged_classes: std::collections::BTreeSet::new(),
+            root_key_check: None,
+            updated_at: chrono::Utc::now(),
+        };
+        std::fs::write(&keyset_path, serde_json::to_vec(&legacy).unwrap()).unwrap();
+        let keyset_version = || {
+            serde_json::from_slice::<Keyset>(&std::fs::read(&keyset_path).unwrap())
+                .unwrap()
+                .version
+        };
+
+        // A new database proves nothing about the password
+        let memory = MemoryBuilder::new()
+            .with_content("Kept across the upgrade")
+            .build();
+        {
+            let crypto_manager = CryptoManager::with_password(&keyset_path, "legacy").unwrap();
+            let db = Database::with_crypto_manager(&db_path, crypto_manager).unwrap();
+            db.store_memory(&memory).await.unwrap();
+        }
+        assert_eq!(keyset_version(), KEYSET_VERSION_PBKDF2);
+
+        // The root key check catches a wrong password; without one, the
+        // database only fails to open
+        assert!(matches!(
+            CryptoManager::with_password(&keyset_path, "wrong"),
+            Err(mimir_core::MimirError::WrongPassword)
+        ));
+        let keyset_data = std::fs::read(&keyset_path).unwrap();
+        let mut unchecked: Keyset = serde_json::from_slice(&keyset_data).unwrap();
+        unchecked.root_key_check = None;
+        std::fs::write(&keyset_path, serde_json::to_vec(&unchecked).unwrap()).unwrap();
+        let crypto_manager = CryptoManager::with_password(&keyset_path, "wrong").unwrap();
+        assert!(matches!(
+            Database::with_crypto_manager(&db_path, crypto_manager),
+            Err(mimir_core::MimirError::WrongPassword)
+        ));
+        std::fs::write(&keyset_path, keyset_data).unwrap();
+        assert_eq!(keyset_version(), KEYSET_VERSION_PBKDF2);
+
+        // Opening it again does, and the keyset moves to Argon2id
+        {
+            let crypto_manager = CryptoManager::with_password(&keyset_path, "legacy").unwrap();
+            Database::with_crypto_manager(&db_path, crypto_manager).unwrap();
+        }
+        assert_eq!(keyset_version(), KEYSET_VERSION_ARGON2ID);
+
+        let crypto_manager = CryptoManager::with_password(&keyset_path, "legacy").unwrap();
+        assert!(!crypto_manager.needs_kdf_upgrade());
+        let db = Database::with_crypto_manager(&db_path, crypto_manager).unwrap();
+        let stored = db.get_memory(memory.id).await.unwrap().unwrap();
+        assert_eq!(stored.content, memory.content);
+    }
+
     #[tokio::test]
     async fn test_memory_data_integrity() {
-        let (mut db, _temp_dir) = create_test_database();
+        let (db, _temp_dir) = create_test_database();
 
         let original_memory = MemoryBuilder::new()
             .with_content("Data integrity test content")
@@ -871,9 +1201,49 @@ mod tests {
         assert_eq!(retrieved_memory.class, original_memory.class);
     }
 
+    #[tokio::test]
+    async fn test_memory_metadata_round_trip() {
+        let (db, _temp_dir) = create_test_database();
+
+        let mut original_memory = MemoryBuilder::new()
+            .with_content("Metadata round trip")
+            .with_class(MemoryClass::Health)
+            .with_scope("project-x")
+            .with_tags(vec!["alpha".to_string(), "beta".to_string()])
+            .with_app_acl(vec!["app-one".to_string(), "app-two".to_string()])
+            .build();
+        original_memory.key_id = "health-v2".to_string();
+        original_memory.updated_at = original_memory.created_at + chrono::Duration::seconds(90);
+
+        db.store_memory(&original_memory).await.unwrap();
+
+        let retrieved = db.get_memory(original_memory.id).await.unwrap().unwrap();
+        assert_eq!(retrieved.scope.as_deref(), Some("project-x"));
+        assert_eq!(retrieved.tags, original_memory.tags);
+        assert_eq!(retrieved.app_acl, original_memory.app_acl);
+        assert_eq!(retrieved.key_id, "health-v2");
+        assert_eq!(
+            retrieved.created_at.timestamp(),
+            original_memory.created_at.timestamp()
+        );
+        assert_eq!(
+            retrieved.updated_at.timestamp(),
+            original_memory.updated_at.timestamp()
+        );
+
+        // The owning app is the first ACL entry
+        let by_source = db.get_last_memories("app-one", 10).await.unwrap();
+        assert_eq!(by_source.len(), 1);
+        assert!(db
+            .get_last_memories("app-two", 10)
+            .await
+            .unwrap()
+            .is_empty());
+    }
+
     #[tokio::test]
     async fn test_large_content_handling() {
-        let (mut db, _temp_dir) = create_test_database();
+        let (db, _temp_dir) = create_test_database();
 
         // Test with large content (simulating edge cases)
         let large_content = "x".repeat(10_000); // 10KB content
@@ -890,7 +1260,7 @@ mod tests {
 
     #[tokio::test]
     async fn test_special_characters_in_content() {
-        let (mut db, _temp_dir) = create_test_database();
+        let (db, _temp_dir) = create_test_database();
 
         let special_contents = vec![
             "🧠 Memory with emojis 🔒",
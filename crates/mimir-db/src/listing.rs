@@ -0,0 +1,263 @@
+//! Filtered, cursor-paginated memory listing
+//!
+//! Class, source and time filters run in SQL. Tags and scope are encrypted, so
+//! those filters are applied while rows are decrypted one at a time from the
+//! open cursor; scanning stops as soon as a page is full.
+
+use crate::{decode_memory, Database, MemoryRow, MEMORY_COLUMNS};
+use mimir_core::{MemoryFilter, MemoryPage, Result};
+use rusqlite::{params_from_iter, types::Value};
+
+/// Largest page `list_memories` will return
+pub const MAX_PAGE_SIZE: usize = 500;
+
+/// Position after the last memory of a page: `<created ts>:<id>`
+fn encode_cursor(ts: i64, id: &str) -> String {
+    format!("{}:{}", ts, id)
+}
+
+/// Created timestamp and ID decoded from a cursor
+type CursorPosition = (i64, String);
+
+fn decode_cursor(cursor: &str) -> Result<CursorPosition> {
+    cursor
+        .split_once(':')
+        .and_then(|(ts, id)| Some((ts.parse().ok()?, id.to_string())))
+        .ok_or_else(|| {
+            mimir_core::MimirError::Database(anyhow::anyhow!("Invalid cursor: {}", cursor))
+        })
+}
+
+impl Database {
+    /// List memories matching `filter`, newest first
+    ///
+    /// Pass the returned `next_cursor` back to continue after the last page.
+    /// `limit` is capped at [`MAX_PAGE_SIZE`].
+    pub async fn list_memories(
+        &self,
+        filter: &MemoryFilter,
+        cursor: Option<&str>,
+        limit: usize,
+    ) -> Result<MemoryPage> {
+        let limit = limit.min(MAX_PAGE_SIZE);
+        if limit == 0 {
+            return Ok(MemoryPage::default());
+        }
+
+        // Trashed memories are never listed
+        let mut conditions: Vec<&str> = vec!["deleted_ts IS NULL"];
+        let mut values: Vec<Value> = Vec::new();
+
+        if let Some(class) = &filter.class {
+            conditions.push("class_id = ?");
+            values.push(Value::Text(class.as_str().to_string()));
+        }
+        if let Some(source) = &filter.source {
+            conditions.push("source = ?");
+            values.push(Value::Text(source.clone()));
+        }
+        if let Some(after) = filter.created_after {
+            conditions.push("ts >= ?");
+            values.push(Value::Integer(after.timestamp()));
+        }
+        if let Some(before) = filter.created_before {
+            conditions.push("ts < ?");
+            values.push(Value::Integer(before.timestamp()));
+        }
+        if let Some(after) = filter.updated_after {
+            conditions.push("COALESCE(updated_ts, ts) >= ?");
+            values.push(Value::Integer(after.timestamp()));
+        }
+        if let Some(before) = filter.updated_before {
+            conditions.push("COALESCE(updated_ts, ts) < ?");
+            values.push(Value::Integer(before.timestamp()));
+        }
+        if let Some(cursor) = cursor {
+            let (ts, id) = decode_cursor(cursor)?;
+            conditions.push("(ts < ? OR (ts = ? AND id < ?))");
+            values.push(Value::Integer(ts));
+            values.push(Value::Integer(ts));
+            values.push(Value::Text(id));
+        }
+
+        let sql = format!(
+            "SELECT {} FROM memory WHERE {} ORDER BY ts DESC, id DESC",
+            MEMORY_COLUMNS,
+            conditions.join(" AND ")
+        );
+
+        let conn = self.reader().await?;
+        let mut stmt = conn.prepare(&sql).map_err(|e| {
+            mimir_core::MimirError::Database(anyhow::anyhow!("Failed to prepare query: {}", e))
+        })?;
+        let mut rows = stmt.query(params_from_iter(values)).map_err(|e| {
+            mimir_core::MimirError::Database(anyhow::anyhow!("Failed to execute query: {}", e))
+        })?;
+
+        let mut page = MemoryPage::default();
+        let mut last_position = None;
+        loop {
+            let row = match rows.next() {
+                Ok(Some(row)) => row,
+                Ok(None) => {
+                    // Cursor exhausted: this is the last page
+                    return Ok(page);
+                }
+                Err(e) => {
+                    return Err(mimir_core::MimirError::Database(anyhow::anyhow!(
+                        "Failed to read row: {}",
+                        e
+                    )))
+                }
+            };
+
+            if page.memories.len() == limit {
+                // At least one more row remains
+                page.next_cursor = last_position;
+                return Ok(page);
+            }
+
+            let memory_row = MemoryRow::from_row(row).map_err(|e| {
+                mimir_core::MimirError::Database(anyhow::anyhow!("Failed to read row: {}", e))
+            })?;
+            let position = encode_cursor(memory_row.ts, &memory_row.id);
+            let memory = decode_memory(&self.crypto_manager, memory_row)?;
+
+            // The cursor advances past rows the encrypted filters reject too
+            last_position = Some(position);
+
+            let tags_match = filter.tags.iter().all(|tag| memory.tags.contains(tag));
+            let scope_match = filter.scope.is_none() || memory.scope == filter.scope;
+            if tags_match && scope_match {
+                page.memories.push(memory);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mimir_core::crypto::CryptoManager;
+    use mimir_core::test_utils::MemoryBuilder;
+    use mimir_core::MemoryClass;
+    use tempfile::TempDir;
+
+    fn create_test_database() -> (Database, TempDir) {
+        let temp_dir = TempDir::new().unwrap();
+        let crypto_manager =
+            CryptoManager::with_password(temp_dir.path().join("keyset.json"), "test-password")
+                .unwrap();
+        let db =
+            Database::with_crypto_manager(temp_dir.path().join("test.db"), crypto_manager).unwrap();
+        (db, temp_dir)
+    }
+
+    async fn list_contents(db: &mut Database, filter: MemoryFilter) -> Vec<String> {
+        let page = db.list_memories(&filter, None, 10).await.unwrap();
+        page.memories.into_iter().map(|m| m.content).collect()
+    }
+
+    #[tokio::test]
+    async fn test_list_memories_pagination() {
+        let (db, _temp_dir) = create_test_database();
+        let now = chrono::Utc::now();
+
+        for i in 0..7 {
+            let mut memory = MemoryBuilder::new()
+                .with_content(format!("Memory {}", i))
+                .build();
+            memory.created_at = now - chrono::Duration::seconds(100 - i);
+            memory.updated_at = memory.created_at;
+            db.store_memory(&memory).await.unwrap();
+        }
+
+        let filter = MemoryFilter::default();
+        let mut cursor = None;
+        let mut contents = Vec::new();
+        let mut pages = 0;
+        loop {
+            let page = db
+                .list_memories(&filter, cursor.as_deref(), 3)
+                .await
+                .unwrap();
+            pages += 1;
+            contents.extend(page.memories.into_iter().map(|m| m.content));
+            match page.next_cursor {
+                Some(next) => cursor = Some(next),
+                None => break,
+            }
+        }
+
+        assert_eq!(pages, 3);
+        let expected: Vec<String> = (0..7).rev().map(|i| format!("Memory {}", i)).collect();
+        assert_eq!(contents, expected);
+    }
+
+    #[tokio::test]
+    async fn test_list_memories_filters() {
+        let (mut db, _temp_dir) = create_test_database();
+        let now = chrono::Utc::now();
+
+        let mut old_work = MemoryBuilder::new()
+            .with_content("Old work")
+            .with_class(MemoryClass::Work)
+            .with_tags(vec!["project".to_string()])
+            .with_scope("odin")
+            .build();
+        old_work.created_at = now - chrono::Duration::days(10);
+        old_work.updated_at = old_work.created_at;
+        let new_work = MemoryBuilder::new()
+            .with_content("New work")
+            .with_class(MemoryClass::Work)
+            .with_tags(vec!["project".to_string(), "urgent".to_string()])
+            .with_app_acl(vec!["planner".to_string()])
+            .build();
+        let personal = MemoryBuilder::new()
+            .with_content("Personal")
+            .with_tags(vec!["urgent".to_string()])
+            .build();
+        for memory in [&old_work, &new_work, &personal] {
+            db.store_memory(memory).await.unwrap();
+        }
+
+        let by_class = MemoryFilter {
+            class: Some(MemoryClass::Work),
+            ..Default::default()
+        };
+        assert_eq!(list_contents(&mut db, by_class).await.len(), 2);
+
+        let by_tags = MemoryFilter {
+            tags: vec!["project".to_string(), "urgent".to_string()],
+            ..Default::default()
+        };
+        assert_eq!(list_contents(&mut db, by_tags).await, vec!["New work"]);
+
+        let by_scope = MemoryFilter {
+            scope: Some("odin".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(list_contents(&mut db, by_scope).await, vec!["Old work"]);
+
+        let by_source = MemoryFilter {
+            source: Some("planner".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(list_contents(&mut db, by_source).await, vec!["New work"]);
+
+        let by_time = MemoryFilter {
+            created_before: Some(now - chrono::Duration::days(1)),
+            ..Default::default()
+        };
+        assert_eq!(list_contents(&mut db, by_time).await, vec!["Old work"]);
+    }
+
+    #[tokio::test]
+    async fn test_list_memories_invalid_cursor() {
+        let (db, _temp_dir) = create_test_database();
+        let result = db
+            .list_memories(&MemoryFilter::default(), Some("not-a-cursor"), 10)
+            .await;
+        assert!(result.is_err());
+    }
+}
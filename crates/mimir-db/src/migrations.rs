@@ -0,0 +1,738 @@
+//! Versioned schema migrations for the encrypted database
+//!
+//! Every schema change is a numbered forward migration. Applied versions are
+//! recorded in the `schema_version` table and pending ones run, in order and
+//! inside a single transaction, when the database is opened.
+
+use mimir_core::{crypto::CryptoManager, Result};
+use rusqlite::{params, Connection, OptionalExtension, Transaction};
+use std::path::{Path, PathBuf};
+
+/// Function that applies a migration inside the open transaction
+type MigrationFn = fn(&Transaction<'_>) -> rusqlite::Result<()>;
+
+/// Version of the migration that failed, with its error
+type MigrationFailure = (u32, rusqlite::Error);
+
+/// A single forward schema migration
+pub struct Migration {
+    /// Version this migration brings the schema to (strictly increasing)
+    pub version: u32,
+    /// Short human-readable name
+    pub name: &'static str,
+    /// Apply the migration inside the open transaction
+    up: MigrationFn,
+}
+
+/// All migrations in the order they must be applied
+pub const MIGRATIONS: &[Migration] = &[
+    Migration {
+        version: 1,
+        name: "create_memory_table",
+        up: create_memory_table,
+    },
+    Migration {
+        version: 2,
+        name: "memory_metadata_columns",
+        up: memory_metadata_columns,
+    },
+    Migration {
+        version: 3,
+        name: "memory_revisions",
+        up: memory_revisions,
+    },
+    Migration {
+        version: KEYWORD_INDEX_VERSION,
+        name: "memory_keyword_index",
+        up: memory_keyword_index,
+    },
+    Migration {
+        version: 5,
+        name: "memory_trash",
+        up: memory_trash,
+    },
+    Migration {
+        version: 6,
+        name: "memory_expiry",
+        up: memory_expiry,
+    },
+    Migration {
+        version: 7,
+        name: "memory_relation",
+        up: memory_relation,
+    },
+    Migration {
+        version: 8,
+        name: "audit_log",
+        up: audit_log,
+    },
+    Migration {
+        version: 9,
+        name: "class_key_versions",
+        up: class_key_versions,
+    },
+    Migration {
+        version: 10,
+        name: "memory_embedding",
+        up: memory_embedding,
+    },
+    Migration {
+        version: 11,
+        name: "memory_attachment",
+        up: memory_attachment,
+    },
+    Migration {
+        version: 12,
+        name: "audit_head",
+        up: audit_head,
+    },
+];
+
+/// Version that introduced the keyword index; existing memories are indexed
+/// when the database opens right after applying it
+pub(crate) const KEYWORD_INDEX_VERSION: u32 = 4;
+
+/// Migration recorded in `schema_version`
+#[derive(Debug, Clone)]
+pub struct AppliedMigration {
+    pub version: u32,
+    pub name: String,
+    pub applied_at: chrono::DateTime<chrono::Utc>,
+}
+
+/// Migration known to this build but not yet applied
+#[derive(Debug, Clone)]
+pub struct PendingMigration {
+    pub version: u32,
+    pub name: &'static str,
+}
+
+/// Applied and pending migrations for a database
+#[derive(Debug, Clone, Default)]
+pub struct MigrationStatus {
+    pub applied: Vec<AppliedMigration>,
+    pub pending: Vec<PendingMigration>,
+}
+
+impl MigrationStatus {
+    /// Highest applied schema version (0 for an unversioned database)
+    pub fn current_version(&self) -> u32 {
+        self.applied.iter().map(|m| m.version).max().unwrap_or(0)
+    }
+}
+
+/// Latest schema version known to this build
+pub fn latest_version() -> u32 {
+    MIGRATIONS.last().map(|m| m.version).unwrap_or(0)
+}
+
+/// Open a database read-only with the vault key and report its migration status
+pub fn migration_status<P: AsRef<Path>>(
+    db_path: P,
+    crypto_manager: &CryptoManager,
+) -> Result<MigrationStatus> {
+    let db_path = db_path.as_ref();
+    if !db_path.exists() {
+        return Ok(MigrationStatus {
+            applied: vec![],
+            pending: MIGRATIONS
+                .iter()
+                .map(|m| PendingMigration {
+                    version: m.version,
+                    name: m.name,
+                })
+                .collect(),
+        });
+    }
+
+    let conn = Connection::open_with_flags(db_path, rusqlite::OpenFlags::SQLITE_OPEN_READ_ONLY)
+        .map_err(|e| {
+            mimir_core::MimirError::Database(anyhow::anyhow!("Failed to open database: {}", e))
+        })?;
+    crate::apply_key(&conn, crypto_manager)?;
+    read_status(&conn)
+}
+
+/// Bring the schema up to date, running pending migrations in one transaction
+///
+/// Returns the versions that were applied. Before anything is applied to a
+/// database that already holds tables, a copy of it is taken with the online
+/// backup API and written next to it; if a migration then fails, the
+/// transaction is rolled back and the error names the copy. The copy is
+/// deleted once the migrations have committed.
+pub(crate) fn run_migrations(
+    conn: &mut Connection,
+    db_path: &Path,
+    crypto_manager: &CryptoManager,
+) -> Result<Vec<u32>> {
+    ensure_version_table(conn)?;
+
+    let current = current_version(conn)?;
+    if current > latest_version() {
+        return Err(mimir_core::MimirError::Database(anyhow::anyhow!(
+            "Database schema version {} is newer than supported version {}",
+            current,
+            latest_version()
+        )));
+    }
+
+    let pending: Vec<&Migration> = MIGRATIONS.iter().filter(|m| m.version > current).collect();
+    if pending.is_empty() {
+        return Ok(vec![]);
+    }
+
+    // A new database has nothing worth keeping
+    let backup = if has_data(conn)? {
+        Some(backup_database(conn, db_path, current, crypto_manager)?)
+    } else {
+        None
+    };
+
+    if let Err((version, e)) = apply_migrations(conn, &pending) {
+        let backup = match backup {
+            Some(path) => format!("pre-migration backup at {}", path.display()),
+            None => "database was empty".to_string(),
+        };
+        return Err(mimir_core::MimirError::Database(anyhow::anyhow!(
+            "Migration {} failed: {} ({})",
+            version,
+            e,
+            backup
+        )));
+    }
+
+    // The migrated database is committed, so the full copy is no longer needed
+    if let Some(path) = backup {
+        std::fs::remove_file(&path).map_err(|e| {
+            mimir_core::MimirError::Database(anyhow::anyhow!(
+                "Failed to remove pre-migration backup {}: {}",
+                path.display(),
+                e
+            ))
+        })?;
+    }
+
+    Ok(pending.iter().map(|m| m.version).collect())
+}
+
+/// Read migration status without applying anything
+pub(crate) fn read_status(conn: &Connection) -> Result<MigrationStatus> {
+    let has_table: bool = conn
+        .query_row(
+            "SELECT count(*) FROM sqlite_master WHERE type = 'table' AND name = 'schema_version'",
+            [],
+            |row| row.get::<_, i64>(0),
+        )
+        .map(|count| count > 0)
+        .map_err(|e| {
+            mimir_core::MimirError::Database(anyhow::anyhow!(
+                "Failed to inspect schema_version table: {}",
+                e
+            ))
+        })?;
+
+    let mut applied = Vec::new();
+    if has_table {
+        let mut stmt = conn
+            .prepare("SELECT version, name, applied_at FROM schema_version ORDER BY version")
+            .map_err(|e| {
+                mimir_core::MimirError::Database(anyhow::anyhow!("Failed to prepare query: {}", e))
+            })?;
+        let rows = stmt
+            .query_map([], |row| {
+                Ok((
+                    row.get::<_, u32>(0)?,
+                    row.get::<_, String>(1)?,
+                    row.get::<_, i64>(2)?,
+                ))
+            })
+            .map_err(|e| {
+                mimir_core::MimirError::Database(anyhow::anyhow!("Failed to execute query: {}", e))
+            })?;
+
+        for row in rows {
+            let (version, name, applied_ts) = row.map_err(|e| {
+                mimir_core::MimirError::Database(anyhow::anyhow!("Failed to read row: {}", e))
+            })?;
+            let applied_at = chrono::DateTime::from_timestamp(applied_ts, 0).ok_or_else(|| {
+                mimir_core::MimirError::Database(anyhow::anyhow!(
+                    "Invalid timestamp: {}",
+                    applied_ts
+                ))
+            })?;
+            applied.push(AppliedMigration {
+                version,
+                name,
+                applied_at,
+            });
+        }
+    }
+
+    let current = applied.iter().map(|m| m.version).max().unwrap_or(0);
+    let pending = MIGRATIONS
+        .iter()
+        .filter(|m| m.version > current)
+        .map(|m| PendingMigration {
+            version: m.version,
+            name: m.name,
+        })
+        .collect();
+
+    Ok(MigrationStatus { applied, pending })
+}
+
+fn ensure_version_table(conn: &Connection) -> Result<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS schema_version (
+            version    INTEGER PRIMARY KEY,
+            name       TEXT NOT NULL,
+            applied_at INTEGER NOT NULL
+        )",
+        [],
+    )
+    .map_err(|e| {
+        mimir_core::MimirError::Database(anyhow::anyhow!(
+            "Failed to create schema_version table: {}",
+            e
+        ))
+    })?;
+    Ok(())
+}
+
+fn current_version(conn: &Connection) -> Result<u32> {
+    conn.query_row("SELECT MAX(version) FROM schema_version", [], |row| {
+        row.get::<_, Option<u32>>(0)
+    })
+    .optional()
+    .map(|version| version.flatten().unwrap_or(0))
+    .map_err(|e| {
+        mimir_core::MimirError::Database(anyhow::anyhow!("Failed to read schema version: {}", e))
+    })
+}
+
+/// Apply migrations in one transaction, returning the failing version on error
+fn apply_migrations(
+    conn: &mut Connection,
+    pending: &[&Migration],
+) -> std::result::Result<(), MigrationFailure> {
+    let first = pending.first().map(|m| m.version).unwrap_or(0);
+    let tx = conn.transaction().map_err(|e| (first, e))?;
+
+    for migration in pending {
+        (migration.up)(&tx).map_err(|e| (migration.version, e))?;
+        tx.execute(
+            "INSERT INTO schema_version (version, name, applied_at) VALUES (?1, ?2, ?3)",
+            params![
+                migration.version,
+                migration.name,
+                chrono::Utc::now().timestamp()
+            ],
+        )
+        .map_err(|e| (migration.version, e))?;
+    }
+
+    let last = pending.last().map(|m| m.version).unwrap_or(0);
+    tx.commit().map_err(|e| (last, e))
+}
+
+/// Whether the database holds any table besides `schema_version`
+fn has_data(conn: &Connection) -> Result<bool> {
+    conn.query_row(
+        "SELECT EXISTS(SELECT 1 FROM sqlite_master
+                       WHERE type = 'table' AND name != 'schema_version')",
+        [],
+        |row| row.get(0),
+    )
+    .map_err(|e| {
+        mimir_core::MimirError::Database(anyhow::anyhow!("Failed to inspect database: {}", e))
+    })
+}
+
+/// Back the database up as `<name>.pre-migration-v<version>-<timestamp>.bak`
+fn backup_database(
+    conn: &Connection,
+    db_path: &Path,
+    version: u32,
+    crypto_manager: &CryptoManager,
+) -> Result<PathBuf> {
+    let file_name = db_path
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_else(|| "mimir.db".to_string());
+    let backup_path = db_path.with_file_name(format!(
+        "{}.pre-migration-v{}-{}.bak",
+        file_name,
+        version,
+        chrono::Utc::now().format("%Y%m%dT%H%M%S")
+    ));
+    crate::backup::copy_database(conn, &backup_path, crypto_manager).map_err(|e| {
+        mimir_core::MimirError::Database(anyhow::anyhow!(
+            "Pre-migration backup to {} failed: {}",
+            backup_path.display(),
+            e
+        ))
+    })?;
+    Ok(backup_path)
+}
+
+// --- Migrations ---------------------------------------------------------
+
+/// v1: the original memory table and its indexes
+fn create_memory_table(tx: &Transaction<'_>) -> rusqlite::Result<()> {
+    tx.execute_batch(
+        "CREATE TABLE IF NOT EXISTS memory (
+            id        TEXT PRIMARY KEY,
+            source    TEXT NOT NULL,
+            class_id  TEXT NOT NULL,
+            text_enc  BLOB NOT NULL,
+            vec_id    INTEGER NOT NULL,
+            ts        INTEGER NOT NULL
+        );
+        CREATE INDEX IF NOT EXISTS idx_memory_class_id ON memory(class_id);
+        CREATE INDEX IF NOT EXISTS idx_memory_source_ts ON memory(source, ts DESC);",
+    )
+}
+
+/// v2: encrypted scope/tags/ACL plus updated timestamp and key ID
+fn memory_metadata_columns(tx: &Transaction<'_>) -> rusqlite::Result<()> {
+    // Vaults opened by builds that predate versioning may already have some columns
+    for (column, definition) in [
+        ("updated_ts", "INTEGER"),
+        ("key_id", "TEXT"),
+        ("scope_enc", "BLOB"),
+        ("tags_enc", "BLOB"),
+        ("acl_enc", "BLOB"),
+    ] {
+        add_column_if_missing(tx, "memory", column, definition)?;
+    }
+    Ok(())
+}
+
+/// v3: encrypted prior versions of updated memories
+fn memory_revisions(tx: &Transaction<'_>) -> rusqlite::Result<()> {
+    tx.execute_batch(
+        "CREATE TABLE memory_revision (
+            memory_id     TEXT NOT NULL,
+            revision      INTEGER NOT NULL,
+            source        TEXT NOT NULL,
+            class_id      TEXT NOT NULL,
+            text_enc      BLOB NOT NULL,
+            vec_id        INTEGER NOT NULL,
+            ts            INTEGER NOT NULL,
+            updated_ts    INTEGER,
+            key_id        TEXT,
+            scope_enc     BLOB,
+            tags_enc      BLOB,
+            acl_enc       BLOB,
+            superseded_ts INTEGER NOT NULL,
+            PRIMARY KEY (memory_id, revision)
+        );",
+    )
+}
+
+/// v4: blind keyword index (HMAC tokens, never plaintext terms)
+fn memory_keyword_index(tx: &Transaction<'_>) -> rusqlite::Result<()> {
+    tx.execute_batch(
+        "CREATE TABLE memory_keyword (
+            memory_id TEXT NOT NULL,
+            class_id  TEXT NOT NULL,
+            token     BLOB NOT NULL,
+            tf        INTEGER NOT NULL,
+            PRIMARY KEY (memory_id, token)
+        );
+        CREATE INDEX idx_memory_keyword_token ON memory_keyword(class_id, token);",
+    )
+}
+
+/// v5: soft delete timestamp for the trash
+fn memory_trash(tx: &Transaction<'_>) -> rusqlite::Result<()> {
+    tx.execute_batch(
+        "ALTER TABLE memory ADD COLUMN deleted_ts INTEGER;
+        CREATE INDEX idx_memory_deleted_ts ON memory(deleted_ts);",
+    )
+}
+
+/// v6: optional expiry time, kept with revisions too
+fn memory_expiry(tx: &Transaction<'_>) -> rusqlite::Result<()> {
+    tx.execute_batch(
+        "ALTER TABLE memory ADD COLUMN expires_ts INTEGER;
+        ALTER TABLE memory_revision ADD COLUMN expires_ts INTEGER;
+        CREATE INDEX idx_memory_expires_ts ON memory(expires_ts);",
+    )
+}
+
+/// v7: typed edges between memories
+fn memory_relation(tx: &Transaction<'_>) -> rusqlite::Result<()> {
+    tx.execute_batch(
+        "CREATE TABLE memory_relation (
+            source_id TEXT NOT NULL,
+            target_id TEXT NOT NULL,
+            kind      TEXT NOT NULL,
+            class_id  TEXT NOT NULL,
+            label_enc BLOB,
+            ts        INTEGER NOT NULL,
+            PRIMARY KEY (source_id, target_id, kind)
+        );
+        CREATE INDEX idx_memory_relation_target ON memory_relation(target_id);",
+    )
+}
+
+/// v8: append-only, hash-chained audit log
+fn audit_log(tx: &Transaction<'_>) -> rusqlite::Result<()> {
+    tx.execute_batch(
+        "CREATE TABLE audit_log (
+            seq        INTEGER PRIMARY KEY,
+            ts         INTEGER NOT NULL,
+            app_id     TEXT NOT NULL,
+            op         TEXT NOT NULL,
+            memory_ids TEXT NOT NULL,
+            guardrail  TEXT,
+            prev_hash  BLOB NOT NULL,
+            hash       BLOB NOT NULL
+        );
+        CREATE INDEX idx_audit_log_app_ts ON audit_log(app_id, ts);
+        CREATE INDEX idx_audit_log_ts ON audit_log(ts);
+        CREATE TRIGGER audit_log_no_update BEFORE UPDATE ON audit_log
+        BEGIN SELECT RAISE(ABORT, 'audit_log is append-only'); END;
+        CREATE TRIGGER audit_log_no_delete BEFORE DELETE ON audit_log
+        BEGIN SELECT RAISE(ABORT, 'audit_log is append-only'); END;",
+    )
+}
+
+/// v9: class key version of every encrypted row, for re-encryption after rotation
+fn class_key_versions(tx: &Transaction<'_>) -> rusqlite::Result<()> {
+    tx.execute_batch(
+        "ALTER TABLE memory ADD COLUMN key_version INTEGER NOT NULL DEFAULT 0;
+        ALTER TABLE memory_revision ADD COLUMN key_version INTEGER NOT NULL DEFAULT 0;
+        ALTER TABLE memory_relation ADD COLUMN key_version INTEGER NOT NULL DEFAULT 0;
+        CREATE INDEX idx_memory_class_key_version ON memory(class_id, key_version);",
+    )
+}
+
+/// v10: encrypted embeddings, so the vector index can be rebuilt
+fn memory_embedding(tx: &Transaction<'_>) -> rusqlite::Result<()> {
+    tx.execute_batch(
+        "ALTER TABLE memory ADD COLUMN embedding_enc BLOB;
+        ALTER TABLE memory_revision ADD COLUMN embedding_enc BLOB;",
+    )
+}
+
+/// v11: encrypted file attachments
+fn memory_attachment(tx: &Transaction<'_>) -> rusqlite::Result<()> {
+    tx.execute_batch(
+        "CREATE TABLE memory_attachment (
+            id            TEXT PRIMARY KEY,
+            memory_id     TEXT NOT NULL,
+            class_id      TEXT NOT NULL,
+            name_enc      BLOB NOT NULL,
+            mime_type_enc BLOB NOT NULL,
+            data_enc      BLOB NOT NULL,
+            nonce         BLOB NOT NULL,
+            size          INTEGER NOT NULL,
+            ts            INTEGER NOT NULL,
+            key_version   INTEGER NOT NULL DEFAULT 0
+        );
+        CREATE INDEX idx_memory_attachment_memory ON memory_attachment(memory_id);
+        CREATE INDEX idx_memory_attachment_class_key_version
+            ON memory_attachment(class_id, key_version);",
+    )
+}
+
+/// v12: the newest audit entry, kept in step by a trigger, so entries cut
+/// off the end of the chain are noticed
+fn audit_head(tx: &Transaction<'_>) -> rusqlite::Result<()> {
+    tx.execute_batch(
+        "CREATE TABLE audit_head (
+            id   INTEGER PRIMARY KEY CHECK (id = 1),
+            seq  INTEGER NOT NULL,
+            hash BLOB NOT NULL
+        );
+        INSERT INTO audit_head (id, seq, hash)
+        SELECT 1, COALESCE(MAX(seq), 0),
+               COALESCE((SELECT hash FROM audit_log ORDER BY seq DESC LIMIT 1), zeroblob(32))
+        FROM audit_log;
+        CREATE TRIGGER audit_head_follow AFTER INSERT ON audit_log
+        BEGIN UPDATE audit_head SET seq = NEW.seq, hash = NEW.hash WHERE id = 1; END;
+        CREATE TRIGGER audit_head_no_rewind BEFORE UPDATE ON audit_head
+        WHEN NEW.id != OLD.id OR NEW.seq != OLD.seq + 1
+        BEGIN SELECT RAISE(ABORT, 'audit_head only moves forward'); END;
+        CREATE TRIGGER audit_head_no_delete BEFORE DELETE ON audit_head
+        BEGIN SELECT RAISE(ABORT, 'audit_head cannot be removed'); END;",
+    )
+}
+
+/// Add a column unless the table already has it
+fn add_column_if_missing(
+    tx: &Transaction<'_>,
+    table: &str,
+    column: &str,
+    definition: &str,
+) -> rusqlite::Result<()> {
+    let exists: i64 = tx.query_row(
+        &format!(
+            "SELECT count(*) FROM pragma_table_info('{}') WHERE name = ?1",
+            table
+        ),
+        [column],
+        |row| row.get(0),
+    )?;
+    if exists == 0 {
+        tx.execute(
+            &format!("ALTER TABLE {} ADD COLUMN {} {}", table, column, definition),
+            [],
+        )?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Database;
+    use tempfile::TempDir;
+
+    fn create_crypto_manager(temp_dir: &TempDir) -> CryptoManager {
+        CryptoManager::with_password(temp_dir.path().join("keyset.json"), "test-password").unwrap()
+    }
+
+    fn backup_files(temp_dir: &TempDir) -> Vec<PathBuf> {
+        std::fs::read_dir(temp_dir.path())
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .filter(|path| path.extension().is_some_and(|ext| ext == "bak"))
+            .collect()
+    }
+
+    #[test]
+    fn test_new_database_is_at_latest_version() {
+        let temp_dir = TempDir::new().unwrap();
+        let db_path = temp_dir.path().join("test.db");
+
+        let _db =
+            Database::with_crypto_manager(&db_path, create_crypto_manager(&temp_dir)).unwrap();
+
+        let status = migration_status(&db_path, &create_crypto_manager(&temp_dir)).unwrap();
+        assert_eq!(status.current_version(), latest_version());
+        assert_eq!(status.applied.len(), MIGRATIONS.len());
+        assert!(status.pending.is_empty());
+
+        // There was nothing to back up
+        assert!(backup_files(&temp_dir).is_empty());
+    }
+
+    #[test]
+    fn test_missing_database_reports_all_pending() {
+        let temp_dir = TempDir::new().unwrap();
+        let status = migration_status(
+            temp_dir.path().join("missing.db"),
+            &create_crypto_manager(&temp_dir),
+        )
+        .unwrap();
+
+        assert!(status.applied.is_empty());
+        assert_eq!(status.pending.len(), MIGRATIONS.len());
+    }
+
+    #[test]
+    fn test_unversioned_database_is_upgraded() {
+        let temp_dir = TempDir::new().unwrap();
+        let db_path = temp_dir.path().join("legacy.db");
+
+        // Original schema, as created before versioning existed
+        {
+            let conn = Connection::open(&db_path).unwrap();
+            crate::apply_key(&conn, &create_crypto_manager(&temp_dir)).unwrap();
+            conn.execute_batch(
+                "CREATE TABLE memory (
+                    id TEXT PRIMARY KEY, source TEXT NOT NULL, class_id TEXT NOT NULL,
+                    text_enc BLOB NOT NULL, vec_id INTEGER NOT NULL, ts INTEGER NOT NULL
+                );",
+            )
+            .unwrap();
+        }
+
+        let status = migration_status(&db_path, &create_crypto_manager(&temp_dir)).unwrap();
+        assert_eq!(status.current_version(), 0);
+        assert_eq!(status.pending.len(), MIGRATIONS.len());
+
+        let _db =
+            Database::with_crypto_manager(&db_path, create_crypto_manager(&temp_dir)).unwrap();
+
+        let status = migration_status(&db_path, &create_crypto_manager(&temp_dir)).unwrap();
+        assert_eq!(status.current_version(), latest_version());
+        assert!(status.pending.is_empty());
+
+        // The pre-migration backup is removed once the migrations commit
+        assert!(backup_files(&temp_dir).is_empty());
+
+        // Reopening an up-to-date database takes no backup either
+        let _db =
+            Database::with_crypto_manager(&db_path, create_crypto_manager(&temp_dir)).unwrap();
+        assert!(backup_files(&temp_dir).is_empty());
+    }
+
+    #[test]
+    fn test_failed_migration_keeps_backup() {
+        let temp_dir = TempDir::new().unwrap();
+        let db_path = temp_dir.path().join("legacy.db");
+
+        // A stray table that a later migration tries to create
+        {
+            let conn = Connection::open(&db_path).unwrap();
+            crate::apply_key(&conn, &create_crypto_manager(&temp_dir)).unwrap();
+            conn.execute_batch(
+                "CREATE TABLE memory (
+                    id TEXT PRIMARY KEY, source TEXT NOT NULL, class_id TEXT NOT NULL,
+                    text_enc BLOB NOT NULL, vec_id INTEGER NOT NULL, ts INTEGER NOT NULL
+                );
+                CREATE TABLE memory_relation (id INTEGER PRIMARY KEY);",
+            )
+            .unwrap();
+        }
+
+        let error = Database::with_crypto_manager(&db_path, create_crypto_manager(&temp_dir))
+            .err()
+            .unwrap()
+            .to_string();
+
+        // The unmigrated database was backed up first, under the same key
+        let backups = backup_files(&temp_dir);
+        assert_eq!(backups.len(), 1);
+        let backup_name = backups[0]
+            .file_name()
+            .unwrap()
+            .to_string_lossy()
+            .into_owned();
+        assert!(backup_name.starts_with("legacy.db.pre-migration-v0-"));
+        assert!(error.contains(&backup_name));
+        let status = migration_status(&backups[0], &create_crypto_manager(&temp_dir)).unwrap();
+        assert_eq!(status.current_version(), 0);
+
+        // The failed migrations were rolled back
+        let status = migration_status(&db_path, &create_crypto_manager(&temp_dir)).unwrap();
+        assert_eq!(status.current_version(), 0);
+    }
+
+    #[test]
+    fn test_newer_schema_is_rejected() {
+        let temp_dir = TempDir::new().unwrap();
+        let db_path = temp_dir.path().join("future.db");
+
+        {
+            let _db =
+                Database::with_crypto_manager(&db_path, create_crypto_manager(&temp_dir)).unwrap();
+        }
+        {
+            let conn = Connection::open(&db_path).unwrap();
+            crate::apply_key(&conn, &create_crypto_manager(&temp_dir)).unwrap();
+            conn.execute(
+                "INSERT INTO schema_version (version, name, applied_at) VALUES (?1, 'future', 0)",
+                [latest_version() + 1],
+            )
+            .unwrap();
+        }
+
+        let result = Database::with_crypto_manager(&db_path, create_crypto_manager(&temp_dir));
+        assert!(result.is_err());
+    }
+}
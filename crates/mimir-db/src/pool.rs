@@ -0,0 +1,168 @@
+//! Read connection pool
+//!
+//! The database runs in WAL mode, so readers neither block the writer nor
+//! each other. Reads go through a small pool of read-only SQLCipher
+//! connections that are opened on demand and keyed like the write
+//! connection; every write stays on the single write connection.
+
+use crate::apply_key;
+use mimir_core::{crypto::CryptoManager, Result};
+use rusqlite::{Connection, OpenFlags};
+use std::ops::Deref;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, PoisonError};
+use std::time::Duration;
+use tokio::sync::{Semaphore, SemaphorePermit};
+
+/// Number of read connections a database opens at most
+pub const DEFAULT_READ_CONNECTIONS: usize = 4;
+
+/// How long a connection waits on a locked database before giving up
+pub(crate) const BUSY_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Pool of read-only connections to one database file
+pub(crate) struct ReadPool {
+    db_path: PathBuf,
+    idle: Mutex<Vec<Connection>>,
+    permits: Semaphore,
+}
+
+impl ReadPool {
+    pub(crate) fn new(db_path: &Path, size: usize) -> Self {
+        Self {
+            db_path: db_path.to_path_buf(),
+            idle: Mutex::new(Vec::new()),
+            permits: Semaphore::new(size.max(1)),
+        }
+    }
+
+    /// Take a read connection, waiting while every connection is in use
+    pub(crate) async fn get(&self, crypto_manager: &CryptoManager) -> Result<PooledConnection<'_>> {
+        let permit = self.permits.acquire().await.map_err(|e| {
+            mimir_core::MimirError::Database(anyhow::anyhow!("Read pool closed: {}", e))
+        })?;
+
+        let idle = self
+            .idle
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .pop();
+        let conn = match idle {
+            Some(conn) => conn,
+            None => self.open(crypto_manager)?,
+        };
+
+        Ok(PooledConnection {
+            conn: Some(conn),
+            pool: self,
+            _permit: permit,
+        })
+    }
+
+    fn open(&self, crypto_manager: &CryptoManager) -> Result<Connection> {
+        let conn = Connection::open_with_flags(
+            &self.db_path,
+            OpenFlags::SQLITE_OPEN_READ_ONLY | OpenFlags::SQLITE_OPEN_NO_MUTEX,
+        )
+        .map_err(|e| {
+            mimir_core::MimirError::Database(anyhow::anyhow!(
+                "Failed to open read connection: {}",
+                e
+            ))
+        })?;
+        apply_key(&conn, crypto_manager)?;
+        conn.busy_timeout(BUSY_TIMEOUT).map_err(|e| {
+            mimir_core::MimirError::Database(anyhow::anyhow!("Failed to set busy timeout: {}", e))
+        })?;
+        Ok(conn)
+    }
+}
+
+/// Read connection borrowed from the pool, returned to it on drop
+pub(crate) struct PooledConnection<'a> {
+    conn: Option<Connection>,
+    pool: &'a ReadPool,
+    _permit: SemaphorePermit<'a>,
+}
+
+impl Deref for PooledConnection<'_> {
+    type Target = Connection;
+
+    fn deref(&self) -> &Connection {
+        self.conn
+            .as_ref()
+            .expect("connection is only taken on drop")
+    }
+}
+
+impl Drop for PooledConnection<'_> {
+    fn drop(&mut self) {
+        if let Some(conn) = self.conn.take() {
+            self.pool
+                .idle
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .push(conn);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Database;
+    use mimir_core::crypto::CryptoManager;
+    use mimir_core::test_utils::MemoryBuilder;
+    use std::sync::Arc;
+    use tempfile::TempDir;
+
+    fn create_test_database() -> (Database, TempDir) {
+        let temp_dir = TempDir::new().unwrap();
+        let crypto_manager =
+            CryptoManager::with_password(temp_dir.path().join("keyset.json"), "test-password")
+                .unwrap();
+        let db =
+            Database::with_crypto_manager(temp_dir.path().join("test.db"), crypto_manager).unwrap();
+        (db, temp_dir)
+    }
+
+    #[tokio::test]
+    async fn test_database_uses_wal() {
+        let (db, _temp_dir) = create_test_database();
+
+        let conn = db.conn.lock().await;
+        let mode: String = conn
+            .query_row("PRAGMA journal_mode", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(mode, "wal");
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_reads() {
+        let (db, _temp_dir) = create_test_database();
+        let db = Arc::new(db);
+
+        let memory = MemoryBuilder::new().with_content("Shared note").build();
+        db.store_memory(&memory).await.unwrap();
+
+        // Readers share the pool and see the committed write
+        let readers: Vec<_> = (0..8)
+            .map(|_| {
+                let db = db.clone();
+                tokio::spawn(async move { db.get_memory(memory.id).await })
+            })
+            .collect();
+        for reader in readers {
+            let read = reader.await.unwrap().unwrap().unwrap();
+            assert_eq!(read.content, "Shared note");
+        }
+
+        // A reader holding a connection does not block the writer
+        let reader = db.readers.get(&db.crypto_manager).await.unwrap();
+        let other = MemoryBuilder::new()
+            .with_content("Written meanwhile")
+            .build();
+        db.store_memory(&other).await.unwrap();
+        drop(reader);
+        assert!(db.get_memory(other.id).await.unwrap().is_some());
+    }
+}
@@ -0,0 +1,407 @@
+//! Typed relations between memories
+//!
+//! Edges live in `memory_relation`, one per (source, target, kind). An edge's
+//! label is encrypted under the class key of its source memory, and that
+//! class is stored with the edge. Edges touching a trashed memory are hidden
+//! until it is restored, and destroyed along with it when it is purged.
+
+use crate::{decrypt_blob, encrypt_blob, timestamp_to_datetime, Database};
+use mimir_core::crypto::CryptoManager;
+use mimir_core::{Memory, MemoryId, MemoryRelation, RelationKind, Result};
+use rusqlite::{params, Connection, OptionalExtension};
+use std::collections::HashSet;
+
+/// Edges touching a memory in either direction whose endpoints are both live
+const LIVE_EDGES_QUERY: &str =
+    "SELECT r.source_id, r.target_id, r.kind, r.class_id, r.label_enc, r.ts
+     FROM memory_relation r
+     JOIN memory s ON s.id = r.source_id AND s.deleted_ts IS NULL
+     JOIN memory t ON t.id = r.target_id AND t.deleted_ts IS NULL
+     WHERE r.source_id = ?1 OR r.target_id = ?1
+     ORDER BY r.ts ASC, r.kind ASC";
+
+/// A memory reached by walking relations
+#[derive(Debug, Clone)]
+pub struct RelatedMemory {
+    /// Number of edges between the starting memory and this one
+    pub hops: usize,
+    /// The edge this memory was reached through
+    pub relation: MemoryRelation,
+    pub memory: Memory,
+}
+
+/// Raw relation columns as stored
+struct RelationRow {
+    source_id: String,
+    target_id: String,
+    kind: String,
+    class_id: String,
+    label_enc: Option<Vec<u8>>,
+    ts: i64,
+}
+
+fn live_edges(conn: &Connection, memory_id: &str) -> Result<Vec<RelationRow>> {
+    conn.prepare_cached(LIVE_EDGES_QUERY)
+        .and_then(|mut stmt| {
+            stmt.query_map(params![memory_id], |row| {
+                Ok(RelationRow {
+                    source_id: row.get(0)?,
+                    target_id: row.get(1)?,
+                    kind: row.get(2)?,
+                    class_id: row.get(3)?,
+                    label_enc: row.get(4)?,
+                    ts: row.get(5)?,
+                })
+            })?
+            .collect::<rusqlite::Result<Vec<_>>>()
+        })
+        .map_err(|e| {
+            mimir_core::MimirError::Database(anyhow::anyhow!("Failed to read relations: {}", e))
+        })
+}
+
+fn decode_relation(crypto_manager: &CryptoManager, row: RelationRow) -> Result<MemoryRelation> {
+    let parse_id = |id: &str| {
+        uuid::Uuid::parse_str(id)
+            .map_err(|e| mimir_core::MimirError::Database(anyhow::anyhow!("Invalid UUID: {}", e)))
+    };
+    let kind = row
+        .kind
+        .parse::<RelationKind>()
+        .map_err(|e| mimir_core::MimirError::Database(anyhow::anyhow!(e)))?;
+    let label = match &row.label_enc {
+        Some(data) => {
+            let bytes = decrypt_blob(crypto_manager, &row.class_id, data)?;
+            Some(String::from_utf8(bytes).map_err(|e| {
+                mimir_core::MimirError::Database(anyhow::anyhow!("Invalid UTF-8: {}", e))
+            })?)
+        }
+        None => None,
+    };
+
+    Ok(MemoryRelation {
+        source: parse_id(&row.source_id)?,
+        target: parse_id(&row.target_id)?,
+        kind,
+        label,
+        created_at: timestamp_to_datetime(row.ts)?,
+    })
+}
+
+impl Database {
+    /// Create an edge between two memories, replacing any edge of the same kind
+    ///
+    /// Both memories must exist and not be in the trash.
+    pub async fn link_memories(&self, relation: &MemoryRelation) -> Result<()> {
+        if relation.source == relation.target {
+            return Err(mimir_core::MimirError::Database(anyhow::anyhow!(
+                "Cannot relate a memory to itself"
+            )));
+        }
+
+        let conn = self.conn.lock().await;
+        let class_of = |id: MemoryId| {
+            conn.query_row(
+                "SELECT class_id FROM memory WHERE id = ?1 AND deleted_ts IS NULL",
+                params![id.to_string()],
+                |row| row.get::<_, String>(0),
+            )
+            .optional()
+            .map_err(|e| {
+                mimir_core::MimirError::Database(anyhow::anyhow!("Failed to read memory: {}", e))
+            })?
+            .ok_or_else(|| {
+                mimir_core::MimirError::Database(anyhow::anyhow!("Memory not found: {}", id))
+            })
+        };
+        let class_id = class_of(relation.source)?;
+        class_of(relation.target)?;
+
+        let label_enc = match &relation.label {
+            Some(label) => Some(encrypt_blob(
+                &self.crypto_manager,
+                &class_id,
+                label.as_bytes(),
+            )?),
+            None => None,
+        };
+
+        conn.execute(
+            "INSERT OR REPLACE INTO memory_relation
+                (source_id, target_id, kind, class_id, label_enc, ts, key_version)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            params![
+                relation.source.to_string(),
+                relation.target.to_string(),
+                relation.kind.as_str(),
+                class_id,
+                label_enc,
+                relation.created_at.timestamp(),
+                self.crypto_manager.class_key_version(&class_id),
+            ],
+        )
+        .map_err(|e| {
+            mimir_core::MimirError::Database(anyhow::anyhow!("Failed to link memories: {}", e))
+        })?;
+
+        Ok(())
+    }
+
+    /// Remove the edges from `source` to `target`, of one kind or of every kind
+    ///
+    /// Returns the number of edges removed.
+    pub async fn unlink_memories(
+        &self,
+        source: MemoryId,
+        target: MemoryId,
+        kind: Option<RelationKind>,
+    ) -> Result<usize> {
+        let conn = self.conn.lock().await;
+        let removed = conn
+            .execute(
+                "DELETE FROM memory_relation
+                 WHERE source_id = ?1 AND target_id = ?2 AND (?3 IS NULL OR kind = ?3)",
+                params![
+                    source.to_string(),
+                    target.to_string(),
+                    kind.map(|kind| kind.as_str())
+                ],
+            )
+            .map_err(|e| {
+                mimir_core::MimirError::Database(anyhow::anyhow!(
+                    "Failed to unlink memories: {}",
+                    e
+                ))
+            })?;
+        Ok(removed)
+    }
+
+    /// List the edges to and from a memory, oldest first
+    pub async fn list_relations(&self, id: MemoryId) -> Result<Vec<MemoryRelation>> {
+        let rows = {
+            let conn = self.reader().await?;
+            live_edges(&conn, &id.to_string())?
+        };
+        rows.into_iter()
+            .map(|row| decode_relation(&self.crypto_manager, row))
+            .collect()
+    }
+
+    /// Walk relations outward from a memory for up to `max_hops` edges
+    ///
+    /// Edges are followed in both directions. Each reachable memory is
+    /// returned once, at its shortest distance, nearest first; the starting
+    /// memory is not included. `kinds` limits which edges are followed, and
+    /// an empty slice follows all of them.
+    pub async fn traverse_relations(
+        &self,
+        start: MemoryId,
+        max_hops: usize,
+        kinds: &[RelationKind],
+    ) -> Result<Vec<RelatedMemory>> {
+        let mut visited = HashSet::from([start]);
+        let mut frontier = vec![start];
+        let mut reached = Vec::new();
+
+        for hops in 1..=max_hops {
+            let mut next = Vec::new();
+            for id in &frontier {
+                let rows = {
+                    let conn = self.reader().await?;
+                    live_edges(&conn, &id.to_string())?
+                };
+                for row in rows {
+                    let relation = decode_relation(&self.crypto_manager, row)?;
+                    if !kinds.is_empty() && !kinds.contains(&relation.kind) {
+                        continue;
+                    }
+                    let neighbor = if relation.source == *id {
+                        relation.target
+                    } else {
+                        relation.source
+                    };
+                    if visited.insert(neighbor) {
+                        next.push(neighbor);
+                        reached.push((hops, neighbor, relation));
+                    }
+                }
+            }
+            if next.is_empty() {
+                break;
+            }
+            frontier = next;
+        }
+
+        let mut related = Vec::with_capacity(reached.len());
+        for (hops, id, relation) in reached {
+            if let Some(memory) = self.get_memory(id).await? {
+                related.push(RelatedMemory {
+                    hops,
+                    relation,
+                    memory,
+                });
+            }
+        }
+        Ok(related)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mimir_core::test_utils::MemoryBuilder;
+    use tempfile::TempDir;
+
+    fn create_test_database() -> (Database, TempDir) {
+        let temp_dir = TempDir::new().unwrap();
+        let crypto_manager =
+            CryptoManager::with_password(temp_dir.path().join("keyset.json"), "test-password")
+                .unwrap();
+        let db =
+            Database::with_crypto_manager(temp_dir.path().join("test.db"), crypto_manager).unwrap();
+        (db, temp_dir)
+    }
+
+    fn relation(source: &Memory, target: &Memory, kind: RelationKind) -> MemoryRelation {
+        MemoryRelation {
+            source: source.id,
+            target: target.id,
+            kind,
+            label: None,
+            created_at: chrono::Utc::now(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_link_and_unlink() {
+        let (db, _temp_dir) = create_test_database();
+
+        let old = MemoryBuilder::new().with_content("Lives in Oslo").build();
+        let new = MemoryBuilder::new().with_content("Lives in Bergen").build();
+        db.store_memories(&[old.clone(), new.clone()])
+            .await
+            .unwrap();
+
+        let mut supersedes = relation(&new, &old, RelationKind::Supersedes);
+        supersedes.label = Some("moved in 2024".to_string());
+        db.link_memories(&supersedes).await.unwrap();
+        db.link_memories(&relation(&new, &old, RelationKind::Contradicts))
+            .await
+            .unwrap();
+
+        // Visible from both ends, with the label decrypted
+        let relations = db.list_relations(old.id).await.unwrap();
+        assert_eq!(relations.len(), 2);
+        assert_eq!(db.list_relations(new.id).await.unwrap(), relations);
+        let stored = relations
+            .iter()
+            .find(|r| r.kind == RelationKind::Supersedes)
+            .unwrap();
+        assert_eq!(stored.label.as_deref(), Some("moved in 2024"));
+
+        // Labels are not stored in plaintext
+        {
+            let conn = db.conn.lock().await;
+            let label_enc: Vec<u8> = conn
+                .query_row(
+                    "SELECT label_enc FROM memory_relation WHERE kind = 'supersedes'",
+                    [],
+                    |row| row.get(0),
+                )
+                .unwrap();
+            assert!(!String::from_utf8_lossy(&label_enc).contains("moved in 2024"));
+        }
+
+        assert_eq!(
+            db.unlink_memories(new.id, old.id, Some(RelationKind::Contradicts))
+                .await
+                .unwrap(),
+            1
+        );
+        assert_eq!(db.unlink_memories(new.id, old.id, None).await.unwrap(), 1);
+        assert!(db.list_relations(old.id).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_link_requires_live_memories() {
+        let (db, _temp_dir) = create_test_database();
+
+        let a = MemoryBuilder::new().with_content("A").build();
+        let b = MemoryBuilder::new().with_content("B").build();
+        db.store_memory(&a).await.unwrap();
+
+        assert!(db
+            .link_memories(&relation(&a, &a, RelationKind::RelatedTo))
+            .await
+            .is_err());
+        assert!(db
+            .link_memories(&relation(&a, &b, RelationKind::RelatedTo))
+            .await
+            .is_err());
+
+        db.store_memory(&b).await.unwrap();
+        db.delete_memory(b.id).await.unwrap();
+        assert!(db
+            .link_memories(&relation(&a, &b, RelationKind::RelatedTo))
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_traverse_relations() {
+        let (db, _temp_dir) = create_test_database();
+
+        // a -> b <- c -> d, plus a contradicts e
+        let memories: Vec<Memory> = ["a", "b", "c", "d", "e"]
+            .iter()
+            .map(|content| MemoryBuilder::new().with_content(*content).build())
+            .collect();
+        db.store_memories(&memories).await.unwrap();
+        let [a, b, c, d, e] = [0, 1, 2, 3, 4].map(|i| &memories[i]);
+        for edge in [
+            relation(a, b, RelationKind::RelatedTo),
+            relation(c, b, RelationKind::DerivedFrom),
+            relation(c, d, RelationKind::RelatedTo),
+            relation(a, e, RelationKind::Contradicts),
+        ] {
+            db.link_memories(&edge).await.unwrap();
+        }
+
+        let reached = |related: Vec<RelatedMemory>| {
+            related
+                .into_iter()
+                .map(|r| (r.memory.content, r.hops))
+                .collect::<std::collections::BTreeSet<_>>()
+        };
+
+        let one_hop = db.traverse_relations(a.id, 1, &[]).await.unwrap();
+        assert_eq!(
+            reached(one_hop),
+            [("b".to_string(), 1), ("e".to_string(), 1)].into()
+        );
+
+        let three_hops = db.traverse_relations(a.id, 3, &[]).await.unwrap();
+        assert_eq!(three_hops.len(), 4);
+        assert_eq!(three_hops.last().unwrap().memory.id, d.id);
+        assert_eq!(three_hops.last().unwrap().hops, 3);
+
+        let related_only = db
+            .traverse_relations(a.id, 3, &[RelationKind::RelatedTo])
+            .await
+            .unwrap();
+        assert_eq!(reached(related_only), [("b".to_string(), 1)].into());
+
+        // Trashed memories break the path until restored; purging drops their edges
+        db.delete_memory(b.id).await.unwrap();
+        assert_eq!(db.traverse_relations(a.id, 3, &[]).await.unwrap().len(), 1);
+        db.restore_memory(b.id).await.unwrap();
+        assert_eq!(db.traverse_relations(a.id, 3, &[]).await.unwrap().len(), 4);
+        db.delete_memory(b.id).await.unwrap();
+        db.empty_trash().await.unwrap();
+        let conn = db.conn.lock().await;
+        let edges: i64 = conn
+            .query_row("SELECT COUNT(*) FROM memory_relation", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(edges, 2);
+    }
+}
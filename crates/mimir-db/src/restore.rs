@@ -0,0 +1,747 @@
+//! Restoring the vault from a snapshot
+//!
+//! A restore never touches the snapshot itself. Its files are checked against
+//! the manifest, copied into a staging directory, migrated to the current
+//! schema and fully decrypted with the vault's current keys before anything
+//! in the vault changes. From there either the whole vault is swapped for the
+//! staged copy, or the rows of one class or one memory are copied across.
+//!
+//! A full restore moves the current database and vector files aside, renames
+//! the staged database into place and reopens the vault. The old files are
+//! only deleted once the reopened vault holds what the snapshot held; on any
+//! failure they are moved back. Snapshots hold no vectors, so the vector
+//! index is left to be rebuilt from the restored embeddings.
+
+use crate::backup::{
+    file_checksum, read_manifest, SnapshotFileKind, SnapshotManifest, SNAPSHOT_DATABASE_FILE,
+};
+use crate::{
+    apply_key, decode_memory, keyword, migrations, revisions, Database, MemoryRow, MEMORY_COLUMNS,
+};
+use mimir_core::{crypto::CryptoManager, MemoryClass, MemoryId, Result};
+use rusqlite::{params, Connection};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// Suffixes of files SQLite keeps next to a database
+pub(crate) const DATABASE_SIDECARS: [&str; 3] = ["-wal", "-shm", "-journal"];
+
+/// Memory counts of a vault, taken by decrypting every row
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct VaultCounts {
+    /// Memories outside the trash
+    pub memories: usize,
+    /// Memories in the trash
+    pub trashed: usize,
+    /// Memories outside the trash, per class
+    pub classes: BTreeMap<String, usize>,
+}
+
+/// What a partial restore copies out of a snapshot
+#[derive(Debug, Clone, PartialEq)]
+pub enum RestoreScope {
+    /// Every memory of one class
+    Class(MemoryClass),
+    /// A single memory
+    Memory(MemoryId),
+}
+
+/// A snapshot copied into a staging directory, verified and ready to restore
+#[derive(Debug, Clone)]
+pub struct StagedSnapshot {
+    /// Staging directory holding the migrated database
+    pub dir: PathBuf,
+    pub manifest: SnapshotManifest,
+    /// Counts of the staged database, which decrypted in full
+    pub counts: VaultCounts,
+}
+
+impl StagedSnapshot {
+    fn database_path(&self) -> PathBuf {
+        self.dir.join(SNAPSHOT_DATABASE_FILE)
+    }
+
+    /// Remove the staging directory
+    pub fn discard(self) -> Result<()> {
+        fs::remove_dir_all(&self.dir).map_err(|e| {
+            mimir_core::MimirError::Database(anyhow::anyhow!(
+                "Failed to remove staging directory {}: {}",
+                self.dir.display(),
+                e
+            ))
+        })
+    }
+}
+
+/// Check every file of a snapshot against the sizes and SHA-256 in its manifest
+///
+/// Fails on the first missing or altered file, or if the snapshot holds no
+/// database.
+pub fn verify_snapshot(snapshot_dir: &Path) -> Result<SnapshotManifest> {
+    let manifest = read_manifest(snapshot_dir)?;
+
+    if !manifest
+        .files
+        .iter()
+        .any(|file| file.kind == SnapshotFileKind::Database)
+    {
+        return Err(mimir_core::MimirError::Database(anyhow::anyhow!(
+            "Snapshot {} has no database",
+            snapshot_dir.display()
+        )));
+    }
+
+    for file in &manifest.files {
+        let (sha256, size) = file_checksum(&snapshot_dir.join(&file.name)).map_err(|e| {
+            mimir_core::MimirError::Database(anyhow::anyhow!(
+                "Failed to read snapshot file {}: {}",
+                file.name,
+                e
+            ))
+        })?;
+        if size != file.size || sha256 != file.sha256 {
+            return Err(mimir_core::MimirError::Database(anyhow::anyhow!(
+                "Snapshot file {} does not match its manifest checksum",
+                file.name
+            )));
+        }
+    }
+
+    Ok(manifest)
+}
+
+/// Decrypt every memory row and count them
+fn count_memories(conn: &Connection, crypto_manager: &CryptoManager) -> Result<VaultCounts> {
+    let mut stmt = conn
+        .prepare(&format!(
+            "SELECT {}, deleted_ts FROM memory",
+            MEMORY_COLUMNS
+        ))
+        .map_err(|e| {
+            mimir_core::MimirError::Database(anyhow::anyhow!("Failed to prepare query: {}", e))
+        })?;
+    let rows = stmt
+        .query_map([], |row| {
+            Ok((MemoryRow::from_row(row)?, row.get::<_, Option<i64>>(13)?))
+        })
+        .map_err(|e| {
+            mimir_core::MimirError::Database(anyhow::anyhow!("Failed to execute query: {}", e))
+        })?;
+
+    let mut counts = VaultCounts::default();
+    for row in rows {
+        let (memory_row, deleted_ts) = row.map_err(|e| {
+            mimir_core::MimirError::Database(anyhow::anyhow!("Failed to read row: {}", e))
+        })?;
+        let id = memory_row.id.clone();
+        let memory = decode_memory(crypto_manager, memory_row).map_err(|e| {
+            mimir_core::MimirError::Database(anyhow::anyhow!(
+                "Memory {} does not decrypt with the current keys: {}",
+                id,
+                e
+            ))
+        })?;
+        if deleted_ts.is_some() {
+            counts.trashed += 1;
+        } else {
+            counts.memories += 1;
+            *counts
+                .classes
+                .entry(memory.class.as_str().to_string())
+                .or_default() += 1;
+        }
+    }
+    Ok(counts)
+}
+
+/// Files moved, as (from, to) pairs
+type MovedFiles = Vec<(PathBuf, PathBuf)>;
+
+/// Move a file to `to` if it exists, recording the move
+fn move_if_exists(from: &Path, to: &Path, moved: &mut MovedFiles) -> Result<()> {
+    if !from.exists() {
+        return Ok(());
+    }
+    fs::rename(from, to).map_err(|e| {
+        mimir_core::MimirError::Database(anyhow::anyhow!(
+            "Failed to move {} to {}: {}",
+            from.display(),
+            to.display(),
+            e
+        ))
+    })?;
+    moved.push((from.to_path_buf(), to.to_path_buf()));
+    Ok(())
+}
+
+/// Files moved during a full restore, so it can be undone
+struct VaultSwap {
+    aside_dir: PathBuf,
+    /// Current vault files moved aside, as (original, aside)
+    set_aside: MovedFiles,
+    /// Staged files moved into the vault, as (staged, installed)
+    installed: MovedFiles,
+}
+
+impl VaultSwap {
+    /// Put the staged files back and the old vault files where they were
+    fn rollback(self) -> Result<()> {
+        let mut restored = Vec::new();
+        for (staged, installed) in self.installed.iter().rev() {
+            move_if_exists(installed, staged, &mut restored)?;
+        }
+        for (original, aside) in self.set_aside.iter().rev() {
+            move_if_exists(aside, original, &mut restored)?;
+        }
+        let _ = fs::remove_dir(&self.aside_dir);
+        Ok(())
+    }
+}
+
+impl Database {
+    /// Decrypt every memory in the vault, trash included, and count them
+    pub async fn vault_counts(&self) -> Result<VaultCounts> {
+        let conn = self.reader().await?;
+        count_memories(&conn, &self.crypto_manager)
+    }
+
+    /// Verify a snapshot and copy it into `staging_dir` ready to restore
+    ///
+    /// The staged database is migrated to the current schema and decrypted
+    /// in full with this vault's keys, so a snapshot the current key
+    /// material cannot read is rejected before anything is restored. The
+    /// snapshot directory itself is left untouched.
+    pub async fn stage_snapshot(
+        &self,
+        snapshot_dir: &Path,
+        staging_dir: &Path,
+    ) -> Result<StagedSnapshot> {
+        let manifest = verify_snapshot(snapshot_dir)?;
+
+        if staging_dir.exists() {
+            fs::remove_dir_all(staging_dir).map_err(|e| {
+                mimir_core::MimirError::Database(anyhow::anyhow!(
+                    "Failed to clear staging directory: {}",
+                    e
+                ))
+            })?;
+        }
+        fs::create_dir_all(staging_dir).map_err(|e| {
+            mimir_core::MimirError::Database(anyhow::anyhow!(
+                "Failed to create staging directory: {}",
+                e
+            ))
+        })?;
+
+        let mut staged = StagedSnapshot {
+            dir: staging_dir.to_path_buf(),
+            manifest,
+            counts: VaultCounts::default(),
+        };
+        match self.prepare_staged(snapshot_dir, &mut staged) {
+            Ok(()) => Ok(staged),
+            Err(e) => {
+                let _ = staged.discard();
+                Err(e)
+            }
+        }
+    }
+
+    fn prepare_staged(&self, snapshot_dir: &Path, staged: &mut StagedSnapshot) -> Result<()> {
+        // Vector files left in older snapshots are not restored
+        for file in &staged.manifest.files {
+            if file.kind != SnapshotFileKind::Database {
+                continue;
+            }
+            let target = staged.dir.join(SNAPSHOT_DATABASE_FILE);
+            fs::copy(snapshot_dir.join(&file.name), target).map_err(|e| {
+                mimir_core::MimirError::Database(anyhow::anyhow!(
+                    "Failed to stage {}: {}",
+                    file.name,
+                    e
+                ))
+            })?;
+        }
+
+        let db_path = staged.database_path();
+        let mut conn = Connection::open(&db_path).map_err(|e| {
+            mimir_core::MimirError::Database(anyhow::anyhow!(
+                "Failed to open snapshot database: {}",
+                e
+            ))
+        })?;
+        apply_key(&conn, &self.crypto_manager).map_err(|e| {
+            mimir_core::MimirError::Database(anyhow::anyhow!(
+                "Snapshot does not open with the current vault key: {}",
+                e
+            ))
+        })?;
+        let applied = migrations::run_migrations(&mut conn, &db_path, &self.crypto_manager)?;
+        if applied.contains(&migrations::KEYWORD_INDEX_VERSION) {
+            keyword::rebuild_index(&mut conn, &self.crypto_manager)?;
+        }
+
+        staged.counts = count_memories(&conn, &self.crypto_manager)?;
+        Ok(())
+    }
+
+    /// Copy one class or one memory from a staged snapshot into this vault
+    ///
+    /// Restored rows replace the current ones, which are kept as revisions
+    /// first; trash state, keyword entries and relations between restored
+    /// and existing memories come along. Memories created since the snapshot
+    /// are left alone. Returns the restored IDs; their vectors are not part
+    /// of the copy.
+    pub async fn restore_from_snapshot(
+        &self,
+        staged: &StagedSnapshot,
+        scope: &RestoreScope,
+    ) -> Result<Vec<MemoryId>> {
+        let mut conn = self.conn.lock().await;
+        let db_key = format!("x'{}'", hex::encode(self.crypto_manager.get_db_key_bytes()));
+        conn.execute(
+            "ATTACH DATABASE ?1 AS snapshot KEY ?2",
+            params![staged.database_path().to_string_lossy(), db_key],
+        )
+        .map_err(|e| {
+            mimir_core::MimirError::Database(anyhow::anyhow!(
+                "Failed to attach snapshot database: {}",
+                e
+            ))
+        })?;
+
+        let result = copy_from_attached(&mut conn, scope);
+        let detached = conn.execute("DETACH DATABASE snapshot", []).map_err(|e| {
+            mimir_core::MimirError::Database(anyhow::anyhow!(
+                "Failed to detach snapshot database: {}",
+                e
+            ))
+        });
+        let ids = result?;
+        detached?;
+
+        ids.iter()
+            .map(|id| {
+                uuid::Uuid::parse_str(id).map_err(|e| {
+                    mimir_core::MimirError::Database(anyhow::anyhow!("Invalid UUID: {}", e))
+                })
+            })
+            .collect()
+    }
+
+    /// Replace the whole vault with a staged snapshot and reopen it
+    ///
+    /// `db_path` is this database's file and `vector_files` the files the
+    /// vector store saves into. The current database and vector files are
+    /// moved aside while the staged database is put in place, then the vault
+    /// is reopened with the same keys and counted. Only when the counts match
+    /// the staged snapshot are the old files deleted; otherwise they are
+    /// moved back and the error returned. The vector store is left empty, to
+    /// be rebuilt from the embeddings stored in the restored database.
+    pub async fn restore_snapshot(
+        self,
+        staged: StagedSnapshot,
+        db_path: &Path,
+        vector_files: &[PathBuf],
+    ) -> Result<Database> {
+        let Database {
+            conn,
+            readers,
+            crypto_manager,
+        } = self;
+        // Close the current database before its file is moved
+        drop(readers);
+        drop(conn);
+
+        let vault_dir = db_path.parent().unwrap_or_else(|| Path::new("."));
+        let aside_dir = vault_dir.join(format!(
+            ".pre-restore-{}",
+            chrono::Utc::now().format("%Y%m%dT%H%M%S%.3fZ")
+        ));
+        fs::create_dir_all(&aside_dir).map_err(|e| {
+            mimir_core::MimirError::Database(anyhow::anyhow!(
+                "Failed to create directory for the current vault: {}",
+                e
+            ))
+        })?;
+
+        let mut swap = VaultSwap {
+            aside_dir,
+            set_aside: Vec::new(),
+            installed: Vec::new(),
+        };
+        let result = match swap_in(&staged, db_path, vector_files, &mut swap) {
+            Ok(()) => reopen_restored(db_path, crypto_manager, &staged.counts).await,
+            Err(e) => Err(e),
+        };
+
+        match result {
+            Ok(db) => {
+                fs::remove_dir_all(&swap.aside_dir).map_err(|e| {
+                    mimir_core::MimirError::Database(anyhow::anyhow!(
+                        "Vault restored but the previous files in {} could not be removed: {}",
+                        swap.aside_dir.display(),
+                        e
+                    ))
+                })?;
+                let _ = staged.discard();
+                Ok(db)
+            }
+            Err(e) => {
+                let aside_dir = swap.aside_dir.clone();
+                swap.rollback().map_err(|rollback_err| {
+                    mimir_core::MimirError::Database(anyhow::anyhow!(
+                        "Restore failed: {} (rolling back also failed: {}; previous vault files are in {})",
+                        e,
+                        rollback_err,
+                        aside_dir.display()
+                    ))
+                })?;
+                Err(e)
+            }
+        }
+    }
+}
+
+/// Open the swapped-in database and check it holds what was staged
+async fn reopen_restored(
+    db_path: &Path,
+    crypto_manager: Arc<CryptoManager>,
+    expected: &VaultCounts,
+) -> Result<Database> {
+    let db = Database::with_crypto_manager(db_path, crypto_manager)?;
+    let counts = db.vault_counts().await?;
+    if &counts != expected {
+        return Err(mimir_core::MimirError::Database(anyhow::anyhow!(
+            "Restored vault holds {} memories, expected {}",
+            counts.memories,
+            expected.memories
+        )));
+    }
+    Ok(db)
+}
+
+/// Move the current vault files aside and the staged files into place
+fn swap_in(
+    staged: &StagedSnapshot,
+    db_path: &Path,
+    vector_files: &[PathBuf],
+    swap: &mut VaultSwap,
+) -> Result<()> {
+    let db_name = db_path.file_name().ok_or_else(|| {
+        mimir_core::MimirError::Database(anyhow::anyhow!(
+            "Invalid database path: {}",
+            db_path.display()
+        ))
+    })?;
+
+    let mut current = vec![db_path.to_path_buf()];
+    for suffix in DATABASE_SIDECARS {
+        let mut name = db_name.to_os_string();
+        name.push(suffix);
+        current.push(db_path.with_file_name(name));
+    }
+    current.extend(vector_files.iter().cloned());
+
+    for (index, path) in current.iter().enumerate() {
+        // Prefix with the position so equal names from different directories cannot collide
+        let name = path.file_name().unwrap_or_default().to_string_lossy();
+        let aside = swap.aside_dir.join(format!("{}-{}", index, name));
+        move_if_exists(path, &aside, &mut swap.set_aside)?;
+    }
+
+    move_if_exists(&staged.database_path(), db_path, &mut swap.installed)
+}
+
+/// Copy the rows selected by `scope` from the attached `snapshot` database
+///
+/// Returns the restored IDs.
+fn copy_from_attached(conn: &mut Connection, scope: &RestoreScope) -> Result<Vec<String>> {
+    let tx = conn.transaction().map_err(|e| {
+        mimir_core::MimirError::Database(anyhow::anyhow!("Failed to begin transaction: {}", e))
+    })?;
+
+    let ids: Vec<String> = {
+        let (sql, value) = match scope {
+            RestoreScope::Class(class) => (
+                "SELECT id FROM snapshot.memory WHERE class_id = ?1 ORDER BY id",
+                class.as_str().to_string(),
+            ),
+            RestoreScope::Memory(id) => (
+                "SELECT id FROM snapshot.memory WHERE id = ?1",
+                id.to_string(),
+            ),
+        };
+        tx.prepare(sql)
+            .and_then(|mut stmt| {
+                stmt.query_map(params![value], |row| row.get(0))?
+                    .collect::<rusqlite::Result<Vec<_>>>()
+            })
+            .map_err(|e| {
+                mimir_core::MimirError::Database(anyhow::anyhow!(
+                    "Failed to read snapshot memories: {}",
+                    e
+                ))
+            })?
+    };
+    if let (RestoreScope::Memory(id), true) = (scope, ids.is_empty()) {
+        return Err(mimir_core::MimirError::Database(anyhow::anyhow!(
+            "Memory {} is not in the snapshot",
+            id
+        )));
+    }
+
+    let now = chrono::Utc::now().timestamp();
+    for id in &ids {
+        revisions::snapshot_revision(&tx, id, now)?;
+        copy_memory(&tx, id).map_err(|e| {
+            mimir_core::MimirError::Database(anyhow::anyhow!(
+                "Failed to restore memory {}: {}",
+                id,
+                e
+            ))
+        })?;
+    }
+    // Relations only once every restored row is back, and only to memories that exist
+    for id in &ids {
+        tx.execute(
+            "INSERT OR IGNORE INTO main.memory_relation
+             SELECT * FROM snapshot.memory_relation
+             WHERE (source_id = ?1 OR target_id = ?1)
+               AND source_id IN (SELECT id FROM main.memory)
+               AND target_id IN (SELECT id FROM main.memory)",
+            params![id],
+        )
+        .map_err(|e| {
+            mimir_core::MimirError::Database(anyhow::anyhow!(
+                "Failed to restore relations of memory {}: {}",
+                id,
+                e
+            ))
+        })?;
+    }
+
+    tx.commit().map_err(|e| {
+        mimir_core::MimirError::Database(anyhow::anyhow!("Failed to commit transaction: {}", e))
+    })?;
+    Ok(ids)
+}
+
+/// Replace a memory row, its keyword entries and attachments with the snapshot's
+fn copy_memory(conn: &Connection, id: &str) -> rusqlite::Result<()> {
+    conn.execute(
+        &format!(
+            "INSERT OR REPLACE INTO main.memory ({columns}, deleted_ts, key_version)
+             SELECT {columns}, deleted_ts, key_version FROM snapshot.memory WHERE id = ?1",
+            columns = MEMORY_COLUMNS
+        ),
+        params![id],
+    )?;
+    conn.execute(
+        "DELETE FROM main.memory_keyword WHERE memory_id = ?1",
+        params![id],
+    )?;
+    conn.execute(
+        "INSERT INTO main.memory_keyword SELECT * FROM snapshot.memory_keyword WHERE memory_id = ?1",
+        params![id],
+    )?;
+    conn.execute(
+        "DELETE FROM main.memory_attachment WHERE memory_id = ?1",
+        params![id],
+    )?;
+    conn.execute(
+        "INSERT INTO main.memory_attachment
+         SELECT * FROM snapshot.memory_attachment WHERE memory_id = ?1",
+        params![id],
+    )?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mimir_core::test_utils::MemoryBuilder;
+    use tempfile::TempDir;
+
+    fn open_database(dir: &Path) -> Database {
+        let crypto_manager =
+            CryptoManager::with_password(dir.join("keyset.json"), "test-password").unwrap();
+        Database::with_crypto_manager(dir.join("test.db"), crypto_manager).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_verify_snapshot_detects_tampering() {
+        let temp_dir = TempDir::new().unwrap();
+        let db = open_database(temp_dir.path());
+        db.store_memory(&MemoryBuilder::new().with_content("Kept").build())
+            .await
+            .unwrap();
+        let snapshot = db
+            .create_snapshot(
+                &temp_dir.path().join("backups"),
+                &temp_dir.path().join("keyset.json"),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(verify_snapshot(&snapshot).unwrap().memory_count, 1);
+
+        let keyset = snapshot.join(crate::backup::SNAPSHOT_KEYSET_FILE);
+        let mut data = fs::read(&keyset).unwrap();
+        data.push(b' ');
+        fs::write(&keyset, data).unwrap();
+        assert!(verify_snapshot(&snapshot).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_stage_snapshot_requires_current_keys() {
+        let temp_dir = TempDir::new().unwrap();
+        let db = open_database(temp_dir.path());
+        db.store_memory(&MemoryBuilder::new().with_content("Secret").build())
+            .await
+            .unwrap();
+        let snapshot = db
+            .create_snapshot(
+                &temp_dir.path().join("backups"),
+                &temp_dir.path().join("keyset.json"),
+            )
+            .await
+            .unwrap();
+
+        let staged = db
+            .stage_snapshot(&snapshot, &temp_dir.path().join("staging"))
+            .await
+            .unwrap();
+        assert_eq!(staged.counts, db.vault_counts().await.unwrap());
+        staged.discard().unwrap();
+
+        // A vault with a different password cannot read the snapshot
+        let other_dir = TempDir::new().unwrap();
+        let crypto_manager =
+            CryptoManager::with_password(other_dir.path().join("keyset.json"), "other-password")
+                .unwrap();
+        let other = Database::with_crypto_manager(other_dir.path().join("test.db"), crypto_manager)
+            .unwrap();
+        let staging = other_dir.path().join("staging");
+        assert!(other.stage_snapshot(&snapshot, &staging).await.is_err());
+        assert!(!staging.exists());
+    }
+
+    #[tokio::test]
+    async fn test_partial_restore() {
+        let temp_dir = TempDir::new().unwrap();
+        let db = open_database(temp_dir.path());
+
+        let mut work = MemoryBuilder::new()
+            .with_content("Quarterly plan")
+            .with_class(MemoryClass::Work)
+            .build();
+        let personal = MemoryBuilder::new()
+            .with_content("Birthday list")
+            .with_class(MemoryClass::Personal)
+            .build();
+        db.store_memories(&[work.clone(), personal.clone()])
+            .await
+            .unwrap();
+        let snapshot = db
+            .create_snapshot(
+                &temp_dir.path().join("backups"),
+                &temp_dir.path().join("keyset.json"),
+            )
+            .await
+            .unwrap();
+
+        work.content = "Quarterly plan, revised".to_string();
+        db.update_memory(&work).await.unwrap();
+        db.delete_memory(personal.id).await.unwrap();
+        let newer = MemoryBuilder::new()
+            .with_content("Added later")
+            .with_class(MemoryClass::Work)
+            .build();
+        db.store_memory(&newer).await.unwrap();
+
+        let staged = db
+            .stage_snapshot(&snapshot, &temp_dir.path().join("staging"))
+            .await
+            .unwrap();
+
+        let restored = db
+            .restore_from_snapshot(&staged, &RestoreScope::Class(MemoryClass::Work))
+            .await
+            .unwrap();
+        assert_eq!(restored, vec![work.id]);
+        assert_eq!(
+            db.get_memory(work.id).await.unwrap().unwrap().content,
+            "Quarterly plan"
+        );
+        // The replaced version stays in the history and later memories stay put
+        assert_eq!(db.list_revisions(work.id).await.unwrap().len(), 2);
+        assert!(db.get_memory(newer.id).await.unwrap().is_some());
+        assert!(db.get_memory(personal.id).await.unwrap().is_none());
+
+        let restored = db
+            .restore_from_snapshot(&staged, &RestoreScope::Memory(personal.id))
+            .await
+            .unwrap();
+        assert_eq!(restored, vec![personal.id]);
+        assert_eq!(
+            db.keyword_search("birthday", 10).await.unwrap(),
+            vec![personal.id]
+        );
+
+        assert!(db
+            .restore_from_snapshot(&staged, &RestoreScope::Memory(newer.id))
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_full_restore_swaps_vault() {
+        let temp_dir = TempDir::new().unwrap();
+        let db = open_database(temp_dir.path());
+        let db_path = temp_dir.path().join("test.db");
+        let vector_file = temp_dir.path().join("vector_store_index.bin");
+
+        let kept = MemoryBuilder::new().with_content("Before").build();
+        db.store_memory(&kept).await.unwrap();
+        fs::write(&vector_file, b"old vectors").unwrap();
+        let snapshot = db
+            .create_snapshot(
+                &temp_dir.path().join("backups"),
+                &temp_dir.path().join("keyset.json"),
+            )
+            .await
+            .unwrap();
+
+        let lost = MemoryBuilder::new().with_content("After").build();
+        db.store_memory(&lost).await.unwrap();
+        fs::write(&vector_file, b"new vectors").unwrap();
+
+        let staged = db
+            .stage_snapshot(&snapshot, &temp_dir.path().join("staging"))
+            .await
+            .unwrap();
+        let db = db
+            .restore_snapshot(staged, &db_path, std::slice::from_ref(&vector_file))
+            .await
+            .unwrap();
+
+        assert!(db.get_memory(kept.id).await.unwrap().is_some());
+        assert!(db.get_memory(lost.id).await.unwrap().is_none());
+        // The vectors, which may not match the snapshot, are left to be rebuilt
+        assert!(!vector_file.exists());
+
+        // Nothing is left aside once the restore succeeded
+        let leftovers: Vec<_> = fs::read_dir(temp_dir.path())
+            .unwrap()
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| {
+                let name = entry.file_name().to_string_lossy().to_string();
+                name.starts_with(".pre-restore-") || name == "staging"
+            })
+            .collect();
+        assert!(leftovers.is_empty());
+    }
+}
@@ -0,0 +1,288 @@
+//! Memory revision history
+//!
+//! `Database::update_memory` copies the row it replaces into `memory_revision`
+//! before writing the new version. Revisions keep the original ciphertext and
+//! class, so they stay encrypted under the class key they were written with.
+
+use crate::{decode_memory, Database, MemoryRow};
+use mimir_core::{Memory, MemoryId, Result};
+use rusqlite::{params, Connection};
+
+/// Columns selected for revision reads: the memory columns, then revision metadata
+const REVISION_COLUMNS: &str = "memory_id, source, class_id, text_enc, vec_id, ts, updated_ts, \
+     key_id, scope_enc, tags_enc, acl_enc, expires_ts, embedding_enc, revision, superseded_ts";
+
+/// A prior version of a memory
+#[derive(Debug, Clone)]
+pub struct MemoryRevision {
+    /// Revision number, starting at 1 for the first replaced version
+    pub revision: u32,
+    /// When this version was replaced by a newer one
+    pub superseded_at: chrono::DateTime<chrono::Utc>,
+    /// The memory as it was before being replaced
+    pub memory: Memory,
+}
+
+/// Copy the current row of a memory into `memory_revision`
+///
+/// Does nothing if the memory does not exist yet.
+pub(crate) fn snapshot_revision(
+    conn: &Connection,
+    memory_id: &str,
+    superseded_ts: i64,
+) -> Result<()> {
+    conn.execute(
+        "INSERT INTO memory_revision
+            (memory_id, revision, source, class_id, text_enc, vec_id, ts, updated_ts,
+             key_id, scope_enc, tags_enc, acl_enc, expires_ts, embedding_enc, key_version,
+             superseded_ts)
+         SELECT id,
+                COALESCE((SELECT MAX(revision) FROM memory_revision WHERE memory_id = ?1), 0) + 1,
+                source, class_id, text_enc, vec_id, ts, updated_ts,
+                key_id, scope_enc, tags_enc, acl_enc, expires_ts, embedding_enc, key_version, ?2
+         FROM memory WHERE id = ?1",
+        params![memory_id, superseded_ts],
+    )
+    .map_err(|e| {
+        mimir_core::MimirError::Database(anyhow::anyhow!("Failed to store memory revision: {}", e))
+    })?;
+    Ok(())
+}
+
+impl Database {
+    /// List the prior revisions of a memory, oldest first
+    pub async fn list_revisions(&self, id: MemoryId) -> Result<Vec<MemoryRevision>> {
+        self.query_revisions(
+            &format!(
+                "SELECT {} FROM memory_revision WHERE memory_id = ?1 ORDER BY revision ASC",
+                REVISION_COLUMNS
+            ),
+            params![id.to_string()],
+        )
+        .await
+    }
+
+    /// Get a single prior revision of a memory
+    pub async fn get_revision(
+        &self,
+        id: MemoryId,
+        revision: u32,
+    ) -> Result<Option<MemoryRevision>> {
+        let mut revisions = self
+            .query_revisions(
+                &format!(
+                    "SELECT {} FROM memory_revision WHERE memory_id = ?1 AND revision = ?2",
+                    REVISION_COLUMNS
+                ),
+                params![id.to_string(), revision],
+            )
+            .await?;
+        Ok(revisions.pop())
+    }
+
+    /// Read a memory as it was at a point in time
+    ///
+    /// Returns `None` if the memory did not exist yet at `at` (or is now in
+    /// the trash).
+    pub async fn get_memory_as_of(
+        &self,
+        id: MemoryId,
+        at: chrono::DateTime<chrono::Utc>,
+    ) -> Result<Option<Memory>> {
+        let at_ts = at.timestamp();
+
+        match self.get_memory(id).await? {
+            Some(current) if current.updated_at.timestamp() <= at_ts => return Ok(Some(current)),
+            Some(_) => {}
+            None => return Ok(None),
+        }
+
+        let mut revisions = self
+            .query_revisions(
+                &format!(
+                    "SELECT {} FROM memory_revision
+                     WHERE memory_id = ?1 AND COALESCE(updated_ts, ts) <= ?2
+                     ORDER BY revision DESC LIMIT 1",
+                    REVISION_COLUMNS
+                ),
+                params![id.to_string(), at_ts],
+            )
+            .await?;
+        Ok(revisions.pop().map(|revision| revision.memory))
+    }
+
+    /// Restore a memory to an earlier revision
+    ///
+    /// The revert is itself an update: the current version becomes a new
+    /// revision, so a revert can be undone.
+    pub async fn revert_memory(&self, id: MemoryId, revision: u32) -> Result<Memory> {
+        let target = self.get_revision(id, revision).await?.ok_or_else(|| {
+            mimir_core::MimirError::Database(anyhow::anyhow!(
+                "Revision {} of memory {} not found",
+                revision,
+                id
+            ))
+        })?;
+
+        let mut memory = target.memory;
+        memory.updated_at = chrono::Utc::now();
+        self.update_memory(&memory).await?;
+
+        Ok(memory)
+    }
+
+    async fn query_revisions(
+        &self,
+        sql: &str,
+        params: impl rusqlite::Params,
+    ) -> Result<Vec<MemoryRevision>> {
+        let conn = self.reader().await?;
+        let mut stmt = conn.prepare(sql).map_err(|e| {
+            mimir_core::MimirError::Database(anyhow::anyhow!("Failed to prepare query: {}", e))
+        })?;
+
+        let rows = stmt
+            .query_map(params, |row| {
+                Ok((
+                    MemoryRow::from_row(row)?,
+                    row.get::<_, u32>(13)?,
+                    row.get::<_, i64>(14)?,
+                ))
+            })
+            .map_err(|e| {
+                mimir_core::MimirError::Database(anyhow::anyhow!("Failed to execute query: {}", e))
+            })?;
+
+        let mut revisions = Vec::new();
+        for row in rows {
+            let (memory_row, revision, superseded_ts) = row.map_err(|e| {
+                mimir_core::MimirError::Database(anyhow::anyhow!("Failed to read row: {}", e))
+            })?;
+            revisions.push(MemoryRevision {
+                revision,
+                superseded_at: crate::timestamp_to_datetime(superseded_ts)?,
+                memory: decode_memory(&self.crypto_manager, memory_row)?,
+            });
+        }
+
+        Ok(revisions)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mimir_core::crypto::CryptoManager;
+    use mimir_core::test_utils::MemoryBuilder;
+    use tempfile::TempDir;
+
+    fn create_test_database() -> (Database, TempDir) {
+        let temp_dir = TempDir::new().unwrap();
+        let crypto_manager =
+            CryptoManager::with_password(temp_dir.path().join("keyset.json"), "test-password")
+                .unwrap();
+        let db =
+            Database::with_crypto_manager(temp_dir.path().join("test.db"), crypto_manager).unwrap();
+        (db, temp_dir)
+    }
+
+    #[tokio::test]
+    async fn test_update_keeps_prior_revision() {
+        let (db, _temp_dir) = create_test_database();
+
+        let mut memory = MemoryBuilder::new().with_content("Original").build();
+        db.store_memory(&memory).await.unwrap();
+        assert!(db.list_revisions(memory.id).await.unwrap().is_empty());
+
+        memory.content = "Changed".to_string();
+        db.update_memory(&memory).await.unwrap();
+
+        let revisions = db.list_revisions(memory.id).await.unwrap();
+        assert_eq!(revisions.len(), 1);
+        assert_eq!(revisions[0].revision, 1);
+        assert_eq!(revisions[0].memory.content, "Original");
+        assert_eq!(
+            db.get_memory(memory.id).await.unwrap().unwrap().content,
+            "Changed"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_get_memory_as_of() {
+        let (db, _temp_dir) = create_test_database();
+        let now = chrono::Utc::now();
+
+        let mut memory = MemoryBuilder::new().with_content("First").build();
+        memory.created_at = now - chrono::Duration::seconds(100);
+        memory.updated_at = memory.created_at;
+        db.store_memory(&memory).await.unwrap();
+
+        memory.content = "Second".to_string();
+        memory.updated_at = now - chrono::Duration::seconds(50);
+        db.update_memory(&memory).await.unwrap();
+
+        memory.content = "Third".to_string();
+        memory.updated_at = now;
+        db.update_memory(&memory).await.unwrap();
+
+        let at = |secs: i64| now - chrono::Duration::seconds(secs);
+        assert!(db
+            .get_memory_as_of(memory.id, at(200))
+            .await
+            .unwrap()
+            .is_none());
+        assert_eq!(
+            db.get_memory_as_of(memory.id, at(75))
+                .await
+                .unwrap()
+                .unwrap()
+                .content,
+            "First"
+        );
+        assert_eq!(
+            db.get_memory_as_of(memory.id, at(25))
+                .await
+                .unwrap()
+                .unwrap()
+                .content,
+            "Second"
+        );
+        assert_eq!(
+            db.get_memory_as_of(memory.id, now)
+                .await
+                .unwrap()
+                .unwrap()
+                .content,
+            "Third"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_revert_memory() {
+        let (db, _temp_dir) = create_test_database();
+
+        let mut memory = MemoryBuilder::new()
+            .with_content("Good fact")
+            .with_tags(vec!["verified".to_string()])
+            .build();
+        db.store_memory(&memory).await.unwrap();
+
+        memory.content = "Bad fact".to_string();
+        memory.tags = vec![];
+        db.update_memory(&memory).await.unwrap();
+
+        let reverted = db.revert_memory(memory.id, 1).await.unwrap();
+        assert_eq!(reverted.content, "Good fact");
+
+        let current = db.get_memory(memory.id).await.unwrap().unwrap();
+        assert_eq!(current.content, "Good fact");
+        assert_eq!(current.tags, vec!["verified".to_string()]);
+
+        // The bad version is kept as a revision, so the revert can be undone
+        let revisions = db.list_revisions(memory.id).await.unwrap();
+        assert_eq!(revisions.len(), 2);
+        assert_eq!(revisions[1].memory.content, "Bad fact");
+
+        assert!(db.revert_memory(memory.id, 99).await.is_err());
+    }
+}
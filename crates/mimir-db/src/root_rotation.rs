@@ -0,0 +1,185 @@
+//! Root key rotation
+//!
+//! The SQLCipher key and the class keys all hang off the root key. Rotating
+//! it rekeys the database with `PRAGMA rekey` and hands the classes that
+//! still have data to the crypto manager, which pins their keys in the
+//! keyset wrapped under the new root, so no row needs re-encrypting.
+//! `rotate_class_key` can replace a pinned key afterwards.
+//!
+//! The crypto manager journals the new root key before anything changes.
+//! Running `rotate_root_key` again after a crash resumes from wherever the
+//! rotation stopped: a database that already opens under the new key is
+//! not rekeyed twice. Snapshots taken before the rotation stay encrypted
+//! under the old root key.
+//!
+//! Vector stores saved with vector rotation are not supported: their rotation
+//! matrix is derived from the root key, and nothing re-rotates them under the
+//! new one. `mimir-cli rotate-root` refuses to start while the saved store is
+//! marked rotated. The daemon saves its stores unrotated, so they need no
+//! change.
+
+use crate::apply_db_key;
+use mimir_core::{crypto::CryptoManager, Result};
+use rusqlite::Connection;
+use std::path::Path;
+
+/// What a root key rotation did
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RootRotationReport {
+    /// Whether this run rekeyed the database, rather than an interrupted one
+    pub rekeyed: bool,
+    /// Classes whose keys were re-wrapped under the new root key
+    pub classes: Vec<String>,
+}
+
+/// Open a database if `db_key` is its key
+fn open_with_key(db_path: &Path, db_key: &[u8; 32]) -> Result<Option<Connection>> {
+    let conn = Connection::open_with_flags(db_path, rusqlite::OpenFlags::SQLITE_OPEN_READ_WRITE)
+        .map_err(|e| {
+            mimir_core::MimirError::Database(anyhow::anyhow!("Failed to open database: {}", e))
+        })?;
+    Ok(apply_db_key(&conn, db_key).is_ok().then_some(conn))
+}
+
+/// Re-encrypt every page of the database under a new key
+fn rekey(conn: &Connection, db_key: &[u8; 32]) -> rusqlite::Result<()> {
+    // Fold the WAL into the main file first so no page is left under the old key
+    conn.execute_batch(&format!(
+        "PRAGMA wal_checkpoint(TRUNCATE); PRAGMA rekey = \"x'{}'\";",
+        hex::encode(db_key)
+    ))
+}
+
+/// Every class with data in the database, trash and history included
+fn classes_in_use(conn: &Connection) -> rusqlite::Result<Vec<String>> {
+    conn.prepare(
+        "SELECT class_id FROM memory
+         UNION SELECT class_id FROM memory_revision
+         UNION SELECT class_id FROM memory_relation
+         UNION SELECT class_id FROM memory_keyword
+         UNION SELECT class_id FROM memory_attachment",
+    )?
+    .query_map([], |row| row.get::<_, String>(0))?
+    .collect()
+}
+
+/// Rotate the root key of the vault whose database is at `db_path`
+///
+/// Nothing else may have the database open. On success the crypto manager
+/// holds the new root key and the keyset and keychain are updated.
+pub fn rotate_root_key<P: AsRef<Path>>(
+    db_path: P,
+    crypto_manager: &mut CryptoManager,
+) -> Result<RootRotationReport> {
+    let db_path = db_path.as_ref();
+    let database_exists = db_path.exists();
+
+    // A PBKDF2 keyset has to move to Argon2id before its root key can be re-wrapped
+    if crypto_manager.needs_kdf_upgrade() && database_exists {
+        open_with_key(db_path, &crypto_manager.get_db_key_bytes())?
+            .ok_or(mimir_core::MimirError::WrongPassword)?;
+        crypto_manager.complete_kdf_upgrade()?;
+    }
+
+    let rotation = crypto_manager.begin_root_rotation()?;
+    let mut report = RootRotationReport {
+        rekeyed: false,
+        classes: Vec::new(),
+    };
+    if database_exists {
+        let conn = match open_with_key(db_path, &rotation.new_db_key)? {
+            Some(conn) => conn,
+            None => {
+                let conn = open_with_key(db_path, &rotation.old_db_key)?.ok_or_else(|| {
+                    mimir_core::MimirError::Database(anyhow::anyhow!(
+                        "Neither the old nor the new root key opens the database"
+                    ))
+                })?;
+                rekey(&conn, &rotation.new_db_key).map_err(|e| {
+                    mimir_core::MimirError::Database(anyhow::anyhow!(
+                        "Failed to rekey database: {}",
+                        e
+                    ))
+                })?;
+                report.rekeyed = true;
+                conn
+            }
+        };
+        report.classes = classes_in_use(&conn).map_err(|e| {
+            mimir_core::MimirError::Database(anyhow::anyhow!(
+                "Failed to read classes in use: {}",
+                e
+            ))
+        })?;
+    }
+
+    crypto_manager.finish_root_rotation(&report.classes)?;
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Database;
+    use mimir_core::test_utils::MemoryBuilder;
+    use mimir_core::MemoryClass;
+    use tempfile::TempDir;
+
+    #[tokio::test]
+    async fn test_rotate_root_key() {
+        let temp_dir = TempDir::new().unwrap();
+        let keyset_path = temp_dir.path().join("keyset.json");
+        let db_path = temp_dir.path().join("test.db");
+
+        let crypto_manager = CryptoManager::with_password(&keyset_path, "test-password").unwrap();
+        let old_db_key = crypto_manager.get_db_key_bytes();
+        let db = Database::with_crypto_manager(&db_path, crypto_manager).unwrap();
+        let memory = MemoryBuilder::new()
+            .with_content("Allergic to penicillin")
+            .with_class(MemoryClass::Health)
+            .with_tags(vec!["allergy".to_string()])
+            .build();
+        db.store_memory(&memory).await.unwrap();
+        db.add_attachment(memory.id, "note.txt", "text/plain", b"rash")
+            .await
+            .unwrap();
+        drop(db);
+
+        // An interruption after the rekey leaves the database under the new key
+        let crypto_manager = CryptoManager::with_password(&keyset_path, "test-password").unwrap();
+        let rotation = crypto_manager.begin_root_rotation().unwrap();
+        let conn = open_with_key(&db_path, &rotation.old_db_key)
+            .unwrap()
+            .unwrap();
+        rekey(&conn, &rotation.new_db_key).unwrap();
+        drop(conn);
+        assert!(open_with_key(&db_path, &old_db_key).unwrap().is_none());
+        assert!(Database::with_crypto_manager(&db_path, crypto_manager).is_err());
+
+        // Running the rotation again finishes it without a second rekey
+        let mut crypto_manager =
+            CryptoManager::with_password(&keyset_path, "test-password").unwrap();
+        let report = rotate_root_key(&db_path, &mut crypto_manager).unwrap();
+        assert!(!report.rekeyed);
+        assert_eq!(report.classes, vec!["health".to_string()]);
+        assert_eq!(crypto_manager.get_db_key_bytes(), *rotation.new_db_key);
+
+        // A fresh rotation rekeys, and the data stays readable throughout
+        let mut crypto_manager =
+            CryptoManager::with_password(&keyset_path, "test-password").unwrap();
+        let report = rotate_root_key(&db_path, &mut crypto_manager).unwrap();
+        assert!(report.rekeyed);
+        assert!(open_with_key(&db_path, &rotation.new_db_key)
+            .unwrap()
+            .is_none());
+
+        let crypto_manager = CryptoManager::with_password(&keyset_path, "test-password").unwrap();
+        let db = Database::with_crypto_manager(&db_path, crypto_manager).unwrap();
+        let stored = db.get_memory(memory.id).await.unwrap().unwrap();
+        assert_eq!(stored.content, "Allergic to penicillin");
+        assert_eq!(stored.tags, vec!["allergy".to_string()]);
+        let attachments = db.list_attachments(memory.id).await.unwrap();
+        let attachment = db.get_attachment(attachments[0].id).await.unwrap().unwrap();
+        assert_eq!(attachment.data, b"rash");
+    }
+}
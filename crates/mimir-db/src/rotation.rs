@@ -0,0 +1,528 @@
+//! Class key rotation and re-encryption
+//!
+//! Rotating a class key makes a fresh key current for new writes while the
+//! older versions stay in the keyset for reads. `reencrypt_class_batch` then
+//! moves the class's rows onto the current key a batch at a time: memories
+//! with their blind index entries, revisions, relation labels and
+//! attachments. Every row
+//! records the key version it is encrypted under, so the job can stop at any
+//! point and pick up where it left off. The older keys are retired only once
+//! no row refers to them.
+
+use crate::{attachment, decrypt_blob, encrypt_blob, keyword, Database};
+use mimir_core::{crypto::CryptoManager, Result};
+use rusqlite::{params, Connection};
+
+/// Rows re-encrypted per batch unless the caller picks another size
+pub const DEFAULT_REENCRYPTION_BATCH: usize = 100;
+
+/// Where re-encrypting a class stands after a batch
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReencryptionProgress {
+    /// Key version rows are being moved to
+    pub key_version: u32,
+    /// Rows re-encrypted by this batch
+    pub reencrypted: usize,
+    /// Rows still under an older key version
+    pub remaining: usize,
+    /// Key versions retired because nothing used them any more
+    pub retired_versions: Vec<u32>,
+}
+
+impl ReencryptionProgress {
+    /// Whether every row is under the current key
+    pub fn is_complete(&self) -> bool {
+        self.remaining == 0
+    }
+}
+
+/// A nullable encrypted column
+type OptionalBlob = Option<Vec<u8>>;
+
+/// Decrypt a stored blob under whichever key version it uses and encrypt it under the current one
+fn reencrypt_blob(
+    crypto_manager: &CryptoManager,
+    class_id: &str,
+    data: OptionalBlob,
+) -> Result<OptionalBlob> {
+    data.map(|data| {
+        let plaintext = decrypt_blob(crypto_manager, class_id, &data)?;
+        encrypt_blob(crypto_manager, class_id, &plaintext)
+    })
+    .transpose()
+}
+
+/// Count the rows of a class not yet under `version`
+fn count_pending(conn: &Connection, class_id: &str, version: u32) -> Result<usize> {
+    conn.query_row(
+        "SELECT (SELECT COUNT(*) FROM memory WHERE class_id = ?1 AND key_version != ?2)
+              + (SELECT COUNT(*) FROM memory_revision WHERE class_id = ?1 AND key_version != ?2)
+              + (SELECT COUNT(*) FROM memory_relation WHERE class_id = ?1 AND key_version != ?2)
+              + (SELECT COUNT(*) FROM memory_attachment WHERE class_id = ?1 AND key_version != ?2)",
+        params![class_id, version],
+        |row| row.get::<_, i64>(0),
+    )
+    .map(|count| count as usize)
+    .map_err(|e| {
+        mimir_core::MimirError::Database(anyhow::anyhow!("Failed to count pending rows: {}", e))
+    })
+}
+
+/// Re-encrypt up to `limit` memory rows with their keyword entries
+fn reencrypt_memories(
+    conn: &Connection,
+    crypto_manager: &CryptoManager,
+    class_id: &str,
+    version: u32,
+    limit: usize,
+) -> Result<usize> {
+    type Row = (
+        String,
+        Vec<u8>,
+        Option<Vec<u8>>,
+        Option<Vec<u8>>,
+        Option<Vec<u8>>,
+        Option<Vec<u8>>,
+    );
+    let rows: Vec<Row> = conn
+        .prepare(
+            "SELECT id, text_enc, scope_enc, tags_enc, acl_enc, embedding_enc FROM memory
+             WHERE class_id = ?1 AND key_version != ?2 LIMIT ?3",
+        )
+        .and_then(|mut stmt| {
+            stmt.query_map(params![class_id, version, limit as i64], |row| {
+                Ok((
+                    row.get(0)?,
+                    row.get(1)?,
+                    row.get(2)?,
+                    row.get(3)?,
+                    row.get(4)?,
+                    row.get(5)?,
+                ))
+            })?
+            .collect::<rusqlite::Result<Vec<_>>>()
+        })
+        .map_err(|e| {
+            mimir_core::MimirError::Database(anyhow::anyhow!("Failed to read memories: {}", e))
+        })?;
+
+    for (id, text_enc, scope_enc, tags_enc, acl_enc, embedding_enc) in &rows {
+        let content = decrypt_blob(crypto_manager, class_id, text_enc).map_err(|e| {
+            mimir_core::MimirError::Database(anyhow::anyhow!(
+                "Memory {} does not decrypt under any key of class '{}': {}",
+                id,
+                class_id,
+                e
+            ))
+        })?;
+        let text_enc = encrypt_blob(crypto_manager, class_id, &content)?;
+        let scope_enc = reencrypt_blob(crypto_manager, class_id, scope_enc.clone())?;
+        let tags_enc = reencrypt_blob(crypto_manager, class_id, tags_enc.clone())?;
+        let acl_enc = reencrypt_blob(crypto_manager, class_id, acl_enc.clone())?;
+        let embedding_enc = reencrypt_blob(crypto_manager, class_id, embedding_enc.clone())?;
+        let terms =
+            keyword::blind_terms(crypto_manager, class_id, &String::from_utf8_lossy(&content))?;
+
+        conn.execute(
+            "UPDATE memory SET text_enc = ?1, scope_enc = ?2, tags_enc = ?3, acl_enc = ?4,
+                    embedding_enc = ?5, key_version = ?6
+             WHERE id = ?7",
+            params![
+                text_enc,
+                scope_enc,
+                tags_enc,
+                acl_enc,
+                embedding_enc,
+                version,
+                id
+            ],
+        )
+        .and_then(|_| keyword::write_terms(conn, id, class_id, &terms))
+        .map_err(|e| {
+            mimir_core::MimirError::Database(anyhow::anyhow!(
+                "Failed to re-encrypt memory {}: {}",
+                id,
+                e
+            ))
+        })?;
+    }
+    Ok(rows.len())
+}
+
+/// Re-encrypt up to `limit` revision rows
+fn reencrypt_revisions(
+    conn: &Connection,
+    crypto_manager: &CryptoManager,
+    class_id: &str,
+    version: u32,
+    limit: usize,
+) -> Result<usize> {
+    type Row = (
+        String,
+        i64,
+        Vec<u8>,
+        Option<Vec<u8>>,
+        Option<Vec<u8>>,
+        Option<Vec<u8>>,
+        Option<Vec<u8>>,
+    );
+    let rows: Vec<Row> = conn
+        .prepare(
+            "SELECT memory_id, revision, text_enc, scope_enc, tags_enc, acl_enc, embedding_enc
+             FROM memory_revision
+             WHERE class_id = ?1 AND key_version != ?2 LIMIT ?3",
+        )
+        .and_then(|mut stmt| {
+            stmt.query_map(params![class_id, version, limit as i64], |row| {
+                Ok((
+                    row.get(0)?,
+                    row.get(1)?,
+                    row.get(2)?,
+                    row.get(3)?,
+                    row.get(4)?,
+                    row.get(5)?,
+                    row.get(6)?,
+                ))
+            })?
+            .collect::<rusqlite::Result<Vec<_>>>()
+        })
+        .map_err(|e| {
+            mimir_core::MimirError::Database(anyhow::anyhow!("Failed to read revisions: {}", e))
+        })?;
+
+    for (memory_id, revision, text_enc, scope_enc, tags_enc, acl_enc, embedding_enc) in &rows {
+        let text_enc = reencrypt_blob(crypto_manager, class_id, Some(text_enc.clone()))?;
+        let scope_enc = reencrypt_blob(crypto_manager, class_id, scope_enc.clone())?;
+        let tags_enc = reencrypt_blob(crypto_manager, class_id, tags_enc.clone())?;
+        let acl_enc = reencrypt_blob(crypto_manager, class_id, acl_enc.clone())?;
+        let embedding_enc = reencrypt_blob(crypto_manager, class_id, embedding_enc.clone())?;
+
+        conn.execute(
+            "UPDATE memory_revision SET text_enc = ?1, scope_enc = ?2, tags_enc = ?3,
+                    acl_enc = ?4, embedding_enc = ?5, key_version = ?6
+             WHERE memory_id = ?7 AND revision = ?8",
+            params![
+                text_enc,
+                scope_enc,
+                tags_enc,
+                acl_enc,
+                embedding_enc,
+                version,
+                memory_id,
+                revision
+            ],
+        )
+        .map_err(|e| {
+            mimir_core::MimirError::Database(anyhow::anyhow!(
+                "Failed to re-encrypt revision {} of memory {}: {}",
+                revision,
+                memory_id,
+                e
+            ))
+        })?;
+    }
+    Ok(rows.len())
+}
+
+/// Re-encrypt up to `limit` relation labels
+fn reencrypt_relations(
+    conn: &Connection,
+    crypto_manager: &CryptoManager,
+    class_id: &str,
+    version: u32,
+    limit: usize,
+) -> Result<usize> {
+    type Row = (String, String, String, Option<Vec<u8>>);
+    let rows: Vec<Row> = conn
+        .prepare(
+            "SELECT source_id, target_id, kind, label_enc FROM memory_relation
+             WHERE class_id = ?1 AND key_version != ?2 LIMIT ?3",
+        )
+        .and_then(|mut stmt| {
+            stmt.query_map(params![class_id, version, limit as i64], |row| {
+                Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?))
+            })?
+            .collect::<rusqlite::Result<Vec<_>>>()
+        })
+        .map_err(|e| {
+            mimir_core::MimirError::Database(anyhow::anyhow!("Failed to read relations: {}", e))
+        })?;
+
+    for (source_id, target_id, kind, label_enc) in &rows {
+        let label_enc = reencrypt_blob(crypto_manager, class_id, label_enc.clone())?;
+        conn.execute(
+            "UPDATE memory_relation SET label_enc = ?1, key_version = ?2
+             WHERE source_id = ?3 AND target_id = ?4 AND kind = ?5",
+            params![label_enc, version, source_id, target_id, kind],
+        )
+        .map_err(|e| {
+            mimir_core::MimirError::Database(anyhow::anyhow!(
+                "Failed to re-encrypt relation label: {}",
+                e
+            ))
+        })?;
+    }
+    Ok(rows.len())
+}
+
+/// Re-encrypt up to `limit` attachments with their names and MIME types
+fn reencrypt_attachments(
+    conn: &Connection,
+    crypto_manager: &CryptoManager,
+    class_id: &str,
+    version: u32,
+    limit: usize,
+) -> Result<usize> {
+    type Row = (String, Vec<u8>, Vec<u8>, Vec<u8>, Vec<u8>);
+    let rows: Vec<Row> = conn
+        .prepare(
+            "SELECT id, name_enc, mime_type_enc, data_enc, nonce FROM memory_attachment
+             WHERE class_id = ?1 AND key_version != ?2 LIMIT ?3",
+        )
+        .and_then(|mut stmt| {
+            stmt.query_map(params![class_id, version, limit as i64], |row| {
+                Ok((
+                    row.get(0)?,
+                    row.get(1)?,
+                    row.get(2)?,
+                    row.get(3)?,
+                    row.get(4)?,
+                ))
+            })?
+            .collect::<rusqlite::Result<Vec<_>>>()
+        })
+        .map_err(|e| {
+            mimir_core::MimirError::Database(anyhow::anyhow!("Failed to read attachments: {}", e))
+        })?;
+
+    let count = rows.len();
+    for (id, name_enc, mime_type_enc, data_enc, nonce) in rows {
+        let name_enc = reencrypt_blob(crypto_manager, class_id, Some(name_enc))?;
+        let mime_type_enc = reencrypt_blob(crypto_manager, class_id, Some(mime_type_enc))?;
+        let data = attachment::decrypt_data(crypto_manager, class_id, data_enc, nonce)?;
+        let (data_enc, nonce) = attachment::encrypt_data(crypto_manager, class_id, &data)?;
+
+        conn.execute(
+            "UPDATE memory_attachment SET name_enc = ?1, mime_type_enc = ?2, data_enc = ?3,
+                    nonce = ?4, key_version = ?5
+             WHERE id = ?6",
+            params![name_enc, mime_type_enc, data_enc, nonce, version, id],
+        )
+        .map_err(|e| {
+            mimir_core::MimirError::Database(anyhow::anyhow!(
+                "Failed to re-encrypt attachment {}: {}",
+                id,
+                e
+            ))
+        })?;
+    }
+    Ok(count)
+}
+
+impl Database {
+    /// Version of the key a class is currently encrypted under
+    pub fn class_key_version(&self, class_id: &str) -> u32 {
+        self.crypto_manager.class_key_version(class_id)
+    }
+
+    /// Rotate a class key, returning the new version
+    ///
+    /// Existing rows stay readable under their old key until
+    /// `reencrypt_class_batch` has moved them all to the new one.
+    pub fn rotate_class_key(&self, class_id: &str) -> Result<u32> {
+        self.crypto_manager.rotate_class_key(class_id)
+    }
+
+    /// Rows of a class still encrypted under an older key version
+    pub async fn pending_reencryption(&self, class_id: &str) -> Result<usize> {
+        let conn = self.reader().await?;
+        count_pending(
+            &conn,
+            class_id,
+            self.crypto_manager.class_key_version(class_id),
+        )
+    }
+
+    /// Re-encrypt up to `batch_size` rows of a class under its current key
+    ///
+    /// Each batch commits on its own, so an interrupted job loses at most one
+    /// batch of work and calling this again resumes it. Once no row is left
+    /// under an older version, the older keys are retired.
+    pub async fn reencrypt_class_batch(
+        &self,
+        class_id: &str,
+        batch_size: usize,
+    ) -> Result<ReencryptionProgress> {
+        let version = self.crypto_manager.class_key_version(class_id);
+        let mut conn = self.conn.lock().await;
+
+        let tx = conn.transaction().map_err(|e| {
+            mimir_core::MimirError::Database(anyhow::anyhow!("Failed to begin transaction: {}", e))
+        })?;
+        let mut reencrypted = 0;
+        for step in [
+            reencrypt_memories,
+            reencrypt_revisions,
+            reencrypt_relations,
+            reencrypt_attachments,
+        ] {
+            if reencrypted >= batch_size {
+                break;
+            }
+            reencrypted += step(
+                &tx,
+                &self.crypto_manager,
+                class_id,
+                version,
+                batch_size - reencrypted,
+            )?;
+        }
+        let remaining = count_pending(&tx, class_id, version)?;
+        tx.commit().map_err(|e| {
+            mimir_core::MimirError::Database(anyhow::anyhow!("Failed to commit transaction: {}", e))
+        })?;
+
+        let retired_versions = if remaining == 0 {
+            self.crypto_manager.retire_class_keys(class_id)?
+        } else {
+            vec![]
+        };
+
+        Ok(ReencryptionProgress {
+            key_version: version,
+            reencrypted,
+            remaining,
+            retired_versions,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mimir_core::test_utils::MemoryBuilder;
+    use mimir_core::{MemoryClass, MemoryRelation, RelationKind};
+    use tempfile::TempDir;
+
+    fn create_test_database() -> (Database, TempDir) {
+        let temp_dir = TempDir::new().unwrap();
+        let crypto_manager =
+            CryptoManager::with_password(temp_dir.path().join("keyset.json"), "test-password")
+                .unwrap();
+        let db =
+            Database::with_crypto_manager(temp_dir.path().join("test.db"), crypto_manager).unwrap();
+        (db, temp_dir)
+    }
+
+    #[tokio::test]
+    async fn test_rotation_reencrypts_class() {
+        let (db, temp_dir) = create_test_database();
+
+        let mut memories = Vec::new();
+        for index in 0..5 {
+            memories.push(
+                MemoryBuilder::new()
+                    .with_content(format!("Payroll note {}", index))
+                    .with_class(MemoryClass::Work)
+                    .with_tags(vec!["finance".to_string()])
+                    .with_embedding(vec![index as f32, 1.0])
+                    .with_embedding_model("test-model")
+                    .build(),
+            );
+        }
+        let personal = MemoryBuilder::new()
+            .with_content("Untouched")
+            .with_class(MemoryClass::Personal)
+            .build();
+        db.store_memories(&memories).await.unwrap();
+        db.store_memory(&personal).await.unwrap();
+
+        // A revision and a labelled relation are encrypted under the class key too
+        let mut updated = memories[0].clone();
+        updated.content = "Payroll note revised".to_string();
+        db.update_memory(&updated).await.unwrap();
+        db.link_memories(&MemoryRelation {
+            source: memories[1].id,
+            target: memories[2].id,
+            kind: RelationKind::RelatedTo,
+            label: Some("same quarter".to_string()),
+            created_at: chrono::Utc::now(),
+        })
+        .await
+        .unwrap();
+
+        let attachment = db
+            .add_attachment(
+                memories[3].id,
+                "payslip.pdf",
+                "application/pdf",
+                b"%PDF-1.7",
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(db.rotate_class_key("work").unwrap(), 1);
+        // 5 memories, 1 revision, 1 relation, 1 attachment
+        assert_eq!(db.pending_reencryption("work").await.unwrap(), 8);
+        assert_eq!(db.pending_reencryption("personal").await.unwrap(), 0);
+
+        // Rows under the old key still read and search while the job runs
+        let progress = db.reencrypt_class_batch("work", 3).await.unwrap();
+        assert_eq!((progress.reencrypted, progress.remaining), (3, 5));
+        assert!(progress.retired_versions.is_empty());
+        assert_eq!(db.keyword_search("payroll", 10).await.unwrap().len(), 5);
+        for memory in &memories[1..] {
+            assert!(db.get_memory(memory.id).await.unwrap().is_some());
+        }
+
+        // Resume from a freshly opened vault
+        drop(db);
+        let crypto_manager =
+            CryptoManager::with_password(temp_dir.path().join("keyset.json"), "test-password")
+                .unwrap();
+        let db =
+            Database::with_crypto_manager(temp_dir.path().join("test.db"), crypto_manager).unwrap();
+        assert_eq!(db.class_key_version("work"), 1);
+        let progress = db.reencrypt_class_batch("work", 100).await.unwrap();
+        assert_eq!((progress.reencrypted, progress.remaining), (5, 0));
+        assert!(progress.is_complete());
+        assert_eq!(progress.retired_versions, vec![0]);
+
+        // Everything reads with only the new key left
+        let reloaded =
+            CryptoManager::with_password(temp_dir.path().join("keyset.json"), "test-password")
+                .unwrap();
+        assert!(reloaded.retiring_class_key_versions("work").is_empty());
+        assert_eq!(
+            db.get_memory(memories[0].id)
+                .await
+                .unwrap()
+                .unwrap()
+                .content,
+            "Payroll note revised"
+        );
+        assert_eq!(
+            db.get_memory(memories[4].id).await.unwrap().unwrap().tags,
+            vec!["finance".to_string()]
+        );
+        assert_eq!(
+            db.get_memory(memories[4].id)
+                .await
+                .unwrap()
+                .unwrap()
+                .embedding,
+            Some(vec![4.0, 1.0])
+        );
+        assert_eq!(db.list_revisions(memories[0].id).await.unwrap().len(), 1);
+        let relations = db.list_relations(memories[1].id).await.unwrap();
+        assert_eq!(relations[0].label.as_deref(), Some("same quarter"));
+        let attachment = db.get_attachment(attachment.id).await.unwrap().unwrap();
+        assert_eq!(attachment.info.name, "payslip.pdf");
+        assert_eq!(attachment.data, b"%PDF-1.7");
+        assert_eq!(db.keyword_search("payroll", 10).await.unwrap().len(), 5);
+        assert_eq!(
+            db.get_memory(personal.id).await.unwrap().unwrap().content,
+            "Untouched"
+        );
+    }
+}
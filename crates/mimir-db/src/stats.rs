@@ -0,0 +1,272 @@
+//! Vault statistics
+//!
+//! Class, source and creation time are stored in the clear, so those
+//! breakdowns are SQL aggregates. Tags are encrypted; only the tag column is
+//! decrypted to count them. Trashed memories are counted on their own and
+//! left out of every breakdown.
+
+use crate::{decrypt_strings, restore::DATABASE_SIDECARS, Database};
+use mimir_core::{MemoryId, Result};
+use rusqlite::Connection;
+use serde::Serialize;
+use std::collections::{BTreeMap, HashSet};
+use std::path::Path;
+
+/// Number of memories per class, source, tag or month
+pub type Breakdown = BTreeMap<String, usize>;
+
+/// Statistics computed by the database
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct VaultStats {
+    /// Memories outside the trash
+    pub memories: usize,
+    /// Memories in the trash
+    pub trashed: usize,
+    /// Earlier revisions kept for history
+    pub revisions: usize,
+    pub relations: usize,
+    pub attachments: usize,
+    /// Plaintext size of all attachments
+    pub attachment_bytes: u64,
+    pub by_class: Breakdown,
+    pub by_source: Breakdown,
+    pub by_tag: Breakdown,
+    /// Memories per creation month, keyed `YYYY-MM` in UTC
+    pub by_month: Breakdown,
+    /// Size of the database file and its sidecars on disk
+    pub database_bytes: u64,
+}
+
+/// Disagreement between the database and the vector index
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct OrphanCounts {
+    /// Memories outside the trash with no vector
+    pub memories_without_vectors: usize,
+    /// Vectors whose memory is missing or in the trash
+    pub vectors_without_memories: usize,
+}
+
+impl OrphanCounts {
+    /// Compare the live memory IDs with the IDs held by the vector index
+    pub fn compare(memory_ids: &HashSet<MemoryId>, vector_ids: &[MemoryId]) -> Self {
+        let vector_ids: HashSet<&MemoryId> = vector_ids.iter().collect();
+        Self {
+            memories_without_vectors: memory_ids
+                .iter()
+                .filter(|id| !vector_ids.contains(id))
+                .count(),
+            vectors_without_memories: vector_ids
+                .iter()
+                .filter(|id| !memory_ids.contains(*id))
+                .count(),
+        }
+    }
+}
+
+/// Run a `key, count` query into a map
+fn count_by(conn: &Connection, sql: &str) -> rusqlite::Result<Breakdown> {
+    let mut stmt = conn.prepare(sql)?;
+    let rows = stmt.query_map([], |row| {
+        Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)? as usize))
+    })?;
+    rows.collect()
+}
+
+fn count(conn: &Connection, sql: &str) -> rusqlite::Result<usize> {
+    conn.query_row(sql, [], |row| row.get::<_, i64>(0))
+        .map(|count| count as usize)
+}
+
+/// Total size of a database file and whichever sidecars exist
+fn database_size(path: &Path) -> u64 {
+    let file_size = |path: &Path| std::fs::metadata(path).map(|m| m.len()).unwrap_or(0);
+    let mut size = file_size(path);
+    for suffix in DATABASE_SIDECARS {
+        let mut sidecar = path.as_os_str().to_owned();
+        sidecar.push(suffix);
+        size += file_size(Path::new(&sidecar));
+    }
+    size
+}
+
+/// Everything but the tag breakdown, which needs decryption
+fn query_stats(conn: &Connection) -> rusqlite::Result<VaultStats> {
+    Ok(VaultStats {
+        memories: count(conn, "SELECT COUNT(*) FROM memory WHERE deleted_ts IS NULL")?,
+        trashed: count(
+            conn,
+            "SELECT COUNT(*) FROM memory WHERE deleted_ts IS NOT NULL",
+        )?,
+        revisions: count(conn, "SELECT COUNT(*) FROM memory_revision")?,
+        relations: count(conn, "SELECT COUNT(*) FROM memory_relation")?,
+        attachments: count(conn, "SELECT COUNT(*) FROM memory_attachment")?,
+        attachment_bytes: conn.query_row(
+            "SELECT COALESCE(SUM(size), 0) FROM memory_attachment",
+            [],
+            |row| row.get::<_, i64>(0),
+        )? as u64,
+        by_class: count_by(
+            conn,
+            "SELECT class_id, COUNT(*) FROM memory WHERE deleted_ts IS NULL GROUP BY class_id",
+        )?,
+        by_source: count_by(
+            conn,
+            "SELECT source, COUNT(*) FROM memory WHERE deleted_ts IS NULL GROUP BY source",
+        )?,
+        by_tag: BTreeMap::new(),
+        by_month: count_by(
+            conn,
+            "SELECT strftime('%Y-%m', ts, 'unixepoch') AS month, COUNT(*) FROM memory
+             WHERE deleted_ts IS NULL GROUP BY month",
+        )?,
+        database_bytes: conn
+            .path()
+            .map(|path| database_size(Path::new(path)))
+            .unwrap_or(0),
+    })
+}
+
+impl Database {
+    /// Count the vault's memories, their breakdowns and the database size
+    pub async fn vault_stats(&self) -> Result<VaultStats> {
+        let conn = self.reader().await?;
+        let mut stats = query_stats(&conn).map_err(|e| {
+            mimir_core::MimirError::Database(anyhow::anyhow!("Failed to compute stats: {}", e))
+        })?;
+        let tag_rows = conn
+            .prepare(
+                "SELECT class_id, tags_enc FROM memory
+                 WHERE deleted_ts IS NULL AND tags_enc IS NOT NULL",
+            )
+            .and_then(|mut stmt| {
+                stmt.query_map([], |row| {
+                    Ok((row.get::<_, String>(0)?, row.get::<_, Vec<u8>>(1)?))
+                })?
+                .collect::<rusqlite::Result<Vec<_>>>()
+            })
+            .map_err(|e| {
+                mimir_core::MimirError::Database(anyhow::anyhow!("Failed to read tags: {}", e))
+            })?;
+        drop(conn);
+
+        for (class_id, tags_enc) in tag_rows {
+            for tag in decrypt_strings(&self.crypto_manager, &class_id, &tags_enc)? {
+                *stats.by_tag.entry(tag).or_default() += 1;
+            }
+        }
+        Ok(stats)
+    }
+
+    /// IDs of every memory outside the trash
+    pub async fn live_memory_ids(&self) -> Result<HashSet<MemoryId>> {
+        let conn = self.reader().await?;
+        let ids = conn
+            .prepare("SELECT id FROM memory WHERE deleted_ts IS NULL")
+            .and_then(|mut stmt| {
+                stmt.query_map([], |row| row.get::<_, String>(0))?
+                    .collect::<rusqlite::Result<Vec<_>>>()
+            })
+            .map_err(|e| {
+                mimir_core::MimirError::Database(anyhow::anyhow!(
+                    "Failed to read memory IDs: {}",
+                    e
+                ))
+            })?;
+        ids.iter()
+            .map(|id| {
+                uuid::Uuid::parse_str(id).map_err(|e| {
+                    mimir_core::MimirError::Database(anyhow::anyhow!("Invalid UUID: {}", e))
+                })
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mimir_core::crypto::CryptoManager;
+    use mimir_core::test_utils::MemoryBuilder;
+    use mimir_core::MemoryClass;
+    use tempfile::TempDir;
+
+    fn create_test_database() -> (Database, TempDir) {
+        let temp_dir = TempDir::new().unwrap();
+        let crypto_manager =
+            CryptoManager::with_password(temp_dir.path().join("keyset.json"), "test-password")
+                .unwrap();
+        let db =
+            Database::with_crypto_manager(temp_dir.path().join("test.db"), crypto_manager).unwrap();
+        (db, temp_dir)
+    }
+
+    #[tokio::test]
+    async fn test_vault_stats() {
+        let (db, _temp_dir) = create_test_database();
+        assert_eq!(db.vault_stats().await.unwrap().memories, 0);
+
+        let march = chrono::DateTime::parse_from_rfc3339("2024-03-15T12:00:00Z")
+            .unwrap()
+            .with_timezone(&chrono::Utc);
+        let mut dentist = MemoryBuilder::new()
+            .with_content("Dentist on Tuesday")
+            .with_class(MemoryClass::Health)
+            .with_tags(vec!["appointment".to_string(), "teeth".to_string()])
+            .with_app_acl(vec!["calendar".to_string()])
+            .build();
+        dentist.created_at = march;
+        let standup = MemoryBuilder::new()
+            .with_content("Standup moved to 10am")
+            .with_class(MemoryClass::Work)
+            .with_tags(vec!["appointment".to_string()])
+            .with_app_acl(vec!["calendar".to_string()])
+            .build();
+        let trashed = MemoryBuilder::new().with_content("Old note").build();
+        db.store_memories(&[dentist.clone(), standup.clone(), trashed.clone()])
+            .await
+            .unwrap();
+        db.delete_memory(trashed.id).await.unwrap();
+        db.add_attachment(dentist.id, "referral.pdf", "application/pdf", b"%PDF-1.7")
+            .await
+            .unwrap();
+
+        let stats = db.vault_stats().await.unwrap();
+        assert_eq!(stats.memories, 2);
+        assert_eq!(stats.trashed, 1);
+        assert_eq!(stats.attachments, 1);
+        assert_eq!(stats.attachment_bytes, 8);
+        assert_eq!(
+            stats.by_class,
+            BTreeMap::from([("health".to_string(), 1), ("work".to_string(), 1)])
+        );
+        assert_eq!(
+            stats.by_source,
+            BTreeMap::from([("calendar".to_string(), 2)])
+        );
+        assert_eq!(
+            stats.by_tag,
+            BTreeMap::from([("appointment".to_string(), 2), ("teeth".to_string(), 1)])
+        );
+        assert_eq!(stats.by_month.get("2024-03"), Some(&1));
+        assert_eq!(stats.by_month.values().sum::<usize>(), 2);
+        assert!(stats.database_bytes > 0);
+
+        let ids = db.live_memory_ids().await.unwrap();
+        assert_eq!(ids, HashSet::from([dentist.id, standup.id]));
+    }
+
+    #[test]
+    fn test_orphan_counts() {
+        let kept = uuid::Uuid::new_v4();
+        let unindexed = uuid::Uuid::new_v4();
+        let stale = uuid::Uuid::new_v4();
+        let counts = OrphanCounts::compare(&HashSet::from([kept, unindexed]), &[kept, stale]);
+        assert_eq!(
+            counts,
+            OrphanCounts {
+                memories_without_vectors: 1,
+                vectors_without_memories: 1,
+            }
+        );
+    }
+}
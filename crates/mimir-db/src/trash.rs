@@ -0,0 +1,253 @@
+//! Soft delete and the trash
+//!
+//! `Database::delete_memory` only stamps `deleted_ts`: the row, its revisions
+//! and its keyword entries stay on disk, still encrypted, and every read path
+//! skips them. A trashed memory can be restored until it is purged, which
+//! destroys it for good.
+
+use crate::{attachment, decode_memory, Database, MemoryRow, MEMORY_COLUMNS};
+use mimir_core::{Memory, MemoryId, Result};
+use rusqlite::{params, Connection};
+
+/// A deleted memory waiting in the trash
+#[derive(Debug, Clone)]
+pub struct TrashedMemory {
+    /// When the memory was deleted
+    pub deleted_at: chrono::DateTime<chrono::Utc>,
+    /// The memory as it was when deleted
+    pub memory: Memory,
+}
+
+/// Permanently remove memories with their revisions, keyword entries,
+/// relations and attachments
+///
+/// Callers should run this inside a transaction.
+pub(crate) fn destroy(conn: &Connection, ids: &[String]) -> rusqlite::Result<()> {
+    for id in ids {
+        conn.execute("DELETE FROM memory WHERE id = ?1", params![id])?;
+        conn.execute(
+            "DELETE FROM memory_revision WHERE memory_id = ?1",
+            params![id],
+        )?;
+        conn.execute(
+            "DELETE FROM memory_keyword WHERE memory_id = ?1",
+            params![id],
+        )?;
+        conn.execute(
+            "DELETE FROM memory_relation WHERE source_id = ?1 OR target_id = ?1",
+            params![id],
+        )?;
+    }
+    attachment::destroy(conn, ids)?;
+    Ok(())
+}
+
+impl Database {
+    /// Move a trashed memory back out of the trash
+    ///
+    /// Returns `false` if the memory is not in the trash.
+    pub async fn restore_memory(&self, id: MemoryId) -> Result<bool> {
+        let conn = self.conn.lock().await;
+        let restored = conn
+            .execute(
+                "UPDATE memory SET deleted_ts = NULL WHERE id = ?1 AND deleted_ts IS NOT NULL",
+                params![id.to_string()],
+            )
+            .map_err(|e| {
+                mimir_core::MimirError::Database(anyhow::anyhow!("Failed to restore memory: {}", e))
+            })?;
+        Ok(restored > 0)
+    }
+
+    /// List trashed memories, most recently deleted first
+    pub async fn list_trash(&self) -> Result<Vec<TrashedMemory>> {
+        let conn = self.reader().await?;
+        let mut stmt = conn
+            .prepare(&format!(
+                "SELECT {}, deleted_ts FROM memory WHERE deleted_ts IS NOT NULL
+                 ORDER BY deleted_ts DESC, id DESC",
+                MEMORY_COLUMNS
+            ))
+            .map_err(|e| {
+                mimir_core::MimirError::Database(anyhow::anyhow!("Failed to prepare query: {}", e))
+            })?;
+
+        let rows = stmt
+            .query_map([], |row| {
+                Ok((MemoryRow::from_row(row)?, row.get::<_, i64>(13)?))
+            })
+            .map_err(|e| {
+                mimir_core::MimirError::Database(anyhow::anyhow!("Failed to execute query: {}", e))
+            })?;
+
+        let mut trash = Vec::new();
+        for row in rows {
+            let (memory_row, deleted_ts) = row.map_err(|e| {
+                mimir_core::MimirError::Database(anyhow::anyhow!("Failed to read row: {}", e))
+            })?;
+            trash.push(TrashedMemory {
+                deleted_at: crate::timestamp_to_datetime(deleted_ts)?,
+                memory: decode_memory(&self.crypto_manager, memory_row)?,
+            });
+        }
+
+        Ok(trash)
+    }
+
+    /// Permanently remove memories that were deleted before `deleted_before`
+    ///
+    /// Returns the IDs that were destroyed so callers can drop their vectors.
+    pub async fn purge_trash(
+        &self,
+        deleted_before: chrono::DateTime<chrono::Utc>,
+    ) -> Result<Vec<MemoryId>> {
+        self.destroy_trashed(Some(deleted_before.timestamp())).await
+    }
+
+    /// Permanently remove every memory in the trash
+    pub async fn empty_trash(&self) -> Result<Vec<MemoryId>> {
+        self.destroy_trashed(None).await
+    }
+
+    async fn destroy_trashed(&self, deleted_before: Option<i64>) -> Result<Vec<MemoryId>> {
+        let mut conn = self.conn.lock().await;
+        let tx = conn.transaction().map_err(|e| {
+            mimir_core::MimirError::Database(anyhow::anyhow!("Failed to begin transaction: {}", e))
+        })?;
+
+        let ids: Vec<String> = tx
+            .prepare(
+                "SELECT id FROM memory
+                 WHERE deleted_ts IS NOT NULL AND (?1 IS NULL OR deleted_ts < ?1)",
+            )
+            .and_then(|mut stmt| {
+                stmt.query_map(params![deleted_before], |row| row.get(0))?
+                    .collect::<rusqlite::Result<Vec<_>>>()
+            })
+            .map_err(|e| {
+                mimir_core::MimirError::Database(anyhow::anyhow!("Failed to read trash: {}", e))
+            })?;
+
+        destroy(&tx, &ids).map_err(|e| {
+            mimir_core::MimirError::Database(anyhow::anyhow!("Failed to purge trash: {}", e))
+        })?;
+        tx.commit().map_err(|e| {
+            mimir_core::MimirError::Database(anyhow::anyhow!("Failed to commit transaction: {}", e))
+        })?;
+
+        ids.iter()
+            .map(|id| {
+                uuid::Uuid::parse_str(id).map_err(|e| {
+                    mimir_core::MimirError::Database(anyhow::anyhow!("Invalid UUID: {}", e))
+                })
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mimir_core::crypto::CryptoManager;
+    use mimir_core::test_utils::MemoryBuilder;
+    use mimir_core::MemoryFilter;
+    use tempfile::TempDir;
+
+    fn create_test_database() -> (Database, TempDir) {
+        let temp_dir = TempDir::new().unwrap();
+        let crypto_manager =
+            CryptoManager::with_password(temp_dir.path().join("keyset.json"), "test-password")
+                .unwrap();
+        let db =
+            Database::with_crypto_manager(temp_dir.path().join("test.db"), crypto_manager).unwrap();
+        (db, temp_dir)
+    }
+
+    #[tokio::test]
+    async fn test_deleted_memory_is_hidden_and_restorable() {
+        let (db, _temp_dir) = create_test_database();
+
+        let memory = MemoryBuilder::new()
+            .with_content("Recoverable note")
+            .build();
+        db.store_memory(&memory).await.unwrap();
+        db.delete_memory(memory.id).await.unwrap();
+
+        assert!(db.get_memory(memory.id).await.unwrap().is_none());
+        assert!(db
+            .keyword_search("recoverable", 10)
+            .await
+            .unwrap()
+            .is_empty());
+        let page = db
+            .list_memories(&MemoryFilter::default(), None, 10)
+            .await
+            .unwrap();
+        assert!(page.memories.is_empty());
+
+        let trash = db.list_trash().await.unwrap();
+        assert_eq!(trash.len(), 1);
+        assert_eq!(trash[0].memory.content, "Recoverable note");
+
+        assert!(db.restore_memory(memory.id).await.unwrap());
+        assert!(!db.restore_memory(memory.id).await.unwrap());
+        assert_eq!(
+            db.get_memory(memory.id).await.unwrap().unwrap().content,
+            "Recoverable note"
+        );
+        assert_eq!(
+            db.keyword_search("recoverable", 10).await.unwrap(),
+            vec![memory.id]
+        );
+        assert!(db.list_trash().await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_purge_trash_respects_window() {
+        let (db, _temp_dir) = create_test_database();
+
+        let old = MemoryBuilder::new().with_content("Old").build();
+        let recent = MemoryBuilder::new().with_content("Recent").build();
+        db.store_memories(&[old.clone(), recent.clone()])
+            .await
+            .unwrap();
+        db.delete_memory(old.id).await.unwrap();
+        db.delete_memory(recent.id).await.unwrap();
+
+        // Backdate one deletion past the purge window
+        {
+            let conn = db.conn.lock().await;
+            conn.execute(
+                "UPDATE memory SET deleted_ts = deleted_ts - 86400 WHERE id = ?1",
+                params![old.id.to_string()],
+            )
+            .unwrap();
+        }
+
+        let cutoff = chrono::Utc::now() - chrono::Duration::hours(1);
+        assert_eq!(db.purge_trash(cutoff).await.unwrap(), vec![old.id]);
+        assert!(!db.restore_memory(old.id).await.unwrap());
+
+        let trash = db.list_trash().await.unwrap();
+        assert_eq!(trash.len(), 1);
+        assert_eq!(trash[0].memory.id, recent.id);
+    }
+
+    #[tokio::test]
+    async fn test_empty_trash_removes_history() {
+        let (db, _temp_dir) = create_test_database();
+
+        let mut memory = MemoryBuilder::new().with_content("Original").build();
+        db.store_memory(&memory).await.unwrap();
+        memory.content = "Changed".to_string();
+        db.update_memory(&memory).await.unwrap();
+
+        db.delete_memory(memory.id).await.unwrap();
+        // History is kept while the memory can still be restored
+        assert_eq!(db.list_revisions(memory.id).await.unwrap().len(), 1);
+
+        assert_eq!(db.empty_trash().await.unwrap(), vec![memory.id]);
+        assert!(db.list_revisions(memory.id).await.unwrap().is_empty());
+        assert!(db.list_trash().await.unwrap().is_empty());
+    }
+}
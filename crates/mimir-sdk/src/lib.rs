@@ -1,6 +1,8 @@
 //! Mimir SDK - Client library for accessing the memory vault
 
-use mimir_core::{AppId, MemoryIngestion, MemoryQuery, MemoryResult, Result};
+use mimir_core::{
+    AppId, MemoryFilter, MemoryIngestion, MemoryPage, MemoryQuery, MemoryResult, Result,
+};
 
 /// Client for interacting with Mimir memory vault
 pub struct MemoryClient {
@@ -29,6 +31,19 @@ impl MemoryClient {
         Ok(vec![])
     }
 
+    /// List memories matching a filter, one page at a time
+    ///
+    /// Pass the returned `next_cursor` back as `cursor` to fetch the next page.
+    pub async fn list_memories(
+        &self,
+        _filter: MemoryFilter,
+        _cursor: Option<String>,
+        _limit: usize,
+    ) -> Result<MemoryPage> {
+        // TODO: Call the list_memories MCP tool
+        Ok(MemoryPage::default())
+    }
+
     /// Check if the daemon is healthy
     pub async fn health(&self) -> Result<bool> {
         // TODO: Implement health check
@@ -94,6 +109,22 @@ mod tests {
         assert_eq!(memories.len(), 0); // Stub returns empty vec
     }
 
+    #[tokio::test]
+    async fn test_list_memories_stub() {
+        let client = MemoryClient::new("http://localhost:8100", "test-app");
+
+        let filter = MemoryFilter {
+            class: Some(MemoryClass::Work),
+            tags: vec!["project".to_string()],
+            ..Default::default()
+        };
+
+        // Should succeed with stub implementation
+        let page = client.list_memories(filter, None, 20).await.unwrap();
+        assert!(page.memories.is_empty());
+        assert!(page.next_cursor.is_none());
+    }
+
     #[tokio::test]
     async fn test_health_check_stub() {
         let client = MemoryClient::new("http://localhost:8100", "test-app");
@@ -240,6 +240,17 @@ impl Embedder {
         &self.model_path
     }
 
+    /// Identifier recorded with stored embeddings: the name of the model's
+    /// directory, which also holds its tokenizer
+    pub fn model_id(&self) -> String {
+        let path = Path::new(&self.model_path);
+        path.parent()
+            .and_then(|dir| dir.file_name())
+            .or_else(|| path.file_name())
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_else(|| self.model_path.clone())
+    }
+
     /// Get embedding dimension
     pub fn embedding_dimension(&self) -> usize {
         self.embedding_dimension
@@ -44,30 +44,33 @@ impl<'a> SecureVectorStore<'a> {
             ));
         }
 
+        Ok(Self {
+            hnsw: Self::new_index(),
+            embedder: None,
+            rotation_matrix: None,
+            dimension,
+            next_id: 0,
+            id_mapping: HashMap::new(),
+            reverse_mapping: HashMap::new(),
+            original_vectors: HashMap::new(),
+        })
+    }
+
+    /// Create an empty HNSW index
+    fn new_index() -> Hnsw<'a, f32, DistCosine> {
         // Use parameters suitable for larger dimensions and deterministic results
         let max_connections = 32; // Increased for better connectivity
         let max_elements = 10000; // Maximum number of elements
         let max_layer = 16; // Maximum number of layers
         let ef_construction = 32; // Increased for better construction quality
 
-        let hnsw = Hnsw::new(
+        Hnsw::new(
             max_connections,
             max_elements,
             max_layer,
             ef_construction,
             DistCosine,
-        );
-
-        Ok(Self {
-            hnsw,
-            embedder: None,
-            rotation_matrix: None,
-            dimension,
-            next_id: 0,
-            id_mapping: HashMap::new(),
-            reverse_mapping: HashMap::new(),
-            original_vectors: HashMap::new(),
-        })
+        )
     }
 
     /// Create a secure vector store with embedder
@@ -154,16 +157,21 @@ impl<'a> SecureVectorStore<'a> {
 
     /// Add text to the store (converts to embedding first)
     pub async fn add_text(&mut self, text: &str, memory_id: MemoryId) -> VectorResult<()> {
+        // Generate embedding
+        let embedding = self.embed_text(text).await?;
+
+        // Add the embedding
+        self.add_raw_vector(embedding, memory_id).await
+    }
+
+    /// Generate the embedding for a text without adding it to the store
+    pub async fn embed_text(&mut self, text: &str) -> VectorResult<Vec<f32>> {
         let embedder = self
             .embedder
             .as_mut()
             .ok_or_else(|| VectorError::InvalidInput("No embedder available".to_string()))?;
 
-        // Generate embedding
-        let embedding = embedder.embed(text).await?;
-
-        // Add the embedding
-        self.add_raw_vector(embedding, memory_id).await
+        embedder.embed(text).await
     }
 
     /// Search for similar vectors
@@ -264,6 +272,11 @@ impl<'a> SecureVectorStore<'a> {
         self.embedder.as_ref().map(|e| e.embedding_dimension())
     }
 
+    /// Identifier of the embedding model, if an embedder is attached
+    pub fn embedding_model(&self) -> Option<String> {
+        self.embedder.as_ref().map(|e| e.model_id())
+    }
+
     /// Attach an embedder to the store
     pub async fn attach_embedder<P: AsRef<Path>>(&mut self, model_path: P) -> VectorResult<()> {
         let embedder = Embedder::new(model_path).await?;
@@ -286,6 +299,20 @@ impl<'a> SecureVectorStore<'a> {
         self.reverse_mapping.contains_key(memory_id)
     }
 
+    /// IDs of every memory with a vector in the store
+    pub fn memory_ids(&self) -> Vec<MemoryId> {
+        self.reverse_mapping.keys().copied().collect()
+    }
+
+    /// Remove every vector, keeping the embedder and rotation matrix
+    pub fn clear(&mut self) {
+        self.hnsw = Self::new_index();
+        self.next_id = 0;
+        self.id_mapping.clear();
+        self.reverse_mapping.clear();
+        self.original_vectors.clear();
+    }
+
     /// Get the next internal ID
     pub fn next_id(&self) -> usize {
         self.next_id
@@ -438,6 +465,30 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn test_clear() {
+        let mut store = SecureVectorStore::new(128).unwrap();
+        let memory_id = Uuid::new_v4();
+        let vector = generate_test_embedding(128);
+        store
+            .add_raw_vector(vector.clone(), memory_id)
+            .await
+            .unwrap();
+
+        store.clear();
+        assert!(store.is_empty());
+        assert!(!store.contains(&memory_id));
+        assert!(store
+            .search_raw_vector(&vector, 1)
+            .await
+            .unwrap()
+            .is_empty());
+
+        // The same ID can be added again after a clear
+        store.add_raw_vector(vector, memory_id).await.unwrap();
+        assert_eq!(store.len(), 1);
+    }
+
     #[tokio::test]
     async fn test_search_with_empty_store() {
         let store = SecureVectorStore::new(128).unwrap();
@@ -96,6 +96,15 @@ impl MemoryManager {
         true
     }
 
+    /// Check if adding a batch of vectors would exceed memory limits
+    pub fn can_add_vectors(&self, count: usize, total_size_bytes: usize) -> bool {
+        let current_count = self.vector_count.load(Ordering::Relaxed);
+        let current_memory = self.memory_usage.load(Ordering::Relaxed);
+
+        current_count + count <= self.config.max_vectors
+            && current_memory + total_size_bytes <= self.config.max_memory_bytes
+    }
+
     /// Record addition of a vector
     pub fn record_vector_added(&self, vector_size_bytes: usize) {
         self.vector_count.fetch_add(1, Ordering::Relaxed);
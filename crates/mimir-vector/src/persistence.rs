@@ -9,6 +9,18 @@ use std::fs;
 use std::io::{Read, Write};
 use std::path::{Path, PathBuf};
 
+/// Vector store metadata file, relative to the vault directory
+pub const METADATA_FILE: &str = "vector_store_metadata.bin";
+
+/// HNSW index file, relative to the vault directory
+pub const INDEX_FILE: &str = "vector_store_index.bin";
+
+/// Stored vectors file, relative to the vault directory
+pub const VECTORS_FILE: &str = "vector_store_vectors.bin";
+
+/// Every file a saved vector store consists of
+pub const STORE_FILES: [&str; 3] = [METADATA_FILE, INDEX_FILE, VECTORS_FILE];
+
 /// Metadata for the vector store
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct VectorStoreMetadata {
@@ -52,9 +64,9 @@ impl VectorStorePersistence {
     /// Create a new persistence manager
     pub fn new<P: AsRef<Path>>(vault_path: P) -> Self {
         let vault_path = vault_path.as_ref().to_path_buf();
-        let metadata_path = vault_path.join("vector_store_metadata.bin");
-        let index_path = vault_path.join("vector_store_index.bin");
-        let vectors_path = vault_path.join("vector_store_vectors.bin");
+        let metadata_path = vault_path.join(METADATA_FILE);
+        let index_path = vault_path.join(INDEX_FILE);
+        let vectors_path = vault_path.join(VECTORS_FILE);
 
         Self {
             vault_path,
@@ -210,6 +222,16 @@ impl VectorStorePersistence {
         Ok(store)
     }
 
+    /// Whether the saved store was written with vector rotation enabled
+    ///
+    /// False when nothing has been saved yet.
+    pub fn is_rotated(&self) -> VectorResult<bool> {
+        if !self.metadata_path.exists() {
+            return Ok(false);
+        }
+        Ok(self.load_metadata()?.has_rotation)
+    }
+
     /// Check if a saved store exists
     pub fn store_exists(&self) -> bool {
         self.metadata_path.exists() && self.vectors_path.exists()
@@ -220,6 +242,15 @@ impl VectorStorePersistence {
         &self.vault_path
     }
 
+    /// Total size of the saved store files, in bytes
+    pub fn disk_size(&self) -> u64 {
+        [&self.metadata_path, &self.index_path, &self.vectors_path]
+            .iter()
+            .filter_map(|path| fs::metadata(path).ok())
+            .map(|metadata| metadata.len())
+            .sum()
+    }
+
     /// Delete the saved store
     pub fn delete_store(&self) -> VectorResult<()> {
         if self.metadata_path.exists() {
@@ -258,11 +289,12 @@ mod tests {
 
         assert_eq!(persistence.vault_path(), temp_dir.path());
         assert!(!persistence.store_exists());
+        assert!(!persistence.is_rotated().unwrap());
     }
 
     #[test]
     fn test_metadata_serialization() {
-        let metadata = VectorStoreMetadata {
+        let mut metadata = VectorStoreMetadata {
             version: 1,
             dimension: 768,
             vector_count: 100,
@@ -290,6 +322,11 @@ mod tests {
         assert_eq!(loaded.version, metadata.version);
         assert_eq!(loaded.dimension, metadata.dimension);
         assert_eq!(loaded.vector_count, metadata.vector_count);
+        assert!(!persistence.is_rotated().unwrap());
+
+        metadata.has_rotation = true;
+        persistence.save_metadata(&metadata).unwrap();
+        assert!(persistence.is_rotated().unwrap());
     }
 
     #[test]
@@ -1,11 +1,14 @@
 //! Thread-safe vector store wrapper
 
-use crate::batch_ops::{BatchConfig, BatchOperations, SearchQuery, VectorInsert};
+use crate::batch_ops::{
+    BatchConfig, BatchInsertResult, BatchOperations, SearchQuery, VectorInsert,
+};
 use crate::error::{VectorError, VectorResult};
 use crate::hnsw_store::{SearchResult, SecureVectorStore};
 use crate::memory_manager::{MemoryConfig, MemoryManager};
 use crate::persistence::VectorStorePersistence;
 use mimir_core::{crypto::RootKey, MemoryId};
+use std::collections::HashSet;
 use std::path::Path;
 use std::sync::Arc;
 use tokio::sync::Mutex;
@@ -160,6 +163,12 @@ impl ThreadSafeVectorStore {
         Ok(())
     }
 
+    /// Generate the embedding for a text without adding it to the store
+    pub async fn embed_text(&self, text: &str) -> VectorResult<Vec<f32>> {
+        let mut store = self.store.lock().await;
+        store.embed_text(text).await
+    }
+
     /// Search for similar vectors
     pub async fn search(&self, query: Vec<f32>, k: usize) -> VectorResult<Vec<SearchResult>> {
         let store = self.store.lock().await;
@@ -195,16 +204,139 @@ impl ThreadSafeVectorStore {
     }
 
     /// Batch insert vectors
+    ///
+    /// The batch is all-or-nothing: every vector is validated before any is
+    /// inserted, and if one is rejected nothing is added. The result lists an
+    /// error for each rejected vector; `inserted_count` is either the batch
+    /// size or zero.
     pub async fn batch_insert(
         &self,
         vectors: Vec<VectorInsert>,
-    ) -> VectorResult<crate::batch_ops::BatchInsertResult> {
-        if let Some(batch_ops) = &self.batch_ops {
-            batch_ops.batch_insert(vectors).await
-        } else {
-            Err(VectorError::InvalidInput(
-                "Batch operations not configured".to_string(),
-            ))
+    ) -> VectorResult<BatchInsertResult> {
+        let mut store = self.store.lock().await;
+        Ok(self.insert_all(&mut store, vectors).await)
+    }
+
+    /// Replace the whole index with `vectors`
+    ///
+    /// Used to rebuild the index from stored embeddings without running the
+    /// embedder. The store is cleared first, so if the batch is rejected it is
+    /// left empty; callers should drop vectors of the wrong dimension.
+    pub async fn rebuild(&self, vectors: Vec<VectorInsert>) -> VectorResult<BatchInsertResult> {
+        let mut store = self.store.lock().await;
+        store.clear();
+        self.memory_manager.reset_stats();
+        Ok(self.insert_all(&mut store, vectors).await)
+    }
+
+    /// Batch insert texts, embedding all of them before inserting any
+    ///
+    /// Has the same all-or-nothing semantics as [`Self::batch_insert`]; a text
+    /// that fails to embed rejects the whole batch. Each text is embedded
+    /// separately and the store is only held for the whole batch while the
+    /// vectors are inserted, so searches are not blocked by a large batch.
+    pub async fn batch_insert_texts(
+        &self,
+        texts: Vec<(MemoryId, String)>,
+    ) -> VectorResult<BatchInsertResult> {
+        if !self.has_embedder().await {
+            return Err(VectorError::InvalidInput(
+                "No embedder available".to_string(),
+            ));
+        }
+
+        let mut vectors = Vec::with_capacity(texts.len());
+        let mut errors = Vec::new();
+        for (memory_id, text) in texts {
+            match self.embed_text(&text).await {
+                Ok(vector) => vectors.push(VectorInsert { memory_id, vector }),
+                Err(e) => errors.push((memory_id, e.to_string())),
+            }
+        }
+
+        if !errors.is_empty() {
+            return Ok(BatchInsertResult {
+                inserted_count: 0,
+                failed_count: errors.len(),
+                errors,
+            });
+        }
+
+        let mut store = self.store.lock().await;
+        Ok(self.insert_all(&mut store, vectors).await)
+    }
+
+    /// Validate and insert a batch while holding the store lock
+    async fn insert_all(
+        &self,
+        store: &mut SecureVectorStore<'static>,
+        vectors: Vec<VectorInsert>,
+    ) -> BatchInsertResult {
+        let mut errors = Vec::new();
+        let mut seen = HashSet::new();
+        for insert in &vectors {
+            if insert.vector.len() != store.dimension() {
+                let error = VectorError::DimensionMismatch {
+                    expected: store.dimension(),
+                    actual: insert.vector.len(),
+                };
+                errors.push((insert.memory_id, error.to_string()));
+            } else if store.contains(&insert.memory_id) || !seen.insert(insert.memory_id) {
+                errors.push((
+                    insert.memory_id,
+                    format!("Memory ID {} already exists in store", insert.memory_id),
+                ));
+            }
+        }
+
+        let sizes: Vec<usize> = vectors
+            .iter()
+            .map(|insert| insert.vector.len() * std::mem::size_of::<f32>())
+            .collect();
+        if errors.is_empty()
+            && !self
+                .memory_manager
+                .can_add_vectors(vectors.len(), sizes.iter().sum())
+        {
+            errors = vectors
+                .iter()
+                .map(|insert| (insert.memory_id, "Memory limit exceeded".to_string()))
+                .collect();
+        }
+
+        if !errors.is_empty() {
+            return BatchInsertResult {
+                inserted_count: 0,
+                failed_count: errors.len(),
+                errors,
+            };
+        }
+
+        let mut inserted = Vec::with_capacity(vectors.len());
+        for insert in vectors {
+            let memory_id = insert.memory_id;
+            if let Err(e) = store.add_raw_vector(insert.vector, memory_id).await {
+                // Undo the vectors already added so the batch leaves no trace
+                for id in inserted {
+                    let _ = store.remove_vector(id).await;
+                }
+                return BatchInsertResult {
+                    inserted_count: 0,
+                    failed_count: 1,
+                    errors: vec![(memory_id, e.to_string())],
+                };
+            }
+            inserted.push(memory_id);
+        }
+
+        for size in sizes {
+            self.memory_manager.record_vector_added(size);
+        }
+
+        BatchInsertResult {
+            inserted_count: inserted.len(),
+            failed_count: 0,
+            errors: vec![],
         }
     }
 
@@ -267,6 +399,17 @@ impl ThreadSafeVectorStore {
         store.contains(memory_id)
     }
 
+    /// IDs of every memory with a vector in the store
+    pub async fn memory_ids(&self) -> Vec<MemoryId> {
+        let store = self.store.lock().await;
+        store.memory_ids()
+    }
+
+    /// Size of the saved store on disk, in bytes
+    pub fn disk_size(&self) -> u64 {
+        self.persistence.disk_size()
+    }
+
     /// Get embedding dimension
     pub async fn dimension(&self) -> usize {
         let store = self.store.lock().await;
@@ -279,6 +422,12 @@ impl ThreadSafeVectorStore {
         store.has_embedder()
     }
 
+    /// Identifier of the embedding model, if an embedder is attached
+    pub async fn embedding_model(&self) -> Option<String> {
+        let store = self.store.lock().await;
+        store.embedding_model()
+    }
+
     /// Check if rotation is enabled
     pub async fn has_rotation(&self) -> bool {
         let store = self.store.lock().await;
@@ -380,6 +529,79 @@ mod tests {
         assert_eq!(store.len().await, 10);
     }
 
+    #[tokio::test]
+    async fn test_batch_insert() {
+        let temp_dir = TempDir::new().unwrap();
+        let store = ThreadSafeVectorStore::new(temp_dir.path(), 128, None, None).unwrap();
+
+        let vectors: Vec<VectorInsert> = (0..5)
+            .map(|_| VectorInsert {
+                memory_id: Uuid::new_v4(),
+                vector: generate_test_embedding(128),
+            })
+            .collect();
+        let ids: Vec<MemoryId> = vectors.iter().map(|insert| insert.memory_id).collect();
+
+        let result = store.batch_insert(vectors).await.unwrap();
+        assert_eq!(result.inserted_count, 5);
+        assert_eq!(result.failed_count, 0);
+        assert_eq!(store.len().await, 5);
+        for id in &ids {
+            assert!(store.contains(id).await);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_batch_insert_is_all_or_nothing() {
+        let temp_dir = TempDir::new().unwrap();
+        let store = ThreadSafeVectorStore::new(temp_dir.path(), 128, None, None).unwrap();
+
+        let existing = Uuid::new_v4();
+        store
+            .add_vector(existing, generate_test_embedding(128))
+            .await
+            .unwrap();
+
+        let good = Uuid::new_v4();
+        let wrong_dimension = Uuid::new_v4();
+        let vectors = vec![
+            VectorInsert {
+                memory_id: good,
+                vector: generate_test_embedding(128),
+            },
+            VectorInsert {
+                memory_id: wrong_dimension,
+                vector: generate_test_embedding(64),
+            },
+            VectorInsert {
+                memory_id: existing,
+                vector: generate_test_embedding(128),
+            },
+        ];
+
+        let result = store.batch_insert(vectors).await.unwrap();
+        assert_eq!(result.inserted_count, 0);
+        assert_eq!(result.failed_count, 2);
+        let failed: Vec<MemoryId> = result.errors.iter().map(|(id, _)| *id).collect();
+        assert_eq!(failed, vec![wrong_dimension, existing]);
+
+        // Nothing from the rejected batch was added
+        assert_eq!(store.len().await, 1);
+        assert!(!store.contains(&good).await);
+    }
+
+    #[tokio::test]
+    async fn test_batch_insert_texts_requires_embedder() {
+        let temp_dir = TempDir::new().unwrap();
+        let store = ThreadSafeVectorStore::new(temp_dir.path(), 128, None, None).unwrap();
+
+        let result = store
+            .batch_insert_texts(vec![(Uuid::new_v4(), "text".to_string())])
+            .await;
+        assert!(result.is_err());
+        assert!(store.is_empty().await);
+    }
+
     #[tokio::test]
     async fn test_memory_limits() {
         let temp_dir = TempDir::new().unwrap();
@@ -9,4 +9,6 @@ pub mod llm_service;
 
 // Re-export commonly used functions for external use (e.g., testing)
 pub use mimir_core::{Config, MimirError, Result};
-pub use storage::{IntegratedStorage, MemoryAddResult, MemorySearchResult, StorageStats};
+pub use storage::{
+    IntegratedStorage, MemoryAddResult, MemoryBatchResult, MemorySearchResult, StorageStats,
+};
@@ -4,13 +4,14 @@
 
 use crate::vault::{check_vault_status, ensure_vault_ready};
 use clap::{Parser, Subcommand};
-use mimir_core::{Config, Result};
+use mimir_core::{unlock::Unlock, Config, Result};
 use rmcp::transport::streamable_http_server::{
     StreamableHttpService, session::local::LocalSessionManager,
 };
 use axum::{Router, routing::post};
 use tokio::net::TcpListener;
 use std::path::PathBuf;
+use std::sync::Arc;
 use tracing::{error, info, warn};
 use rmcp::ServiceExt;
 
@@ -41,6 +42,28 @@ struct Cli {
     #[arg(long)]
     auto_init: bool,
 
+    /// Vault to open (repeatable); every configured vault if omitted.
+    /// Sessions start on the first one
+    #[arg(long = "vault")]
+    vaults: Vec<String>,
+
+    /// Read the vault password from a file, e.g. a systemd credential
+    #[arg(long, value_name = "PATH", group = "unlock")]
+    password_file: Option<PathBuf>,
+
+    /// Read the vault password from an inherited file descriptor
+    #[arg(long, value_name = "FD", group = "unlock")]
+    password_fd: Option<i32>,
+
+    /// Read the vault password from this environment variable.
+    /// No variable is read unless named here
+    #[arg(long, value_name = "VAR", group = "unlock")]
+    password_env: Option<String>,
+
+    /// Unlock with a key file holding the root key as hex or a recovery phrase
+    #[arg(long, value_name = "PATH", group = "unlock")]
+    key_file: Option<PathBuf>,
+
     /// Server mode
     #[command(subcommand)]
     mode: Option<ServerMode>,
@@ -85,29 +108,64 @@ async fn main() -> Result<()> {
             .map_err(|e| mimir_core::MimirError::Config(format!("Failed to load config: {}", e)))?;
     }
 
+    // Resolve the vaults to open, each with its own paths
+    let mut vault_names = if cli.vaults.is_empty() {
+        config.vault_names()
+    } else {
+        cli.vaults.clone()
+    };
+    let mut seen = std::collections::HashSet::new();
+    vault_names.retain(|name| seen.insert(name.clone()));
+    let vault_configs = vault_names
+        .iter()
+        .map(|name| Ok((name.clone(), config.for_vault(name)?)))
+        .collect::<Result<Vec<(String, Config)>>>()?;
+
     // Check vault status and auto-initialize if needed
-    info!("Checking vault status...");
-    info!("Config vault path: {}", config.get_vault_path().display());
-    info!(
-        "Config database path: {}",
-        config.get_database_path().display()
-    );
-    info!("Config keyset path: {}", config.get_keyset_path().display());
-    let vault_status = check_vault_status(&config);
-    info!("{}", vault_status.status_message());
-
-    if !vault_status.is_ready() {
-        if cli.auto_init {
-            info!("Auto-initializing vault...");
-            ensure_vault_ready(&config, true).await?;
-        } else {
-            error!("Vault not ready. Use --auto-init to auto-initialize or run 'mimir-cli init' first.");
-            return Err(mimir_core::MimirError::Initialization(
-                "Vault not initialized".to_string(),
-            ));
+    for (name, vault_config) in &vault_configs {
+        info!("Checking status of vault '{}'...", name);
+        info!(
+            "Config vault path: {}",
+            vault_config.get_vault_path().display()
+        );
+        info!(
+            "Config database path: {}",
+            vault_config.get_database_path().display()
+        );
+        info!(
+            "Config keyset path: {}",
+            vault_config.get_keyset_path().display()
+        );
+        let vault_status = check_vault_status(vault_config);
+        info!("{}", vault_status.status_message());
+
+        if !vault_status.is_ready() {
+            if cli.auto_init {
+                info!("Auto-initializing vault '{}'...", name);
+                ensure_vault_ready(vault_config, true).await?;
+            } else {
+                error!("Vault '{}' not ready. Use --auto-init to auto-initialize or run 'mimir-cli init' first.", name);
+                return Err(mimir_core::MimirError::Initialization(format!(
+                    "Vault '{}' not initialized",
+                    name
+                )));
+            }
         }
     }
 
+    // Read the unlock secret once; a file descriptor cannot be read twice
+    let unlock = if let Some(path) = &cli.password_file {
+        Unlock::password_from_file(path)?
+    } else if let Some(fd) = cli.password_fd {
+        Unlock::password_from_fd(fd)?
+    } else if let Some(var) = &cli.password_env {
+        Unlock::password_from_env(var)?
+    } else if let Some(path) = &cli.key_file {
+        Unlock::KeyFile(path.clone())
+    } else {
+        Unlock::Keychain
+    };
+
     // Ensure model files are present and valid
     let (model_path, _tokenizer_path, _vocab_path) =
         model::ensure_model_files().await.map_err(mimir_core::MimirError::ServerError)?;
@@ -120,6 +178,20 @@ async fn main() -> Result<()> {
     // Initialize LLM service
     llm_service::initialize_llm_service(&config).await?;
 
+    // Open every vault and host them all in one MCP server
+    let (default_name, default_config) = &vault_configs[0];
+    let storage = open_vault(default_name, default_config, &unlock, &model_path).await?;
+    let mut mcp_server = mcp::MimirServer::with_vault(default_name, storage);
+    for (name, vault_config) in &vault_configs[1..] {
+        let storage = open_vault(name, vault_config, &unlock, &model_path).await?;
+        mcp_server = mcp_server.add_vault(name, storage);
+    }
+
+    // Purge expired trash and memories and take backups in the background
+    mcp_server.start_trash_purge(&config.trash);
+    mcp_server.start_expiry_reaper(&config.expiry);
+    mcp_server.start_backups(&config);
+
     // Determine server mode
     let server_mode = cli.mode.unwrap_or(ServerMode::Mcp { stdio: false });
 
@@ -129,70 +201,80 @@ async fn main() -> Result<()> {
             info!("Starting MCP server");
             if force_stdio {
                 config.mcp.transport = mimir_core::config::McpTransport::Stdio;
-                start_mcp_server(config).await
+                start_mcp_service(mcp_server).await
             } else {
-                // Setup crypto managers
-                let (db_crypto_manager, storage_crypto_manager) = setup_crypto_managers(&config).await?;
-                // Create database
-                let database = create_database(&config, db_crypto_manager)?;
-                // Create vector store
-                let vector_store = create_vector_store_with_model(&config, &model_path).await?;
-                // Create integrated storage
-                let storage = create_integrated_storage(database, vector_store, storage_crypto_manager).await?;
-                // Create the MCP server with integrated storage
-                let mcp_server = mcp::MimirServer::new(storage);
                 start_mcp_streamhttp_server(config, mcp_server).await
             }
         }
     }
 }
 
-/// Setup crypto managers for database and storage encryption
-async fn setup_crypto_managers(
+/// Unlock a vault and open its database, vector store and integrated storage
+async fn open_vault(
+    name: &str,
     config: &Config,
-) -> Result<(
-    mimir_core::crypto::CryptoManager,
-    mimir_core::crypto::CryptoManager,
-)> {
-    if config.use_password_encryption {
-        info!("Password encryption detected. Please enter your vault password:");
-
-        // Read password from stdin
-        let mut password = String::new();
-        std::io::stdin().read_line(&mut password).map_err(|e| {
-            mimir_core::MimirError::Initialization(format!("Failed to read password: {}", e))
-        })?;
-        let password = password.trim();
-
-        if password.is_empty() {
-            return Err(mimir_core::MimirError::Initialization(
-                "Password cannot be empty".to_string(),
-            ));
-        }
+    unlock: &Unlock,
+    model_path: &std::path::Path,
+) -> Result<storage::IntegratedStorage> {
+    info!("Opening vault '{}'", name);
+    let crypto_manager = setup_crypto_manager(config, unlock).await?;
+    let database = create_database(config, crypto_manager)?;
+    let vector_store = create_vector_store_with_model(config, model_path).await?;
+    create_integrated_storage(config, database, vector_store).await
+}
 
-        info!("Attempting to unlock vault with provided password...");
+/// Setup the crypto manager shared by the database and storage
+///
+/// A password vault without a password or key file from the command line
+/// prompts on stdin. A keychain vault ignores a password.
+async fn setup_crypto_manager(
+    config: &Config,
+    unlock: &Unlock,
+) -> Result<Arc<mimir_core::crypto::CryptoManager>> {
+    let keyset_path = config.get_keyset_path();
+    let crypto_manager = match unlock {
+        Unlock::Keychain if config.use_password_encryption => {
+            info!("Password encryption detected. Please enter your vault password:");
+
+            // Read password from stdin
+            let mut password = zeroize::Zeroizing::new(String::new());
+            std::io::stdin().read_line(&mut password).map_err(|e| {
+                mimir_core::MimirError::Initialization(format!("Failed to read password: {}", e))
+            })?;
+            let password = zeroize::Zeroizing::new(password.trim().to_string());
+
+            if password.is_empty() {
+                return Err(mimir_core::MimirError::Initialization(
+                    "Password cannot be empty".to_string(),
+                ));
+            }
 
-        let db_crypto_manager =
-            mimir_core::crypto::CryptoManager::with_password(&config.get_keyset_path(), password)?;
-        let storage_crypto_manager =
-            mimir_core::crypto::CryptoManager::with_password(&config.get_keyset_path(), password)?;
+            info!("Attempting to unlock vault with provided password...");
+            Unlock::Password(password).crypto_manager(&keyset_path)
+        }
+        Unlock::Password(_) if !config.use_password_encryption => {
+            mimir_core::crypto::CryptoManager::new(&keyset_path)
+        }
+        unlock => unlock.crypto_manager(&keyset_path),
+    };
 
-        Ok((db_crypto_manager, storage_crypto_manager))
-    } else {
-        let db_crypto_manager = mimir_core::crypto::CryptoManager::new(&config.get_keyset_path())?;
-        let storage_crypto_manager =
-            mimir_core::crypto::CryptoManager::new(&config.get_keyset_path())?;
-        Ok((db_crypto_manager, storage_crypto_manager))
-    }
+    // Name the keyset, so a damaged one is not mistaken for a wrong password
+    let crypto_manager = crypto_manager.map_err(|e| match e {
+        mimir_core::MimirError::CorruptKeyset(reason) => {
+            mimir_core::MimirError::CorruptKeyset(format!("{}: {}", keyset_path.display(), reason))
+        }
+        e => e,
+    })?;
+    Ok(Arc::new(crypto_manager))
 }
 
 /// Create database with crypto manager
 fn create_database(
     config: &Config,
-    db_crypto_manager: mimir_core::crypto::CryptoManager,
+    crypto_manager: Arc<mimir_core::crypto::CryptoManager>,
 ) -> Result<mimir_db::Database> {
     let database =
-        mimir_db::Database::with_crypto_manager(&config.get_database_path(), db_crypto_manager)?;
+        mimir_db::Database::with_crypto_manager(&config.get_database_path(), crypto_manager)?;
     Ok(database)
 }
 
@@ -261,19 +343,33 @@ async fn create_vector_store_with_model(config: &Config, model_path: &std::path:
     }
 }
 
-/// Create integrated storage system
+/// Create integrated storage system, sharing the database's crypto manager
 async fn create_integrated_storage(
+    config: &Config,
     database: mimir_db::Database,
     vector_store: mimir_vector::ThreadSafeVectorStore,
-    storage_crypto_manager: mimir_core::crypto::CryptoManager,
 ) -> Result<storage::IntegratedStorage> {
+    let crypto_manager = database.crypto_manager().clone();
     let mut storage =
-        storage::IntegratedStorage::new(database, vector_store, storage_crypto_manager).await?;
+        storage::IntegratedStorage::new(database, vector_store, crypto_manager)
+            .await?
+            .with_expiry_config(config.expiry.clone());
     
     // Add LLM service if available
     if let Some(llm_service) = llm_service::get_llm_service() {
         storage = storage.with_llm_service(llm_service);
     }
+
+    // A lost vector index is rebuilt from the embeddings stored in the database
+    if storage.get_stats().await?.vector_memories == 0 {
+        match storage.rebuild_vector_index().await {
+            Ok(report) if report.indexed > 0 => {
+                info!("Rebuilt vector index with {} vectors", report.indexed)
+            }
+            Ok(_) => {}
+            Err(e) => warn!("Failed to rebuild vector index: {}", e),
+        }
+    }
     
     Ok(storage)
 }
@@ -322,28 +418,6 @@ async fn start_mcp_service(mcp_server: mcp::MimirServer) -> Result<()> {
     }
 }
 
-async fn start_mcp_server(config: Config) -> Result<()> {
-    info!("Starting MCP server");
-
-    // Setup crypto managers
-    let (db_crypto_manager, storage_crypto_manager) = setup_crypto_managers(&config).await?;
-
-    // Create database
-    let database = create_database(&config, db_crypto_manager)?;
-
-    // Create vector store
-    let vector_store = create_vector_store_with_model(&config, &model::ensure_model_files().await.map_err(mimir_core::MimirError::ServerError).unwrap().0).await?;
-
-    // Create integrated storage
-    let storage = create_integrated_storage(database, vector_store, storage_crypto_manager).await?;
-
-    // Create the MCP server with integrated storage
-    let mcp_server = mcp::MimirServer::new(storage);
-
-    // Start the MCP service
-    start_mcp_service(mcp_server).await
-}
-
 async fn start_mcp_streamhttp_server(config: Config, mcp_server: mcp::MimirServer) -> Result<()> {
     let addr = format!("{}:{}", config.server.host, config.server.port);
     println!("Starting MCP server on {}", addr);
@@ -351,7 +425,8 @@ async fn start_mcp_streamhttp_server(config: Config, mcp_server: mcp::MimirServe
         .map_err(|e| mimir_core::MimirError::ServerError(format!("Failed to bind: {}", e)))?;
 
     let service = StreamableHttpService::new(
-        move || Ok(mcp_server.clone()),
+        // Every session starts on the default vault
+        move || Ok(mcp_server.new_session()),
         LocalSessionManager::default().into(),
         Default::default(),
     );
@@ -380,6 +455,14 @@ mod tests {
         assert!(cli.config.is_none());
         assert!(cli.port.is_none());
         assert!(cli.mode.is_none());
+        assert!(cli.vaults.is_empty());
+    }
+
+    #[test]
+    fn test_cli_parsing_vaults() {
+        let cli = Cli::try_parse_from(&["mimir", "--vault", "work", "--vault", "default", "mcp"])
+            .unwrap();
+        assert_eq!(cli.vaults, vec!["work", "default"]);
     }
 
     #[test]
@@ -431,6 +514,7 @@ mod tests {
         assert!(help_str.contains("--debug"));
         assert!(help_str.contains("--port"));
         assert!(help_str.contains("--config"));
+        assert!(help_str.contains("--vault"));
         assert!(help_str.contains("mcp"));
     }
 
@@ -525,7 +609,7 @@ mod tests {
             let crypto_manager = mimir_core::crypto::CryptoManager::new(&keyset_path)
                 .expect("Failed to create test crypto manager");
 
-            let result = create_database(&config, crypto_manager);
+            let result = create_database(&config, Arc::new(crypto_manager));
             assert!(result.is_ok());
             // Removed assertion that db_path.exists() as file creation is not guaranteed until a write occurs
         }
@@ -542,7 +626,7 @@ mod tests {
             let crypto_manager = mimir_core::crypto::CryptoManager::new(&keyset_path)
                 .expect("Failed to create test crypto manager");
 
-            let result = create_database(&config, crypto_manager);
+            let result = create_database(&config, Arc::new(crypto_manager));
             // This should fail due to invalid path
             assert!(result.is_err());
         }
@@ -554,13 +638,11 @@ mod tests {
             let keyset_path = temp_dir.path().join("keyset.json");
 
             // Create crypto manager
-            let db_crypto_manager = mimir_core::crypto::CryptoManager::new(&keyset_path)
-                .expect("Failed to create test crypto manager");
-            let storage_crypto_manager = mimir_core::crypto::CryptoManager::new(&keyset_path)
+            let crypto_manager = mimir_core::crypto::CryptoManager::new(&keyset_path)
                 .expect("Failed to create test crypto manager");
 
             // Create database
-            let database = mimir_db::Database::with_crypto_manager(&db_path, db_crypto_manager)
+            let database = mimir_db::Database::with_crypto_manager(&db_path, crypto_manager)
                 .expect("Failed to create test database");
 
             // Create vector store
@@ -568,8 +650,7 @@ mod tests {
                 mimir_vector::ThreadSafeVectorStore::new(temp_dir.path(), 128, None, None)
                     .expect("Failed to create test vector store");
 
-            let result =
-                create_integrated_storage(database, vector_store, storage_crypto_manager).await;
+            let result = create_integrated_storage(&Config::default(), database, vector_store).await;
             assert!(result.is_ok());
         }
 
@@ -588,7 +669,7 @@ mod tests {
         }
 
         #[test]
-        fn test_setup_crypto_managers_no_password() {
+        fn test_setup_crypto_manager_no_password() {
             let temp_dir = TempDir::new().unwrap();
             let mut config = Config::default();
             config.vault_path = temp_dir.path().to_path_buf();
@@ -597,14 +678,45 @@ mod tests {
             // This test would require mocking stdin for password input
             // For now, we'll just test the no-password path
             let rt = tokio::runtime::Runtime::new().unwrap();
-            let result = rt.block_on(setup_crypto_managers(&config));
+            let result = rt.block_on(setup_crypto_manager(&config, &Unlock::Keychain));
             assert!(result.is_ok());
 
-            let (db_crypto, storage_crypto) = result.unwrap();
-            // Verify both crypto managers were created by checking keyset file exists
+            // Verify the crypto manager was created by checking keyset file exists
             assert!(config.get_keyset_path().exists());
         }
 
+        #[test]
+        fn test_setup_crypto_manager_password_file() {
+            let temp_dir = TempDir::new().unwrap();
+            let mut config = Config::default();
+            config.vault_path = temp_dir.path().to_path_buf();
+            config.use_password_encryption = true;
+            mimir_core::crypto::CryptoManager::with_password(&config.get_keyset_path(), "hunter2")
+                .expect("Failed to create test keyset");
+
+            let password_file = temp_dir.path().join("password");
+            let rt = tokio::runtime::Runtime::new().unwrap();
+            std::fs::write(&password_file, "hunter2\n").unwrap();
+            let unlock = Unlock::password_from_file(&password_file).unwrap();
+            assert!(rt.block_on(setup_crypto_manager(&config, &unlock)).is_ok());
+
+            std::fs::write(&password_file, "hunter3\n").unwrap();
+            let unlock = Unlock::password_from_file(&password_file).unwrap();
+            assert!(matches!(
+                rt.block_on(setup_crypto_manager(&config, &unlock)),
+                Err(mimir_core::MimirError::WrongPassword)
+            ));
+
+            // A damaged keyset is reported as such, with its path
+            std::fs::write(config.get_keyset_path(), "{").unwrap();
+            match rt.block_on(setup_crypto_manager(&config, &unlock)) {
+                Err(mimir_core::MimirError::CorruptKeyset(reason)) => {
+                    assert!(reason.contains("keyset"));
+                }
+                _ => panic!("Expected a corrupt keyset error"),
+            }
+        }
+
         #[test]
         fn test_helper_functions_error_handling() {
             let temp_dir = TempDir::new().unwrap();
@@ -617,7 +729,7 @@ mod tests {
                 .expect("Failed to create test crypto manager");
 
             // Test that create_database properly handles invalid paths
-            let result = create_database(&config, crypto_manager);
+            let result = create_database(&config, Arc::new(crypto_manager));
             assert!(result.is_err());
 
             // Verify the error is of the expected type
@@ -1,11 +1,16 @@
-use crate::storage::IntegratedStorage;
-use mimir_core::{Memory as CoreMemory, MemoryClass};
+use crate::storage::{IntegratedStorage, StorageStats, SYSTEM_APP_ID};
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use mimir_core::{
+    Memory as CoreMemory, MemoryClass, MemoryFilter, MemoryRelation, RelationKind, DEFAULT_VAULT,
+};
+use mimir_db::RelatedMemory;
 use rmcp::{
     handler::server::router::tool::ToolRouter, handler::server::tool::Parameters, model::*,
-    schemars, tool, tool_handler, tool_router,
+    schemars, service::RequestContext, tool, tool_handler, tool_router, RoleServer,
 };
+use std::collections::BTreeMap;
 use std::future::Future;
-use std::sync::Arc;
+use std::sync::{Arc, PoisonError, RwLock};
 use tracing::{info, warn};
 use uuid::Uuid;
 
@@ -14,18 +19,34 @@ use uuid::Uuid;
 pub struct AddMemoryParams {
     pub source: String,
     pub text: String,
+    /// When the memory should be deleted, as an RFC 3339 timestamp
+    /// (defaults to the class TTL, if any)
+    #[serde(default)]
+    pub expires_at: Option<String>,
+    /// Vault to use instead of the session's vault
+    #[serde(default)]
+    pub vault: Option<String>,
 }
 
 /// Parameters for deleting a memory
 #[derive(Debug, schemars::JsonSchema, serde::Deserialize, serde::Serialize)]
 struct DeleteMemoryParams {
     id: String,
+    /// Vault to use instead of the session's vault
+    #[serde(default)]
+    vault: Option<String>,
 }
 
 /// Parameters for searching memories
 #[derive(Debug, schemars::JsonSchema, serde::Deserialize, serde::Serialize)]
 struct SearchMemoriesParams {
     query: String,
+    /// Also return memories up to this many relation hops from each result
+    #[serde(default)]
+    expand_hops: Option<usize>,
+    /// Vault to use instead of the session's vault
+    #[serde(default)]
+    vault: Option<String>,
 }
 
 /// Parameters for updating a memory
@@ -33,27 +54,353 @@ struct SearchMemoriesParams {
 struct UpdateMemoryParams {
     id: String,
     text: String,
+    /// Vault to use instead of the session's vault
+    #[serde(default)]
+    vault: Option<String>,
+}
+
+/// Parameters for listing memories
+#[derive(Debug, Default, schemars::JsonSchema, serde::Deserialize, serde::Serialize)]
+struct ListMemoriesParams {
+    /// Memory class: personal, work, health, financial or a custom class
+    class: Option<String>,
+    /// Owning app of the memories
+    source: Option<String>,
+    /// Only memories carrying all of these tags
+    #[serde(default)]
+    tags: Vec<String>,
+    scope: Option<String>,
+    /// RFC 3339 timestamps bounding creation and last update time
+    created_after: Option<String>,
+    created_before: Option<String>,
+    updated_after: Option<String>,
+    updated_before: Option<String>,
+    /// Cursor returned by the previous page
+    cursor: Option<String>,
+    /// Page size (default 20)
+    limit: Option<usize>,
+    /// Vault to use instead of the session's vault
+    #[serde(default)]
+    vault: Option<String>,
+}
+
+/// Parameters for tools that only act on a vault
+#[derive(Debug, Default, schemars::JsonSchema, serde::Deserialize, serde::Serialize)]
+struct VaultParams {
+    /// Vault to use instead of the session's vault
+    #[serde(default)]
+    vault: Option<String>,
+}
+
+/// Parameters for choosing the session's vault
+#[derive(Debug, schemars::JsonSchema, serde::Deserialize, serde::Serialize)]
+struct SelectVaultParams {
+    /// Name of a vault hosted by this server
+    name: String,
+}
+
+/// Default page size for the list_memories tool
+const DEFAULT_LIST_LIMIT: usize = 20;
+
+/// Parse an optional RFC 3339 timestamp tool parameter
+fn parse_timestamp(
+    value: Option<String>,
+) -> std::result::Result<Option<chrono::DateTime<chrono::Utc>>, ErrorData> {
+    value
+        .map(|value| {
+            chrono::DateTime::parse_from_rfc3339(&value)
+                .map(|at| at.with_timezone(&chrono::Utc))
+                .map_err(|e| {
+                    ErrorData::invalid_request(
+                        format!("Invalid timestamp '{}': {}", value, e),
+                        None,
+                    )
+                })
+        })
+        .transpose()
+}
+
+/// Parameters for listing the revision history of a memory
+#[derive(Debug, schemars::JsonSchema, serde::Deserialize, serde::Serialize)]
+struct MemoryRevisionsParams {
+    id: String,
+    /// Vault to use instead of the session's vault
+    #[serde(default)]
+    vault: Option<String>,
+}
+
+/// Parameters for reading a memory at a point in time
+#[derive(Debug, schemars::JsonSchema, serde::Deserialize, serde::Serialize)]
+struct MemoryAsOfParams {
+    id: String,
+    /// RFC 3339 timestamp, e.g. 2024-05-01T12:00:00Z
+    timestamp: String,
+    /// Vault to use instead of the session's vault
+    #[serde(default)]
+    vault: Option<String>,
+}
+
+/// Parameters for reverting a memory to an earlier revision
+#[derive(Debug, schemars::JsonSchema, serde::Deserialize, serde::Serialize)]
+struct RevertMemoryParams {
+    id: String,
+    revision: u32,
+    /// Vault to use instead of the session's vault
+    #[serde(default)]
+    vault: Option<String>,
+}
+
+/// Parameters for restoring a memory from the trash
+#[derive(Debug, schemars::JsonSchema, serde::Deserialize, serde::Serialize)]
+struct RestoreMemoryParams {
+    id: String,
+    /// Vault to use instead of the session's vault
+    #[serde(default)]
+    vault: Option<String>,
+}
+
+/// Parameters for relating two memories
+#[derive(Debug, schemars::JsonSchema, serde::Deserialize, serde::Serialize)]
+struct LinkMemoriesParams {
+    source_id: String,
+    target_id: String,
+    /// supersedes, related_to, derived_from or contradicts
+    relation: String,
+    /// Optional description of the relation
+    label: Option<String>,
+    /// Vault to use instead of the session's vault
+    #[serde(default)]
+    vault: Option<String>,
+}
+
+/// Parameters for removing a relation between two memories
+#[derive(Debug, schemars::JsonSchema, serde::Deserialize, serde::Serialize)]
+struct UnlinkMemoriesParams {
+    source_id: String,
+    target_id: String,
+    /// Relation type to remove; all relations between the two if omitted
+    relation: Option<String>,
+    /// Vault to use instead of the session's vault
+    #[serde(default)]
+    vault: Option<String>,
+}
+
+/// Parameters for walking the relations of a memory
+#[derive(Debug, schemars::JsonSchema, serde::Deserialize, serde::Serialize)]
+struct RelatedMemoriesParams {
+    id: String,
+    /// Maximum number of relation hops (default 1)
+    hops: Option<usize>,
+    /// Only follow these relation types
+    #[serde(default)]
+    relations: Vec<String>,
+    /// Vault to use instead of the session's vault
+    #[serde(default)]
+    vault: Option<String>,
+}
+
+/// Parameters for attaching a file to a memory
+#[derive(Debug, schemars::JsonSchema, serde::Deserialize, serde::Serialize)]
+struct AddAttachmentParams {
+    memory_id: String,
+    /// File name, e.g. receipt.pdf
+    name: String,
+    /// MIME type, e.g. application/pdf
+    mime_type: String,
+    /// File contents, base64 encoded
+    data: String,
+    /// Vault to use instead of the session's vault
+    #[serde(default)]
+    vault: Option<String>,
+}
+
+/// Parameters for listing the attachments of a memory
+#[derive(Debug, schemars::JsonSchema, serde::Deserialize, serde::Serialize)]
+struct ListAttachmentsParams {
+    memory_id: String,
+    /// Vault to use instead of the session's vault
+    #[serde(default)]
+    vault: Option<String>,
+}
+
+/// Parameters for reading or removing an attachment
+#[derive(Debug, schemars::JsonSchema, serde::Deserialize, serde::Serialize)]
+struct AttachmentParams {
+    id: String,
+    /// Vault to use instead of the session's vault
+    #[serde(default)]
+    vault: Option<String>,
+}
+
+/// Default hop count for the related_memories tool
+const DEFAULT_RELATION_HOPS: usize = 1;
+
+/// Parse a relation type tool parameter
+fn parse_relation(value: &str) -> std::result::Result<RelationKind, ErrorData> {
+    value
+        .parse()
+        .map_err(|e: String| ErrorData::invalid_request(e, None))
+}
+
+/// Format the related memories attached to a search result
+fn format_neighbors(neighbors: &[RelatedMemory]) -> String {
+    neighbors
+        .iter()
+        .map(|related| {
+            format!(
+                "   - {} ({} hop{}) ID: {} | Content: '{}'\n",
+                related.relation.kind.as_str(),
+                related.hops,
+                if related.hops == 1 { "" } else { "s" },
+                related.memory.id,
+                related.memory.content
+            )
+        })
+        .collect()
+}
+
+/// Format the database breakdowns, sizes and orphans of `get_vault_stats`
+fn format_vault_stats(stats: &StorageStats) -> String {
+    let database = &stats.database;
+    let mut text = format!(
+        "\n• In trash: {}\n• Revisions: {}\n• Relations: {}\n• Attachments: {} ({} bytes)",
+        database.trashed,
+        database.revisions,
+        database.relations,
+        database.attachments,
+        database.attachment_bytes
+    );
+    text.push_str(&format!(
+        "\n• On disk: database {} bytes, vectors {} bytes, keyset {} bytes",
+        database.database_bytes, stats.vector_bytes, stats.keyset_bytes
+    ));
+    text.push_str(&format!(
+        "\n• Orphans: {} memories without vectors, {} vectors without memories",
+        stats.orphans.memories_without_vectors, stats.orphans.vectors_without_memories
+    ));
+    for (title, counts) in [
+        ("class", &database.by_class),
+        ("source", &database.by_source),
+        ("tag", &database.by_tag),
+        ("month", &database.by_month),
+    ] {
+        if counts.is_empty() {
+            continue;
+        }
+        text.push_str(&format!("\nBy {}:", title));
+        for (key, count) in counts {
+            text.push_str(&format!("\n  {}: {}", key, count));
+        }
+    }
+    text
 }
 
 /// Mimir MCP Server for memory management
+///
+/// One server can host several vaults. Each tool call acts on the vault it
+/// names, or else on the vault selected for the session.
 #[derive(Clone)]
 pub struct MimirServer {
     /// Tool router for handling MCP tool calls
     pub tool_router: ToolRouter<Self>,
-    /// Integrated storage for database and vector operations
-    storage: Arc<IntegratedStorage>,
+    /// Integrated storage of each hosted vault, by name
+    vaults: BTreeMap<String, Arc<IntegratedStorage>>,
+    /// Vault a new session starts on
+    default_vault: String,
+    /// Vault selected for the current session
+    session_vault: Arc<RwLock<String>>,
+    /// App the session's client named itself as, recorded in the audit log
+    session_app: Arc<RwLock<String>>,
 }
 
 #[tool_router]
 impl MimirServer {
-    /// Create a new Mimir MCP server instance
+    /// Create a new Mimir MCP server instance hosting the default vault
     pub fn new(storage: IntegratedStorage) -> Self {
+        Self::with_vault(DEFAULT_VAULT, storage)
+    }
+
+    /// Create a server hosting one named vault, which sessions start on
+    pub fn with_vault(name: impl Into<String>, storage: IntegratedStorage) -> Self {
+        let name = name.into();
         Self {
             tool_router: Self::tool_router(),
-            storage: Arc::new(storage),
+            vaults: BTreeMap::from([(name.clone(), Arc::new(storage))]),
+            session_vault: Arc::new(RwLock::new(name.clone())),
+            session_app: Arc::new(RwLock::new(SYSTEM_APP_ID.to_string())),
+            default_vault: name,
         }
     }
 
+    /// Host another named vault
+    pub fn add_vault(mut self, name: impl Into<String>, storage: IntegratedStorage) -> Self {
+        self.vaults.insert(name.into(), Arc::new(storage));
+        self
+    }
+
+    /// Server for a new client session, starting on the default vault
+    pub fn new_session(&self) -> Self {
+        Self {
+            session_vault: Arc::new(RwLock::new(self.default_vault.clone())),
+            session_app: Arc::new(RwLock::new(SYSTEM_APP_ID.to_string())),
+            ..self.clone()
+        }
+    }
+
+    /// Names of the hosted vaults
+    pub fn vault_names(&self) -> Vec<String> {
+        self.vaults.keys().cloned().collect()
+    }
+
+    /// Name of the vault selected for the session
+    fn session_vault(&self) -> String {
+        self.session_vault
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+    }
+
+    /// App the session's client named itself as when it connected
+    fn session_app(&self) -> String {
+        self.session_app
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+    }
+
+    /// Record the app the session's client named itself as
+    fn set_session_app(&self, app_id: &str) {
+        *self
+            .session_app
+            .write()
+            .unwrap_or_else(PoisonError::into_inner) = app_id.to_string();
+    }
+
+    /// Storage of the named vault, or of the session's vault if none is named
+    fn storage(
+        &self,
+        vault: Option<&str>,
+    ) -> std::result::Result<&Arc<IntegratedStorage>, ErrorData> {
+        let name = vault
+            .map(str::to_string)
+            .unwrap_or_else(|| self.session_vault());
+        self.vaults.get(&name).ok_or_else(|| {
+            ErrorData::invalid_request(
+                format!(
+                    "Unknown vault '{}'; this server hosts: {}",
+                    name,
+                    self.vault_names().join(", ")
+                ),
+                None,
+            )
+        })
+    }
+
+    /// Storage of the vault sessions start on
+    fn default_storage(&self) -> &Arc<IntegratedStorage> {
+        &self.vaults[&self.default_vault]
+    }
+
     /// Add sample data for demonstration
     pub async fn add_sample_data(&self) -> std::result::Result<(), Box<dyn std::error::Error>> {
         // Add some sample memories
@@ -62,6 +409,7 @@ impl MimirServer {
                 id: Uuid::new_v4(),
                 content: "I need to remember to buy groceries tomorrow".to_string(),
                 embedding: None,
+                embedding_model: None,
                 class: MemoryClass::Personal,
                 scope: None,
                 tags: vec!["shopping".to_string(), "reminder".to_string()],
@@ -69,11 +417,13 @@ impl MimirServer {
                 key_id: "personal".to_string(),
                 created_at: chrono::Utc::now(),
                 updated_at: chrono::Utc::now(),
+                expires_at: None,
             },
             CoreMemory {
                 id: Uuid::new_v4(),
                 content: "Meeting with client scheduled for next Tuesday at 2 PM".to_string(),
                 embedding: None,
+                embedding_model: None,
                 class: MemoryClass::Work,
                 scope: None,
                 tags: vec!["meeting".to_string(), "client".to_string()],
@@ -81,11 +431,12 @@ impl MimirServer {
                 key_id: "work".to_string(),
                 created_at: chrono::Utc::now(),
                 updated_at: chrono::Utc::now(),
+                expires_at: None,
             },
         ];
 
         for memory in sample_memories {
-            self.storage
+            self.default_storage()
                 .add_memory(memory)
                 .await
                 .map_err(|e| Box::new(e) as Box<dyn std::error::Error>)?;
@@ -98,13 +449,21 @@ impl MimirServer {
     #[tool(description = "Pass all useful information about a user")]
     async fn add_memory(
         &self,
-        Parameters(AddMemoryParams { source, text }): Parameters<AddMemoryParams>,
+        Parameters(AddMemoryParams {
+            source,
+            text,
+            expires_at,
+            vault,
+        }): Parameters<AddMemoryParams>,
     ) -> std::result::Result<CallToolResult, ErrorData> {
+        let storage = self.storage(vault.as_deref())?;
+        let expires_at = parse_timestamp(expires_at)?;
+
         // Generate a unique ID for the memory
         let memory_id = Uuid::new_v4();
 
         // Try to summarize the memory content if LLM service is available
-        let processed_content = if let Some(llm_service) = self.storage.get_llm_service() {
+        let processed_content = if let Some(llm_service) = storage.get_llm_service() {
             match llm_service.summarize_memory(&text, 150).await {
                 Ok(summary) => {
                     info!("Previous content: {}", text);
@@ -126,6 +485,7 @@ impl MimirServer {
             id: memory_id,
             content: processed_content,
             embedding: None,
+            embedding_model: None,
             class: MemoryClass::Personal, // Default to personal
             scope: None,
             tags: vec![],
@@ -133,10 +493,11 @@ impl MimirServer {
             key_id: memory_id.to_string(),
             created_at: chrono::Utc::now(),
             updated_at: chrono::Utc::now(),
+            expires_at,
         };
 
         // Store memory using integrated storage
-        match self.storage.add_memory(core_memory).await {
+        match storage.add_memory(core_memory).await {
             Ok(result) => {
                 let success_text = if result.database_stored && result.vector_stored {
                     format!(
@@ -162,19 +523,22 @@ impl MimirServer {
     }
 
     /// Delete a memory by ID
-    #[tool(description = "Delete a memory by ID")]
+    #[tool(
+        description = "Delete a memory by ID. The memory moves to the trash and can be restored with restore_memory until it is purged"
+    )]
     async fn delete_memory(
         &self,
-        Parameters(DeleteMemoryParams { id }): Parameters<DeleteMemoryParams>,
+        Parameters(DeleteMemoryParams { id, vault }): Parameters<DeleteMemoryParams>,
     ) -> std::result::Result<CallToolResult, ErrorData> {
+        let storage = self.storage(vault.as_deref())?;
         let memory_id = Uuid::parse_str(&id)
             .map_err(|e| ErrorData::invalid_request(format!("Invalid UUID: {}", e), None))?;
 
-        match self.storage.delete_memory(memory_id).await {
+        match storage.delete_memory(&self.session_app(), memory_id).await {
             Ok(deleted) => {
                 if deleted {
                     Ok(CallToolResult::success(vec![Content::text(format!(
-                        "Successfully deleted memory with ID: {}",
+                        "Moved memory with ID {} to the trash",
                         id
                     ))]))
                 } else {
@@ -195,9 +559,28 @@ impl MimirServer {
     #[tool(description = "Get provided context from a users message")]
     async fn search_memories(
         &self,
-        Parameters(SearchMemoriesParams { query }): Parameters<SearchMemoriesParams>,
+        Parameters(SearchMemoriesParams {
+            query,
+            expand_hops,
+            vault,
+        }): Parameters<SearchMemoriesParams>,
     ) -> std::result::Result<CallToolResult, ErrorData> {
-        match self.storage.search_memories(&query, 5).await {
+        let storage = self.storage(vault.as_deref())?;
+        let search_result = match storage
+            .search_memories(&self.session_app(), &query, 5)
+            .await
+        {
+            Ok(mut results) => match expand_hops {
+                Some(hops) if hops > 0 => storage
+                    .expand_with_neighbors(&mut results, hops)
+                    .await
+                    .map(|_| results),
+                _ => Ok(results),
+            },
+            Err(e) => Err(e),
+        };
+
+        match search_result {
             Ok(results) => {
                 if results.is_empty() {
                     Ok(CallToolResult::success(vec![Content::text(format!(
@@ -206,10 +589,14 @@ impl MimirServer {
                     ))]))
                 } else {
                     // Try to summarize search results if LLM service is available
-                    let result_text = if let Some(llm_service) = self.storage.get_llm_service() {
+                    let result_text = if let Some(llm_service) = storage.get_llm_service() {
                         // Extract content from search results for summarization
                         let search_contents: Vec<String> = results.iter()
-                            .map(|result| result.memory.content.clone())
+                            .flat_map(|result| {
+                                std::iter::once(&result.memory)
+                                    .chain(result.neighbors.iter().map(|related| &related.memory))
+                            })
+                            .map(|memory| memory.content.clone())
                             .collect();
                         
                         match llm_service.summarize_search_results(&query, &search_contents).await {
@@ -242,6 +629,7 @@ impl MimirServer {
                                         result.similarity,
                                         result.memory.content
                                     ));
+                                    detailed_text.push_str(&format_neighbors(&result.neighbors));
                                 }
                                 detailed_text
                             }
@@ -258,6 +646,7 @@ impl MimirServer {
                                 result.similarity,
                                 result.memory.content
                             ));
+                            detailed_text.push_str(&format_neighbors(&result.neighbors));
                         }
                         detailed_text
                     };
@@ -272,23 +661,48 @@ impl MimirServer {
         }
     }
 
-    /// List all memories in the vault
-    #[tool(description = "List all memories about the user")]
-    async fn list_memories(&self) -> std::result::Result<CallToolResult, ErrorData> {
-        match self
-            .storage
-            .get_memories_by_class(&MemoryClass::Personal)
+    /// List memories in the vault, one page at a time
+    #[tool(
+        description = "List memories about the user, newest first. Supports filters on class, source, tags, scope and created/updated time, and returns a cursor for the next page"
+    )]
+    async fn list_memories(
+        &self,
+        Parameters(params): Parameters<ListMemoriesParams>,
+    ) -> std::result::Result<CallToolResult, ErrorData> {
+        let storage = self.storage(params.vault.as_deref())?;
+        let filter = MemoryFilter {
+            class: params.class.map(|class| match class.parse() {
+                Ok(class) => class,
+                Err(never) => match never {},
+            }),
+            source: params.source,
+            tags: params.tags,
+            scope: params.scope,
+            created_after: parse_timestamp(params.created_after)?,
+            created_before: parse_timestamp(params.created_before)?,
+            updated_after: parse_timestamp(params.updated_after)?,
+            updated_before: parse_timestamp(params.updated_before)?,
+        };
+        let limit = params.limit.unwrap_or(DEFAULT_LIST_LIMIT);
+
+        match storage
+            .list_memories(
+                &self.session_app(),
+                &filter,
+                params.cursor.as_deref(),
+                limit,
+            )
             .await
         {
-            Ok(memories) => {
-                if memories.is_empty() {
+            Ok(page) => {
+                if page.memories.is_empty() {
                     Ok(CallToolResult::success(vec![Content::text(
                         "No memories found in vault".to_string(),
                     )]))
                 } else {
                     let mut result_text =
-                        format!("Found {} memories in vault:\n", memories.len());
-                    for (i, memory) in memories.iter().enumerate() {
+                        format!("Found {} memories in vault:\n", page.memories.len());
+                    for (i, memory) in page.memories.iter().enumerate() {
                         result_text.push_str(&format!(
                             "{}. ID: {} | Class: {:?} | Content: '{}'\n",
                             i + 1,
@@ -297,6 +711,9 @@ impl MimirServer {
                             memory.content
                         ));
                     }
+                    if let Some(cursor) = page.next_cursor {
+                        result_text.push_str(&format!("Next cursor: {}\n", cursor));
+                    }
                     Ok(CallToolResult::success(vec![Content::text(result_text)]))
                 }
             }
@@ -309,16 +726,21 @@ impl MimirServer {
 
     /// Get vault statistics
     #[tool(description = "Get vault statistics and summary")]
-    async fn get_vault_stats(&self) -> std::result::Result<CallToolResult, ErrorData> {
-        match self.storage.get_stats().await {
+    async fn get_vault_stats(
+        &self,
+        Parameters(VaultParams { vault }): Parameters<VaultParams>,
+    ) -> std::result::Result<CallToolResult, ErrorData> {
+        let storage = self.storage(vault.as_deref())?;
+        match storage.get_stats().await {
             Ok(stats) => {
-                let stats_text = format!(
+                let mut stats_text = format!(
                     "Vault Statistics:\n• Database memories: {}\n• Vector memories: {}\n• Memory usage: {} bytes\n• Vector store usage: {:.1}%",
                     stats.database_memories,
                     stats.vector_memories,
                     stats.memory_usage_bytes,
                     stats.vector_count_percentage
                 );
+                stats_text.push_str(&format_vault_stats(&stats));
 
                 Ok(CallToolResult::success(vec![Content::text(stats_text)]))
             }
@@ -333,13 +755,15 @@ impl MimirServer {
     #[tool(description = "Update an existing memory by ID with new text")]
     async fn update_memory(
         &self,
-        Parameters(UpdateMemoryParams { id, text }): Parameters<UpdateMemoryParams>,
+        Parameters(UpdateMemoryParams { id, text, vault }): Parameters<UpdateMemoryParams>,
     ) -> std::result::Result<CallToolResult, ErrorData> {
+        let storage = self.storage(vault.as_deref())?;
         let memory_id = Uuid::parse_str(&id)
             .map_err(|e| ErrorData::invalid_request(format!("Invalid UUID: {}", e), None))?;
 
         // Get existing memory first
-        let existing_memory = match self.storage.get_memory(memory_id).await {
+        let app_id = self.session_app();
+        let existing_memory = match storage.get_memory(&app_id, memory_id).await {
             Ok(Some(memory)) => memory,
             Ok(None) => {
                 return Err(ErrorData::invalid_request(
@@ -361,7 +785,7 @@ impl MimirServer {
         updated_memory.updated_at = chrono::Utc::now();
 
         // Update in storage
-        match self.storage.update_memory(updated_memory).await {
+        match storage.update_memory(&app_id, updated_memory).await {
             Ok(result) => {
                 let success_text = if result.database_stored && result.vector_stored {
                     format!(
@@ -386,10 +810,487 @@ impl MimirServer {
         }
     }
 
+    /// List the revision history of a memory
+    #[tool(description = "List previous revisions of a memory by ID")]
+    async fn list_memory_revisions(
+        &self,
+        Parameters(MemoryRevisionsParams { id, vault }): Parameters<MemoryRevisionsParams>,
+    ) -> std::result::Result<CallToolResult, ErrorData> {
+        let storage = self.storage(vault.as_deref())?;
+        let memory_id = Uuid::parse_str(&id)
+            .map_err(|e| ErrorData::invalid_request(format!("Invalid UUID: {}", e), None))?;
+
+        match storage.list_memory_revisions(memory_id).await {
+            Ok(revisions) => {
+                if revisions.is_empty() {
+                    Ok(CallToolResult::success(vec![Content::text(format!(
+                        "No previous revisions for memory {}",
+                        id
+                    ))]))
+                } else {
+                    let mut result_text =
+                        format!("Found {} revisions for memory {}:\n", revisions.len(), id);
+                    for revision in &revisions {
+                        result_text.push_str(&format!(
+                            "{}. Replaced: {} | Content: '{}'\n",
+                            revision.revision,
+                            revision.superseded_at.to_rfc3339(),
+                            revision.memory.content
+                        ));
+                    }
+                    Ok(CallToolResult::success(vec![Content::text(result_text)]))
+                }
+            }
+            Err(e) => Err(ErrorData::invalid_request(
+                format!("Failed to list revisions: {}", e),
+                None,
+            )),
+        }
+    }
+
+    /// Read a memory as it was at a point in time
+    #[tool(description = "Get a memory by ID as it was at a given RFC 3339 timestamp")]
+    async fn get_memory_as_of(
+        &self,
+        Parameters(MemoryAsOfParams {
+            id,
+            timestamp,
+            vault,
+        }): Parameters<MemoryAsOfParams>,
+    ) -> std::result::Result<CallToolResult, ErrorData> {
+        let storage = self.storage(vault.as_deref())?;
+        let memory_id = Uuid::parse_str(&id)
+            .map_err(|e| ErrorData::invalid_request(format!("Invalid UUID: {}", e), None))?;
+        let at = chrono::DateTime::parse_from_rfc3339(&timestamp)
+            .map_err(|e| ErrorData::invalid_request(format!("Invalid timestamp: {}", e), None))?
+            .with_timezone(&chrono::Utc);
+
+        match storage.get_memory_as_of(memory_id, at).await {
+            Ok(Some(memory)) => Ok(CallToolResult::success(vec![Content::text(format!(
+                "Memory {} as of {}: '{}' (last updated {})",
+                id,
+                at.to_rfc3339(),
+                memory.content,
+                memory.updated_at.to_rfc3339()
+            ))])),
+            Ok(None) => Ok(CallToolResult::success(vec![Content::text(format!(
+                "Memory {} did not exist at {}",
+                id,
+                at.to_rfc3339()
+            ))])),
+            Err(e) => Err(ErrorData::invalid_request(
+                format!("Failed to read memory: {}", e),
+                None,
+            )),
+        }
+    }
+
+    /// Revert a memory to an earlier revision
+    #[tool(description = "Revert a memory by ID to an earlier revision number")]
+    async fn revert_memory(
+        &self,
+        Parameters(RevertMemoryParams {
+            id,
+            revision,
+            vault,
+        }): Parameters<RevertMemoryParams>,
+    ) -> std::result::Result<CallToolResult, ErrorData> {
+        let storage = self.storage(vault.as_deref())?;
+        let memory_id = Uuid::parse_str(&id)
+            .map_err(|e| ErrorData::invalid_request(format!("Invalid UUID: {}", e), None))?;
+
+        match storage
+            .revert_memory(&self.session_app(), memory_id, revision)
+            .await
+        {
+            Ok(result) => {
+                let success_text = if result.vector_stored {
+                    format!(
+                        "Reverted memory {} to revision {} (database and vector store)",
+                        id, revision
+                    )
+                } else {
+                    format!(
+                        "Reverted memory {} to revision {} (database only)",
+                        id, revision
+                    )
+                };
+                Ok(CallToolResult::success(vec![Content::text(success_text)]))
+            }
+            Err(e) => Err(ErrorData::invalid_request(
+                format!("Failed to revert memory: {}", e),
+                None,
+            )),
+        }
+    }
+
+    /// Restore a deleted memory from the trash
+    #[tool(description = "Restore a deleted memory from the trash by ID")]
+    async fn restore_memory(
+        &self,
+        Parameters(RestoreMemoryParams { id, vault }): Parameters<RestoreMemoryParams>,
+    ) -> std::result::Result<CallToolResult, ErrorData> {
+        let storage = self.storage(vault.as_deref())?;
+        let memory_id = Uuid::parse_str(&id)
+            .map_err(|e| ErrorData::invalid_request(format!("Invalid UUID: {}", e), None))?;
+
+        match storage.restore_memory(&self.session_app(), memory_id).await {
+            Ok(true) => Ok(CallToolResult::success(vec![Content::text(format!(
+                "Restored memory with ID {} from the trash",
+                id
+            ))])),
+            Ok(false) => Ok(CallToolResult::success(vec![Content::text(format!(
+                "Memory with ID {} is not in the trash",
+                id
+            ))])),
+            Err(e) => Err(ErrorData::invalid_request(
+                format!("Failed to restore memory: {}", e),
+                None,
+            )),
+        }
+    }
+
+    /// List memories in the trash
+    #[tool(description = "List deleted memories that are still in the trash and can be restored")]
+    async fn list_trash(
+        &self,
+        Parameters(VaultParams { vault }): Parameters<VaultParams>,
+    ) -> std::result::Result<CallToolResult, ErrorData> {
+        let storage = self.storage(vault.as_deref())?;
+        match storage.list_trash().await {
+            Ok(trash) => {
+                if trash.is_empty() {
+                    return Ok(CallToolResult::success(vec![Content::text(
+                        "The trash is empty".to_string(),
+                    )]));
+                }
+
+                let mut result_text = format!("{} memories in the trash:\n", trash.len());
+                for (i, trashed) in trash.iter().enumerate() {
+                    result_text.push_str(&format!(
+                        "{}. ID: {} | Deleted: {} | Content: '{}'\n",
+                        i + 1,
+                        trashed.memory.id,
+                        trashed.deleted_at.to_rfc3339(),
+                        trashed.memory.content
+                    ));
+                }
+                Ok(CallToolResult::success(vec![Content::text(result_text)]))
+            }
+            Err(e) => Err(ErrorData::invalid_request(
+                format!("Failed to list trash: {}", e),
+                None,
+            )),
+        }
+    }
+
+    /// Permanently delete everything in the trash
+    #[tool(description = "Permanently delete every memory in the trash. This cannot be undone")]
+    async fn empty_trash(
+        &self,
+        Parameters(VaultParams { vault }): Parameters<VaultParams>,
+    ) -> std::result::Result<CallToolResult, ErrorData> {
+        let storage = self.storage(vault.as_deref())?;
+        match storage.empty_trash(&self.session_app()).await {
+            Ok(count) => Ok(CallToolResult::success(vec![Content::text(format!(
+                "Permanently deleted {} memories from the trash",
+                count
+            ))])),
+            Err(e) => Err(ErrorData::invalid_request(
+                format!("Failed to empty trash: {}", e),
+                None,
+            )),
+        }
+    }
+
+    /// Relate one memory to another
+    #[tool(
+        description = "Record a typed relation from one memory to another: supersedes, related_to, derived_from or contradicts, with an optional label"
+    )]
+    async fn link_memories(
+        &self,
+        Parameters(LinkMemoriesParams {
+            source_id,
+            target_id,
+            relation,
+            label,
+            vault,
+        }): Parameters<LinkMemoriesParams>,
+    ) -> std::result::Result<CallToolResult, ErrorData> {
+        let storage = self.storage(vault.as_deref())?;
+        let source = Uuid::parse_str(&source_id)
+            .map_err(|e| ErrorData::invalid_request(format!("Invalid UUID: {}", e), None))?;
+        let target = Uuid::parse_str(&target_id)
+            .map_err(|e| ErrorData::invalid_request(format!("Invalid UUID: {}", e), None))?;
+        let kind = parse_relation(&relation)?;
+
+        let relation = MemoryRelation {
+            source,
+            target,
+            kind,
+            label,
+            created_at: chrono::Utc::now(),
+        };
+        match storage.link_memories(&self.session_app(), &relation).await {
+            Ok(()) => Ok(CallToolResult::success(vec![Content::text(format!(
+                "Linked memory {} {} memory {}",
+                source_id,
+                kind.as_str(),
+                target_id
+            ))])),
+            Err(e) => Err(ErrorData::invalid_request(
+                format!("Failed to link memories: {}", e),
+                None,
+            )),
+        }
+    }
+
+    /// Remove relations between two memories
+    #[tool(
+        description = "Remove the relation of a given type from one memory to another, or all of them if no type is given"
+    )]
+    async fn unlink_memories(
+        &self,
+        Parameters(UnlinkMemoriesParams {
+            source_id,
+            target_id,
+            relation,
+            vault,
+        }): Parameters<UnlinkMemoriesParams>,
+    ) -> std::result::Result<CallToolResult, ErrorData> {
+        let storage = self.storage(vault.as_deref())?;
+        let source = Uuid::parse_str(&source_id)
+            .map_err(|e| ErrorData::invalid_request(format!("Invalid UUID: {}", e), None))?;
+        let target = Uuid::parse_str(&target_id)
+            .map_err(|e| ErrorData::invalid_request(format!("Invalid UUID: {}", e), None))?;
+        let kind = relation.as_deref().map(parse_relation).transpose()?;
+
+        match storage
+            .unlink_memories(&self.session_app(), source, target, kind)
+            .await
+        {
+            Ok(count) => Ok(CallToolResult::success(vec![Content::text(format!(
+                "Removed {} relations from memory {} to memory {}",
+                count, source_id, target_id
+            ))])),
+            Err(e) => Err(ErrorData::invalid_request(
+                format!("Failed to unlink memories: {}", e),
+                None,
+            )),
+        }
+    }
+
+    /// Walk the relations of a memory
+    #[tool(
+        description = "List the memories related to a memory, following relations in both directions up to a number of hops"
+    )]
+    async fn related_memories(
+        &self,
+        Parameters(RelatedMemoriesParams {
+            id,
+            hops,
+            relations,
+            vault,
+        }): Parameters<RelatedMemoriesParams>,
+    ) -> std::result::Result<CallToolResult, ErrorData> {
+        let storage = self.storage(vault.as_deref())?;
+        let memory_id = Uuid::parse_str(&id)
+            .map_err(|e| ErrorData::invalid_request(format!("Invalid UUID: {}", e), None))?;
+        let kinds = relations
+            .iter()
+            .map(|relation| parse_relation(relation))
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+        let hops = hops.unwrap_or(DEFAULT_RELATION_HOPS);
+
+        match storage.get_related_memories(memory_id, hops, &kinds).await {
+            Ok(related) => {
+                if related.is_empty() {
+                    return Ok(CallToolResult::success(vec![Content::text(format!(
+                        "No memories related to memory {}",
+                        id
+                    ))]));
+                }
+
+                let mut result_text = format!("Memories related to {}:\n", id);
+                for (i, related) in related.iter().enumerate() {
+                    result_text.push_str(&format!(
+                        "{}. ID: {} | Hops: {} | Relation: {} ({} -> {}){} | Content: '{}'\n",
+                        i + 1,
+                        related.memory.id,
+                        related.hops,
+                        related.relation.kind.as_str(),
+                        related.relation.source,
+                        related.relation.target,
+                        related
+                            .relation
+                            .label
+                            .as_ref()
+                            .map(|label| format!(" '{}'", label))
+                            .unwrap_or_default(),
+                        related.memory.content
+                    ));
+                }
+                Ok(CallToolResult::success(vec![Content::text(result_text)]))
+            }
+            Err(e) => Err(ErrorData::invalid_request(
+                format!("Failed to get related memories: {}", e),
+                None,
+            )),
+        }
+    }
+
+    /// Attach a file to a memory
+    #[tool(
+        description = "Attach a file, such as an image or PDF, to a memory. The data is base64 encoded and stored encrypted with the memory"
+    )]
+    async fn add_attachment(
+        &self,
+        Parameters(AddAttachmentParams {
+            memory_id,
+            name,
+            mime_type,
+            data,
+            vault,
+        }): Parameters<AddAttachmentParams>,
+    ) -> std::result::Result<CallToolResult, ErrorData> {
+        let storage = self.storage(vault.as_deref())?;
+        let memory_id = Uuid::parse_str(&memory_id)
+            .map_err(|e| ErrorData::invalid_request(format!("Invalid UUID: {}", e), None))?;
+        let data = BASE64
+            .decode(data.as_bytes())
+            .map_err(|e| ErrorData::invalid_request(format!("Invalid base64 data: {}", e), None))?;
+
+        match storage
+            .add_attachment(&self.session_app(), memory_id, &name, &mime_type, &data)
+            .await
+        {
+            Ok(info) => Ok(CallToolResult::success(vec![Content::text(format!(
+                "Attached '{}' ({} bytes) to memory {} with ID {}",
+                info.name, info.size, info.memory_id, info.id
+            ))])),
+            Err(e) => Err(ErrorData::invalid_request(
+                format!("Failed to add attachment: {}", e),
+                None,
+            )),
+        }
+    }
+
+    /// List the attachments of a memory
+    #[tool(description = "List the files attached to a memory by ID")]
+    async fn list_attachments(
+        &self,
+        Parameters(ListAttachmentsParams { memory_id, vault }): Parameters<ListAttachmentsParams>,
+    ) -> std::result::Result<CallToolResult, ErrorData> {
+        let storage = self.storage(vault.as_deref())?;
+        let id = Uuid::parse_str(&memory_id)
+            .map_err(|e| ErrorData::invalid_request(format!("Invalid UUID: {}", e), None))?;
+
+        match storage.list_attachments(id).await {
+            Ok(attachments) => {
+                if attachments.is_empty() {
+                    return Ok(CallToolResult::success(vec![Content::text(format!(
+                        "Memory {} has no attachments",
+                        memory_id
+                    ))]));
+                }
+
+                let mut result_text = format!("Attachments of memory {}:\n", memory_id);
+                for (i, info) in attachments.iter().enumerate() {
+                    result_text.push_str(&format!(
+                        "{}. ID: {} | Name: '{}' | Type: {} | Size: {} bytes | Added: {}\n",
+                        i + 1,
+                        info.id,
+                        info.name,
+                        info.mime_type,
+                        info.size,
+                        info.created_at.to_rfc3339()
+                    ));
+                }
+                Ok(CallToolResult::success(vec![Content::text(result_text)]))
+            }
+            Err(e) => Err(ErrorData::invalid_request(
+                format!("Failed to list attachments: {}", e),
+                None,
+            )),
+        }
+    }
+
+    /// Read an attachment
+    #[tool(description = "Get an attached file by ID as an embedded resource")]
+    async fn get_attachment(
+        &self,
+        Parameters(AttachmentParams { id, vault }): Parameters<AttachmentParams>,
+    ) -> std::result::Result<CallToolResult, ErrorData> {
+        let storage = self.storage(vault.as_deref())?;
+        let attachment_id = Uuid::parse_str(&id)
+            .map_err(|e| ErrorData::invalid_request(format!("Invalid UUID: {}", e), None))?;
+
+        match storage
+            .get_attachment(&self.session_app(), attachment_id)
+            .await
+        {
+            Ok(Some(attachment)) => Ok(CallToolResult::success(vec![
+                Content::text(format!(
+                    "'{}' ({}, {} bytes) attached to memory {}",
+                    attachment.info.name,
+                    attachment.info.mime_type,
+                    attachment.info.size,
+                    attachment.info.memory_id
+                )),
+                Content::resource(ResourceContents::BlobResourceContents {
+                    uri: format!("mimir://attachment/{}", attachment.info.id),
+                    mime_type: Some(attachment.info.mime_type),
+                    blob: BASE64.encode(&attachment.data),
+                }),
+            ])),
+            Ok(None) => Err(ErrorData::invalid_request(
+                format!("Attachment with ID {} not found", id),
+                None,
+            )),
+            Err(e) => Err(ErrorData::invalid_request(
+                format!("Failed to get attachment: {}", e),
+                None,
+            )),
+        }
+    }
+
+    /// Remove an attachment
+    #[tool(description = "Permanently remove an attached file by ID")]
+    async fn remove_attachment(
+        &self,
+        Parameters(AttachmentParams { id, vault }): Parameters<AttachmentParams>,
+    ) -> std::result::Result<CallToolResult, ErrorData> {
+        let storage = self.storage(vault.as_deref())?;
+        let attachment_id = Uuid::parse_str(&id)
+            .map_err(|e| ErrorData::invalid_request(format!("Invalid UUID: {}", e), None))?;
+
+        match storage
+            .remove_attachment(&self.session_app(), attachment_id)
+            .await
+        {
+            Ok(true) => Ok(CallToolResult::success(vec![Content::text(format!(
+                "Removed attachment with ID {}",
+                id
+            ))])),
+            Ok(false) => Err(ErrorData::invalid_request(
+                format!("Attachment with ID {} not found", id),
+                None,
+            )),
+            Err(e) => Err(ErrorData::invalid_request(
+                format!("Failed to remove attachment: {}", e),
+                None,
+            )),
+        }
+    }
+
     /// Clear all memories from the vault
     #[tool(description = "Clear all memories from the vault")]
-    async fn clear_vault(&self) -> std::result::Result<CallToolResult, ErrorData> {
-        match self.storage.clear_vault().await {
+    async fn clear_vault(
+        &self,
+        Parameters(VaultParams { vault }): Parameters<VaultParams>,
+    ) -> std::result::Result<CallToolResult, ErrorData> {
+        let storage = self.storage(vault.as_deref())?;
+        match storage.clear_vault(&self.session_app()).await {
             Ok(count) => Ok(CallToolResult::success(vec![Content::text(format!(
                 "Successfully cleared {} memories from vault",
                 count
@@ -401,18 +1302,118 @@ impl MimirServer {
         }
     }
 
-    /// Save the vector store to disk
+    /// Start purging memories that outlive the trash retention window in
+    /// every hosted vault
+    pub fn start_trash_purge(
+        &self,
+        trash: &mimir_core::config::TrashConfig,
+    ) -> Vec<tokio::task::JoinHandle<()>> {
+        self.vaults
+            .values()
+            .map(|storage| {
+                storage.clone().spawn_trash_purge(
+                    chrono::Duration::days(trash.retention_days as i64),
+                    std::time::Duration::from_secs(trash.purge_interval_hours as u64 * 3600),
+                )
+            })
+            .collect()
+    }
+
+    /// Start deleting memories once their expiry time has passed in every
+    /// hosted vault
+    pub fn start_expiry_reaper(
+        &self,
+        expiry: &mimir_core::config::ExpiryConfig,
+    ) -> Vec<tokio::task::JoinHandle<()>> {
+        self.vaults
+            .values()
+            .map(|storage| {
+                storage
+                    .clone()
+                    .spawn_expiry_reaper(std::time::Duration::from_secs(
+                        expiry.reap_interval_minutes as u64 * 60,
+                    ))
+            })
+            .collect()
+    }
+
+    /// Start taking scheduled snapshots of every hosted vault if auto-backup
+    /// is enabled
+    ///
+    /// Each vault backs up according to its own profile in `config`.
+    pub fn start_backups(&self, config: &mimir_core::Config) -> Vec<tokio::task::JoinHandle<()>> {
+        if !config.auto_backup.enabled || config.auto_backup.interval_hours == 0 {
+            return Vec::new();
+        }
+        self.vaults
+            .iter()
+            .filter_map(|(name, storage)| match config.for_vault(name) {
+                Ok(vault_config) => Some(storage.clone().spawn_backups(vault_config)),
+                Err(e) => {
+                    warn!("Not backing up vault '{}': {}", name, e);
+                    None
+                }
+            })
+            .collect()
+    }
+
+    /// Save the vector store of every hosted vault to disk
     pub async fn save_vector_store(&self) -> std::result::Result<(), Box<dyn std::error::Error>> {
-        self.storage
-            .save_vector_store()
-            .await
-            .map_err(|e| Box::new(e) as Box<dyn std::error::Error>)
+        for storage in self.vaults.values() {
+            storage
+                .save_vector_store()
+                .await
+                .map_err(|e| Box::new(e) as Box<dyn std::error::Error>)?;
+        }
+        Ok(())
+    }
+
+    /// List the vaults this server hosts
+    #[tool(description = "List the memory vaults this server hosts and which one the session uses")]
+    async fn list_vaults(&self) -> std::result::Result<CallToolResult, ErrorData> {
+        let session_vault = self.session_vault();
+        let mut result_text = format!("{} vaults:\n", self.vaults.len());
+        for name in self.vaults.keys() {
+            result_text.push_str(&format!(
+                "• {}{}\n",
+                name,
+                if *name == session_vault {
+                    " (selected)"
+                } else {
+                    ""
+                }
+            ));
+        }
+        Ok(CallToolResult::success(vec![Content::text(result_text)]))
+    }
+
+    /// Choose the vault used by tool calls that name none
+    #[tool(
+        description = "Select the vault that later tool calls in this session use when they do not name one"
+    )]
+    async fn select_vault(
+        &self,
+        Parameters(SelectVaultParams { name }): Parameters<SelectVaultParams>,
+    ) -> std::result::Result<CallToolResult, ErrorData> {
+        self.storage(Some(&name))?;
+        *self
+            .session_vault
+            .write()
+            .unwrap_or_else(PoisonError::into_inner) = name.clone();
+        Ok(CallToolResult::success(vec![Content::text(format!(
+            "Session now uses vault '{}'",
+            name
+        ))]))
     }
 
     /// Manual save tool for debugging
     #[tool(description = "Manually save the vector store to disk")]
-    async fn save_vault(&self) -> std::result::Result<CallToolResult, ErrorData> {
-        match self.storage.save_vector_store().await {
+    async fn save_vault(
+        &self,
+        Parameters(VaultParams { vault }): Parameters<VaultParams>,
+    ) -> std::result::Result<CallToolResult, ErrorData> {
+        let storage = self.storage(vault.as_deref())?;
+        match storage.save_vector_store().await {
             Ok(_) => Ok(CallToolResult::success(vec![Content::text(
                 "Vector store saved successfully to disk".to_string(),
             )])),
@@ -423,10 +1424,36 @@ impl MimirServer {
         }
     }
 
+    /// Rebuild the vector index from the stored embeddings
+    #[tool(
+        description = "Rebuild the vector search index from the embeddings stored in the vault, without re-embedding"
+    )]
+    async fn rebuild_vector_index(
+        &self,
+        Parameters(VaultParams { vault }): Parameters<VaultParams>,
+    ) -> std::result::Result<CallToolResult, ErrorData> {
+        let storage = self.storage(vault.as_deref())?;
+        match storage.rebuild_vector_index().await {
+            Ok(report) => Ok(CallToolResult::success(vec![Content::text(format!(
+                "Rebuilt vector index with {} memories; {} have no usable stored embedding",
+                report.indexed,
+                report.skipped.len()
+            ))])),
+            Err(e) => Err(ErrorData::invalid_request(
+                format!("Failed to rebuild vector index: {}", e),
+                None,
+            )),
+        }
+    }
+
     /// Check vector store status
     #[tool(description = "Check vector store status and statistics")]
-    async fn vector_store_status(&self) -> std::result::Result<CallToolResult, ErrorData> {
-        let stats = match self.storage.get_stats().await {
+    async fn vector_store_status(
+        &self,
+        Parameters(VaultParams { vault }): Parameters<VaultParams>,
+    ) -> std::result::Result<CallToolResult, ErrorData> {
+        let storage = self.storage(vault.as_deref())?;
+        let stats = match storage.get_stats().await {
             Ok(stats) => stats,
             Err(e) => {
                 return Err(ErrorData::invalid_request(
@@ -436,7 +1463,7 @@ impl MimirServer {
             }
         };
 
-        let has_embedder = self.storage.has_vector_embedder().await;
+        let has_embedder = storage.has_vector_embedder().await;
 
         let status_text = format!(
             "Vector Store Status:\n• Vector count: {}\n• Database memories: {}\n• Has embedder: {}\n• Memory usage: {} bytes",
@@ -460,6 +1487,19 @@ impl rmcp::ServerHandler for MimirServer {
             ..Default::default()
         }
     }
+
+    /// Remember the client's name as the app its tool calls are audited under
+    async fn initialize(
+        &self,
+        request: InitializeRequestParam,
+        context: RequestContext<RoleServer>,
+    ) -> std::result::Result<InitializeResult, ErrorData> {
+        self.set_session_app(&request.client_info.name);
+        if context.peer.peer_info().is_none() {
+            context.peer.set_peer_info(request);
+        }
+        Ok(self.get_info())
+    }
 }
 
 #[cfg(test)]
@@ -472,21 +1512,19 @@ mod tests {
     use rmcp::ServerHandler;
     use tempfile::TempDir;
 
-    async fn create_test_server(use_embedder: bool) -> (MimirServer, TempDir) {
+    async fn create_test_storage(use_embedder: bool) -> (IntegratedStorage, TempDir) {
         let temp_dir = TempDir::new().unwrap();
         let db_path = temp_dir.path().join("test.db");
         let keyset_path = temp_dir.path().join("keyset.json");
 
-        // Create crypto manager for database
-        let db_crypto_manager = CryptoManager::with_password(&keyset_path, "test-password")
-            .expect("Failed to create test crypto manager");
-
-        // Create crypto manager for integrated storage
-        let storage_crypto_manager = CryptoManager::with_password(&keyset_path, "test-password")
-            .expect("Failed to create test crypto manager");
+        // One crypto manager, shared by the database and integrated storage
+        let crypto_manager = Arc::new(
+            CryptoManager::with_password(&keyset_path, "test-password")
+                .expect("Failed to create test crypto manager"),
+        );
 
         // Create database
-        let database = Database::with_crypto_manager(&db_path, db_crypto_manager)
+        let database = Database::with_crypto_manager(&db_path, crypto_manager.clone())
             .expect("Failed to create test database");
 
         // Create vector store
@@ -510,10 +1548,15 @@ mod tests {
                 .expect("Failed to create test vector store")
         };
 
-        let storage = IntegratedStorage::new(database, vector_store, storage_crypto_manager)
+        let storage = IntegratedStorage::new(database, vector_store, crypto_manager)
             .await
             .expect("Failed to create integrated storage");
 
+        (storage, temp_dir)
+    }
+
+    async fn create_test_server(use_embedder: bool) -> (MimirServer, TempDir) {
+        let (storage, temp_dir) = create_test_storage(use_embedder).await;
         (MimirServer::new(storage), temp_dir)
     }
 
@@ -522,7 +1565,10 @@ mod tests {
         let (server, _temp_dir) = create_test_server(false).await;
 
         // Test that the server starts with empty storage
-        let stats = server.get_vault_stats().await.unwrap();
+        let stats = server
+            .get_vault_stats(Parameters(VaultParams::default()))
+            .await
+            .unwrap();
         assert!(format!("{:?}", stats).contains("0"));
     }
 
@@ -534,13 +1580,17 @@ mod tests {
         let add_params = AddMemoryParams {
             source: "test-agent".to_string(),
             text: "Test memory content".to_string(),
+            expires_at: None,
+            vault: None,
         };
 
         let result = server.add_memory(Parameters(add_params)).await;
         assert!(result.is_ok());
 
         // Test listing memories
-        let list_result = server.list_memories().await;
+        let list_result = server
+            .list_memories(Parameters(ListMemoriesParams::default()))
+            .await;
         assert!(list_result.is_ok());
 
         // Note: We can't test deletion with a specific ID since IDs are now auto-generated
@@ -551,7 +1601,7 @@ mod tests {
     async fn test_search_functionality() {
         let (server, _temp_dir) = create_test_server(true).await;
         // Skip test if embedder is not available
-        if !server.storage.has_vector_embedder().await {
+        if !server.default_storage().has_vector_embedder().await {
             eprintln!("[SKIP] test_search_functionality: embedder/model not available");
             return;
         }
@@ -562,13 +1612,17 @@ mod tests {
         // Search for content
         let search_params = SearchMemoriesParams {
             query: "Mimir".to_string(),
+            expand_hops: None,
+            vault: None,
         };
 
         let search_result = server.search_memories(Parameters(search_params)).await;
         assert!(search_result.is_ok());
 
         // Test vault stats
-        let stats_result = server.get_vault_stats().await;
+        let stats_result = server
+            .get_vault_stats(Parameters(VaultParams::default()))
+            .await;
         assert!(stats_result.is_ok());
     }
 
@@ -588,6 +1642,22 @@ mod tests {
         assert!(tool_names.contains(&"search_memories".to_string()));
         assert!(tool_names.contains(&"list_memories".to_string()));
         assert!(tool_names.contains(&"get_vault_stats".to_string()));
+        assert!(tool_names.contains(&"list_memory_revisions".to_string()));
+        assert!(tool_names.contains(&"get_memory_as_of".to_string()));
+        assert!(tool_names.contains(&"revert_memory".to_string()));
+        assert!(tool_names.contains(&"restore_memory".to_string()));
+        assert!(tool_names.contains(&"list_trash".to_string()));
+        assert!(tool_names.contains(&"empty_trash".to_string()));
+        assert!(tool_names.contains(&"link_memories".to_string()));
+        assert!(tool_names.contains(&"unlink_memories".to_string()));
+        assert!(tool_names.contains(&"related_memories".to_string()));
+        assert!(tool_names.contains(&"add_attachment".to_string()));
+        assert!(tool_names.contains(&"list_attachments".to_string()));
+        assert!(tool_names.contains(&"get_attachment".to_string()));
+        assert!(tool_names.contains(&"remove_attachment".to_string()));
+        assert!(tool_names.contains(&"rebuild_vector_index".to_string()));
+        assert!(tool_names.contains(&"list_vaults".to_string()));
+        assert!(tool_names.contains(&"select_vault".to_string()));
 
         // Test that tools have descriptions
         let add_tool = tools.iter().find(|t| t.name == "add_memory").unwrap();
@@ -600,6 +1670,85 @@ mod tests {
             .contains("Pass all useful information about a user"));
     }
 
+    #[tokio::test]
+    async fn test_multiple_vaults() {
+        let (server, _temp_dir) = create_test_server(false).await;
+        let (work_storage, _work_dir) = create_test_storage(false).await;
+        let server = server.add_vault("work", work_storage);
+        assert_eq!(server.vault_names(), vec!["default", "work"]);
+
+        // A tool call can name its vault
+        let add_params = AddMemoryParams {
+            source: "test-agent".to_string(),
+            text: "Quarterly report due Friday".to_string(),
+            expires_at: None,
+            vault: Some("work".to_string()),
+        };
+        server.add_memory(Parameters(add_params)).await.unwrap();
+        let default_list = server
+            .list_memories(Parameters(ListMemoriesParams::default()))
+            .await
+            .unwrap();
+        assert!(format!("{:?}", default_list).contains("No memories found"));
+
+        // Selecting a vault only changes that session's vault
+        let session = server.new_session();
+        session
+            .select_vault(Parameters(SelectVaultParams {
+                name: "work".to_string(),
+            }))
+            .await
+            .unwrap();
+        let work_list = session
+            .list_memories(Parameters(ListMemoriesParams::default()))
+            .await
+            .unwrap();
+        assert!(format!("{:?}", work_list).contains("Quarterly report due Friday"));
+        let other_list = server
+            .list_memories(Parameters(ListMemoriesParams::default()))
+            .await
+            .unwrap();
+        assert!(format!("{:?}", other_list).contains("No memories found"));
+
+        // Unknown vaults are rejected
+        assert!(session
+            .select_vault(Parameters(SelectVaultParams {
+                name: "missing".to_string(),
+            }))
+            .await
+            .is_err());
+        assert!(server
+            .get_vault_stats(Parameters(VaultParams {
+                vault: Some("missing".to_string()),
+            }))
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_tool_calls_are_audited_as_session_app() {
+        let (server, _temp_dir) = create_test_server(false).await;
+        let session = server.new_session();
+        session.set_session_app("notes-app");
+
+        session
+            .list_memories(Parameters(ListMemoriesParams::default()))
+            .await
+            .unwrap();
+        server
+            .list_memories(Parameters(ListMemoriesParams::default()))
+            .await
+            .unwrap();
+
+        let entries = server
+            .default_storage()
+            .audit_entries(&mimir_db::AuditFilter::default())
+            .await
+            .unwrap();
+        let apps: Vec<&str> = entries.iter().map(|entry| entry.app_id.as_str()).collect();
+        assert_eq!(apps, vec!["notes-app", SYSTEM_APP_ID]);
+    }
+
     #[tokio::test]
     async fn test_server_handler_info() {
         let (server, _temp_dir) = create_test_server(false).await;
@@ -626,6 +1775,8 @@ mod tests {
         let add_params = AddMemoryParams {
             source: "test-agent".to_string(),
             text: long_text.to_string(),
+            expires_at: None,
+            vault: None,
         };
 
         let result = server.add_memory(Parameters(add_params)).await;
@@ -633,7 +1784,9 @@ mod tests {
 
         // Verify that the memory was added (we can't easily test the actual summarization
         // without a real LLM service, but we can verify the operation completes successfully)
-        let list_result = server.list_memories().await;
+        let list_result = server
+            .list_memories(Parameters(ListMemoriesParams::default()))
+            .await;
         assert!(list_result.is_ok());
     }
 }
@@ -1,19 +1,29 @@
 //! Integrated storage manager for coordinating database and vector store operations
 
-use mimir_core::{crypto::CryptoManager, Memory, MemoryClass, MemoryId, Result};
-use mimir_db::Database;
-use mimir_vector::ThreadSafeVectorStore;
+use mimir_core::{
+    config::ExpiryConfig, crypto::CryptoManager, Config, Memory, MemoryClass, MemoryFilter,
+    MemoryId, MemoryPage, MemoryRelation, RelationKind, Result,
+};
+use mimir_db::{
+    backup, Attachment, AttachmentId, AttachmentInfo, AuditEntry, AuditFilter, Database,
+    MemoryRevision, OrphanCounts, RelatedMemory, TrashedMemory, VaultStats,
+};
+use mimir_vector::{batch_ops::VectorInsert, ThreadSafeVectorStore};
 use std::sync::Arc;
 use tokio::sync::Mutex;
 use tracing::{error, info, warn};
 
+/// App recorded in the audit log when no calling app is known
+pub const SYSTEM_APP_ID: &str = "mimir";
+
 /// Integrated storage manager that coordinates database and vector store operations
 pub struct IntegratedStorage {
-    database: Arc<Mutex<Database>>,
+    database: Arc<Database>,
     vector_store: Arc<ThreadSafeVectorStore>,
     crypto_manager: Arc<CryptoManager>,
     llm_service: Option<Arc<super::llm_service::LlmService>>,
     similarity_threshold: Arc<Mutex<f32>>,
+    expiry: ExpiryConfig,
 }
 
 /// Search result with full memory data
@@ -22,6 +32,8 @@ pub struct MemorySearchResult {
     pub memory: Memory,
     pub similarity: f32,
     pub distance: f32,
+    /// Related memories, filled in by `expand_with_neighbors`
+    pub neighbors: Vec<RelatedMemory>,
 }
 
 /// Memory addition result
@@ -32,19 +44,45 @@ pub struct MemoryAddResult {
     pub database_stored: bool,
 }
 
+/// Batch addition result
+#[derive(Debug, Clone)]
+pub struct MemoryBatchResult {
+    /// Whether the batch was stored; if false, none of it was
+    pub committed: bool,
+    /// Outcome for each memory, in input order
+    pub results: Vec<MemoryAddResult>,
+    /// Memories that caused the batch to be rejected, with the reason
+    pub errors: Vec<(MemoryId, String)>,
+}
+
+/// Outcome of rebuilding the vector index from the database
+#[derive(Debug, Clone, Default)]
+pub struct VectorRebuildReport {
+    /// Vectors now in the index
+    pub indexed: usize,
+    /// Memories left out: stored without an embedding, or with one from a
+    /// different model or dimension
+    pub skipped: Vec<MemoryId>,
+}
+
 impl IntegratedStorage {
     /// Create a new integrated storage manager
+    ///
+    /// Concurrent callers share the database without locking it: reads run
+    /// in parallel on its read pool. Pass the crypto manager the database
+    /// was opened with so both use one key cache.
     pub async fn new(
         database: Database,
         vector_store: ThreadSafeVectorStore,
-        crypto_manager: CryptoManager,
+        crypto_manager: Arc<CryptoManager>,
     ) -> Result<Self> {
         Ok(Self {
-            database: Arc::new(Mutex::new(database)),
+            database: Arc::new(database),
             vector_store: Arc::new(vector_store),
-            crypto_manager: Arc::new(crypto_manager),
+            crypto_manager,
             llm_service: None,
             similarity_threshold: Arc::new(Mutex::new(0.6)), // Default similarity threshold
+            expiry: ExpiryConfig::default(),
         })
     }
 
@@ -54,6 +92,12 @@ impl IntegratedStorage {
         self
     }
 
+    /// Set the default time-to-live per memory class
+    pub fn with_expiry_config(mut self, expiry: ExpiryConfig) -> Self {
+        self.expiry = expiry;
+        self
+    }
+
     /// Set the similarity threshold for search results
     pub async fn set_similarity_threshold(&self, threshold: f32) {
         let mut threshold_guard = self.similarity_threshold.lock().await;
@@ -67,8 +111,9 @@ impl IntegratedStorage {
     }
 
     /// Add a memory to both database and vector store
-    pub async fn add_memory(&self, memory: Memory) -> Result<MemoryAddResult> {
+    pub async fn add_memory(&self, mut memory: Memory) -> Result<MemoryAddResult> {
         info!("Adding memory to integrated storage: {}", memory.id);
+        self.apply_default_expiry(&mut memory);
 
         let mut result = MemoryAddResult {
             memory_id: memory.id,
@@ -76,11 +121,13 @@ impl IntegratedStorage {
             database_stored: false,
         };
 
-        // Step 1: Store in database first
-        let db_result = {
-            let mut db = self.database.lock().await;
-            db.store_memory(&memory).await
-        };
+        // Step 1: Generate the embedding so the database keeps a copy of it
+        if let Err(e) = self.embed_memory(&mut memory).await {
+            warn!("Failed to embed memory: {}", e);
+        }
+
+        // Step 2: Store in database
+        let db_result = self.database.store_memory(&memory).await;
 
         match db_result {
             Ok(_) => {
@@ -93,7 +140,7 @@ impl IntegratedStorage {
             }
         }
 
-        // Step 2: Generate embedding and store in vector store
+        // Step 3: Index the embedding in the vector store
         let vector_result = self.add_memory_to_vector_store(&memory).await;
         match vector_result {
             Ok(_) => {
@@ -107,26 +154,114 @@ impl IntegratedStorage {
             }
         }
 
+        self.record_audit(app_of(&memory), "add", &[memory.id], None)
+            .await;
         Ok(result)
     }
 
     /// Add multiple memories in batch
-    pub async fn add_memories(&self, memories: Vec<Memory>) -> Result<Vec<MemoryAddResult>> {
+    ///
+    /// The batch is all-or-nothing. Every embedding is inserted into the vector
+    /// store before the database rows are written in one transaction; if either
+    /// step rejects the batch, nothing is kept. Without an embedder the batch
+    /// is stored in the database only, as with `add_memory`.
+    pub async fn add_memories(&self, mut memories: Vec<Memory>) -> Result<MemoryBatchResult> {
         info!("Adding {} memories to integrated storage", memories.len());
+        for memory in &mut memories {
+            self.apply_default_expiry(memory);
+        }
+
+        let mut batch = MemoryBatchResult {
+            committed: false,
+            results: memories
+                .iter()
+                .map(|memory| MemoryAddResult {
+                    memory_id: memory.id,
+                    vector_stored: false,
+                    database_stored: false,
+                })
+                .collect(),
+            errors: vec![],
+        };
 
-        let mut results = Vec::new();
+        // Step 1: Embed the whole batch, then insert it into the vector store
+        let has_embedder = self.vector_store.has_embedder().await;
+        if has_embedder {
+            let mut errors = Vec::new();
+            for memory in &mut memories {
+                if let Err(e) = self.embed_memory(memory).await {
+                    errors.push((memory.id, e.to_string()));
+                }
+            }
+
+            if errors.is_empty() {
+                let vectors = memories
+                    .iter()
+                    .filter_map(|memory| {
+                        memory.embedding.clone().map(|vector| VectorInsert {
+                            memory_id: memory.id,
+                            vector,
+                        })
+                    })
+                    .collect();
+                errors = self
+                    .vector_store
+                    .batch_insert(vectors)
+                    .await
+                    .map_err(|e| mimir_core::MimirError::VectorStore(e.to_string()))?
+                    .errors;
+            }
 
-        for memory in memories {
-            let result = self.add_memory(memory).await?;
-            results.push(result);
+            if !errors.is_empty() {
+                warn!(
+                    "Rejected batch of {} memories: {} failed in vector store",
+                    memories.len(),
+                    errors.len()
+                );
+                batch.errors = errors;
+                return Ok(batch);
+            }
+        } else {
+            warn!("Vector store has no embedder; storing batch in database only");
         }
 
-        info!("Successfully added {} memories", results.len());
-        Ok(results)
+        // Step 2: Store all rows in a single database transaction
+        let db_result = self.database.store_memories(&memories).await;
+
+        if let Err(e) = db_result {
+            error!("Failed to store batch in database: {}", e);
+            // Take the batch back out of the vector store
+            if has_embedder {
+                for memory in &memories {
+                    let _ = self.vector_store.remove_vector(memory.id).await;
+                }
+            }
+            return Err(e);
+        }
+
+        batch.committed = true;
+        for result in &mut batch.results {
+            result.database_stored = true;
+            result.vector_stored = has_embedder;
+        }
+        let ids: Vec<MemoryId> = memories.iter().map(|memory| memory.id).collect();
+        let app_id = memories.first().map(app_of).unwrap_or(SYSTEM_APP_ID);
+        self.record_audit(app_id, "add", &ids, None).await;
+
+        info!("Successfully added {} memories", batch.results.len());
+        Ok(batch)
     }
 
     /// Search memories using vector similarity
-    pub async fn search_memories(&self, query: &str, k: usize) -> Result<Vec<MemorySearchResult>> {
+    ///
+    /// The search is audited for `app_id`, with the number of results the
+    /// similarity threshold held back as its guardrail decision.
+    pub async fn search_memories(
+        &self,
+        app_id: &str,
+        query: &str,
+        k: usize,
+    ) -> Result<Vec<MemorySearchResult>> {
         info!("Searching memories with query: '{}' (k={})", query, k);
 
         // Step 1: Search vector store
@@ -142,10 +277,7 @@ impl IntegratedStorage {
         let mut search_results = Vec::new();
 
         for result in vector_results {
-            let memory_result = {
-                let mut db = self.database.lock().await;
-                db.get_memory(result.id).await
-            };
+            let memory_result = self.database.get_memory(result.id).await;
 
             match memory_result {
                 Ok(Some(memory)) => {
@@ -154,6 +286,7 @@ impl IntegratedStorage {
                         memory,
                         similarity: result.similarity,
                         distance,
+                        neighbors: vec![],
                     });
                 }
                 Ok(None) => {
@@ -192,24 +325,157 @@ impl IntegratedStorage {
             filtered_results.len(),
             threshold
         );
+
+        let ids: Vec<MemoryId> = filtered_results
+            .iter()
+            .map(|result| result.memory.id)
+            .collect();
+        let guardrail = format!(
+            "similarity below {:.3}: {} of {} results withheld",
+            threshold,
+            original_count - filtered_results.len(),
+            original_count
+        );
+        self.record_audit(app_id, "search", &ids, Some(&guardrail))
+            .await;
         Ok(filtered_results)
     }
 
+    /// Attach the memories within `hops` relation edges of each search result
+    ///
+    /// Memories that are already results in their own right are left out.
+    pub async fn expand_with_neighbors(
+        &self,
+        results: &mut [MemorySearchResult],
+        hops: usize,
+    ) -> Result<()> {
+        let result_ids: std::collections::HashSet<MemoryId> =
+            results.iter().map(|result| result.memory.id).collect();
+        for result in results.iter_mut() {
+            result.neighbors = self
+                .database
+                .traverse_relations(result.memory.id, hops, &[])
+                .await?
+                .into_iter()
+                .filter(|related| !result_ids.contains(&related.memory.id))
+                .collect();
+        }
+        Ok(())
+    }
+
+    /// Create a typed edge between two memories
+    pub async fn link_memories(&self, app_id: &str, relation: &MemoryRelation) -> Result<()> {
+        self.database.link_memories(relation).await?;
+        self.record_audit(app_id, "link", &[relation.source, relation.target], None)
+            .await;
+        Ok(())
+    }
+
+    /// Remove edges from `source` to `target`, of one kind or all kinds
+    pub async fn unlink_memories(
+        &self,
+        app_id: &str,
+        source: MemoryId,
+        target: MemoryId,
+        kind: Option<RelationKind>,
+    ) -> Result<usize> {
+        let removed = self.database.unlink_memories(source, target, kind).await?;
+        self.record_audit(app_id, "unlink", &[source, target], None)
+            .await;
+        Ok(removed)
+    }
+
+    /// List the edges to and from a memory
+    pub async fn list_relations(&self, memory_id: MemoryId) -> Result<Vec<MemoryRelation>> {
+        self.database.list_relations(memory_id).await
+    }
+
+    /// Memories reachable from `memory_id` within `hops` edges
+    pub async fn get_related_memories(
+        &self,
+        memory_id: MemoryId,
+        hops: usize,
+        kinds: &[RelationKind],
+    ) -> Result<Vec<RelatedMemory>> {
+        self.database
+            .traverse_relations(memory_id, hops, kinds)
+            .await
+    }
+
+    /// Attach a file to a memory, encrypted under the memory's class key
+    pub async fn add_attachment(
+        &self,
+        app_id: &str,
+        memory_id: MemoryId,
+        name: &str,
+        mime_type: &str,
+        data: &[u8],
+    ) -> Result<AttachmentInfo> {
+        let info = self
+            .database
+            .add_attachment(memory_id, name, mime_type, data)
+            .await?;
+        self.record_audit(app_id, "attach", &[memory_id], None)
+            .await;
+        Ok(info)
+    }
+
+    /// List the attachments of a memory
+    pub async fn list_attachments(&self, memory_id: MemoryId) -> Result<Vec<AttachmentInfo>> {
+        self.database.list_attachments(memory_id).await
+    }
+
+    /// Read and decrypt an attachment
+    pub async fn get_attachment(
+        &self,
+        app_id: &str,
+        id: AttachmentId,
+    ) -> Result<Option<Attachment>> {
+        let attachment = self.database.get_attachment(id).await?;
+        if let Some(attachment) = &attachment {
+            self.record_audit(
+                app_id,
+                "read_attachment",
+                &[attachment.info.memory_id],
+                None,
+            )
+            .await;
+        }
+        Ok(attachment)
+    }
+
+    /// Permanently remove an attachment
+    ///
+    /// Returns `false` if it did not exist.
+    pub async fn remove_attachment(&self, app_id: &str, id: AttachmentId) -> Result<bool> {
+        match self.database.remove_attachment(id).await? {
+            Some(memory_id) => {
+                self.record_audit(app_id, "detach", &[memory_id], None)
+                    .await;
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+
     /// Get memory by ID
-    pub async fn get_memory(&self, memory_id: MemoryId) -> Result<Option<Memory>> {
-        let mut db = self.database.lock().await;
-        db.get_memory(memory_id).await
+    pub async fn get_memory(&self, app_id: &str, memory_id: MemoryId) -> Result<Option<Memory>> {
+        let memory = self.database.get_memory(memory_id).await?;
+        if memory.is_some() {
+            self.record_audit(app_id, "read", &[memory_id], None).await;
+        }
+        Ok(memory)
     }
 
     /// Delete memory from both storage systems
-    pub async fn delete_memory(&self, memory_id: MemoryId) -> Result<bool> {
+    ///
+    /// The database row moves to the trash and its vector is dropped, so the
+    /// memory no longer shows up in search until it is restored.
+    pub async fn delete_memory(&self, app_id: &str, memory_id: MemoryId) -> Result<bool> {
         info!("Deleting memory: {}", memory_id);
 
         // Delete from database first
-        {
-            let db = self.database.lock().await;
-            db.delete_memory(memory_id).await?;
-        }
+        self.database.delete_memory(memory_id).await?;
 
         // Delete from vector store
         let vector_result = self.vector_store.remove_vector(memory_id).await;
@@ -223,23 +489,219 @@ impl IntegratedStorage {
             }
         }
 
+        self.record_audit(app_id, "delete", &[memory_id], None)
+            .await;
+        Ok(true)
+    }
+
+    /// Restore a memory from the trash and re-index its vector
+    ///
+    /// Returns `false` if the memory is not in the trash.
+    pub async fn restore_memory(&self, app_id: &str, memory_id: MemoryId) -> Result<bool> {
+        info!("Restoring memory from trash: {}", memory_id);
+
+        if !self.database.restore_memory(memory_id).await? {
+            return Ok(false);
+        }
+        let memory = self.database.get_memory(memory_id).await?;
+
+        if let Some(memory) = memory {
+            if let Err(e) = self.update_memory_in_vector_store(&memory).await {
+                warn!("Failed to re-index restored memory in vector store: {}", e);
+            }
+        }
+
+        self.record_audit(app_id, "restore", &[memory_id], None)
+            .await;
         Ok(true)
     }
 
+    /// List memories in the trash, most recently deleted first
+    pub async fn list_trash(&self) -> Result<Vec<TrashedMemory>> {
+        self.database.list_trash().await
+    }
+
+    /// Permanently destroy every memory in the trash
+    pub async fn empty_trash(&self, app_id: &str) -> Result<usize> {
+        let purged = self.database.empty_trash().await?;
+        self.drop_purged_vectors(&purged).await;
+        self.record_audit(app_id, "empty_trash", &purged, None)
+            .await;
+        Ok(purged.len())
+    }
+
+    /// Permanently destroy memories that have been in the trash longer than `retention`
+    pub async fn purge_trash(&self, retention: chrono::Duration) -> Result<usize> {
+        let purged = self
+            .database
+            .purge_trash(chrono::Utc::now() - retention)
+            .await?;
+        self.drop_purged_vectors(&purged).await;
+        if !purged.is_empty() {
+            self.record_audit(SYSTEM_APP_ID, "purge_trash", &purged, None)
+                .await;
+        }
+        Ok(purged.len())
+    }
+
+    /// Run `purge_trash` in the background every `interval`
+    pub fn spawn_trash_purge(
+        self: Arc<Self>,
+        retention: chrono::Duration,
+        interval: std::time::Duration,
+    ) -> tokio::task::JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            loop {
+                ticker.tick().await;
+                match self.purge_trash(retention).await {
+                    Ok(0) => {}
+                    Ok(count) => info!("Purged {} memories from trash", count),
+                    Err(e) => warn!("Failed to purge trash: {}", e),
+                }
+            }
+        })
+    }
+
+    /// Permanently destroy every memory whose expiry time has passed
+    ///
+    /// Expired memories are deleted outright, trash included, and the removal
+    /// is reported to the audit log.
+    pub async fn reap_expired(&self) -> Result<Vec<MemoryId>> {
+        let expired = self.database.purge_expired(chrono::Utc::now()).await?;
+        for memory_id in &expired {
+            let _ = self.vector_store.remove_vector(*memory_id).await;
+        }
+
+        if !expired.is_empty() {
+            info!("Deleted {} expired memories", expired.len());
+            self.record_audit(SYSTEM_APP_ID, "expire", &expired, None)
+                .await;
+        }
+        Ok(expired)
+    }
+
+    /// Run `reap_expired` in the background every `interval`
+    pub fn spawn_expiry_reaper(
+        self: Arc<Self>,
+        interval: std::time::Duration,
+    ) -> tokio::task::JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            loop {
+                ticker.tick().await;
+                if let Err(e) = self.reap_expired().await {
+                    warn!("Failed to delete expired memories: {}", e);
+                }
+            }
+        })
+    }
+
+    /// Take a snapshot of the vault into the configured backup directory
+    ///
+    /// The snapshot holds no vectors; the index is rebuilt from the stored
+    /// embeddings after a restore. Old snapshots are then rotated out down to
+    /// `max_backups`.
+    pub async fn create_backup(&self, config: &Config) -> Result<std::path::PathBuf> {
+        let backup_dir = config.get_backup_path();
+        let snapshot = self
+            .database
+            .create_snapshot(&backup_dir, &config.get_keyset_path())
+            .await?;
+        info!("Created vault snapshot {}", snapshot.display());
+
+        for removed in backup::rotate_snapshots(&backup_dir, config.auto_backup.max_backups)? {
+            info!("Removed old snapshot {}", removed.display());
+        }
+        self.record_audit(SYSTEM_APP_ID, "backup", &[], None).await;
+        Ok(snapshot)
+    }
+
+    /// Run `create_backup` in the background every `auto_backup.interval_hours`
+    pub fn spawn_backups(self: Arc<Self>, config: Config) -> tokio::task::JoinHandle<()> {
+        let interval =
+            std::time::Duration::from_secs(config.auto_backup.interval_hours as u64 * 3600);
+        tokio::spawn(async move {
+            // The first snapshot is due one interval after startup
+            let mut ticker =
+                tokio::time::interval_at(tokio::time::Instant::now() + interval, interval);
+            loop {
+                ticker.tick().await;
+                if let Err(e) = self.create_backup(&config).await {
+                    error!("Scheduled backup failed: {}", e);
+                }
+            }
+        })
+    }
+
+    /// Append an entry to the tamper-evident audit log
+    ///
+    /// `guardrail` records the guardrail decision, if any, that let the
+    /// operation through. A failure to audit is logged and does not undo the
+    /// operation.
+    pub async fn record_audit(
+        &self,
+        app_id: &str,
+        op: &str,
+        memory_ids: &[MemoryId],
+        guardrail: Option<&str>,
+    ) {
+        if let Err(e) = self
+            .database
+            .append_audit(app_id, op, memory_ids, guardrail)
+            .await
+        {
+            error!("Failed to record '{}' in audit log: {}", op, e);
+        }
+    }
+
+    /// Read audit log entries, oldest first
+    pub async fn audit_entries(&self, filter: &AuditFilter) -> Result<Vec<AuditEntry>> {
+        self.database.audit_entries(filter).await
+    }
+
+    /// Fill in the class default TTL for memories stored without an expiry
+    fn apply_default_expiry(&self, memory: &mut Memory) {
+        if memory.expires_at.is_none() {
+            memory.expires_at = self
+                .expiry
+                .default_expires_at(&memory.class, memory.created_at);
+        }
+    }
+
+    /// Vectors are dropped on delete; this only catches ones re-added since
+    async fn drop_purged_vectors(&self, purged: &[MemoryId]) {
+        for memory_id in purged {
+            let _ = self.vector_store.remove_vector(*memory_id).await;
+        }
+    }
+
     /// Get memories by class
     pub async fn get_memories_by_class(&self, class: &MemoryClass) -> Result<Vec<Memory>> {
-        let mut db = self.database.lock().await;
-        db.get_memories_by_class(class).await
+        self.database.get_memories_by_class(class).await
+    }
+
+    /// List memories matching a filter, one page at a time
+    pub async fn list_memories(
+        &self,
+        app_id: &str,
+        filter: &MemoryFilter,
+        cursor: Option<&str>,
+        limit: usize,
+    ) -> Result<MemoryPage> {
+        let page = self.database.list_memories(filter, cursor, limit).await?;
+        let ids: Vec<MemoryId> = page.memories.iter().map(|memory| memory.id).collect();
+        self.record_audit(app_id, "list", &ids, None).await;
+        Ok(page)
     }
 
     /// Get last N memories for a user
     pub async fn get_last_memories(&self, source: &str, limit: usize) -> Result<Vec<Memory>> {
-        let mut db = self.database.lock().await;
-        db.get_last_memories(source, limit).await
+        self.database.get_last_memories(source, limit).await
     }
 
     /// Update an existing memory in both database and vector store
-    pub async fn update_memory(&self, memory: Memory) -> Result<MemoryAddResult> {
+    pub async fn update_memory(&self, app_id: &str, mut memory: Memory) -> Result<MemoryAddResult> {
         info!("Updating memory in integrated storage: {}", memory.id);
 
         let mut result = MemoryAddResult {
@@ -248,11 +710,15 @@ impl IntegratedStorage {
             database_stored: false,
         };
 
-        // Step 1: Update in database first
-        let db_result = {
-            let mut db = self.database.lock().await;
-            db.update_memory(&memory).await
-        };
+        // Step 1: Re-embed the new content; a stale embedding is not kept
+        if let Err(e) = self.embed_memory(&mut memory).await {
+            warn!("Failed to embed memory: {}", e);
+            memory.embedding = None;
+            memory.embedding_model = None;
+        }
+
+        // Step 2: Update in database
+        let db_result = self.database.update_memory(&memory).await;
 
         match db_result {
             Ok(_) => {
@@ -265,7 +731,7 @@ impl IntegratedStorage {
             }
         }
 
-        // Step 2: Update in vector store (remove old, add new)
+        // Step 3: Update in vector store (remove old, add new)
         let vector_result = self.update_memory_in_vector_store(&memory).await;
         match vector_result {
             Ok(_) => {
@@ -279,18 +745,64 @@ impl IntegratedStorage {
             }
         }
 
+        self.record_audit(app_id, "update", &[memory.id], None)
+            .await;
+        Ok(result)
+    }
+
+    /// List prior revisions of a memory, oldest first
+    pub async fn list_memory_revisions(&self, memory_id: MemoryId) -> Result<Vec<MemoryRevision>> {
+        self.database.list_revisions(memory_id).await
+    }
+
+    /// Read a memory as it was at a point in time
+    pub async fn get_memory_as_of(
+        &self,
+        memory_id: MemoryId,
+        at: chrono::DateTime<chrono::Utc>,
+    ) -> Result<Option<Memory>> {
+        self.database.get_memory_as_of(memory_id, at).await
+    }
+
+    /// Revert a memory to an earlier revision in both database and vector store
+    pub async fn revert_memory(
+        &self,
+        app_id: &str,
+        memory_id: MemoryId,
+        revision: u32,
+    ) -> Result<MemoryAddResult> {
+        info!("Reverting memory {} to revision {}", memory_id, revision);
+
+        let memory = self.database.revert_memory(memory_id, revision).await?;
+
+        let mut result = MemoryAddResult {
+            memory_id,
+            vector_stored: false,
+            database_stored: true,
+        };
+
+        // Index the revision's embedding so search reflects the revert
+        match self.update_memory_in_vector_store(&memory).await {
+            Ok(_) => {
+                result.vector_stored = true;
+                info!("Memory reverted in vector store: {}", memory_id);
+            }
+            Err(e) => {
+                warn!("Failed to revert memory in vector store: {}", e);
+            }
+        }
+
+        self.record_audit(app_id, "revert", &[memory_id], None)
+            .await;
         Ok(result)
     }
 
     /// Clear all memories from both storage systems
-    pub async fn clear_vault(&self) -> Result<usize> {
+    pub async fn clear_vault(&self, app_id: &str) -> Result<usize> {
         info!("Clearing all memories from vault");
 
         // Step 1: Clear database
-        let db_count = {
-            let mut db = self.database.lock().await;
-            db.clear_all_memories().await
-        };
+        let db_count = self.database.clear_all_memories().await;
 
         let deleted_count = match db_count {
             Ok(count) => {
@@ -307,67 +819,136 @@ impl IntegratedStorage {
         // TODO: Implement clear_all_vectors in vector store or get all IDs and remove individually
         warn!("Vector store not cleared - will be out of sync with database");
 
+        self.record_audit(app_id, "clear", &[], None).await;
         Ok(deleted_count)
     }
 
     /// Get storage statistics
+    ///
+    /// Counts and breakdowns come from the database. Orphans are found by
+    /// comparing the live memory IDs with the IDs in the vector index.
     pub async fn get_stats(&self) -> Result<StorageStats> {
-        let db_count = {
-            // Note: We don't have a direct count method in Database yet
-            // For now, we'll estimate based on vector store count
-            self.vector_store.len().await
-        };
-
-        let vector_count = self.vector_store.len().await;
+        let database = self.database.vault_stats().await?;
+        let memory_ids = self.database.live_memory_ids().await?;
+        let vector_ids = self.vector_store.memory_ids().await;
+        let orphans = OrphanCounts::compare(&memory_ids, &vector_ids);
         let vector_stats = self.vector_store.get_memory_stats();
+        let keyset_bytes = std::fs::metadata(self.crypto_manager.keyset_path())
+            .map(|metadata| metadata.len())
+            .unwrap_or(0);
 
         Ok(StorageStats {
-            database_memories: db_count,
-            vector_memories: vector_count,
+            database_memories: database.memories,
+            vector_memories: vector_ids.len(),
             memory_usage_bytes: vector_stats.memory_bytes,
             vector_count_percentage: self.vector_store.get_vector_count_percentage(),
+            vector_bytes: self.vector_store.disk_size(),
+            keyset_bytes,
+            orphans,
+            database,
         })
     }
 
-    /// Add memory to vector store (internal method)
-    async fn add_memory_to_vector_store(&self, memory: &Memory) -> Result<()> {
-        // Check if vector store has embedder
-        if !self.vector_store.has_embedder().await {
-            return Err(mimir_core::MimirError::VectorStore(
-                "Vector store does not have an embedder configured".to_string(),
-            ));
+    /// Rebuild the vector index from the embeddings stored in the database
+    ///
+    /// The embedder is not run. Memories without a usable stored embedding
+    /// are left out of the index and listed in the report. The rebuilt index
+    /// is saved to disk.
+    pub async fn rebuild_vector_index(&self) -> Result<VectorRebuildReport> {
+        info!("Rebuilding vector index from stored embeddings");
+        let scan = self.database.stored_embeddings().await?;
+        let dimension = self.vector_store.dimension().await;
+        let model = self.vector_store.embedding_model().await;
+
+        let mut report = VectorRebuildReport {
+            indexed: 0,
+            skipped: scan.missing,
+        };
+        let mut vectors = Vec::with_capacity(scan.embeddings.len());
+        for embedding in scan.embeddings {
+            let same_model = model.as_ref().is_none_or(|model| *model == embedding.model);
+            if same_model && embedding.vector.len() == dimension {
+                vectors.push(VectorInsert {
+                    memory_id: embedding.memory_id,
+                    vector: embedding.vector,
+                });
+            } else {
+                report.skipped.push(embedding.memory_id);
+            }
         }
 
-        // Add text to vector store (this will generate embedding internally)
-        self.vector_store
-            .add_text(memory.id, &memory.content)
+        let inserted = self
+            .vector_store
+            .rebuild(vectors)
             .await
             .map_err(|e| mimir_core::MimirError::VectorStore(e.to_string()))?;
+        if let Some((memory_id, e)) = inserted.errors.first() {
+            return Err(mimir_core::MimirError::VectorStore(format!(
+                "Failed to rebuild vector index at memory {}: {}",
+                memory_id, e
+            )));
+        }
+        report.indexed = inserted.inserted_count;
 
-        Ok(())
+        if !report.skipped.is_empty() {
+            warn!(
+                "{} memories have no usable stored embedding and were left out of the index",
+                report.skipped.len()
+            );
+        }
+        self.save_vector_store().await?;
+        Ok(report)
     }
 
-    /// Update memory in vector store (internal method)
-    async fn update_memory_in_vector_store(&self, memory: &Memory) -> Result<()> {
-        // Check if vector store has embedder
+    /// Embed a memory's content, recording the vector and model on it
+    async fn embed_memory(&self, memory: &mut Memory) -> Result<()> {
         if !self.vector_store.has_embedder().await {
             return Err(mimir_core::MimirError::VectorStore(
                 "Vector store does not have an embedder configured".to_string(),
             ));
         }
 
-        // Remove old vector first
-        let _ = self.vector_store.remove_vector(memory.id).await;
-
-        // Add new text to vector store (this will generate embedding internally)
-        self.vector_store
-            .add_text(memory.id, &memory.content)
+        let embedding = self
+            .vector_store
+            .embed_text(&memory.content)
             .await
             .map_err(|e| mimir_core::MimirError::VectorStore(e.to_string()))?;
-
+        memory.embedding = Some(embedding);
+        memory.embedding_model = self.vector_store.embedding_model().await;
         Ok(())
     }
 
+    /// Add memory to vector store (internal method)
+    ///
+    /// Indexes the memory's stored embedding; a memory without one is
+    /// embedded by the vector store.
+    async fn add_memory_to_vector_store(&self, memory: &Memory) -> Result<()> {
+        let added = match &memory.embedding {
+            Some(embedding) => {
+                self.vector_store
+                    .add_vector(memory.id, embedding.clone())
+                    .await
+            }
+            None => {
+                // Check if vector store has embedder
+                if !self.vector_store.has_embedder().await {
+                    return Err(mimir_core::MimirError::VectorStore(
+                        "Vector store does not have an embedder configured".to_string(),
+                    ));
+                }
+                self.vector_store.add_text(memory.id, &memory.content).await
+            }
+        };
+        added.map_err(|e| mimir_core::MimirError::VectorStore(e.to_string()))
+    }
+
+    /// Update memory in vector store (internal method)
+    async fn update_memory_in_vector_store(&self, memory: &Memory) -> Result<()> {
+        // Remove old vector first
+        let _ = self.vector_store.remove_vector(memory.id).await;
+        self.add_memory_to_vector_store(memory).await
+    }
+
     pub async fn has_vector_embedder(&self) -> bool {
         self.vector_store.has_embedder().await
     }
@@ -400,13 +981,28 @@ impl IntegratedStorage {
     }
 }
 
+/// App that owns a memory, for the audit log
+fn app_of(memory: &Memory) -> &str {
+    memory
+        .app_acl
+        .first()
+        .map(String::as_str)
+        .unwrap_or(SYSTEM_APP_ID)
+}
+
 /// Storage statistics
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, serde::Serialize)]
 pub struct StorageStats {
     pub database_memories: usize,
     pub vector_memories: usize,
     pub memory_usage_bytes: usize,
     pub vector_count_percentage: f32,
+    /// Size of the saved vector store files
+    pub vector_bytes: u64,
+    pub keyset_bytes: u64,
+    pub orphans: OrphanCounts,
+    /// Breakdowns and database size
+    pub database: VaultStats,
 }
 
 #[cfg(test)]
@@ -417,30 +1013,29 @@ mod tests {
     use std::path::PathBuf;
     use tempfile::TempDir;
 
+    /// App the tests call storage as
+    const TEST_APP: &str = "chat-app";
+
     async fn create_test_storage() -> (IntegratedStorage, TempDir) {
         let temp_dir = TempDir::new().unwrap();
         let db_path = temp_dir.path().join("test.db");
         let keyset_path = temp_dir.path().join("keyset.json");
 
-        // Create crypto manager for database
-        let db_crypto_manager =
-            mimir_core::crypto::CryptoManager::with_password(&keyset_path, "test-password")
-                .expect("Failed to create test crypto manager");
-
-        // Create crypto manager for integrated storage
-        let storage_crypto_manager =
+        // One crypto manager, shared by the database and integrated storage
+        let crypto_manager = Arc::new(
             mimir_core::crypto::CryptoManager::with_password(&keyset_path, "test-password")
-                .expect("Failed to create test crypto manager");
+                .expect("Failed to create test crypto manager"),
+        );
 
         // Create database
-        let database = Database::with_crypto_manager(&db_path, db_crypto_manager)
+        let database = Database::with_crypto_manager(&db_path, crypto_manager.clone())
             .expect("Failed to create test database");
 
         // Create vector store (without embedder for testing)
         let vector_store = ThreadSafeVectorStore::new(temp_dir.path(), 128, None, None)
             .expect("Failed to create test vector store");
 
-        let storage = IntegratedStorage::new(database, vector_store, storage_crypto_manager)
+        let storage = IntegratedStorage::new(database, vector_store, crypto_manager)
             .await
             .expect("Failed to create integrated storage");
 
@@ -463,6 +1058,219 @@ mod tests {
         assert!(!result.vector_stored);
     }
 
+    #[tokio::test]
+    async fn test_add_memories() {
+        let (storage, _temp_dir) = create_test_storage().await;
+
+        let memories: Vec<Memory> = (0..3)
+            .map(|i| {
+                MemoryBuilder::new()
+                    .with_content(format!("Batch memory {}", i))
+                    .build()
+            })
+            .collect();
+        let batch = storage.add_memories(memories.clone()).await.unwrap();
+
+        assert!(batch.committed);
+        assert!(batch.errors.is_empty());
+        assert_eq!(batch.results.len(), 3);
+        for (result, memory) in batch.results.iter().zip(&memories) {
+            assert_eq!(result.memory_id, memory.id);
+            assert!(result.database_stored);
+            // No embedder in tests, so the batch is stored in the database only
+            assert!(!result.vector_stored);
+            assert!(storage
+                .get_memory(TEST_APP, memory.id)
+                .await
+                .unwrap()
+                .is_some());
+        }
+    }
+
+    #[tokio::test]
+    async fn test_trash_and_restore() {
+        let (storage, _temp_dir) = create_test_storage().await;
+
+        let memory = MemoryBuilder::new().with_content("Undo me").build();
+        storage.add_memory(memory.clone()).await.unwrap();
+
+        storage.delete_memory(TEST_APP, memory.id).await.unwrap();
+        assert!(storage
+            .get_memory(TEST_APP, memory.id)
+            .await
+            .unwrap()
+            .is_none());
+        assert_eq!(storage.list_trash().await.unwrap().len(), 1);
+
+        assert!(storage.restore_memory(TEST_APP, memory.id).await.unwrap());
+        assert!(storage
+            .get_memory(TEST_APP, memory.id)
+            .await
+            .unwrap()
+            .is_some());
+
+        storage.delete_memory(TEST_APP, memory.id).await.unwrap();
+        // Within the retention window nothing is purged
+        assert_eq!(
+            storage
+                .purge_trash(chrono::Duration::days(1))
+                .await
+                .unwrap(),
+            0
+        );
+        assert_eq!(storage.empty_trash(TEST_APP).await.unwrap(), 1);
+        assert!(!storage.restore_memory(TEST_APP, memory.id).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_expiry() {
+        let (storage, _temp_dir) = create_test_storage().await;
+        let mut expiry = ExpiryConfig::default();
+        expiry.class_ttl_days.insert("health".to_string(), 0);
+        let storage = storage.with_expiry_config(expiry);
+
+        let health = MemoryBuilder::new()
+            .with_content("Blood pressure reading")
+            .with_class(MemoryClass::Health)
+            .build();
+        let personal = MemoryBuilder::new()
+            .with_content("Favourite colour is green")
+            .with_class(MemoryClass::Personal)
+            .build();
+        storage.add_memory(health.clone()).await.unwrap();
+        storage.add_memory(personal.clone()).await.unwrap();
+
+        // The zero-day class default expires the health memory at creation
+        let stored = storage
+            .get_memory(TEST_APP, health.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            stored.expires_at.map(|at| at.timestamp()),
+            Some(health.created_at.timestamp())
+        );
+
+        assert_eq!(storage.reap_expired().await.unwrap(), vec![health.id]);
+        assert!(storage
+            .get_memory(TEST_APP, health.id)
+            .await
+            .unwrap()
+            .is_none());
+        assert!(storage
+            .get_memory(TEST_APP, personal.id)
+            .await
+            .unwrap()
+            .is_some());
+    }
+
+    #[tokio::test]
+    async fn test_relations() {
+        let (storage, _temp_dir) = create_test_storage().await;
+
+        let old = MemoryBuilder::new().with_content("Works at Acme").build();
+        let new = MemoryBuilder::new().with_content("Works at Globex").build();
+        storage
+            .add_memories(vec![old.clone(), new.clone()])
+            .await
+            .unwrap();
+
+        let relation = MemoryRelation {
+            source: new.id,
+            target: old.id,
+            kind: RelationKind::Supersedes,
+            label: Some("changed jobs".to_string()),
+            created_at: chrono::Utc::now(),
+        };
+        storage.link_memories(TEST_APP, &relation).await.unwrap();
+        assert_eq!(
+            storage.list_relations(old.id).await.unwrap(),
+            vec![relation]
+        );
+
+        let mut results = vec![MemorySearchResult {
+            memory: new.clone(),
+            similarity: 0.9,
+            distance: 0.1,
+            neighbors: vec![],
+        }];
+        storage
+            .expand_with_neighbors(&mut results, 1)
+            .await
+            .unwrap();
+        assert_eq!(results[0].neighbors.len(), 1);
+        assert_eq!(results[0].neighbors[0].memory.id, old.id);
+
+        assert_eq!(
+            storage
+                .unlink_memories(TEST_APP, new.id, old.id, None)
+                .await
+                .unwrap(),
+            1
+        );
+        assert!(storage
+            .get_related_memories(new.id, 2, &[])
+            .await
+            .unwrap()
+            .is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_operations_are_audited() {
+        let (storage, _temp_dir) = create_test_storage().await;
+
+        let memory = MemoryBuilder::new()
+            .with_content("Audited memory")
+            .with_app_acl(vec!["notes-app".to_string()])
+            .build();
+        storage.add_memory(memory.clone()).await.unwrap();
+        storage.get_memory(TEST_APP, memory.id).await.unwrap();
+        storage.delete_memory(TEST_APP, memory.id).await.unwrap();
+
+        let entries = storage
+            .audit_entries(&AuditFilter::default())
+            .await
+            .unwrap();
+        let ops: Vec<(&str, &str)> = entries
+            .iter()
+            .map(|entry| (entry.app_id.as_str(), entry.op.as_str()))
+            .collect();
+        assert_eq!(
+            ops,
+            vec![
+                ("notes-app", "add"),
+                (TEST_APP, "read"),
+                (TEST_APP, "delete")
+            ]
+        );
+        assert!(entries
+            .iter()
+            .all(|entry| entry.memory_ids == vec![memory.id]));
+    }
+
+    #[tokio::test]
+    async fn test_create_backup() {
+        let (storage, temp_dir) = create_test_storage().await;
+        storage
+            .add_memory(MemoryBuilder::new().with_content("Keep me safe").build())
+            .await
+            .unwrap();
+
+        let mut config = Config::default();
+        config.vault_path = temp_dir.path().to_path_buf();
+        config.keyset_path = PathBuf::from("keyset.json");
+        config.auto_backup.max_backups = 1;
+
+        let first = storage.create_backup(&config).await.unwrap();
+        let second = storage.create_backup(&config).await.unwrap();
+
+        let snapshots = backup::list_snapshots(&config.get_backup_path()).unwrap();
+        assert_eq!(snapshots, vec![second.clone()]);
+        assert!(!first.exists());
+        let manifest = backup::read_manifest(&second).unwrap();
+        assert_eq!(manifest.memory_count, 1);
+    }
+
     #[tokio::test]
     async fn test_get_memory() {
         let (storage, _temp_dir) = create_test_storage().await;
@@ -473,7 +1281,7 @@ mod tests {
             .build();
         storage.add_memory(memory.clone()).await.unwrap();
 
-        let retrieved = storage.get_memory(memory.id).await.unwrap();
+        let retrieved = storage.get_memory(TEST_APP, memory.id).await.unwrap();
         assert!(retrieved.is_some());
         assert_eq!(retrieved.unwrap().id, memory.id);
     }
@@ -488,10 +1296,10 @@ mod tests {
             .build();
         storage.add_memory(memory.clone()).await.unwrap();
 
-        let deleted = storage.delete_memory(memory.id).await.unwrap();
+        let deleted = storage.delete_memory(TEST_APP, memory.id).await.unwrap();
         assert!(deleted);
 
-        let retrieved = storage.get_memory(memory.id).await.unwrap();
+        let retrieved = storage.get_memory(TEST_APP, memory.id).await.unwrap();
         assert!(retrieved.is_none());
     }
 
@@ -526,6 +1334,70 @@ mod tests {
         assert_eq!(work_memories[0].id, memory2.id);
     }
 
+    #[tokio::test]
+    async fn test_revert_memory() {
+        let (storage, _temp_dir) = create_test_storage().await;
+
+        let mut memory = MemoryBuilder::new().with_content("Original").build();
+        storage.add_memory(memory.clone()).await.unwrap();
+
+        memory.content = "Overwritten".to_string();
+        storage
+            .update_memory(TEST_APP, memory.clone())
+            .await
+            .unwrap();
+
+        let revisions = storage.list_memory_revisions(memory.id).await.unwrap();
+        assert_eq!(revisions.len(), 1);
+        assert_eq!(revisions[0].memory.content, "Original");
+
+        let result = storage.revert_memory(TEST_APP, memory.id, 1).await.unwrap();
+        assert!(result.database_stored);
+
+        let current = storage
+            .get_memory(TEST_APP, memory.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(current.content, "Original");
+    }
+
+    #[tokio::test]
+    async fn test_rebuild_vector_index() {
+        let (storage, _temp_dir) = create_test_storage().await;
+
+        let embedded = MemoryBuilder::new()
+            .with_content("Embedded")
+            .with_embedding(vec![0.5; 128])
+            .with_embedding_model("test-model")
+            .build();
+        let other_dimension = MemoryBuilder::new()
+            .with_content("Other dimension")
+            .with_embedding(vec![0.5; 64])
+            .build();
+        let plain = MemoryBuilder::new().with_content("Plain").build();
+        for memory in [embedded.clone(), other_dimension.clone(), plain.clone()] {
+            storage.add_memory(memory).await.unwrap();
+        }
+        assert!(storage.vector_store.contains(&embedded.id).await);
+
+        // Lose the vector, then rebuild the index from the database alone
+        storage
+            .vector_store
+            .remove_vector(embedded.id)
+            .await
+            .unwrap();
+        let report = storage.rebuild_vector_index().await.unwrap();
+        assert_eq!(report.indexed, 1);
+        let mut skipped = report.skipped.clone();
+        skipped.sort();
+        let mut expected = vec![other_dimension.id, plain.id];
+        expected.sort();
+        assert_eq!(skipped, expected);
+        assert!(storage.vector_store.contains(&embedded.id).await);
+        assert_eq!(storage.vector_store.len().await, 1);
+    }
+
     #[tokio::test]
     async fn test_get_stats() {
         let (storage, _temp_dir) = create_test_storage().await;
@@ -533,6 +1405,19 @@ mod tests {
         let stats = storage.get_stats().await.unwrap();
         assert_eq!(stats.database_memories, 0);
         assert_eq!(stats.vector_memories, 0);
+
+        // Without an embedder the memories have no vectors
+        let memory = MemoryBuilder::new()
+            .with_content("Prefers window seats")
+            .with_tags(vec!["travel".to_string()])
+            .build();
+        storage.add_memory(memory).await.unwrap();
+        let stats = storage.get_stats().await.unwrap();
+        assert_eq!(stats.database_memories, 1);
+        assert_eq!(stats.database.by_tag.get("travel"), Some(&1));
+        assert_eq!(stats.orphans.memories_without_vectors, 1);
+        assert_eq!(stats.orphans.vectors_without_memories, 0);
+        assert!(stats.keyset_bytes > 0);
     }
 
     #[tokio::test]
@@ -548,4 +1433,38 @@ mod tests {
         let new_threshold = storage.get_similarity_threshold().await;
         assert_eq!(new_threshold, 0.5);
     }
+
+    #[tokio::test]
+    async fn test_attachments() {
+        let (storage, _temp_dir) = create_test_storage().await;
+
+        let memory = MemoryBuilder::new()
+            .with_content("Whiteboard from the offsite")
+            .build();
+        storage.add_memory(memory.clone()).await.unwrap();
+        let info = storage
+            .add_attachment(TEST_APP, memory.id, "board.jpg", "image/jpeg", b"JFIF")
+            .await
+            .unwrap();
+        assert_eq!(
+            storage.list_attachments(memory.id).await.unwrap(),
+            vec![info.clone()]
+        );
+        let attachment = storage
+            .get_attachment(TEST_APP, info.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(attachment.data, b"JFIF");
+
+        // Destroying the memory takes its attachments with it
+        storage.delete_memory(TEST_APP, memory.id).await.unwrap();
+        storage.empty_trash(TEST_APP).await.unwrap();
+        assert!(storage
+            .get_attachment(TEST_APP, info.id)
+            .await
+            .unwrap()
+            .is_none());
+        assert!(!storage.remove_attachment(TEST_APP, info.id).await.unwrap());
+    }
 }
@@ -33,15 +33,15 @@ mod mcp_integration_tests {
         let temp_dir = TempDir::new().unwrap();
         let db_path = temp_dir.path().join("test.db");
         let keyset_path = temp_dir.path().join("keyset.json");
-        let db_crypto_manager = CryptoManager::with_password(&keyset_path, "test-password")
-            .expect("Failed to create test crypto manager");
-        let storage_crypto_manager = CryptoManager::with_password(&keyset_path, "test-password")
-            .expect("Failed to create test crypto manager");
-        let database = Database::with_crypto_manager(&db_path, db_crypto_manager)
+        let crypto_manager = std::sync::Arc::new(
+            CryptoManager::with_password(&keyset_path, "test-password")
+                .expect("Failed to create test crypto manager"),
+        );
+        let database = Database::with_crypto_manager(&db_path, crypto_manager.clone())
             .expect("Failed to create test database");
         let vector_store = ThreadSafeVectorStore::new(temp_dir.path(), 128, None, None)
             .expect("Failed to create test vector store");
-        IntegratedStorage::new(database, vector_store, storage_crypto_manager)
+        IntegratedStorage::new(database, vector_store, crypto_manager)
             .await
             .expect("Failed to create integrated storage")
     }